@@ -6,20 +6,53 @@
 
 #[macro_use]
 mod analyze;
+mod association_rewrite;
+mod call_hierarchy;
+mod code_lens;
+mod completion;
 mod concurrent;
 mod declarative;
+mod design_hierarchy;
 mod design_unit;
+mod document_highlight;
+mod entity_skeletons;
+mod implementation;
+mod inlay_hint;
+mod instantiation_template;
+mod linked_editing;
 mod lock;
 mod named_entity;
+mod package_body_stub;
+mod port_map_fix;
 mod region;
 mod root;
+mod selection_range;
 mod semantic;
+mod semantic_tokens;
 mod sequential;
+mod signature_help;
 mod target;
+mod type_definition;
+mod use_clause_fix;
 mod visibility;
+mod workspace_symbol;
 
 #[cfg(test)]
 mod tests;
 
 pub use self::root::DesignRoot;
-pub use named_entity::NamedEntity;
+pub use association_rewrite::{AssociationRewrite, AssociationStyle};
+pub use call_hierarchy::{CallItem, CallItemKind, Calls};
+pub use code_lens::CodeLens;
+pub use design_hierarchy::HierarchyNode;
+pub use document_highlight::{Occurrence, OccurrenceKind};
+pub use entity_skeletons::EntitySkeletons;
+pub use inlay_hint::{InlayHint, InlayHintKind};
+pub use instantiation_template::InstantiationTemplate;
+pub use named_entity::{NamedEntity, NamedEntityKind};
+pub use package_body_stub::PackageBodyStub;
+pub use port_map_fix::{PortMapFix, PortMapFixKind};
+pub use semantic_tokens::{SemanticToken, SemanticTokenKind};
+pub use signature_help::{CallSignature, CallSignatures};
+pub use use_clause_fix::UseClauseFix;
+pub use workspace_symbol::{WorkspaceSymbol, WorkspaceSymbolKind};
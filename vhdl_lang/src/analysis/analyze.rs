@@ -96,6 +96,10 @@ pub(super) struct AnalyzeContext<'a> {
     uses: RefCell<FnvHashSet<UnitId>>,
     missing_primary: RefCell<FnvHashSet<(Symbol, Symbol)>>,
     uses_library_all: RefCell<FnvHashSet<Symbol>>,
+
+    // The regions of statements and subprogram bodies within the unit
+    // kept after analysis to find the declarations visible at a position
+    local_regions: RefCell<Vec<(SrcPos, Region<'static>)>>,
 }
 
 impl<'a> AnalyzeContext<'a> {
@@ -109,9 +113,21 @@ impl<'a> AnalyzeContext<'a> {
             uses: RefCell::new(FnvHashSet::default()),
             missing_primary: RefCell::new(FnvHashSet::default()),
             uses_library_all: RefCell::new(FnvHashSet::default()),
+            local_regions: RefCell::new(Vec::new()),
         }
     }
 
+    /// Keep the region of the statement or subprogram body within the source range
+    pub fn add_local_region(&self, source_range: &SrcPos, region: &Region<'_>) {
+        self.local_regions
+            .borrow_mut()
+            .push((source_range.clone(), region.clone().without_parent()));
+    }
+
+    pub fn into_local_regions(self) -> Vec<(SrcPos, Region<'static>)> {
+        self.local_regions.into_inner()
+    }
+
     pub fn work_library_name(&self) -> &Symbol {
         self.current_unit.library_name()
     }
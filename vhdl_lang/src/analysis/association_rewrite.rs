@@ -0,0 +1,242 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2020, Olof Kraigher olof.kraigher@gmail.com
+
+use super::inlay_hint::name_reference;
+use super::named_entity::*;
+use super::root::*;
+use crate::ast::search::*;
+use crate::ast::*;
+use crate::data::*;
+use std::sync::Arc;
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum AssociationStyle {
+    /// Associations such as `clk => sys_clk`
+    Named,
+    /// Associations such as `sys_clk`
+    Positional,
+}
+
+/// A rewrite of the associations of a port map, generic map or subprogram call
+#[derive(PartialEq, Clone, Debug)]
+pub struct AssociationRewrite {
+    /// The style of the rewritten associations
+    pub style: AssociationStyle,
+    /// The text replacing each position where an empty position is an insertion
+    pub edits: Vec<(SrcPos, String)>,
+}
+
+impl DesignRoot {
+    /// Find the rewrites of the innermost port map, generic map or subprogram call enclosing
+    /// the cursor between positional and named associations
+    ///
+    /// Positional associations are named by the formals of the entity, component or subprogram.
+    /// Named associations become positional when they name the formals in the order of the
+    /// interface list without leaving out any formal in between.
+    pub fn find_association_rewrites(
+        &self,
+        source: &Source,
+        cursor: Position,
+    ) -> Vec<AssociationRewrite> {
+        let unit = match self.get_unit_at(source, cursor) {
+            Some(unit) => unit,
+            None => return Vec::new(),
+        };
+
+        let list = {
+            let mut searcher = FindAssociationList::new(source, cursor);
+            let _ = unit.unit.read().search(&mut searcher);
+            match searcher.result {
+                Some(list) => list,
+                None => return Vec::new(),
+            }
+        };
+
+        // The interface list is searched after the search above has finished with the unit
+        let (elems, formals) = match list {
+            AssociationList::Call(elems, formals) => (elems, formals),
+            AssociationList::Instance(elems, ent, list_type) => {
+                let formals = ent
+                    .decl_pos()
+                    .and_then(|decl_pos| FindInterfaceList::search(self, decl_pos, list_type))
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|decl| decl.designator())
+                    .collect();
+                (elems, formals)
+            }
+        };
+
+        association_rewrites(&elems, &formals)
+    }
+}
+
+fn association_rewrites(
+    elems: &[AssociationElement],
+    formals: &[Designator],
+) -> Vec<AssociationRewrite> {
+    let mut result = Vec::new();
+
+    let num_positional = elems
+        .iter()
+        .take_while(|elem| elem.formal.is_none())
+        .count();
+
+    // A map with more positional associations than formals, or formals which are not known,
+    // cannot be rewritten
+    if num_positional > formals.len() {
+        return result;
+    }
+
+    if num_positional > 0 {
+        let edits = elems[..num_positional]
+            .iter()
+            .zip(formals.iter())
+            .map(|(elem, formal)| {
+                let start = elem.actual.pos.start();
+                (
+                    elem.actual.pos.source.pos(start, start),
+                    format!("{} => ", formal),
+                )
+            })
+            .collect();
+
+        result.push(AssociationRewrite {
+            style: AssociationStyle::Named,
+            edits,
+        });
+    }
+
+    let named = &elems[num_positional..];
+    let is_in_order = named
+        .iter()
+        .zip(formals.iter().skip(num_positional))
+        .all(|(elem, formal)| formal_designator(elem) == Some(formal));
+
+    if !named.is_empty() && named.len() <= formals.len() - num_positional && is_in_order {
+        let edits = named
+            .iter()
+            .filter_map(|elem| {
+                let formal = elem.formal.as_ref()?;
+                let pos = formal
+                    .pos
+                    .source
+                    .pos(formal.pos.start(), elem.actual.pos.start());
+                Some((pos, String::new()))
+            })
+            .collect();
+
+        result.push(AssociationRewrite {
+            style: AssociationStyle::Positional,
+            edits,
+        });
+    }
+
+    result
+}
+
+/// The designator of the formal of a named association when it is a simple name
+fn formal_designator(elem: &AssociationElement) -> Option<&Designator> {
+    match elem.formal.as_ref()?.item {
+        Name::Designator(ref designator) => Some(&designator.item),
+        _ => None,
+    }
+}
+
+enum AssociationList {
+    /// The parameters of a subprogram call and the formals of the subprogram
+    Call(Vec<AssociationElement>, Vec<Designator>),
+    /// A generic or port map and the instantiated entity or component
+    Instance(Vec<AssociationElement>, Arc<NamedEntity>, InterfaceListType),
+}
+
+/// Find the innermost association list enclosing the cursor
+struct FindAssociationList {
+    source: Source,
+    cursor: Position,
+    result: Option<AssociationList>,
+}
+
+impl FindAssociationList {
+    fn new(source: &Source, cursor: Position) -> FindAssociationList {
+        FindAssociationList {
+            source: source.clone(),
+            cursor,
+            result: None,
+        }
+    }
+
+    fn encloses_cursor(&self, elems: &[AssociationElement]) -> bool {
+        let (first, last) = match (elems.first(), elems.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return false,
+        };
+
+        let start = match first.formal {
+            Some(ref formal) => formal.pos.start(),
+            None => first.actual.pos.start(),
+        };
+
+        last.actual.pos.source == self.source
+            && start <= self.cursor
+            && self.cursor <= last.actual.pos.end()
+    }
+}
+
+impl Searcher for FindAssociationList {
+    // Calls and instantiations enclosing the cursor are searched from the outermost inwards
+    fn search_function_call(&mut self, call: &FunctionCall) -> SearchState {
+        // Indexed names are also parsed as function calls but do not refer to a subprogram
+        if let Some(signature) = name_reference(&call.name.item).and_then(|ent| ent.signature()) {
+            if self.encloses_cursor(&call.parameters) {
+                self.result = Some(AssociationList::Call(
+                    call.parameters.clone(),
+                    signature.formals().cloned().collect(),
+                ));
+            }
+        }
+        NotFinished
+    }
+
+    fn search_instantiation(&mut self, inst: &InstantiationStatement) -> SearchState {
+        let name = match inst.unit {
+            InstantiatedUnit::Entity(ref name, _) | InstantiatedUnit::Component(ref name) => name,
+            InstantiatedUnit::Configuration(..) => return NotFinished,
+        };
+
+        let reference = match name.item {
+            SelectedName::Designator(ref designator) => &designator.reference,
+            SelectedName::Selected(_, ref suffix) => &suffix.item.reference,
+        };
+
+        if let Some(ent) = reference {
+            for (list_type, elems) in [
+                (InterfaceListType::Generic, &inst.generic_map),
+                (InterfaceListType::Port, &inst.port_map),
+            ]
+            .iter()
+            {
+                if self.encloses_cursor(elems) {
+                    self.result = Some(AssociationList::Instance(
+                        elems.to_vec(),
+                        ent.clone(),
+                        *list_type,
+                    ));
+                }
+            }
+        }
+        NotFinished
+    }
+
+    // Assume source is searched first to filter out design units in other files
+    fn search_source(&mut self, source: &Source) -> SearchState {
+        if source == &self.source {
+            NotFinished
+        } else {
+            Finished(NotFound)
+        }
+    }
+}
@@ -0,0 +1,296 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2020, Olof Kraigher olof.kraigher@gmail.com
+
+use super::named_entity::*;
+use super::root::*;
+use crate::ast::search::*;
+use crate::ast::*;
+use crate::data::*;
+use std::sync::Arc;
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum CallItemKind {
+    Function,
+    Procedure,
+    /// A process or a concurrent procedure call which is equivalent to a process
+    Process,
+    /// A design unit containing calls outside of any subprogram or process
+    Unit,
+}
+
+/// A subprogram or another construct containing calls within the call hierarchy
+#[derive(PartialEq, Clone, Debug)]
+pub struct CallItem {
+    pub name: String,
+    pub kind: CallItemKind,
+    /// A description of the design unit for units
+    pub detail: Option<String>,
+    pub pos: SrcPos,
+}
+
+/// The calls to or from an item at the positions of the called names
+#[derive(PartialEq, Clone, Debug)]
+pub struct Calls {
+    pub item: CallItem,
+    pub positions: Vec<SrcPos>,
+}
+
+impl CallItem {
+    fn function(function: &FunctionSpecification) -> CallItem {
+        CallItem {
+            name: function.designator.item.to_string(),
+            kind: CallItemKind::Function,
+            detail: None,
+            pos: function.designator.pos.clone(),
+        }
+    }
+
+    fn procedure(procedure: &ProcedureSpecification) -> CallItem {
+        CallItem {
+            name: procedure.designator.item.to_string(),
+            kind: CallItemKind::Procedure,
+            detail: None,
+            pos: procedure.designator.pos.clone(),
+        }
+    }
+
+    fn subprogram(decl: &SubprogramDeclaration) -> CallItem {
+        match decl {
+            SubprogramDeclaration::Function(ref function) => CallItem::function(function),
+            SubprogramDeclaration::Procedure(ref procedure) => CallItem::procedure(procedure),
+        }
+    }
+
+    fn callee(ent: &NamedEntity) -> Option<CallItem> {
+        let signature = ent.signature()?;
+        Some(CallItem {
+            name: ent.designator().to_string(),
+            kind: if signature.return_type().is_some() {
+                CallItemKind::Function
+            } else {
+                CallItemKind::Procedure
+            },
+            detail: None,
+            pos: ent.decl_pos()?.clone(),
+        })
+    }
+
+    /// A process or concurrent procedure call is named by its label when it has one
+    fn statement(statement: &LabeledConcurrentStatement, default_name: &str) -> CallItem {
+        let (name, pos) = match statement.label {
+            Some(ref label) => (label.item.to_string(), label.pos.clone()),
+            None => (default_name.to_owned(), statement.statement.pos.clone()),
+        };
+
+        CallItem {
+            name,
+            kind: CallItemKind::Process,
+            detail: None,
+            pos,
+        }
+    }
+
+    fn unit(unit: &LockedUnit) -> CallItem {
+        CallItem {
+            name: unit.ident().item.to_string(),
+            kind: CallItemKind::Unit,
+            detail: Some(unit.describe()),
+            pos: unit.ident().pos.clone(),
+        }
+    }
+}
+
+impl DesignRoot {
+    /// Get the call hierarchy item of the subprogram declared at decl_pos
+    pub fn prepare_call_hierarchy(&self, decl_pos: &SrcPos) -> Option<CallItem> {
+        let mut searcher = FindSubprogramItem::new(decl_pos);
+        let _ = self.search(&mut searcher);
+        searcher.result
+    }
+
+    /// Find the callers of the subprogram declared at decl_pos
+    /// Calls to both the subprogram declaration and its body are considered
+    pub fn find_incoming_calls(&self, decl_pos: &SrcPos) -> Vec<Calls> {
+        let parts = self.find_subprogram_parts(decl_pos);
+        let mut sites = Vec::new();
+
+        for library_name in self.library_names() {
+            if let Some(units) = self.get_library_units(library_name) {
+                for unit in units.values() {
+                    let mut searcher = FindCalls::new(CallItem::unit(unit));
+                    let _ = unit.unit.read().search(&mut searcher);
+                    sites.extend(searcher.sites.into_iter().filter(|site| {
+                        site.callee
+                            .decl_pos()
+                            .map(|pos| parts.contains(pos))
+                            .unwrap_or(false)
+                    }));
+                }
+            }
+        }
+
+        group_calls(sites.into_iter().map(|site| (site.caller, site.pos)))
+    }
+
+    /// Find the subprograms called by the body of the subprogram declared at decl_pos
+    pub fn find_outgoing_calls(&self, decl_pos: &SrcPos) -> Vec<Calls> {
+        let parts = self.find_subprogram_parts(decl_pos);
+        let mut sources: Vec<_> = parts.iter().map(|pos| &pos.source).collect();
+        sources.dedup();
+
+        // The body may be in another source than the declaration
+        let mut calls = Vec::new();
+        for source in sources {
+            for unit in self.get_units_in_source(source) {
+                let mut searcher = FindCalls::new(CallItem::unit(unit));
+                let _ = unit.unit.read().search(&mut searcher);
+                for site in searcher.sites {
+                    if parts.contains(&site.caller.pos) {
+                        if let Some(callee) = CallItem::callee(&site.callee) {
+                            calls.push((callee, site.pos));
+                        }
+                    }
+                }
+            }
+        }
+
+        group_calls(calls.into_iter())
+    }
+}
+
+/// Group the call positions by item sorted by the position of the item
+fn group_calls(calls: impl Iterator<Item = (CallItem, SrcPos)>) -> Vec<Calls> {
+    let mut result: Vec<Calls> = Vec::new();
+
+    for (item, pos) in calls {
+        if let Some(existing) = result.iter_mut().find(|calls| calls.item.pos == item.pos) {
+            existing.positions.push(pos);
+        } else {
+            result.push(Calls {
+                item,
+                positions: vec![pos],
+            });
+        }
+    }
+
+    for calls in result.iter_mut() {
+        calls.positions.sort();
+        calls.positions.dedup();
+    }
+    result.sort_by(|lhs, rhs| lhs.item.pos.cmp(&rhs.item.pos));
+    result
+}
+
+struct FindSubprogramItem {
+    decl_pos: SrcPos,
+    result: Option<CallItem>,
+}
+
+impl FindSubprogramItem {
+    fn new(decl_pos: &SrcPos) -> FindSubprogramItem {
+        FindSubprogramItem {
+            decl_pos: decl_pos.clone(),
+            result: None,
+        }
+    }
+}
+
+impl Searcher for FindSubprogramItem {
+    fn search_decl(&mut self, pos: &SrcPos, decl: FoundDeclaration) -> SearchState {
+        if pos != &self.decl_pos {
+            return NotFinished;
+        }
+
+        self.result = match decl {
+            FoundDeclaration::Function(function) => Some(CallItem::function(function)),
+            FoundDeclaration::Procedure(procedure) => Some(CallItem::procedure(procedure)),
+            _ => None,
+        };
+        Finished(Found)
+    }
+
+    fn search_source(&mut self, source: &Source) -> SearchState {
+        if source == &self.decl_pos.source {
+            NotFinished
+        } else {
+            Finished(NotFound)
+        }
+    }
+}
+
+/// A reference to a subprogram from within the caller
+struct CallSite {
+    caller: CallItem,
+    callee: Arc<NamedEntity>,
+    pos: SrcPos,
+}
+
+/// Find the calls within a caller
+/// Calls within nested subprogram bodies and processes are attributed to them instead
+struct FindCalls {
+    caller: CallItem,
+    sites: Vec<CallSite>,
+}
+
+impl FindCalls {
+    fn new(caller: CallItem) -> FindCalls {
+        FindCalls {
+            caller,
+            sites: Vec::new(),
+        }
+    }
+
+    fn search_nested(&mut self, caller: CallItem, searchable: &impl Search) {
+        let mut searcher = FindCalls::new(caller);
+        let _ = searchable.search(&mut searcher);
+        self.sites.extend(searcher.sites);
+    }
+}
+
+impl Searcher for FindCalls {
+    fn search_pos_with_ref(&mut self, pos: &SrcPos, reference: &Reference) -> SearchState {
+        if let Some(ent) = reference {
+            let actual = ent.as_actual();
+            if actual.is_subprogram() || actual.is_subprogram_decl() {
+                self.sites.push(CallSite {
+                    caller: self.caller.clone(),
+                    callee: ent.clone(),
+                    pos: pos.clone(),
+                });
+            }
+        }
+        NotFinished
+    }
+
+    fn search_subprogram_body(&mut self, body: &SubprogramBody) -> SearchState {
+        let caller = CallItem::subprogram(&body.specification);
+        self.search_nested(caller.clone(), &body.declarations);
+        self.search_nested(caller, &body.statements);
+        Finished(NotFound)
+    }
+
+    fn search_concurrent_statement(
+        &mut self,
+        statement: &LabeledConcurrentStatement,
+    ) -> SearchState {
+        match statement.statement.item {
+            ConcurrentStatement::Process(ref process) => {
+                let caller = CallItem::statement(statement, "process");
+                self.search_nested(caller.clone(), &process.decl);
+                self.search_nested(caller, &process.statements);
+                Finished(NotFound)
+            }
+            ConcurrentStatement::ProcedureCall(ref pcall) => {
+                self.search_nested(
+                    CallItem::statement(statement, "procedure call"),
+                    &pcall.call,
+                );
+                Finished(NotFound)
+            }
+            _ => NotFinished,
+        }
+    }
+}
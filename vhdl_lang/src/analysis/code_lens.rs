@@ -0,0 +1,262 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2020, Olof Kraigher olof.kraigher@gmail.com
+
+use super::design_hierarchy::component_call_name;
+use super::root::*;
+use crate::ast::search::*;
+use crate::ast::*;
+use crate::data::*;
+
+/// A summary of the usage of a declaration shown above it
+#[derive(PartialEq, Clone, Debug)]
+pub struct CodeLens {
+    /// The position of the declared name
+    pub pos: SrcPos,
+    pub title: String,
+    /// The references to the declaration which are listed when the lens is clicked
+    pub references: Vec<SrcPos>,
+}
+
+impl DesignRoot {
+    /// Find the code lenses of the entities, packages and subprograms declared within the source
+    ///
+    /// An entity shows its number of architectures and instantiations, a package its
+    /// number of dependent design units and a subprogram its number of references where
+    /// references to the declaration and the body of a subprogram are counted together.
+    pub fn find_code_lenses(&self, source: &Source) -> Vec<CodeLens> {
+        let mut entities = Vec::new();
+        let mut packages = Vec::new();
+        let mut subprograms = FindSubprogramDecls::default();
+
+        for unit in self.get_units_in_source(source) {
+            match unit.kind() {
+                AnyKind::Primary(PrimaryKind::Entity) => {
+                    entities.push((unit.ident().clone(), unit.unit_id().library_name().clone()));
+                }
+                AnyKind::Primary(PrimaryKind::Package) => {
+                    packages.push((unit.ident().clone(), unit.unit_id().clone()));
+                }
+                _ => {}
+            }
+            let _ = unit.unit.read().search(&mut subprograms);
+        }
+
+        // The declaration and body of each subprogram are counted together
+        let subprograms: Vec<_> = subprograms
+            .result
+            .into_iter()
+            .map(|pos| {
+                let mut parts = self.find_subprogram_parts(&pos);
+                if parts.is_empty() {
+                    parts.push(pos.clone());
+                }
+                (pos, parts)
+            })
+            .collect();
+
+        let mut decl_positions = Vec::new();
+        decl_positions.extend(entities.iter().map(|(ident, _)| ident.pos.clone()));
+        decl_positions.extend(packages.iter().map(|(ident, _)| ident.pos.clone()));
+        for (_, parts) in subprograms.iter() {
+            decl_positions.extend(parts.iter().cloned());
+        }
+
+        let mut searcher = FindUsages::new(decl_positions);
+        for library_name in self.library_names() {
+            searcher.library_name = Some(library_name.clone());
+            let units = self.get_library_units(library_name).into_iter();
+            for unit in units.flat_map(|units| units.values()) {
+                let _ = unit.unit.read().search(&mut searcher);
+            }
+        }
+
+        let mut lenses = Vec::new();
+
+        for (ident, library_name) in entities {
+            let architectures = self.find_implementation(&ident.pos).len();
+            let component_instances: Vec<_> = searcher
+                .component_instances
+                .iter()
+                .filter(|(instance_library_name, name, _)| {
+                    *name == ident.item
+                        && self
+                            .default_entity_library(instance_library_name, name)
+                            .as_ref()
+                            == Some(&library_name)
+                })
+                .map(|(_, _, pos)| pos.clone())
+                .collect();
+            let instantiations = searcher
+                .entity_instances
+                .iter()
+                .filter(|decl_pos| **decl_pos == ident.pos)
+                .count()
+                + component_instances.len();
+
+            let mut references = searcher.references_to(std::slice::from_ref(&ident.pos));
+            references.extend(component_instances);
+            references.sort();
+            references.dedup();
+
+            lenses.push(CodeLens {
+                title: format!(
+                    "{} \u{b7} {}",
+                    plural(architectures, "architecture"),
+                    plural(instantiations, "instantiation")
+                ),
+                references,
+                pos: ident.pos,
+            });
+        }
+
+        for (ident, unit_id) in packages {
+            // The package body depends on the package but does not use it
+            let users = self
+                .get_users_of(&unit_id)
+                .into_iter()
+                .filter(|user| {
+                    user.library_name() != unit_id.library_name()
+                        || user.primary_name() != unit_id.primary_name()
+                })
+                .count();
+            lenses.push(CodeLens {
+                title: format!("used by {}", plural(users, "unit")),
+                references: searcher.references_to(std::slice::from_ref(&ident.pos)),
+                pos: ident.pos,
+            });
+        }
+
+        for (pos, parts) in subprograms {
+            let references = searcher.references_to(&parts);
+            lenses.push(CodeLens {
+                title: plural(references.len(), "reference"),
+                references,
+                pos,
+            });
+        }
+
+        lenses.sort_by(|lhs, rhs| lhs.pos.cmp(&rhs.pos));
+        lenses
+    }
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("{} {}", count, noun)
+    } else {
+        format!("{} {}s", count, noun)
+    }
+}
+
+/// Find the positions of the subprogram declarations and bodies
+#[derive(Default)]
+struct FindSubprogramDecls {
+    result: Vec<SrcPos>,
+}
+
+impl Searcher for FindSubprogramDecls {
+    fn search_decl(&mut self, pos: &SrcPos, decl: FoundDeclaration) -> SearchState {
+        if let FoundDeclaration::Function(..) | FoundDeclaration::Procedure(..) = decl {
+            self.result.push(pos.clone());
+        }
+        NotFinished
+    }
+}
+
+/// Find the references and instantiations of a set of declarations in a single pass
+struct FindUsages {
+    decl_positions: Vec<SrcPos>,
+    /// The position of the referenced declaration and of each reference
+    references: Vec<(SrcPos, SrcPos)>,
+    /// The position of the entity of each entity instantiation
+    entity_instances: Vec<SrcPos>,
+    /// The library of the unit being searched
+    library_name: Option<Symbol>,
+    /// The library of the instantiating unit, the name of the component and the
+    /// position of the component name of each component instantiation
+    component_instances: Vec<(Symbol, Symbol, SrcPos)>,
+}
+
+impl FindUsages {
+    fn new(decl_positions: Vec<SrcPos>) -> FindUsages {
+        FindUsages {
+            decl_positions,
+            references: Vec::new(),
+            entity_instances: Vec::new(),
+            library_name: None,
+            component_instances: Vec::new(),
+        }
+    }
+
+    fn add_component_instance(&mut self, name: &Symbol, pos: &SrcPos) {
+        if let Some(ref library_name) = self.library_name {
+            self.component_instances
+                .push((library_name.clone(), name.clone(), pos.clone()));
+        }
+    }
+
+    fn references_to(&self, decl_positions: &[SrcPos]) -> Vec<SrcPos> {
+        let mut result: Vec<_> = self
+            .references
+            .iter()
+            .filter(|(decl_pos, _)| decl_positions.contains(decl_pos))
+            .map(|(_, pos)| pos.clone())
+            .collect();
+        result.sort();
+        result.dedup();
+        result
+    }
+}
+
+impl Searcher for FindUsages {
+    fn search_pos_with_ref(&mut self, pos: &SrcPos, reference: &Reference) -> SearchState {
+        if let Some(decl_pos) = reference.as_ref().and_then(|ent| ent.decl_pos()) {
+            if self.decl_positions.contains(decl_pos) {
+                self.references.push((decl_pos.clone(), pos.clone()));
+            }
+        }
+        NotFinished
+    }
+
+    fn search_instantiation(&mut self, inst: &InstantiationStatement) -> SearchState {
+        let (name, is_entity) = match inst.unit {
+            InstantiatedUnit::Entity(ref name, _) => (name, true),
+            InstantiatedUnit::Component(ref name) => (name, false),
+            InstantiatedUnit::Configuration(..) => return NotFinished,
+        };
+
+        let (designator, pos) = match name.item {
+            SelectedName::Designator(ref designator) => (designator, &name.pos),
+            SelectedName::Selected(_, ref suffix) => (&suffix.item, &suffix.pos),
+        };
+
+        if is_entity {
+            if let Some(decl_pos) = designator.reference.as_ref().and_then(|ent| ent.decl_pos()) {
+                self.entity_instances.push(decl_pos.clone());
+            }
+        } else if let Some(name) = designator.item.as_identifier() {
+            self.add_component_instance(name, pos);
+        }
+        NotFinished
+    }
+
+    fn search_concurrent_statement(
+        &mut self,
+        statement: &LabeledConcurrentStatement,
+    ) -> SearchState {
+        // A component instantiation without generic or port map is parsed as a procedure call
+        if let ConcurrentStatement::ProcedureCall(ref pcall) = statement.statement.item {
+            if let Some(name) = component_call_name(pcall) {
+                let pos = match pcall.call.name.item {
+                    Name::Selected(_, ref suffix) => &suffix.pos,
+                    _ => &pcall.call.name.pos,
+                };
+                self.add_component_instance(name, pos);
+            }
+        }
+        NotFinished
+    }
+}
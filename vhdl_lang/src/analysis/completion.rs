@@ -0,0 +1,321 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2020, Olof Kraigher olof.kraigher@gmail.com
+
+use super::region::*;
+use super::root::*;
+use crate::ast::*;
+use crate::data::*;
+use fnv::FnvHashSet;
+use std::sync::Arc;
+
+impl DesignRoot {
+    /// List the named entities which may complete the identifier at the cursor
+    ///
+    /// When the cursor is preceded by a selected name prefix such as `lib.`, `pkg.` or `rec.`
+    /// the named entities within the prefix are listed, otherwise all named entities
+    /// visible within the design unit enclosing the cursor
+    pub fn list_completion_options(
+        &self,
+        source: &Source,
+        cursor: Position,
+    ) -> Vec<Arc<NamedEntity>> {
+        let prefix = if let Some(prefix) = selected_name_prefix(source, cursor) {
+            prefix
+        } else {
+            return Vec::new();
+        };
+
+        let visible = self.visible_at(source, cursor);
+
+        // A name which does not occur in the design cannot be the prefix of a named entity
+        let mut names = prefix.iter();
+        let first = if let Some(name) = names.next() {
+            match self.lookup_symbol_utf8(name) {
+                Some(symbol) => Designator::Identifier(symbol),
+                None => return Vec::new(),
+            }
+        } else {
+            return visible;
+        };
+
+        let mut ent = if let Some(ent) = self.lookup_completion_prefix(&visible, &first) {
+            ent
+        } else {
+            return Vec::new();
+        };
+
+        for name in names {
+            let suffix = match self.lookup_symbol_utf8(name) {
+                Some(symbol) => Designator::Identifier(symbol),
+                None => return Vec::new(),
+            };
+            ent = if let Some(ent) = self.lookup_selected_prefix(&ent, &suffix) {
+                ent
+            } else {
+                return Vec::new();
+            };
+        }
+
+        self.list_selected(&ent)
+    }
+
+    /// Named entities visible at the cursor
+    ///
+    /// The regions of the statements and subprogram bodies enclosing the cursor are chained
+    /// from the innermost outwards to the design unit enclosing the cursor
+    pub(super) fn visible_at(&self, source: &Source, cursor: Position) -> Vec<Arc<NamedEntity>> {
+        let mut visible = CompletionOptions::default();
+
+        if let Some(unit) = self.get_unit_at(source, cursor) {
+            let data = self.get_analysis(unit);
+
+            let mut local_regions: Vec<_> = data
+                .result()
+                .local_regions
+                .iter()
+                .filter(|(source_range, _)| {
+                    source_range.source == *source
+                        && source_range.start() <= cursor
+                        && cursor <= source_range.end()
+                })
+                .collect();
+            // The enclosing ranges are nested so the innermost starts last
+            local_regions.sort_by_key(|(source_range, _)| std::cmp::Reverse(source_range.start()));
+            for (_, region) in local_regions {
+                visible.add_region(region);
+            }
+
+            visible.add_region(&data.result().region);
+            visible.add_region(&data.result().root_region);
+
+            if let AnyKind::Secondary(..) = unit.kind() {
+                let unit_id = unit.unit_id();
+                let primary = self
+                    .get_library_units(unit_id.library_name())
+                    .and_then(|units| units.get(&UnitKey::Primary(unit_id.primary_name().clone())));
+
+                if let Some(primary) = primary {
+                    visible.add_region(&self.get_analysis(primary).result().root_region);
+                }
+            }
+        }
+
+        visible.into_vec()
+    }
+
+    /// Lookup the first name of a selected name prefix among the visible names
+    /// Libraries are found even without a library clause since it is commonly typed
+    /// before the library clause is added
+    pub(super) fn lookup_completion_prefix(
+        &self,
+        visible: &[Arc<NamedEntity>],
+        designator: &Designator,
+    ) -> Option<Arc<NamedEntity>> {
+        visible
+            .iter()
+            .find(|ent| ent.designator() == designator && !ent.is_overloaded())
+            .cloned()
+            .or_else(|| {
+                self.get_library_ent(designator.expect_identifier())
+                    .cloned()
+            })
+    }
+
+    pub(super) fn lookup_selected_prefix(
+        &self,
+        prefix: &NamedEntity,
+        suffix: &Designator,
+    ) -> Option<Arc<NamedEntity>> {
+        match prefix.actual_kind() {
+            NamedEntityKind::Library => {
+                let library_name = prefix.as_actual().designator().expect_identifier();
+                let unit = self
+                    .get_library_units(library_name)?
+                    .get(&UnitKey::Primary(suffix.expect_identifier().clone()))?;
+                self.get_analysis(unit).result().ent.clone()
+            }
+            NamedEntityKind::Package(ref region)
+            | NamedEntityKind::PackageInstance(ref region)
+            | NamedEntityKind::LocalPackageInstance(ref region) => {
+                region.lookup_selected(suffix)?.as_non_overloaded().cloned()
+            }
+            NamedEntityKind::Object(ref object) => type_region(object.subtype.base_type())?
+                .lookup_selected(suffix)?
+                .as_non_overloaded()
+                .cloned(),
+            NamedEntityKind::ElementDeclaration(ref subtype) => type_region(subtype.base_type())?
+                .lookup_selected(suffix)?
+                .as_non_overloaded()
+                .cloned(),
+            _ => None,
+        }
+    }
+
+    /// List the named entities which may be selected from the prefix
+    fn list_selected(&self, prefix: &NamedEntity) -> Vec<Arc<NamedEntity>> {
+        let mut options = CompletionOptions::default();
+
+        match prefix.actual_kind() {
+            NamedEntityKind::Library => {
+                let library_name = prefix.as_actual().designator().expect_identifier();
+                if let Some(units) = self.get_library_units(library_name) {
+                    for unit in units.values() {
+                        if let AnyKind::Primary(..) = unit.kind() {
+                            if let Some(ref ent) = self.get_analysis(unit).result().ent {
+                                options.add(ent);
+                            }
+                        }
+                    }
+                }
+            }
+            NamedEntityKind::Package(ref region)
+            | NamedEntityKind::PackageInstance(ref region)
+            | NamedEntityKind::LocalPackageInstance(ref region) => {
+                options.add_immediates(region);
+            }
+            NamedEntityKind::Object(ref object) => {
+                if let Some(region) = type_region(object.subtype.base_type()) {
+                    options.add_immediates(region);
+                }
+            }
+            NamedEntityKind::ElementDeclaration(ref subtype) => {
+                if let Some(region) = type_region(subtype.base_type()) {
+                    options.add_immediates(region);
+                }
+            }
+            _ => {}
+        }
+
+        options.into_vec()
+    }
+}
+
+/// The region of a type whose elements may be selected
+pub(super) fn type_region(typ: &NamedEntity) -> Option<&Region<'static>> {
+    match typ.actual_kind() {
+        NamedEntityKind::RecordType(ref region) | NamedEntityKind::ProtectedType(ref region) => {
+            Some(region)
+        }
+        _ => None,
+    }
+}
+
+/// Collects unique named entities with identifier designators
+#[derive(Default)]
+struct CompletionOptions {
+    ids: FnvHashSet<EntityId>,
+    entities: Vec<Arc<NamedEntity>>,
+}
+
+impl CompletionOptions {
+    fn add(&mut self, ent: &Arc<NamedEntity>) {
+        if let Designator::Identifier(..) = ent.designator() {
+            if self.ids.insert(ent.id()) {
+                self.entities.push(ent.clone());
+            }
+        }
+    }
+
+    fn add_immediates(&mut self, region: &Region<'_>) {
+        for ent in region.immediates() {
+            self.add(ent);
+        }
+    }
+
+    fn add_region(&mut self, region: &Region<'_>) {
+        self.add_immediates(region);
+        for (designator, ent) in region.potentially_visible() {
+            if designator == ent.designator() {
+                self.add(ent);
+            } else {
+                // Visible with another name such as the work library
+                self.add(&Arc::new(NamedEntity::implicit(
+                    designator.clone(),
+                    NamedEntityKind::AliasOf(ent.clone()),
+                    ent.decl_pos(),
+                )));
+            }
+        }
+    }
+
+    fn into_vec(self) -> Vec<Arc<NamedEntity>> {
+        self.entities
+    }
+}
+
+fn is_identifier_char(chr: char) -> bool {
+    chr.is_ascii_alphanumeric() || chr == '_'
+}
+
+/// Find the names of the selected name prefix before the cursor
+/// Returns an empty prefix when the cursor is not within a selected name
+/// and None when the prefix is not a sequence of simple names
+///
+/// Example: With the cursor after `lib.pkg.na` the result is `["lib", "pkg"]`
+fn selected_name_prefix(source: &Source, cursor: Position) -> Option<Vec<String>> {
+    let contents = source.contents();
+    let line: Vec<char> = contents
+        .get_line(cursor.line as usize)?
+        .chars()
+        .take(cursor.character as usize)
+        .collect();
+
+    // Skip the partially typed identifier
+    let mut idx = line.len();
+    while idx > 0 && is_identifier_char(line[idx - 1]) {
+        idx -= 1;
+    }
+
+    let mut names = Vec::new();
+    while idx > 0 && line[idx - 1] == '.' {
+        let end = idx - 1;
+        let mut start = end;
+        while start > 0 && is_identifier_char(line[start - 1]) {
+            start -= 1;
+        }
+
+        if start == end {
+            return None;
+        }
+
+        names.push(line[start..end].iter().collect());
+        idx = start;
+    }
+
+    names.reverse();
+    Some(names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prefix_at(code: &str) -> Option<Vec<String>> {
+        let source = Source::inline(std::path::Path::new("file.vhd"), code);
+        let cursor = source.contents().range().end;
+        selected_name_prefix(&source, cursor)
+    }
+
+    #[test]
+    fn no_prefix() {
+        assert_eq!(prefix_at("  foo"), Some(vec![]));
+        assert_eq!(prefix_at("  foo "), Some(vec![]));
+    }
+
+    #[test]
+    fn selected_prefix() {
+        assert_eq!(prefix_at("use lib."), Some(vec!["lib".to_owned()]));
+        assert_eq!(
+            prefix_at("  x <= lib.pkg.na"),
+            Some(vec!["lib".to_owned(), "pkg".to_owned()])
+        );
+    }
+
+    #[test]
+    fn unsupported_prefix() {
+        assert_eq!(prefix_at("  x <= foo(0)."), None);
+    }
+}
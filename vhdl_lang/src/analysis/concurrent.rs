@@ -11,6 +11,7 @@ use super::*;
 use crate::ast::*;
 use crate::data::*;
 use analyze::*;
+use fnv::FnvHashSet;
 use region::*;
 use target::AssignmentType;
 
@@ -38,7 +39,7 @@ impl<'a> AnalyzeContext<'a> {
             parent.add(label.clone(), NamedEntityKind::Label, diagnostics);
         }
 
-        match statement.statement {
+        match statement.statement.item {
             ConcurrentStatement::Block(ref mut block) => {
                 if let Some(ref mut guard_condition) = block.guard_condition {
                     self.analyze_expression(parent, guard_condition, diagnostics)?;
@@ -58,6 +59,7 @@ impl<'a> AnalyzeContext<'a> {
                 }
                 self.analyze_declarative_part(&mut region, &mut block.decl, diagnostics)?;
                 self.analyze_concurrent_part(&mut region, &mut block.statements, diagnostics)?;
+                self.add_local_region(&statement.statement.pos, &region);
             }
             ConcurrentStatement::Process(ref mut process) => {
                 let ProcessStatement {
@@ -65,6 +67,7 @@ impl<'a> AnalyzeContext<'a> {
                     sensitivity_list,
                     decl,
                     statements,
+                    end_label_pos: _,
                 } = process;
                 if let Some(sensitivity_list) = sensitivity_list {
                     match sensitivity_list {
@@ -79,12 +82,14 @@ impl<'a> AnalyzeContext<'a> {
                 let mut region = parent.nested();
                 self.analyze_declarative_part(&mut region, decl, diagnostics)?;
                 self.analyze_sequential_part(&mut region, statements, diagnostics)?;
+                self.add_local_region(&statement.statement.pos, &region);
             }
             ConcurrentStatement::ForGenerate(ref mut gen) => {
                 let ForGenerateStatement {
                     index_name,
                     discrete_range,
                     body,
+                    end_label_pos: _,
                 } = gen;
                 self.analyze_discrete_range(parent, discrete_range, diagnostics)?;
                 let mut region = parent.nested();
@@ -99,7 +104,7 @@ impl<'a> AnalyzeContext<'a> {
                 let Conditionals {
                     conditionals,
                     else_item,
-                } = gen;
+                } = &mut gen.conds;
                 for conditional in conditionals.iter_mut() {
                     let Conditional { condition, item } = conditional;
                     self.analyze_expression(parent, condition, diagnostics)?;
@@ -112,7 +117,7 @@ impl<'a> AnalyzeContext<'a> {
                 }
             }
             ConcurrentStatement::CaseGenerate(ref mut gen) => {
-                for alternative in gen.alternatives.iter_mut() {
+                for alternative in gen.sels.alternatives.iter_mut() {
                     let mut region = parent.nested();
                     self.analyze_generate_body(&mut region, &mut alternative.item, diagnostics)?;
                 }
@@ -170,6 +175,8 @@ impl<'a> AnalyzeContext<'a> {
             alternative_label,
             decl,
             statements,
+            end_label_pos: _,
+            source_range,
         } = body;
         if let Some(label) = alternative_label {
             region.add(label.clone(), NamedEntityKind::Label, diagnostics);
@@ -178,6 +185,7 @@ impl<'a> AnalyzeContext<'a> {
             self.analyze_declarative_part(region, decl, diagnostics)?;
         }
         self.analyze_concurrent_part(region, statements, diagnostics)?;
+        self.add_local_region(source_range, region);
 
         Ok(())
     }
@@ -188,28 +196,37 @@ impl<'a> AnalyzeContext<'a> {
         instance: &mut InstantiationStatement,
         diagnostics: &mut dyn DiagnosticHandler,
     ) -> FatalNullResult {
-        match instance.unit {
+        let instantiated = match instance.unit {
             // @TODO architecture
             InstantiatedUnit::Entity(ref mut entity_name, ..) => {
                 fn is_entity(kind: &NamedEntityKind) -> bool {
                     matches!(kind, NamedEntityKind::Entity(..))
                 }
 
-                if let Err(err) =
-                    self.resolve_non_overloaded(parent, entity_name, &is_entity, "entity")
-                {
-                    err.add_to(diagnostics)?;
+                match self.resolve_non_overloaded(parent, entity_name, &is_entity, "entity") {
+                    Ok(ent) => Some((ent, entity_name.pos.clone())),
+                    Err(err) => {
+                        err.add_to(diagnostics)?;
+                        None
+                    }
                 }
             }
             InstantiatedUnit::Component(ref mut component_name) => {
                 fn is_component(kind: &NamedEntityKind) -> bool {
-                    matches!(kind, NamedEntityKind::Component)
+                    matches!(kind, NamedEntityKind::Component(..))
                 }
 
-                if let Err(err) =
-                    self.resolve_non_overloaded(parent, component_name, &is_component, "component")
-                {
-                    err.add_to(diagnostics)?;
+                match self.resolve_non_overloaded(
+                    parent,
+                    component_name,
+                    &is_component,
+                    "component",
+                ) {
+                    Ok(ent) => Some((ent, component_name.pos.clone())),
+                    Err(err) => {
+                        err.add_to(diagnostics)?;
+                        None
+                    }
                 }
             }
             InstantiatedUnit::Configuration(ref mut config_name) => {
@@ -225,12 +242,125 @@ impl<'a> AnalyzeContext<'a> {
                 ) {
                     err.add_to(diagnostics)?;
                 }
+                None
             }
         };
 
         self.analyze_assoc_elems(parent, &mut instance.generic_map, diagnostics)?;
         self.analyze_assoc_elems(parent, &mut instance.port_map, diagnostics)?;
 
+        if let Some((ent, pos)) = instantiated {
+            check_port_map(&ent, &pos, &instance.port_map, diagnostics);
+        }
+
         Ok(())
     }
 }
+
+/// Check the port map of an instance against the ports of the instantiated entity or component
+///
+/// Named associations must name a port and ports of mode in without a default value must be
+/// associated. Missing ports are not checked when the port map has positional associations or
+/// formals which are not a port or part of a port such as type conversions.
+fn check_port_map(
+    ent: &NamedEntity,
+    pos: &SrcPos,
+    port_map: &[AssociationElement],
+    diagnostics: &mut dyn DiagnosticHandler,
+) {
+    let region = match ent.actual_kind() {
+        NamedEntityKind::Entity(ref region) | NamedEntityKind::Component(ref region) => region,
+        _ => return,
+    };
+    let is_port = |designator: &Designator| {
+        region
+            .lookup_immediate(designator)
+            .and_then(|ents| ents.as_non_overloaded())
+            .map(|ent| ent.is_port())
+            .unwrap_or(false)
+    };
+
+    let mut associated = FnvHashSet::default();
+    let mut is_known = true;
+
+    for elem in port_map.iter() {
+        let formal = match elem.formal {
+            Some(ref formal) => formal,
+            None => {
+                is_known = false;
+                continue;
+            }
+        };
+
+        // A port whose declaration could not be analyzed is not in the region
+        if let Name::Designator(ref designator) = formal.item {
+            if !is_port(&designator.item) && !region.has_invalid_interface() {
+                diagnostics.push(Diagnostic::error(
+                    &formal.pos,
+                    format!(
+                        "No declaration of '{}' within {}",
+                        designator.item,
+                        ent.describe()
+                    ),
+                ));
+                continue;
+            }
+        }
+
+        match formal_prefix(&formal.item) {
+            Some(designator) if is_port(designator) => {
+                associated.insert(designator.clone());
+            }
+            _ => {
+                is_known = false;
+            }
+        }
+    }
+
+    if !is_known {
+        return;
+    }
+
+    let mut missing: Vec<_> = region
+        .immediates()
+        .filter(|port| {
+            port.is_port()
+                && !associated.contains(port.designator())
+                && matches!(
+                    port.kind(),
+                    NamedEntityKind::Object(Object {
+                        mode: Some(Mode::In),
+                        has_default: false,
+                        ..
+                    })
+                )
+        })
+        .collect();
+    missing.sort_by_key(|port| port.decl_pos().map(|pos| pos.start()));
+
+    for port in missing {
+        let mut error = Diagnostic::error(
+            pos,
+            format!(
+                "Missing association of port '{}' of mode in without a default value",
+                port.designator()
+            ),
+        );
+        if let Some(decl_pos) = port.decl_pos() {
+            error.add_related(decl_pos, "Defined here");
+        }
+        diagnostics.push(error);
+    }
+}
+
+/// The simple name prefix of a formal such as `data` of `data(0)` or `data.valid`
+pub(super) fn formal_prefix(formal: &Name) -> Option<&Designator> {
+    match formal {
+        Name::Designator(ref designator) => Some(&designator.item),
+        Name::Selected(ref prefix, _)
+        | Name::Indexed(ref prefix, _)
+        | Name::Slice(ref prefix, _) => formal_prefix(&prefix.item),
+        Name::FunctionCall(ref call) => formal_prefix(&call.name.item),
+        _ => None,
+    }
+}
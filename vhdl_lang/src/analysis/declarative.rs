@@ -242,6 +242,7 @@ impl<'a> AnalyzeContext<'a> {
                             NamedEntityKind::Object(Object {
                                 class: object_decl.class,
                                 mode: None,
+                                list_type: None,
                                 has_default: object_decl.expression.is_some(),
                                 subtype,
                             })
@@ -268,11 +269,24 @@ impl<'a> AnalyzeContext<'a> {
                 region.add(ident.clone(), NamedEntityKind::File, diagnostics);
             }
             Declaration::Component(ref mut component) => {
-                region.add(&component.ident, NamedEntityKind::Component, diagnostics);
-                let mut region = region.nested();
-                self.analyze_interface_list(&mut region, &mut component.generic_list, diagnostics)?;
-                self.analyze_interface_list(&mut region, &mut component.port_list, diagnostics)?;
-                region.close(diagnostics);
+                let mut component_region = region.nested();
+                self.analyze_interface_list(
+                    &mut component_region,
+                    &mut component.generic_list,
+                    diagnostics,
+                )?;
+                self.analyze_interface_list(
+                    &mut component_region,
+                    &mut component.port_list,
+                    diagnostics,
+                )?;
+                component_region.close(diagnostics);
+                let component_region = component_region.without_parent();
+                region.add(
+                    &component.ident,
+                    NamedEntityKind::Component(Arc::new(component_region)),
+                    diagnostics,
+                );
             }
             Declaration::Attribute(ref mut attr) => match attr {
                 Attribute::Declaration(ref mut attr_decl) => {
@@ -321,6 +335,7 @@ impl<'a> AnalyzeContext<'a> {
                     &mut body.statements,
                     diagnostics,
                 )?;
+                self.add_local_region(&body.source_range, &subpgm_region);
             }
             Declaration::SubprogramDeclaration(ref mut subdecl) => {
                 let mut subpgm_region = region.nested();
@@ -420,7 +435,12 @@ impl<'a> AnalyzeContext<'a> {
                                         &mut body.decl,
                                         diagnostics,
                                     )?;
-                                    parent.add_protected_body(type_decl.ident.clone(), diagnostics);
+                                    let region = region.without_parent();
+                                    parent.add_protected_body(
+                                        type_decl.ident.clone(),
+                                        region,
+                                        diagnostics,
+                                    );
                                     true
                                 } else {
                                     false
@@ -698,6 +718,7 @@ impl<'a> AnalyzeContext<'a> {
                 NamedEntityKind::Object(Object {
                     class: ObjectClass::Constant,
                     mode: Some(Mode::In),
+                    list_type: Some(InterfaceListType::Parameter),
                     subtype: Subtype::new(string),
                     has_default: false,
                 }),
@@ -765,6 +786,7 @@ impl<'a> AnalyzeContext<'a> {
             NamedEntityKind::Object(Object {
                 class: ObjectClass::Constant,
                 mode: Some(Mode::In),
+                list_type: Some(InterfaceListType::Parameter),
                 subtype: Subtype::new(type_ent.clone()),
                 has_default: false,
             }),
@@ -853,6 +875,7 @@ impl<'a> AnalyzeContext<'a> {
                     NamedEntityKind::Object(Object {
                         class: object_decl.class,
                         mode: Some(object_decl.mode),
+                        list_type: Some(object_decl.list_type),
                         subtype,
                         has_default: object_decl.expression.is_some(),
                     }),
@@ -903,6 +926,7 @@ impl<'a> AnalyzeContext<'a> {
                     region.add_named_entity(ent.clone(), diagnostics);
                 }
                 Err(err) => {
+                    region.add_invalid_interface();
                     err.add_to(diagnostics)?;
                 }
             }
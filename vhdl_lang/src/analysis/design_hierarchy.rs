@@ -0,0 +1,571 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2020, Olof Kraigher olof.kraigher@gmail.com
+
+use super::named_entity::*;
+use super::root::*;
+use crate::ast::*;
+use crate::data::*;
+
+/// An instance within the design hierarchy
+#[derive(PartialEq, Clone, Debug)]
+pub struct HierarchyNode {
+    /// The instance label or None for the top level
+    pub label: Option<Symbol>,
+    /// The position of the instance label or of the top level entity or configuration
+    pub pos: SrcPos,
+    pub library_name: Symbol,
+    /// The name of the bound entity or of the component when the instance is unbound
+    pub entity_name: Symbol,
+    pub architecture_name: Option<Symbol>,
+    /// The position of the architecture, or of the entity when it has no architecture,
+    /// or None when the instance is unbound
+    pub unit_pos: Option<SrcPos>,
+    pub children: Vec<HierarchyNode>,
+}
+
+/// The entity and architecture bound to an instance
+struct Binding {
+    library_name: Symbol,
+    entity_name: Symbol,
+    architecture_name: Option<Symbol>,
+    /// The block configuration of the architecture from a configuration declaration
+    config: Option<BlockConfiguration>,
+}
+
+impl DesignRoot {
+    /// Find the tree of instances below the top level entity or configuration
+    /// All libraries are searched in alphabetical order when no library name is given
+    pub fn find_design_hierarchy(
+        &self,
+        library_name: Option<&Symbol>,
+        name: &Symbol,
+    ) -> Option<HierarchyNode> {
+        let library_names = match library_name {
+            Some(library_name) => vec![library_name],
+            None => {
+                let mut library_names: Vec<_> = self.library_names().collect();
+                library_names.sort_by_key(|library_name| library_name.name_utf8());
+                library_names
+            }
+        };
+
+        for library_name in library_names {
+            let unit = match self
+                .get_library_units(library_name)
+                .and_then(|units| units.get(&UnitKey::Primary(name.clone())))
+            {
+                Some(unit) => unit,
+                None => continue,
+            };
+
+            let binding = match *unit.unit.read() {
+                AnyDesignUnit::Primary(AnyPrimaryUnit::Entity(..)) => Binding {
+                    library_name: library_name.clone(),
+                    // Use the spelling of the declaration rather than of the given name
+                    entity_name: unit.ident().item.clone(),
+                    architecture_name: None,
+                    config: None,
+                },
+                AnyDesignUnit::Primary(AnyPrimaryUnit::Configuration(..)) => {
+                    match self.configuration_binding(library_name, name) {
+                        Some(binding) => binding,
+                        None => continue,
+                    }
+                }
+                _ => continue,
+            };
+
+            let mut elaborator = Elaborator::new(self);
+            return Some(elaborator.node(None, unit.ident().pos.clone(), binding));
+        }
+        None
+    }
+
+    /// The entity and architecture configured by a configuration declaration
+    fn configuration_binding(&self, library_name: &Symbol, name: &Symbol) -> Option<Binding> {
+        let unit = self
+            .get_library_units(library_name)?
+            .get(&UnitKey::Primary(name.clone()))?;
+
+        match *unit.unit.read() {
+            AnyDesignUnit::Primary(AnyPrimaryUnit::Configuration(ref config)) => Some(Binding {
+                // A configuration must be within the same library as its entity
+                library_name: library_name.clone(),
+                entity_name: selected_designator(&config.entity_name.item)?.clone(),
+                architecture_name: block_spec_label(&config.block_config.block_spec.item).cloned(),
+                config: Some(config.block_config.clone()),
+            }),
+            _ => None,
+        }
+    }
+
+    /// The architecture used when none is given
+    /// Of several architectures the last one in file order is chosen since the
+    /// most recently analyzed architecture is not known
+    fn default_architecture(&self, library_name: &Symbol, entity_name: &Symbol) -> Option<Symbol> {
+        self.get_library_units(library_name)?
+            .values()
+            .filter(|unit| {
+                unit.kind() == AnyKind::Secondary(SecondaryKind::Architecture)
+                    && unit.unit_id().primary_name() == entity_name
+            })
+            .max_by(|lhs, rhs| lhs.ident().pos.cmp(&rhs.ident().pos))
+            .map(|unit| unit.ident().item.clone())
+    }
+
+    /// The library of an entity bound by default to a component of the same name
+    /// Entities within the same library as the instance are preferred
+    pub(super) fn default_entity_library(
+        &self,
+        library_name: &Symbol,
+        name: &Symbol,
+    ) -> Option<Symbol> {
+        let is_entity = |library_name: &Symbol| {
+            self.get_library_units(library_name)
+                .and_then(|units| units.get(&UnitKey::Primary(name.clone())))
+                .map(|unit| unit.kind() == AnyKind::Primary(PrimaryKind::Entity))
+                .unwrap_or(false)
+        };
+
+        if is_entity(library_name) {
+            return Some(library_name.clone());
+        }
+
+        let mut library_names: Vec<_> = self
+            .library_names()
+            .filter(|library_name| is_entity(library_name))
+            .collect();
+        library_names.sort_by_key(|library_name| library_name.name_utf8());
+        library_names.first().cloned().cloned()
+    }
+}
+
+struct Elaborator<'a> {
+    root: &'a DesignRoot,
+    work_sym: Symbol,
+    /// The library, entity and architecture names of the units being elaborated
+    /// to stop at recursive instantiations
+    stack: Vec<(Symbol, Symbol, Symbol)>,
+}
+
+impl<'a> Elaborator<'a> {
+    fn new(root: &'a DesignRoot) -> Elaborator<'a> {
+        Elaborator {
+            root,
+            work_sym: root.symbol_utf8("work"),
+            stack: Vec::new(),
+        }
+    }
+
+    fn node(&mut self, label: Option<&Ident>, pos: SrcPos, binding: Binding) -> HierarchyNode {
+        let mut node = HierarchyNode {
+            label: label.map(|label| label.item.clone()),
+            pos,
+            library_name: binding.library_name.clone(),
+            entity_name: binding.entity_name.clone(),
+            architecture_name: None,
+            unit_pos: None,
+            children: Vec::new(),
+        };
+
+        let units = match self.root.get_library_units(&binding.library_name) {
+            Some(units) => units,
+            None => return node,
+        };
+
+        match units.get(&UnitKey::Primary(binding.entity_name.clone())) {
+            Some(unit) if unit.kind() == AnyKind::Primary(PrimaryKind::Entity) => {
+                node.unit_pos = Some(unit.ident().pos.clone());
+            }
+            _ => return node,
+        }
+
+        let architecture_name = match binding.architecture_name.clone().or_else(|| {
+            self.root
+                .default_architecture(&binding.library_name, &binding.entity_name)
+        }) {
+            Some(name) => name,
+            None => return node,
+        };
+
+        let unit = match units.get(&UnitKey::Secondary(
+            binding.entity_name.clone(),
+            architecture_name.clone(),
+        )) {
+            Some(unit) => unit,
+            None => return node,
+        };
+
+        node.architecture_name = Some(architecture_name.clone());
+        node.unit_pos = Some(unit.ident().pos.clone());
+
+        let key = (
+            binding.library_name.clone(),
+            binding.entity_name,
+            architecture_name,
+        );
+        if self.stack.contains(&key) {
+            return node;
+        }
+
+        self.stack.push(key);
+        if let AnyDesignUnit::Secondary(AnySecondaryUnit::Architecture(ref arch)) =
+            *unit.unit.read()
+        {
+            self.instances(
+                &binding.library_name,
+                &arch.decl,
+                &arch.statements,
+                binding.config.as_ref(),
+                &mut node.children,
+            );
+        }
+        self.stack.pop();
+
+        node
+    }
+
+    /// Elaborate the instances within the statements of an architecture, block or generate
+    fn instances(
+        &mut self,
+        library_name: &Symbol,
+        decl: &[Declaration],
+        statements: &[LabeledConcurrentStatement],
+        config: Option<&BlockConfiguration>,
+        result: &mut Vec<HierarchyNode>,
+    ) {
+        for statement in statements.iter() {
+            let label = match statement.label {
+                Some(ref label) => label,
+                // Instances, blocks and generate statements always have a label
+                None => continue,
+            };
+            let nested_config = config.and_then(|config| nested_block_config(config, label));
+
+            match statement.statement.item {
+                ConcurrentStatement::Instance(ref inst) => {
+                    let binding = self.instance_binding(library_name, decl, config, label, inst);
+                    let node =
+                        self.instance_node(library_name, label, instantiated_name(inst), binding);
+                    result.push(node);
+                }
+                // A component instantiation without generic or port map is parsed as a procedure call
+                ConcurrentStatement::ProcedureCall(ref pcall) => {
+                    if let Some(component_name) = component_call_name(pcall) {
+                        let binding = self.component_binding(
+                            library_name,
+                            decl,
+                            config,
+                            label,
+                            component_name,
+                        );
+                        let node = self.instance_node(library_name, label, component_name, binding);
+                        result.push(node);
+                    }
+                }
+                ConcurrentStatement::Block(ref block) => {
+                    self.instances(
+                        library_name,
+                        &block.decl,
+                        &block.statements,
+                        nested_config,
+                        result,
+                    );
+                }
+                ConcurrentStatement::ForGenerate(ref gen) => {
+                    self.generate_body(library_name, &gen.body, nested_config, result);
+                }
+                ConcurrentStatement::IfGenerate(ref gen) => {
+                    for cond in gen.conds.conditionals.iter() {
+                        self.generate_body(library_name, &cond.item, nested_config, result);
+                    }
+                    if let Some(ref else_item) = gen.conds.else_item {
+                        self.generate_body(library_name, else_item, nested_config, result);
+                    }
+                }
+                ConcurrentStatement::CaseGenerate(ref gen) => {
+                    for alternative in gen.sels.alternatives.iter() {
+                        self.generate_body(library_name, &alternative.item, nested_config, result);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn instance_node(
+        &mut self,
+        library_name: &Symbol,
+        label: &Ident,
+        name: &Symbol,
+        binding: Option<Binding>,
+    ) -> HierarchyNode {
+        match binding {
+            Some(binding) => self.node(Some(label), label.pos.clone(), binding),
+            None => HierarchyNode {
+                label: Some(label.item.clone()),
+                pos: label.pos.clone(),
+                library_name: library_name.clone(),
+                entity_name: name.clone(),
+                architecture_name: None,
+                unit_pos: None,
+                children: Vec::new(),
+            },
+        }
+    }
+
+    fn generate_body(
+        &mut self,
+        library_name: &Symbol,
+        body: &GenerateBody,
+        config: Option<&BlockConfiguration>,
+        result: &mut Vec<HierarchyNode>,
+    ) {
+        let decl = body.decl.as_deref().unwrap_or(&[]);
+        self.instances(library_name, decl, &body.statements, config, result);
+    }
+
+    /// The binding of an instance or None when it is unbound
+    fn instance_binding(
+        &self,
+        library_name: &Symbol,
+        decl: &[Declaration],
+        config: Option<&BlockConfiguration>,
+        label: &Ident,
+        inst: &InstantiationStatement,
+    ) -> Option<Binding> {
+        match inst.unit {
+            InstantiatedUnit::Entity(ref name, ref architecture) => {
+                let (library_name, entity_name) = self.selected_unit(library_name, &name.item)?;
+                Some(Binding {
+                    library_name,
+                    entity_name,
+                    architecture_name: architecture.as_ref().map(|ident| ident.item.clone()),
+                    config: None,
+                })
+            }
+            InstantiatedUnit::Configuration(ref name) => {
+                let (library_name, config_name) = self.selected_unit(library_name, &name.item)?;
+                self.root.configuration_binding(&library_name, &config_name)
+            }
+            InstantiatedUnit::Component(ref name) => {
+                let component_name = selected_designator(&name.item)?;
+                self.component_binding(library_name, decl, config, label, component_name)
+            }
+        }
+    }
+
+    /// The binding of a component instance or None when it is unbound
+    fn component_binding(
+        &self,
+        library_name: &Symbol,
+        decl: &[Declaration],
+        config: Option<&BlockConfiguration>,
+        label: &Ident,
+        component_name: &Symbol,
+    ) -> Option<Binding> {
+        // A component configuration within a configuration declaration
+        // takes precedence over a configuration specification
+        let (bind_ind, block_config) =
+            match config.and_then(|config| component_config(config, label, component_name)) {
+                Some(component) => (component.bind_ind.as_ref(), component.block_config.as_ref()),
+                None => (
+                    configuration_spec(decl, label, component_name).map(|spec| &spec.bind_ind),
+                    None,
+                ),
+            };
+
+        let block_label =
+            block_config.and_then(|block_config| block_spec_label(&block_config.block_spec.item));
+
+        match bind_ind.and_then(|bind_ind| bind_ind.entity_aspect.as_ref()) {
+            Some(EntityAspect::Entity(ref name, ref architecture)) => {
+                let (library_name, entity_name) = self.selected_unit(library_name, &name.item)?;
+                Some(Binding {
+                    library_name,
+                    entity_name,
+                    architecture_name: architecture
+                        .as_ref()
+                        .map(|ident| &ident.item)
+                        .or(block_label)
+                        .cloned(),
+                    config: block_config.cloned(),
+                })
+            }
+            Some(EntityAspect::Configuration(ref name)) => {
+                let (library_name, config_name) = self.selected_unit(library_name, &name.item)?;
+                self.root.configuration_binding(&library_name, &config_name)
+            }
+            Some(EntityAspect::Open) => None,
+            // Default binding to an entity with the same name as the component
+            None => Some(Binding {
+                library_name: self
+                    .root
+                    .default_entity_library(library_name, component_name)?,
+                entity_name: component_name.clone(),
+                architecture_name: block_label.cloned(),
+                config: block_config.cloned(),
+            }),
+        }
+    }
+
+    /// The library and primary unit name of lib.name or of a name within the current library
+    fn selected_unit(
+        &self,
+        library_name: &Symbol,
+        name: &SelectedName,
+    ) -> Option<(Symbol, Symbol)> {
+        match name {
+            SelectedName::Designator(ref designator) => Some((
+                library_name.clone(),
+                designator.item.as_identifier()?.clone(),
+            )),
+            SelectedName::Selected(ref prefix, ref suffix) => {
+                let prefix_name = selected_designator(&prefix.item)?;
+                let prefix_name = if prefix_name == &self.work_sym {
+                    library_name
+                } else {
+                    prefix_name
+                };
+                Some((
+                    prefix_name.clone(),
+                    suffix.item.item.as_identifier()?.clone(),
+                ))
+            }
+        }
+    }
+}
+
+/// The name of the instantiated component, entity or configuration
+fn instantiated_name(inst: &InstantiationStatement) -> &Symbol {
+    let name = match inst.unit {
+        InstantiatedUnit::Component(ref name)
+        | InstantiatedUnit::Entity(ref name, _)
+        | InstantiatedUnit::Configuration(ref name) => name,
+    };
+    let designator = match name.item {
+        SelectedName::Designator(ref designator) => designator,
+        SelectedName::Selected(_, ref suffix) => &suffix.item,
+    };
+    // Instantiated units are always named by identifiers
+    designator.item.expect_identifier()
+}
+
+/// The name of the component when the procedure call is a component instantiation
+pub(super) fn component_call_name(pcall: &ConcurrentProcedureCall) -> Option<&Symbol> {
+    if !pcall.call.parameters.is_empty() {
+        return None;
+    }
+
+    let designator = match pcall.call.name.item {
+        Name::Designator(ref designator) => designator,
+        Name::Selected(_, ref suffix) => &suffix.item,
+        _ => return None,
+    };
+
+    match designator.reference {
+        Some(ref ent) if matches!(ent.kind(), NamedEntityKind::Component(..)) => {
+            designator.item.as_identifier()
+        }
+        _ => None,
+    }
+}
+
+/// The simple name of the suffix of a selected name
+fn selected_designator(name: &SelectedName) -> Option<&Symbol> {
+    match name {
+        SelectedName::Designator(ref designator) => designator.item.as_identifier(),
+        SelectedName::Selected(_, ref suffix) => suffix.item.item.as_identifier(),
+    }
+}
+
+/// The label of a block specification such as `rtl`, `gen(0 to 3)` or `gen(alt)`
+fn block_spec_label(name: &Name) -> Option<&Symbol> {
+    match name {
+        Name::Designator(ref designator) => designator.item.as_identifier(),
+        Name::Indexed(ref prefix, _) | Name::Slice(ref prefix, _) => block_spec_label(&prefix.item),
+        Name::FunctionCall(ref call) => block_spec_label(&call.name.item),
+        _ => None,
+    }
+}
+
+/// The block configuration of a nested block or generate statement
+fn nested_block_config<'a>(
+    config: &'a BlockConfiguration,
+    label: &Ident,
+) -> Option<&'a BlockConfiguration> {
+    config.items.iter().find_map(|item| match item {
+        ConfigurationItem::Block(ref block) => {
+            if block_spec_label(&block.block_spec.item) == Some(&label.item) {
+                Some(block)
+            } else {
+                None
+            }
+        }
+        ConfigurationItem::Component(..) => None,
+    })
+}
+
+/// Find the component configuration applying to the instance
+fn component_config<'a>(
+    config: &'a BlockConfiguration,
+    label: &Ident,
+    component_name: &Symbol,
+) -> Option<&'a ComponentConfiguration> {
+    let components = config.items.iter().filter_map(|item| match item {
+        ConfigurationItem::Component(ref component) => Some(component),
+        ConfigurationItem::Block(..) => None,
+    });
+    find_component_spec(
+        components,
+        |component| &component.spec,
+        label,
+        component_name,
+    )
+}
+
+/// Find the configuration specification applying to the instance
+fn configuration_spec<'a>(
+    decl: &'a [Declaration],
+    label: &Ident,
+    component_name: &Symbol,
+) -> Option<&'a ConfigurationSpecification> {
+    let specs = decl.iter().filter_map(|decl| match decl {
+        Declaration::Configuration(ref spec) => Some(spec),
+        _ => None,
+    });
+    find_component_spec(specs, |spec| &spec.spec, label, component_name)
+}
+
+/// Find the item whose component specification applies to the instance
+/// An explicit label takes precedence over `all` and `others`
+fn find_component_spec<'a, T>(
+    items: impl Iterator<Item = &'a T> + Clone,
+    spec: impl Fn(&T) -> &ComponentSpecification,
+    label: &Ident,
+    component_name: &Symbol,
+) -> Option<&'a T> {
+    let mut matching = items.filter(|item| {
+        selected_designator(&spec(item).component_name.item) == Some(component_name)
+    });
+
+    matching
+        .clone()
+        .find(|item| match spec(item).instantiation_list {
+            InstantiationList::Labels(ref labels) => {
+                labels.iter().any(|ident| ident.item == label.item)
+            }
+            _ => false,
+        })
+        .or_else(|| {
+            matching.find(|item| {
+                matches!(
+                    spec(item).instantiation_list,
+                    InstantiationList::All | InstantiationList::Others
+                )
+            })
+        })
+}
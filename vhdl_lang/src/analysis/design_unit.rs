@@ -42,9 +42,11 @@ impl<'a> AnalyzeContext<'a> {
             },
             AnyDesignUnit::Secondary(unit) => match unit {
                 AnySecondaryUnit::Architecture(unit) => {
-                    self.analyze_architecture(id, unit, diagnostics)
+                    self.analyze_architecture(id, unit, root_region, region, diagnostics)
+                }
+                AnySecondaryUnit::PackageBody(unit) => {
+                    self.analyze_package_body(unit, root_region, region, diagnostics)
                 }
-                AnySecondaryUnit::PackageBody(unit) => self.analyze_package_body(unit, diagnostics),
             },
         }
     }
@@ -204,6 +206,8 @@ impl<'a> AnalyzeContext<'a> {
         &self,
         id: EntityId,
         unit: &mut ArchitectureBody,
+        root_region: &mut Region<'_>,
+        region: &mut Region<'_>,
         diagnostics: &mut dyn DiagnosticHandler,
     ) -> FatalNullResult {
         unit.entity_name.clear_reference();
@@ -226,12 +230,17 @@ impl<'a> AnalyzeContext<'a> {
             unit.entity_name.set_unique_reference(named_entity);
         }
 
-        let mut root_region = Region::default().with_parent(&entity.result().root_region);
-        self.analyze_context_clause(&mut root_region, &mut unit.context_clause, diagnostics)?;
-        let mut region = Region::extend(&entity.result().region, Some(&root_region));
+        let mut secondary_root_region = Region::default().with_parent(&entity.result().root_region);
+        self.analyze_context_clause(
+            &mut secondary_root_region,
+            &mut unit.context_clause,
+            diagnostics,
+        )?;
+        let mut secondary_region =
+            Region::extend(&entity.result().region, Some(&secondary_root_region));
 
         // Architecture name is visible
-        region.make_potentially_visible(
+        secondary_region.make_potentially_visible(
             Some(unit.pos()),
             Arc::new(NamedEntity::new_with_id(
                 id,
@@ -241,15 +250,20 @@ impl<'a> AnalyzeContext<'a> {
             )),
         );
 
-        self.analyze_declarative_part(&mut region, &mut unit.decl, diagnostics)?;
-        self.analyze_concurrent_part(&mut region, &mut unit.statements, diagnostics)?;
-        region.close(diagnostics);
+        self.analyze_declarative_part(&mut secondary_region, &mut unit.decl, diagnostics)?;
+        self.analyze_concurrent_part(&mut secondary_region, &mut unit.statements, diagnostics)?;
+        secondary_region.close(diagnostics);
+
+        *region = secondary_region.without_parent();
+        *root_region = secondary_root_region.without_parent();
         Ok(())
     }
 
     fn analyze_package_body(
         &self,
         unit: &mut PackageBody,
+        root_region: &mut Region<'_>,
+        region: &mut Region<'_>,
         diagnostics: &mut dyn DiagnosticHandler,
     ) -> FatalNullResult {
         unit.ident.clear_reference();
@@ -273,13 +287,22 @@ impl<'a> AnalyzeContext<'a> {
         }
 
         // @TODO make pattern of primary/secondary extension
-        let mut root_region = Region::default().with_parent(&package.result().root_region);
-        self.analyze_context_clause(&mut root_region, &mut unit.context_clause, diagnostics)?;
+        let mut secondary_root_region =
+            Region::default().with_parent(&package.result().root_region);
+        self.analyze_context_clause(
+            &mut secondary_root_region,
+            &mut unit.context_clause,
+            diagnostics,
+        )?;
+
+        let mut secondary_region =
+            Region::extend(&package.result().region, Some(&secondary_root_region));
 
-        let mut region = Region::extend(&package.result().region, Some(&root_region));
+        self.analyze_declarative_part(&mut secondary_region, &mut unit.decl, diagnostics)?;
+        secondary_region.close(diagnostics);
 
-        self.analyze_declarative_part(&mut region, &mut unit.decl, diagnostics)?;
-        region.close(diagnostics);
+        *region = secondary_region.without_parent();
+        *root_region = secondary_root_region.without_parent();
         Ok(())
     }
 
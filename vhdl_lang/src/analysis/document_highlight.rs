@@ -0,0 +1,151 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2020, Olof Kraigher olof.kraigher@gmail.com
+
+use super::root::*;
+use crate::ast::search::*;
+use crate::ast::*;
+use crate::data::*;
+use fnv::FnvHashSet;
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum OccurrenceKind {
+    Read,
+    /// The named entity is the target of an assignment
+    Write,
+}
+
+/// An occurrence of the identifier of a declaration within a source
+#[derive(PartialEq, Clone, Debug)]
+pub struct Occurrence {
+    pub pos: SrcPos,
+    pub kind: OccurrenceKind,
+}
+
+impl DesignRoot {
+    /// Find all occurrences of the declaration at decl_pos within the source
+    /// The declaration itself and repeated end identifiers are considered to be read
+    pub fn find_occurrences(&self, source: &Source, decl_pos: &SrcPos) -> Vec<Occurrence> {
+        let mut searcher = FindOccurrences::new(source, decl_pos);
+        let _ = self.search(&mut searcher);
+
+        let mut occurrences = searcher.occurrences;
+        occurrences.sort_by_key(|occurrence| occurrence.pos.start());
+        occurrences.dedup_by(|lhs, rhs| lhs.pos == rhs.pos);
+        occurrences
+    }
+}
+
+struct FindOccurrences {
+    source: Source,
+    decl_pos: SrcPos,
+    /// The positions of names which are written by an assignment
+    written: FnvHashSet<SrcPos>,
+    occurrences: Vec<Occurrence>,
+}
+
+impl FindOccurrences {
+    fn new(source: &Source, decl_pos: &SrcPos) -> FindOccurrences {
+        FindOccurrences {
+            source: source.clone(),
+            decl_pos: decl_pos.clone(),
+            written: FnvHashSet::default(),
+            occurrences: Vec::new(),
+        }
+    }
+
+    fn add(&mut self, pos: &SrcPos) {
+        if pos.source == self.source {
+            let kind = if self.written.contains(pos) {
+                OccurrenceKind::Write
+            } else {
+                OccurrenceKind::Read
+            };
+
+            self.occurrences.push(Occurrence {
+                pos: pos.clone(),
+                kind,
+            });
+        }
+    }
+
+    /// Add the names which are written when the name is the target of an assignment
+    /// Index expressions and slice ranges are only read
+    fn add_written_name(&mut self, pos: &SrcPos, name: &Name) {
+        match name {
+            Name::Designator(..) => {
+                self.written.insert(pos.clone());
+            }
+            Name::Selected(prefix, suffix) => {
+                self.add_written_name(&prefix.pos, &prefix.item);
+                self.written.insert(suffix.pos.clone());
+            }
+            Name::Indexed(prefix, ..) | Name::Slice(prefix, ..) => {
+                self.add_written_name(&prefix.pos, &prefix.item);
+            }
+            Name::FunctionCall(fcall) => {
+                self.add_written_name(&fcall.name.pos, &fcall.name.item);
+            }
+            Name::SelectedAll(..) | Name::Attribute(..) | Name::External(..) => {}
+        }
+    }
+
+    fn add_written_expression(&mut self, expr: &WithPos<Expression>) {
+        match expr.item {
+            Expression::Name(ref name) => self.add_written_name(&expr.pos, name),
+            Expression::Aggregate(ref assocs) => self.add_written_aggregate(assocs),
+            _ => {}
+        }
+    }
+
+    fn add_written_aggregate(&mut self, assocs: &[ElementAssociation]) {
+        for assoc in assocs.iter() {
+            match assoc {
+                ElementAssociation::Positional(ref expr)
+                | ElementAssociation::Named(_, ref expr) => self.add_written_expression(expr),
+            }
+        }
+    }
+}
+
+impl Searcher for FindOccurrences {
+    fn search_assignment_target(&mut self, target: &WithPos<Target>) -> SearchState {
+        match target.item {
+            Target::Name(ref name) => self.add_written_name(&target.pos, name),
+            Target::Aggregate(ref assocs) => self.add_written_aggregate(assocs),
+        }
+        NotFinished
+    }
+
+    fn search_decl_pos(&mut self, pos: &SrcPos) -> SearchState {
+        if pos == &self.decl_pos {
+            self.add(pos);
+        }
+        NotFinished
+    }
+
+    fn search_end_ident_pos(&mut self, decl_pos: &SrcPos, end_pos: &SrcPos) -> SearchState {
+        if decl_pos == &self.decl_pos {
+            self.add(end_pos);
+        }
+        NotFinished
+    }
+
+    fn search_pos_with_ref(&mut self, pos: &SrcPos, reference: &Reference) -> SearchState {
+        if reference.as_ref().and_then(|ent| ent.decl_pos()) == Some(&self.decl_pos) {
+            self.add(pos);
+        }
+        NotFinished
+    }
+
+    // Assume source is searched first to filter out design units in other files
+    fn search_source(&mut self, source: &Source) -> SearchState {
+        if source == &self.source {
+            NotFinished
+        } else {
+            Finished(NotFound)
+        }
+    }
+}
@@ -0,0 +1,138 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2020, Olof Kraigher olof.kraigher@gmail.com
+
+use super::root::*;
+use crate::ast::search::*;
+use crate::ast::Range;
+use crate::ast::*;
+use crate::data::*;
+
+/// An architecture and a testbench of an entity
+#[derive(PartialEq, Clone, Debug)]
+pub struct EntitySkeletons {
+    pub library_name: Symbol,
+    pub entity_name: Symbol,
+    /// The end of the entity declaration where the architecture is inserted
+    pub entity_end: SrcPos,
+    /// An architecture named rtl preceded by an empty line
+    pub architecture: String,
+    /// A testbench entity and architecture for a design file of its own
+    pub testbench: String,
+}
+
+impl DesignRoot {
+    /// Find the skeletons of the entity declaration enclosing the cursor
+    pub fn find_entity_skeletons(
+        &self,
+        source: &Source,
+        cursor: Position,
+    ) -> Option<EntitySkeletons> {
+        let unit = self.get_unit_at(source, cursor)?;
+        let library_name = unit.unit_id().library_name().clone();
+
+        // The types of the ports are searched after the entity is no longer read
+        let entity = {
+            let entity = unit.unit.read();
+            match *entity {
+                AnyDesignUnit::Primary(AnyPrimaryUnit::Entity(ref entity)) => entity.clone(),
+                _ => return None,
+            }
+        };
+
+        let range = &entity.source_range;
+        if cursor < range.start() || range.end() < cursor {
+            return None;
+        }
+
+        // The testbench is a new file which is not mapped to the library of the entity
+        let end = range.end();
+        let testbench = entity.testbench(&library_name, |subtype| self.index_subtypes(subtype));
+        Some(EntitySkeletons {
+            entity_name: entity.ident.item.clone(),
+            entity_end: range.source.pos(end, end),
+            architecture: format!("\n\n{}", entity.architecture_stub("rtl")),
+            testbench: format!("{}\n", testbench),
+            library_name,
+        })
+    }
+
+    /// The index subtypes and their directions of a subtype indication of an unconstrained
+    /// array type
+    fn index_subtypes(&self, subtype: &SubtypeIndication) -> Option<Vec<(String, Direction)>> {
+        if subtype.constraint.is_some() {
+            return None;
+        }
+
+        match self.type_definition(&subtype.type_mark)? {
+            TypeDefinition::Array(ref indexes, _) => indexes
+                .iter()
+                .map(|index| match index {
+                    ArrayIndex::IndexSubtypeDefintion(ref type_mark) => {
+                        Some((type_mark.to_string(), self.direction(type_mark)))
+                    }
+                    ArrayIndex::Discrete(..) => None,
+                })
+                .collect(),
+            TypeDefinition::Subtype(ref subtype) => self.index_subtypes(subtype),
+            _ => None,
+        }
+    }
+
+    /// The direction of the range of a scalar type, ascending when not known
+    fn direction(&self, type_mark: &WithPos<SelectedName>) -> Direction {
+        match self.type_definition(type_mark) {
+            Some(TypeDefinition::Integer(Range::Range(ref range))) => range.direction,
+            Some(TypeDefinition::Subtype(ref subtype)) => match subtype.constraint {
+                Some(WithPos {
+                    item: SubtypeConstraint::Range(Range::Range(ref range)),
+                    ..
+                }) => range.direction,
+                _ => self.direction(&subtype.type_mark),
+            },
+            _ => Direction::Ascending,
+        }
+    }
+
+    /// The definition of the type or subtype referenced by the type mark
+    fn type_definition(&self, type_mark: &WithPos<SelectedName>) -> Option<TypeDefinition> {
+        let reference = match type_mark.item {
+            SelectedName::Designator(ref designator) => &designator.reference,
+            SelectedName::Selected(_, ref suffix) => &suffix.item.reference,
+        };
+        let decl_pos = reference.as_ref()?.decl_pos()?;
+
+        let mut searcher = FindTypeDefinition::new(decl_pos);
+        let _ = self.search(&mut searcher);
+        searcher.result
+    }
+}
+
+/// Find the definition of the type declared at the position
+struct FindTypeDefinition {
+    decl_pos: SrcPos,
+    result: Option<TypeDefinition>,
+}
+
+impl FindTypeDefinition {
+    fn new(decl_pos: &SrcPos) -> FindTypeDefinition {
+        FindTypeDefinition {
+            decl_pos: decl_pos.clone(),
+            result: None,
+        }
+    }
+}
+
+impl Searcher for FindTypeDefinition {
+    fn search_decl(&mut self, pos: &SrcPos, decl: FoundDeclaration) -> SearchState {
+        if pos != &self.decl_pos {
+            return NotFinished;
+        }
+        if let FoundDeclaration::Type(type_decl) = decl {
+            self.result = Some(type_decl.def.clone());
+        }
+        Finished(Found)
+    }
+}
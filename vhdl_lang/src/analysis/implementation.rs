@@ -0,0 +1,303 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2020, Olof Kraigher olof.kraigher@gmail.com
+
+use super::root::*;
+use crate::ast::search::*;
+use crate::ast::*;
+use crate::data::*;
+
+/// A declaration which is implemented elsewhere
+enum Implemented {
+    Entity(Symbol),
+    Package(Symbol),
+    Component(Symbol),
+    ProtectedType,
+    /// A subprogram body must conform lexically to its declaration
+    /// thus the formatted specification identifies the body
+    Subprogram(String),
+    /// A subprogram body is an implementation itself
+    SubprogramBody(String),
+    DeferredConstant(Symbol),
+}
+
+impl DesignRoot {
+    /// Find the implementations of the declaration at decl_pos
+    /// An entity is implemented by its architectures, a package by its package body,
+    /// a protected type by its protected body, a subprogram declaration by its subprogram body,
+    /// a deferred constant by its full constant and a component by entities of the same name
+    pub fn find_implementation(&self, decl_pos: &SrcPos) -> Vec<SrcPos> {
+        let (unit_id, implemented) = match self.find_implemented(decl_pos) {
+            Some(value) => value,
+            None => {
+                return Vec::new();
+            }
+        };
+
+        let mut result = match implemented {
+            Implemented::Entity(ref name) => self.find_units(
+                unit_id.library_name(),
+                AnyKind::Secondary(SecondaryKind::Architecture),
+                name,
+            ),
+            Implemented::Package(ref name) => self.find_units(
+                unit_id.library_name(),
+                AnyKind::Secondary(SecondaryKind::PackageBody),
+                name,
+            ),
+            Implemented::Component(ref name) => {
+                // Prefer entities in the same library as the component as a default binding would
+                let kind = AnyKind::Primary(PrimaryKind::Entity);
+                let result = self.find_units(unit_id.library_name(), kind, name);
+                if result.is_empty() {
+                    self.library_names()
+                        .flat_map(|library_name| self.find_units(library_name, kind, name))
+                        .collect()
+                } else {
+                    result
+                }
+            }
+            Implemented::DeferredConstant(ref name) => self.find_full_constants(&unit_id, name),
+            Implemented::SubprogramBody(..) => Vec::new(),
+            Implemented::ProtectedType | Implemented::Subprogram(..) => {
+                let mut searcher = FindImplementation::new(decl_pos, &implemented);
+                for unit in self.get_related_units(&unit_id) {
+                    let _ = unit.unit.read().search(&mut searcher);
+                }
+                searcher.result
+            }
+        };
+
+        result.sort();
+        result
+    }
+
+    /// Find the definition of the declaration at decl_pos
+    /// Subprogram declarations, deferred constants and protected types are defined by their
+    /// body while any other declaration is its own definition
+    pub fn find_definition(&self, decl_pos: &SrcPos) -> SrcPos {
+        let is_completed = matches!(
+            self.find_implemented(decl_pos),
+            Some((_, Implemented::ProtectedType))
+                | Some((_, Implemented::Subprogram(..)))
+                | Some((_, Implemented::DeferredConstant(..)))
+        );
+
+        if is_completed {
+            let mut result = self.find_implementation(decl_pos);
+            if result.len() == 1 {
+                return result.pop().unwrap();
+            }
+        }
+        decl_pos.clone()
+    }
+
+    /// Find the positions of the declaration and the body of the subprogram declared at decl_pos
+    pub(super) fn find_subprogram_parts(&self, decl_pos: &SrcPos) -> Vec<SrcPos> {
+        match self.find_implemented(decl_pos) {
+            Some((unit_id, Implemented::Subprogram(ref specification)))
+            | Some((unit_id, Implemented::SubprogramBody(ref specification))) => {
+                let mut searcher = FindSubprogramParts::new(specification);
+                for unit in self.get_related_units(&unit_id) {
+                    let _ = unit.unit.read().search(&mut searcher);
+                }
+                let mut result = searcher.result;
+                result.sort();
+                result
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Find the declaration at decl_pos and the design unit which contains it
+    fn find_implemented(&self, decl_pos: &SrcPos) -> Option<(UnitId, Implemented)> {
+        for unit in self.get_units_in_source(&decl_pos.source) {
+            let mut searcher = FindImplemented::new(decl_pos);
+            if let Found = unit.unit.read().search(&mut searcher) {
+                return searcher
+                    .result
+                    .map(|implemented| (unit.unit_id().clone(), implemented));
+            }
+        }
+        None
+    }
+
+    /// Find the positions of the design units of kind with the primary name in the library
+    fn find_units(
+        &self,
+        library_name: &Symbol,
+        kind: AnyKind,
+        primary_name: &Symbol,
+    ) -> Vec<SrcPos> {
+        self.get_library_units(library_name)
+            .into_iter()
+            .flat_map(|units| units.values())
+            .filter(|unit| unit.kind() == kind && unit.unit_id().primary_name() == primary_name)
+            .map(|unit| unit.ident().pos.clone())
+            .collect()
+    }
+
+    /// Get the primary unit of unit_id together with its secondary units
+    fn get_related_units(&self, unit_id: &UnitId) -> Vec<&LockedUnit> {
+        self.get_library_units(unit_id.library_name())
+            .into_iter()
+            .flat_map(|units| units.values())
+            .filter(|unit| unit.unit_id().primary_name() == unit_id.primary_name())
+            .collect()
+    }
+
+    /// Find the full constants in the package body of the package declaring the deferred constant
+    fn find_full_constants(&self, unit_id: &UnitId, name: &Symbol) -> Vec<SrcPos> {
+        let mut result = Vec::new();
+        for unit in self.get_related_units(unit_id) {
+            if let AnyDesignUnit::Secondary(AnySecondaryUnit::PackageBody(ref body)) =
+                *unit.unit.read()
+            {
+                for decl in body.decl.iter() {
+                    if let Declaration::Object(ref object) = decl {
+                        if object.class == ObjectClass::Constant
+                            && object.expression.is_some()
+                            && &object.ident.item == name
+                        {
+                            result.push(object.ident.pos.clone());
+                        }
+                    }
+                }
+            }
+        }
+        result
+    }
+}
+
+/// Classify the declaration at decl_pos by how it is implemented
+struct FindImplemented {
+    decl_pos: SrcPos,
+    result: Option<Implemented>,
+}
+
+impl FindImplemented {
+    fn new(decl_pos: &SrcPos) -> FindImplemented {
+        FindImplemented {
+            decl_pos: decl_pos.clone(),
+            result: None,
+        }
+    }
+}
+
+impl Searcher for FindImplemented {
+    fn search_subprogram_body(&mut self, body: &SubprogramBody) -> SearchState {
+        if body.specification.pos() == &self.decl_pos {
+            self.result = Some(Implemented::SubprogramBody(body.specification.to_string()));
+            Finished(Found)
+        } else {
+            NotFinished
+        }
+    }
+
+    fn search_decl(&mut self, pos: &SrcPos, decl: FoundDeclaration) -> SearchState {
+        if pos != &self.decl_pos {
+            return NotFinished;
+        }
+
+        self.result = match decl {
+            FoundDeclaration::Entity(entity) => Some(Implemented::Entity(entity.name().clone())),
+            FoundDeclaration::Package(package) => {
+                Some(Implemented::Package(package.name().clone()))
+            }
+            FoundDeclaration::Component(component) => {
+                Some(Implemented::Component(component.ident.item.clone()))
+            }
+            FoundDeclaration::Type(TypeDeclaration {
+                def: TypeDefinition::Protected(..),
+                ..
+            }) => Some(Implemented::ProtectedType),
+            FoundDeclaration::Function(function) => {
+                Some(Implemented::Subprogram(function.to_string()))
+            }
+            FoundDeclaration::Procedure(procedure) => {
+                Some(Implemented::Subprogram(procedure.to_string()))
+            }
+            FoundDeclaration::Object(ObjectDeclaration {
+                class: ObjectClass::Constant,
+                ident,
+                expression: None,
+                ..
+            }) => Some(Implemented::DeferredConstant(ident.item.clone())),
+            _ => None,
+        };
+        Finished(Found)
+    }
+}
+
+/// Find the bodies of a subprogram declaration or protected type declared at decl_pos
+struct FindImplementation<'a> {
+    decl_pos: SrcPos,
+    implemented: &'a Implemented,
+    result: Vec<SrcPos>,
+}
+
+impl<'a> FindImplementation<'a> {
+    fn new(decl_pos: &SrcPos, implemented: &'a Implemented) -> FindImplementation<'a> {
+        FindImplementation {
+            decl_pos: decl_pos.clone(),
+            implemented,
+            result: Vec::new(),
+        }
+    }
+}
+
+impl<'a> Searcher for FindImplementation<'a> {
+    fn search_subprogram_body(&mut self, body: &SubprogramBody) -> SearchState {
+        if let Implemented::Subprogram(ref specification) = self.implemented {
+            if &body.specification.to_string() == specification {
+                self.result.push(body.specification.pos().clone());
+            }
+        }
+        NotFinished
+    }
+
+    fn search_protected_body(
+        &mut self,
+        ident_pos: &SrcPos,
+        body: &ProtectedTypeBody,
+    ) -> SearchState {
+        if let Implemented::ProtectedType = self.implemented {
+            if body.type_reference.as_ref().and_then(|ent| ent.decl_pos()) == Some(&self.decl_pos) {
+                self.result.push(ident_pos.clone());
+            }
+        }
+        NotFinished
+    }
+}
+
+/// Find the declarations and bodies of subprograms with the specification
+struct FindSubprogramParts<'a> {
+    specification: &'a str,
+    result: Vec<SrcPos>,
+}
+
+impl<'a> FindSubprogramParts<'a> {
+    fn new(specification: &'a str) -> FindSubprogramParts<'a> {
+        FindSubprogramParts {
+            specification,
+            result: Vec::new(),
+        }
+    }
+}
+
+impl<'a> Searcher for FindSubprogramParts<'a> {
+    fn search_decl(&mut self, pos: &SrcPos, decl: FoundDeclaration) -> SearchState {
+        let specification = match decl {
+            FoundDeclaration::Function(function) => function.to_string(),
+            FoundDeclaration::Procedure(procedure) => procedure.to_string(),
+            _ => return NotFinished,
+        };
+
+        if specification == self.specification {
+            self.result.push(pos.clone());
+        }
+        NotFinished
+    }
+}
@@ -0,0 +1,260 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2020, Olof Kraigher olof.kraigher@gmail.com
+
+use super::named_entity::*;
+use super::root::*;
+use crate::ast::search::*;
+use crate::ast::Range;
+use crate::ast::*;
+use crate::data::*;
+use std::sync::Arc;
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum InlayHintKind {
+    /// The formal of a positional association shown before the actual
+    Parameter,
+    /// The subtype of a declaration without a subtype indication shown after its name
+    Type,
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct InlayHint {
+    pub position: Position,
+    pub label: String,
+    pub kind: InlayHintKind,
+}
+
+impl InlayHint {
+    fn parameter(position: Position, formal: &Designator) -> InlayHint {
+        InlayHint {
+            position,
+            label: format!("{} =>", formal),
+            kind: InlayHintKind::Parameter,
+        }
+    }
+
+    fn subtype(pos: &SrcPos, type_name: &str) -> InlayHint {
+        InlayHint {
+            position: pos.end(),
+            label: format!(": {}", type_name),
+            kind: InlayHintKind::Type,
+        }
+    }
+}
+
+impl DesignRoot {
+    /// Find the inlay hints within the source sorted by position
+    ///
+    /// The formal is shown before each positional actual of a port map, generic map or
+    /// call to a subprogram which could be uniquely resolved. The subtype is shown after
+    /// object aliases and loop or generate parameters which are declared without a type mark.
+    pub fn find_inlay_hints(&self, source: &Source) -> Vec<InlayHint> {
+        let mut searcher = FindInlayHints::new(source);
+        let _ = self.search(&mut searcher);
+
+        let mut hints = searcher.hints;
+
+        // The interface lists are searched after the search above has finished with the units
+        for instance in searcher.instances {
+            let formals = instance
+                .ent
+                .decl_pos()
+                .and_then(|decl_pos| FindInterfaceList::search(self, decl_pos, instance.list_type))
+                .unwrap_or_default();
+
+            for (actual, formal) in instance.actuals.iter().zip(formals.iter()) {
+                let formal = formal.designator();
+                if actual.name.as_ref() != Some(&formal) {
+                    hints.push(InlayHint::parameter(actual.position, &formal));
+                }
+            }
+        }
+
+        hints.sort_by_key(|hint| hint.position);
+        hints
+    }
+}
+
+/// A positional actual of a port or generic map
+struct Actual {
+    position: Position,
+    /// The designator when the actual is a simple name
+    name: Option<Designator>,
+}
+
+/// A port or generic map of an instantiated entity or component
+struct Instance {
+    ent: Arc<NamedEntity>,
+    list_type: InterfaceListType,
+    actuals: Vec<Actual>,
+}
+
+struct FindInlayHints {
+    source: Source,
+    hints: Vec<InlayHint>,
+    instances: Vec<Instance>,
+}
+
+impl FindInlayHints {
+    fn new(source: &Source) -> FindInlayHints {
+        FindInlayHints {
+            source: source.clone(),
+            hints: Vec::new(),
+            instances: Vec::new(),
+        }
+    }
+
+    fn add_instance(
+        &mut self,
+        ent: &Arc<NamedEntity>,
+        list_type: InterfaceListType,
+        elems: &[AssociationElement],
+    ) {
+        let actuals: Vec<_> = positional_actuals(elems)
+            .map(|actual| Actual {
+                position: actual.pos.start(),
+                name: actual_name(actual).cloned(),
+            })
+            .collect();
+        if !actuals.is_empty() {
+            self.instances.push(Instance {
+                ent: ent.clone(),
+                list_type,
+                actuals,
+            });
+        }
+    }
+}
+
+impl Searcher for FindInlayHints {
+    fn search_function_call(&mut self, call: &FunctionCall) -> SearchState {
+        // Indexed names are also parsed as function calls but do not refer to a subprogram
+        if let Some(signature) = name_reference(&call.name.item).and_then(|ent| ent.signature()) {
+            for (actual, formal) in positional_actuals(&call.parameters).zip(signature.formals()) {
+                if actual_name(actual) != Some(formal) {
+                    self.hints
+                        .push(InlayHint::parameter(actual.pos.start(), formal));
+                }
+            }
+        }
+        NotFinished
+    }
+
+    fn search_instantiation(&mut self, inst: &InstantiationStatement) -> SearchState {
+        let name = match inst.unit {
+            InstantiatedUnit::Entity(ref name, _) | InstantiatedUnit::Component(ref name) => name,
+            InstantiatedUnit::Configuration(..) => return NotFinished,
+        };
+
+        let reference = match name.item {
+            SelectedName::Designator(ref designator) => &designator.reference,
+            SelectedName::Selected(_, ref suffix) => &suffix.item.reference,
+        };
+
+        if let Some(ent) = reference {
+            self.add_instance(ent, InterfaceListType::Generic, &inst.generic_map);
+            self.add_instance(ent, InterfaceListType::Port, &inst.port_map);
+        }
+        NotFinished
+    }
+
+    fn search_decl(&mut self, pos: &SrcPos, decl: FoundDeclaration) -> SearchState {
+        let type_name = match decl {
+            FoundDeclaration::Alias(AliasDeclaration {
+                subtype_indication: None,
+                signature: None,
+                name,
+                ..
+            }) => name_type_mark(&name.item),
+            FoundDeclaration::ForIndex(_, drange) => discrete_range_type_mark(drange),
+            FoundDeclaration::ForGenerateIndex(_, gen) => {
+                discrete_range_type_mark(&gen.discrete_range)
+            }
+            _ => None,
+        };
+
+        if let Some(type_name) = type_name {
+            self.hints.push(InlayHint::subtype(pos, &type_name));
+        }
+        NotFinished
+    }
+
+    // Assume source is searched first to filter out design units in other files
+    fn search_source(&mut self, source: &Source) -> SearchState {
+        if source == &self.source {
+            NotFinished
+        } else {
+            Finished(NotFound)
+        }
+    }
+}
+
+fn positional_actuals(elems: &[AssociationElement]) -> impl Iterator<Item = &WithPos<ActualPart>> {
+    elems
+        .iter()
+        .take_while(|elem| elem.formal.is_none())
+        .map(|elem| &elem.actual)
+}
+
+/// The designator of an actual which is a simple name
+/// The hint is redundant when it is the same as the formal such as `clk` connected to `clk`
+fn actual_name(actual: &WithPos<ActualPart>) -> Option<&Designator> {
+    if let ActualPart::Expression(Expression::Name(ref name)) = actual.item {
+        if let Name::Designator(ref designator) = name.as_ref() {
+            return Some(&designator.item);
+        }
+    }
+    None
+}
+
+/// The named entity referenced by a simple or selected name
+pub(super) fn name_reference(name: &Name) -> Option<&Arc<NamedEntity>> {
+    match name {
+        Name::Designator(ref designator) => designator.reference.as_ref(),
+        Name::Selected(_, ref suffix) => suffix.item.reference.as_ref(),
+        _ => None,
+    }
+}
+
+/// The name of the type mark of the object referenced by a simple or selected name
+fn name_type_mark(name: &Name) -> Option<String> {
+    match name_reference(name)?.actual_kind() {
+        NamedEntityKind::Object(ref object) => {
+            Some(object.subtype.type_mark().designator().to_string())
+        }
+        _ => None,
+    }
+}
+
+/// The type of a discrete range without a type mark such as `0 to 7` or `idle to busy`
+fn discrete_range_type_mark(drange: &DiscreteRange) -> Option<String> {
+    match drange {
+        DiscreteRange::Range(Range::Range(ref constraint)) => {
+            expression_type_mark(&constraint.left_expr.item)
+                .or_else(|| expression_type_mark(&constraint.right_expr.item))
+        }
+        _ => None,
+    }
+}
+
+fn expression_type_mark(expr: &Expression) -> Option<String> {
+    match expr {
+        // A range of universal integers is implicitly converted to integer
+        Expression::Literal(Literal::AbstractLiteral(AbstractLiteral::Integer(..))) => {
+            Some("integer".to_owned())
+        }
+        Expression::Name(ref name) => match name_reference(name)?.actual_kind() {
+            NamedEntityKind::Object(ref object) => {
+                Some(object.subtype.type_mark().designator().to_string())
+            }
+            NamedEntityKind::EnumLiteral(ref signature) => signature
+                .return_type()
+                .map(|typ| typ.designator().to_string()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
@@ -0,0 +1,156 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2020, Olof Kraigher olof.kraigher@gmail.com
+
+use super::root::*;
+use crate::ast::search::*;
+use crate::ast::*;
+use crate::data::*;
+
+/// An instantiation of an entity replacing a statement which only names the entity
+#[derive(PartialEq, Clone, Debug)]
+pub struct InstantiationTemplate {
+    pub library_name: Symbol,
+    pub entity_name: Symbol,
+    /// The statement naming the entity such as `dma;` or `dma_inst : work.dma;`
+    pub pos: SrcPos,
+    /// The instantiation replacing the statement indented to the column of the statement
+    pub instantiation: String,
+    /// The matching component declaration as whole lines indented to the column of the statement
+    pub component: String,
+    /// Where the component declaration is inserted which is the start of the line after the
+    /// start of the architecture
+    pub component_position: Position,
+}
+
+impl DesignRoot {
+    /// Find the instantiation template of the entity named by the statement at the cursor
+    ///
+    /// The statement is a name without parameters, optionally labeled, which is parsed as a
+    /// procedure call. A simple name refers to an entity within the library of the design unit.
+    pub fn find_instantiation_template(
+        &self,
+        source: &Source,
+        cursor: Position,
+    ) -> Option<InstantiationTemplate> {
+        let unit = self.get_unit_at(source, cursor)?;
+        let (pos, label, name) = {
+            let mut searcher = FindNamingStatement::new(source, cursor);
+            let _ = unit.unit.read().search(&mut searcher);
+            searcher.result?
+        };
+
+        let work_library_name = unit.unit_id().library_name();
+        let library_name = match name.item {
+            Name::Designator(..) => work_library_name.clone(),
+            Name::Selected(ref prefix, _) => match prefix.item {
+                Name::Designator(ref designator) => {
+                    let library_name = designator.item.as_identifier()?;
+                    if *library_name == self.symbol_utf8("work") {
+                        work_library_name.clone()
+                    } else {
+                        library_name.clone()
+                    }
+                }
+                _ => return None,
+            },
+            _ => return None,
+        };
+        let entity_name = match name.item {
+            Name::Designator(ref designator) => designator.item.as_identifier()?,
+            Name::Selected(_, ref suffix) => suffix.item.item.as_identifier()?,
+            _ => return None,
+        };
+
+        let entity_unit = self
+            .get_library_units(&library_name)?
+            .get(&UnitKey::Primary(entity_name.clone()))?;
+        let entity_unit = entity_unit.unit.read();
+        let entity = match *entity_unit {
+            AnyDesignUnit::Primary(AnyPrimaryUnit::Entity(ref entity)) => entity,
+            _ => return None,
+        };
+
+        let label = match label {
+            Some(label) => label.name_utf8(),
+            None => format!("{}_inst", entity_name),
+        };
+        let indent = " ".repeat(pos.start().character as usize);
+
+        Some(InstantiationTemplate {
+            instantiation: indent_lines(
+                &entity.instantiation_template(&label, &library_name),
+                &indent,
+            ),
+            component: format!(
+                "{}{}\n",
+                indent,
+                indent_lines(&entity.component_declaration().to_string(), &indent)
+            ),
+            component_position: Position::new(unit.ident().pos.start().line + 1, 0),
+            library_name,
+            entity_name: entity_name.clone(),
+            pos,
+        })
+    }
+}
+
+/// Indent the lines following the first line which is already indented
+fn indent_lines(text: &str, indent: &str) -> String {
+    text.replace('\n', &format!("\n{}", indent))
+}
+
+/// Find the statement at the cursor which is a name without parameters
+struct FindNamingStatement {
+    source: Source,
+    cursor: Position,
+    result: Option<(SrcPos, Option<Symbol>, WithPos<Name>)>,
+}
+
+impl FindNamingStatement {
+    fn new(source: &Source, cursor: Position) -> FindNamingStatement {
+        FindNamingStatement {
+            source: source.clone(),
+            cursor,
+            result: None,
+        }
+    }
+}
+
+impl Searcher for FindNamingStatement {
+    fn search_concurrent_statement(
+        &mut self,
+        statement: &LabeledConcurrentStatement,
+    ) -> SearchState {
+        let pos = match statement.label {
+            Some(ref label) => label.pos.combine(&statement.statement.pos),
+            None => statement.statement.pos.clone(),
+        };
+
+        if pos.source != self.source || self.cursor < pos.start() || pos.end() < self.cursor {
+            return Finished(NotFound);
+        }
+
+        if let ConcurrentStatement::ProcedureCall(ref pcall) = statement.statement.item {
+            let name = &pcall.call.name;
+            let reference = match name.item {
+                Name::Designator(ref designator) => &designator.reference,
+                Name::Selected(_, ref suffix) => &suffix.item.reference,
+                _ => return NotFinished,
+            };
+            let is_subprogram = reference
+                .as_ref()
+                .map(|ent| ent.is_subprogram() || ent.is_subprogram_decl())
+                .unwrap_or(false);
+
+            if pcall.call.parameters.is_empty() && !is_subprogram {
+                let label = statement.label.as_ref().map(|label| label.item.clone());
+                self.result = Some((pos, label, name.clone()));
+                return Finished(Found);
+            }
+        }
+        NotFinished
+    }
+}
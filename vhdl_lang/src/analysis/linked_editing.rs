@@ -0,0 +1,59 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2020, Olof Kraigher olof.kraigher@gmail.com
+
+use super::root::*;
+use crate::ast::search::*;
+use crate::data::*;
+
+impl DesignRoot {
+    /// Find the name of a design unit, subprogram or statement label together with the
+    /// name repeated after `end` when the cursor is within either of them
+    pub fn find_linked_editing_ranges(&self, source: &Source, cursor: Position) -> Vec<SrcPos> {
+        let mut searcher = FindLinkedNames {
+            source: source.clone(),
+            cursor,
+            result: Vec::new(),
+        };
+        for unit in self.get_units_in_source(source) {
+            if let Found = unit.unit.read().search(&mut searcher) {
+                break;
+            }
+        }
+        searcher.result
+    }
+}
+
+struct FindLinkedNames {
+    source: Source,
+    cursor: Position,
+    result: Vec<SrcPos>,
+}
+
+impl FindLinkedNames {
+    /// The cursor is the gap between two characters and is inside of a name it touches
+    fn is_inside(&self, pos: &SrcPos) -> bool {
+        pos.source == self.source && pos.start() <= self.cursor && self.cursor <= pos.end()
+    }
+}
+
+impl Searcher for FindLinkedNames {
+    fn search_with_pos(&mut self, pos: &SrcPos) -> SearchState {
+        if self.is_inside(pos) {
+            NotFinished
+        } else {
+            Finished(NotFound)
+        }
+    }
+
+    fn search_end_ident_pos(&mut self, decl_pos: &SrcPos, end_pos: &SrcPos) -> SearchState {
+        if self.is_inside(decl_pos) || self.is_inside(end_pos) {
+            self.result = vec![decl_pos.clone(), end_pos.clone()];
+            Finished(Found)
+        } else {
+            NotFinished
+        }
+    }
+}
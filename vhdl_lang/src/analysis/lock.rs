@@ -26,6 +26,18 @@ pub struct AnalysisLock<T, R> {
     state: RwLock<AnalysisState<T, R>>,
 }
 
+impl<T: Clone, R: Clone> Clone for AnalysisLock<T, R> {
+    fn clone(&self) -> Self {
+        let guard = self.state.read();
+        AnalysisLock {
+            state: RwLock::new(AnalysisState {
+                result: guard.result.clone(),
+                data: guard.data.clone(),
+            }),
+        }
+    }
+}
+
 impl<T, R> AnalysisLock<T, R> {
     pub fn new(data: T) -> AnalysisLock<T, R> {
         AnalysisLock {
@@ -15,7 +15,7 @@ pub enum NamedEntityKind {
     UnknownAlias,
     File,
     InterfaceFile(Arc<NamedEntity>),
-    Component,
+    Component(Arc<Region<'static>>),
     Attribute,
     SubprogramDecl(Signature),
     Subprogram(Signature),
@@ -112,7 +112,7 @@ impl NamedEntityKind {
             InterfaceFile(..) => "file",
             ElementDeclaration(..) => "element declaration",
             RecordType(..) => "record type",
-            Component => "component",
+            Component(..) => "component",
             Attribute => "attribute",
             SubprogramDecl(signature) | Subprogram(signature) => {
                 if signature.return_type.is_some() {
@@ -159,6 +159,8 @@ impl std::fmt::Debug for NamedEntityKind {
 pub struct Object {
     pub class: ObjectClass,
     pub mode: Option<Mode>,
+    /// The interface list of an interface object
+    pub list_type: Option<InterfaceListType>,
     pub subtype: Subtype,
     pub has_default: bool,
 }
@@ -282,10 +284,19 @@ impl Signature {
     }
 
     pub fn describe(&self) -> String {
+        self.describe_with_param_ranges().0
+    }
+
+    /// Describe the signature together with the character range of each
+    /// parameter type mark within the description
+    pub fn describe_with_param_ranges(&self) -> (String, Vec<(usize, usize)>) {
         let mut result = String::new();
+        let mut ranges = Vec::with_capacity(self.params.params.len());
         result.push('[');
         for (i, param) in self.params.params.iter().enumerate() {
+            let start = result.chars().count();
             result.push_str(&param.type_mark().designator().to_string());
+            ranges.push((start, result.chars().count()));
 
             if i + 1 < self.params.params.len() {
                 result.push_str(", ");
@@ -302,7 +313,15 @@ impl Signature {
         }
 
         result.push(']');
-        result
+        (result, ranges)
+    }
+
+    /// The designators of the formal parameters in order
+    pub fn formals(&self) -> impl Iterator<Item = &Designator> {
+        self.params
+            .params
+            .iter()
+            .map(|param| param.param.designator())
     }
 
     /// Returns true if the function has no arguments
@@ -445,6 +464,16 @@ impl NamedEntity {
         matches!(self.kind, NamedEntityKind::SubprogramDecl(..))
     }
 
+    pub fn is_port(&self) -> bool {
+        matches!(
+            self.kind,
+            NamedEntityKind::Object(Object {
+                list_type: Some(InterfaceListType::Port),
+                ..
+            })
+        )
+    }
+
     pub fn is_explicit(&self) -> bool {
         !self.implicit
     }
@@ -0,0 +1,337 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2020, Olof Kraigher olof.kraigher@gmail.com
+
+use super::region::*;
+use super::root::*;
+use crate::ast::*;
+use crate::data::*;
+use crate::syntax::Kind;
+use fnv::{FnvHashMap, FnvHashSet};
+
+/// The declarations of a package body which are missing for a package declaration
+#[derive(PartialEq, Clone, Debug)]
+pub struct PackageBodyStub {
+    pub library_name: Symbol,
+    pub package_name: Symbol,
+    /// True when there is no package body and the text is a new package body
+    pub is_new: bool,
+    /// The text inserted at each position which is after a package declaration without a body
+    /// or before the end of the package body and of each incomplete protected type body
+    pub edits: Vec<(SrcPos, String)>,
+}
+
+impl DesignRoot {
+    /// Find the package body stub of the package declaration enclosing the cursor
+    ///
+    /// The stub has a body of each subprogram, a full constant of each deferred constant and a
+    /// protected body of each protected type which is not already in the package body. Methods
+    /// are added to protected type bodies which are already in the package body.
+    pub fn find_package_body_stub(
+        &self,
+        source: &Source,
+        cursor: Position,
+    ) -> Option<PackageBodyStub> {
+        let unit = self.get_unit_at(source, cursor)?;
+        let library_name = unit.unit_id().library_name().clone();
+
+        let (package_name, package_range, body_range, missing, missing_methods) = {
+            let package_data = self.get_analysis(unit);
+            let package = match *package_data {
+                AnyDesignUnit::Primary(AnyPrimaryUnit::Package(ref package)) => package,
+                _ => return None,
+            };
+            let range = &package.source_range;
+            if cursor < range.start() || range.end() < cursor {
+                return None;
+            }
+
+            let name = package.ident.item.clone();
+            let body = self
+                .get_library_units(&library_name)?
+                .get(&UnitKey::Secondary(name.clone(), name.clone()))
+                .map(|body| self.get_analysis(body));
+            let body_decl = match body.as_deref() {
+                Some(AnyDesignUnit::Secondary(AnySecondaryUnit::PackageBody(ref body))) => {
+                    Some((&body.decl, body.source_range.clone()))
+                }
+                _ => None,
+            };
+
+            let mut existing = Existing::new(&package_data.result().region);
+            if let (Some((decl, _)), Some(body)) = (body_decl.as_ref(), body.as_ref()) {
+                existing.add_body(decl, &body.result().region);
+            }
+            let missing: Vec<String> = package
+                .decl
+                .iter()
+                .filter_map(|decl| existing.missing_stub(decl))
+                .collect();
+            let missing_methods: Vec<(SrcPos, Vec<String>)> = package
+                .decl
+                .iter()
+                .filter_map(|decl| existing.missing_methods(decl))
+                .collect();
+
+            (
+                name,
+                range.clone(),
+                body_decl.map(|(_, range)| range),
+                missing,
+                missing_methods,
+            )
+        };
+
+        let (is_new, edits) = match body_range {
+            Some(body_range) => {
+                let source = &body_range.source;
+                let tokens = self.tokens_between(source, body_range.start(), body_range.end());
+                let mut edits = Vec::new();
+
+                for (ident_pos, stubs) in missing_methods.iter() {
+                    // The end of the protected type body is the first one after its name
+                    let end = tokens
+                        .iter()
+                        .zip(tokens.iter().skip(1))
+                        .find(|(token, next)| {
+                            token.pos.start() > ident_pos.start()
+                                && token.kind == Kind::End
+                                && next.kind == Kind::Protected
+                        })
+                        .map(|(token, _)| token.pos.start());
+                    if let Some(end) = end {
+                        edits.push(insert_before_end(source, end, stubs, 2));
+                    }
+                }
+
+                let end = tokens
+                    .iter()
+                    .rev()
+                    .find(|token| token.kind == Kind::End)
+                    .map(|token| token.pos.start());
+                if let (false, Some(end)) = (missing.is_empty(), end) {
+                    edits.push(insert_before_end(source, end, &missing, 1));
+                }
+                (false, edits)
+            }
+            None if missing.is_empty() => (true, Vec::new()),
+            None => {
+                let end = package_range.end();
+                (
+                    true,
+                    vec![(
+                        package_range.source.pos(end, end),
+                        format!(
+                            "\n\npackage body {} is\n{}end package body;",
+                            package_name,
+                            indent(&missing, 1)
+                        ),
+                    )],
+                )
+            }
+        };
+
+        if edits.is_empty() {
+            return None;
+        }
+
+        Some(PackageBodyStub {
+            library_name,
+            package_name,
+            is_new,
+            edits,
+        })
+    }
+}
+
+/// Indent each line of the stubs by the depth where the stubs are separated by an empty line
+fn indent(stubs: &[String], depth: usize) -> String {
+    let indent = "  ".repeat(depth);
+    let indented: Vec<String> = stubs
+        .iter()
+        .map(|stub| {
+            stub.lines()
+                .map(|line| format!("{}{}\n", indent, line))
+                .collect::<String>()
+        })
+        .collect();
+    indented.join("\n")
+}
+
+/// Insert the stubs on lines of their own before the end keyword at the given position
+fn insert_before_end(
+    source: &Source,
+    end: Position,
+    stubs: &[String],
+    depth: usize,
+) -> (SrcPos, String) {
+    let text = indent(stubs, depth);
+    let is_first_on_line = source
+        .contents()
+        .get_line(end.line as usize)
+        .map(|line| {
+            line.chars()
+                .take(end.character as usize)
+                .all(char::is_whitespace)
+        })
+        .unwrap_or(true);
+
+    if is_first_on_line {
+        let start = Position::new(end.line, 0);
+        (source.pos(start, start), format!("\n{}", text))
+    } else {
+        // The end keyword is moved to a line of its own
+        (
+            source.pos(end, end),
+            format!("\n\n{}{}", text, "  ".repeat(depth - 1)),
+        )
+    }
+}
+
+/// The declarations of a package declaration which are in its existing package body
+struct Existing<'a> {
+    /// The analyzed declarations of the package declaration
+    package: &'a Region<'static>,
+    /// The analyzed declarations of the package body where the subprogram bodies replace
+    /// their declarations
+    body: Option<&'a Region<'static>>,
+    constants: FnvHashSet<Symbol>,
+    /// The name position of each protected type body
+    protected_bodies: FnvHashMap<Symbol, SrcPos>,
+}
+
+impl<'a> Existing<'a> {
+    fn new(package: &'a Region<'static>) -> Existing<'a> {
+        Existing {
+            package,
+            body: None,
+            constants: FnvHashSet::default(),
+            protected_bodies: FnvHashMap::default(),
+        }
+    }
+
+    fn add_body(&mut self, decls: &[Declaration], body: &'a Region<'static>) {
+        self.body = Some(body);
+        for decl in decls.iter() {
+            match decl {
+                Declaration::Object(ref object) if object.class == ObjectClass::Constant => {
+                    self.constants.insert(object.ident.item.clone());
+                }
+                Declaration::Type(TypeDeclaration {
+                    ref ident,
+                    def: TypeDefinition::ProtectedBody(..),
+                    ..
+                }) => {
+                    self.protected_bodies
+                        .insert(ident.item.clone(), ident.pos.clone());
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// The stub of a declaration of the package declaration which is not in the package body
+    fn missing_stub(&self, decl: &Declaration) -> Option<String> {
+        match decl {
+            Declaration::SubprogramDeclaration(ref subprogram) => {
+                if has_body(self.package, self.body, subprogram) {
+                    None
+                } else {
+                    Some(subprogram.body_stub())
+                }
+            }
+            Declaration::Object(ref object)
+                if object.class == ObjectClass::Constant && object.expression.is_none() =>
+            {
+                if self.constants.contains(&object.ident.item) {
+                    None
+                } else {
+                    Some(object.full_constant_stub())
+                }
+            }
+            Declaration::Type(ref type_decl) => {
+                if self.protected_bodies.contains_key(&type_decl.ident.item) {
+                    None
+                } else {
+                    type_decl.protected_body_stub()
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// The name of an existing protected type body and the body stubs of the methods of the
+    /// protected type declaration which are not in it
+    fn missing_methods(&self, decl: &Declaration) -> Option<(SrcPos, Vec<String>)> {
+        let (ident, protected) = match decl {
+            Declaration::Type(TypeDeclaration {
+                ref ident,
+                def: TypeDefinition::Protected(ref protected),
+                ..
+            }) => (ident, protected),
+            _ => return None,
+        };
+        let ident_pos = self.protected_bodies.get(&ident.item)?;
+        let body = self.body?.protected_body_region(&ident.item);
+        let ent = self
+            .package
+            .lookup_immediate(&ident.item.clone().into())?
+            .as_non_overloaded()?;
+        let methods = match ent.kind() {
+            NamedEntityKind::ProtectedType(ref region) => region,
+            _ => return None,
+        };
+
+        let missing: Vec<String> = protected
+            .items
+            .iter()
+            .filter_map(|item| {
+                let ProtectedTypeDeclarativeItem::Subprogram(ref subprogram) = item;
+                if has_body(methods, body, subprogram) {
+                    None
+                } else {
+                    Some(subprogram.body_stub())
+                }
+            })
+            .collect();
+
+        if missing.is_empty() {
+            None
+        } else {
+            Some((ident_pos.clone(), missing))
+        }
+    }
+}
+
+/// True when the body region has a subprogram body with the analyzed signature of the
+/// subprogram declared within the declaration region
+fn has_body(
+    decl_region: &Region<'_>,
+    body_region: Option<&Region<'_>>,
+    subprogram: &SubprogramDeclaration,
+) -> bool {
+    let designator = subprogram.designator();
+    let key = match decl_region
+        .lookup_immediate(&designator.item)
+        .and_then(|ents| {
+            ents.iter()
+                .find(|ent| ent.decl_pos() == Some(&designator.pos))
+        })
+        .and_then(|ent| ent.signature())
+    {
+        Some(signature) => signature.key(),
+        None => return false,
+    };
+
+    body_region
+        .and_then(|region| region.lookup_immediate(&designator.item))
+        .map(|ents| {
+            ents.iter().any(|ent| {
+                ent.is_subprogram()
+                    && ent.signature().map(|signature| signature.key()).as_ref() == Some(&key)
+            })
+        })
+        .unwrap_or(false)
+}
@@ -0,0 +1,272 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2020, Olof Kraigher olof.kraigher@gmail.com
+
+use super::concurrent::formal_prefix;
+use super::root::*;
+use crate::ast::search::*;
+use crate::ast::*;
+use crate::data::*;
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum PortMapFixKind {
+    /// Append associations of the ports without a default value which are not associated
+    /// Ports of mode in are associated with a signal of the same name and other ports with open
+    AddMissing,
+    /// Remove associations of formals which are not ports
+    RemoveUnknown,
+}
+
+/// A fix of the port map of an instance
+#[derive(PartialEq, Clone, Debug)]
+pub struct PortMapFix {
+    pub kind: PortMapFixKind,
+    /// The text replacing each position where an empty position is an insertion
+    pub edits: Vec<(SrcPos, String)>,
+}
+
+impl DesignRoot {
+    /// Find the fixes of the port map of the instance at the cursor
+    ///
+    /// Missing ports are not added when a formal is a type conversion since it is not known
+    /// which port is associated.
+    pub fn find_port_map_fixes(&self, source: &Source, cursor: Position) -> Vec<PortMapFix> {
+        let unit = match self.get_unit_at(source, cursor) {
+            Some(unit) => unit,
+            None => return Vec::new(),
+        };
+
+        let (pos, inst) = {
+            let mut searcher = FindInstance::new(source, cursor);
+            let _ = unit.unit.read().search(&mut searcher);
+            match searcher.result {
+                Some(result) => result,
+                None => return Vec::new(),
+            }
+        };
+
+        let name = match inst.unit {
+            InstantiatedUnit::Entity(ref name, _) | InstantiatedUnit::Component(ref name) => name,
+            InstantiatedUnit::Configuration(..) => return Vec::new(),
+        };
+        let reference = match name.item {
+            SelectedName::Designator(ref designator) => &designator.reference,
+            SelectedName::Selected(_, ref suffix) => &suffix.item.reference,
+        };
+
+        // The interface list is searched after the search above has finished with the unit
+        let ports: Vec<_> = match reference
+            .as_ref()
+            .and_then(|ent| ent.decl_pos())
+            .and_then(|decl_pos| FindInterfaceList::search(self, decl_pos, InterfaceListType::Port))
+        {
+            Some(list) => list
+                .into_iter()
+                .filter_map(|decl| match decl {
+                    InterfaceDeclaration::Object(object) => Some(object),
+                    _ => None,
+                })
+                .collect(),
+            None => return Vec::new(),
+        };
+
+        let mut result = Vec::new();
+
+        let edits = remove_unknown_edits(&inst.port_map, &ports);
+        if !edits.is_empty() {
+            result.push(PortMapFix {
+                kind: PortMapFixKind::RemoveUnknown,
+                edits,
+            });
+        }
+
+        let edits = add_missing_edits(&pos, &inst.port_map, &ports);
+        if !edits.is_empty() {
+            result.push(PortMapFix {
+                kind: PortMapFixKind::AddMissing,
+                edits,
+            });
+        }
+
+        result
+    }
+}
+
+fn remove_unknown_edits(
+    port_map: &[AssociationElement],
+    ports: &[InterfaceObjectDeclaration],
+) -> Vec<(SrcPos, String)> {
+    let is_unknown: Vec<bool> = port_map
+        .iter()
+        .map(|elem| match elem.formal {
+            Some(WithPos {
+                item: Name::Designator(ref designator),
+                ..
+            }) => !ports
+                .iter()
+                .any(|port| designator.item.as_identifier() == Some(&port.ident.item)),
+            _ => false,
+        })
+        .collect();
+
+    let (first, last) = match (port_map.first(), port_map.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return Vec::new(),
+    };
+    let source = &last.actual.pos.source;
+
+    // The separating comma is removed together with the association which is the one
+    // before the association unless it is before the first association which is kept
+    match is_unknown.iter().position(|unknown| !unknown) {
+        None => vec![(
+            source.pos(element_start(first), last.actual.pos.end()),
+            String::new(),
+        )],
+        Some(first_kept) => port_map
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| is_unknown[*idx])
+            .map(|(idx, elem)| {
+                let pos = if idx < first_kept {
+                    source.pos(element_start(elem), element_start(&port_map[idx + 1]))
+                } else {
+                    source.pos(port_map[idx - 1].actual.pos.end(), elem.actual.pos.end())
+                };
+                (pos, String::new())
+            })
+            .collect(),
+    }
+}
+
+fn add_missing_edits(
+    pos: &SrcPos,
+    port_map: &[AssociationElement],
+    ports: &[InterfaceObjectDeclaration],
+) -> Vec<(SrcPos, String)> {
+    let num_positional = port_map
+        .iter()
+        .take_while(|elem| elem.formal.is_none())
+        .count();
+
+    let mut associated: Vec<&Symbol> = ports
+        .iter()
+        .take(num_positional)
+        .map(|port| &port.ident.item)
+        .collect();
+
+    for elem in port_map[num_positional..].iter() {
+        let formal = match elem.formal {
+            Some(ref formal) => formal,
+            None => return Vec::new(),
+        };
+        let name = match formal_prefix(&formal.item).and_then(|prefix| prefix.as_identifier()) {
+            Some(name) => name,
+            None => return Vec::new(),
+        };
+
+        // A type conversion of a port is not known to associate any port while a simple
+        // name which is not a port is removed by the other fix
+        let is_port = ports.iter().any(|port| port.ident.item == *name);
+        if !is_port && !matches!(formal.item, Name::Designator(..)) {
+            return Vec::new();
+        }
+        associated.push(name);
+    }
+
+    let missing: Vec<String> = ports
+        .iter()
+        .filter(|port| port.expression.is_none() && !associated.contains(&&port.ident.item))
+        .map(|port| {
+            let name = &port.ident.item;
+            if port.mode == Mode::In {
+                format!("{} => {}", name, name)
+            } else {
+                format!("{} => open", name)
+            }
+        })
+        .collect();
+
+    if missing.is_empty() {
+        return Vec::new();
+    }
+
+    match (port_map.first(), port_map.last()) {
+        (Some(first), Some(last)) => {
+            let end = last.actual.pos.end();
+            // The associations are on separate lines unless the statement ends on the
+            // line of the first association
+            let separator = if element_start(first).line == pos.end().line {
+                ", ".to_owned()
+            } else {
+                let column = element_start(last).character as usize;
+                format!(",\n{}", " ".repeat(column))
+            };
+            vec![(
+                pos.source.pos(end, end),
+                format!("{}{}", separator, missing.join(&separator)),
+            )]
+        }
+        _ => {
+            // Insert a port map before the semicolon ending the statement
+            let end = pos.end();
+            let end = Position::new(end.line, end.character - 1);
+            let indent = " ".repeat(pos.start().character as usize);
+            vec![(
+                pos.source.pos(end, end),
+                format!(
+                    "\n{indent}  port map (\n{indent}    {}\n{indent}  )",
+                    missing.join(&format!(",\n{}    ", indent)),
+                    indent = indent
+                ),
+            )]
+        }
+    }
+}
+
+fn element_start(elem: &AssociationElement) -> Position {
+    match elem.formal {
+        Some(ref formal) => formal.pos.start(),
+        None => elem.actual.pos.start(),
+    }
+}
+
+/// Find the innermost instance whose statement encloses the cursor
+struct FindInstance {
+    source: Source,
+    cursor: Position,
+    result: Option<(SrcPos, InstantiationStatement)>,
+}
+
+impl FindInstance {
+    fn new(source: &Source, cursor: Position) -> FindInstance {
+        FindInstance {
+            source: source.clone(),
+            cursor,
+            result: None,
+        }
+    }
+}
+
+impl Searcher for FindInstance {
+    fn search_concurrent_statement(
+        &mut self,
+        statement: &LabeledConcurrentStatement,
+    ) -> SearchState {
+        let pos = match statement.label {
+            Some(ref label) => label.pos.combine(&statement.statement.pos),
+            None => statement.statement.pos.clone(),
+        };
+
+        if pos.source != self.source || self.cursor < pos.start() || pos.end() < self.cursor {
+            return Finished(NotFound);
+        }
+
+        if let ConcurrentStatement::Instance(ref inst) = statement.statement.item {
+            self.result = Some((pos, inst.clone()));
+            return Finished(Found);
+        }
+        NotFinished
+    }
+}
@@ -168,6 +168,15 @@ impl NamedEntities {
         self.first().kind()
     }
 
+    /// Iterate over all identically named entities
+    pub fn iter(&self) -> impl Iterator<Item = &Arc<NamedEntity>> {
+        let (single, overloaded) = match self {
+            Self::Single(ent) => (Some(ent), None),
+            Self::Overloaded(overloaded) => (None, Some(overloaded.entities())),
+        };
+        single.into_iter().chain(overloaded.into_iter().flatten())
+    }
+
     pub fn make_potentially_visible_in(
         &self,
         visible_pos: Option<&SrcPos>,
@@ -204,8 +213,11 @@ pub struct Region<'a> {
     parent: Option<&'a Region<'a>>,
     visibility: Visibility,
     entities: FnvHashMap<Designator, NamedEntities>,
-    protected_bodies: FnvHashMap<Symbol, SrcPos>,
+    /// The name position and the region of each protected type body
+    protected_bodies: FnvHashMap<Symbol, (SrcPos, Arc<Region<'static>>)>,
     kind: RegionKind,
+    /// True when an interface declaration could not be analyzed and is missing from the region
+    has_invalid_interface: bool,
 }
 
 impl<'a> Region<'a> {
@@ -216,6 +228,7 @@ impl<'a> Region<'a> {
             entities: FnvHashMap::default(),
             protected_bodies: FnvHashMap::default(),
             kind: RegionKind::Other,
+            has_invalid_interface: false,
         }
     }
 
@@ -237,6 +250,7 @@ impl<'a> Region<'a> {
             entities: self.entities,
             protected_bodies: self.protected_bodies,
             kind: self.kind,
+            has_invalid_interface: self.has_invalid_interface,
         }
     }
 
@@ -261,6 +275,7 @@ impl<'a> Region<'a> {
             entities: region.entities.clone(),
             protected_bodies: region.protected_bodies.clone(),
             kind,
+            has_invalid_interface: region.has_invalid_interface,
         }
     }
 
@@ -279,7 +294,14 @@ impl<'a> Region<'a> {
     }
 
     fn get_protected_body(&self, name: &Symbol) -> Option<&SrcPos> {
-        self.protected_bodies.get(name)
+        self.protected_bodies.get(name).map(|(pos, _)| pos)
+    }
+
+    /// The region of the protected type body where the method bodies replace their declarations
+    pub fn protected_body_region(&self, name: &Symbol) -> Option<&Region<'static>> {
+        self.protected_bodies
+            .get(name)
+            .map(|(_, region)| region.as_ref())
     }
 
     fn has_protected_body(&self, name: &Symbol) -> bool {
@@ -304,11 +326,26 @@ impl<'a> Region<'a> {
         self.check_protected_types_have_body(diagnostics);
     }
 
-    pub fn add_protected_body(&mut self, ident: Ident, diagnostics: &mut dyn DiagnosticHandler) {
+    pub fn add_invalid_interface(&mut self) {
+        self.has_invalid_interface = true;
+    }
+
+    /// An interface declaration could not be analyzed so names may be missing from the region
+    pub fn has_invalid_interface(&self) -> bool {
+        self.has_invalid_interface
+    }
+
+    pub fn add_protected_body(
+        &mut self,
+        ident: Ident,
+        region: Region<'static>,
+        diagnostics: &mut dyn DiagnosticHandler,
+    ) {
         if let Some(prev_pos) = self.get_protected_body(&ident.item) {
             diagnostics.push(duplicate_error(&ident.item, &ident.pos, Some(prev_pos)));
         } else {
-            self.protected_bodies.insert(ident.item, ident.pos);
+            self.protected_bodies
+                .insert(ident.item, (ident.pos, Arc::new(region)));
         }
     }
 
@@ -446,6 +483,18 @@ impl<'a> Region<'a> {
             .add_context_visibility(visible_pos, &region.visibility);
     }
 
+    /// Iterate over the named entities declared in this region
+    pub fn immediates(&self) -> impl Iterator<Item = &Arc<NamedEntity>> {
+        self.entities.values().flat_map(|ents| ents.iter())
+    }
+
+    /// Iterate over the named entities made potentially visible in this region
+    /// and the name they are visible as.
+    /// Neither the parent region nor conflicts between visible names are considered
+    pub fn potentially_visible(&self) -> impl Iterator<Item = (&Designator, &Arc<NamedEntity>)> {
+        self.visibility.entities()
+    }
+
     /// Lookup a named entity declared in this region
     pub fn lookup_immediate(&self, designator: &Designator) -> Option<&NamedEntities> {
         self.entities.get(designator)
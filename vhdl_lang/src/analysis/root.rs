@@ -13,18 +13,26 @@ use super::region::*;
 use crate::ast::search::*;
 use crate::ast::*;
 use crate::data::*;
-use crate::syntax::Symbols;
+use crate::syntax::{tokenize_between, Symbols, Token};
 use parking_lot::RwLock;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
 /// A design unit with design unit data
+#[derive(Clone)]
 pub(super) struct AnalysisData {
     pub diagnostics: Vec<Diagnostic>,
     pub has_circular_dependency: bool,
 
-    // Only for primary units
+    // For secondary units the regions do not include
+    // the context clause of the primary unit
     pub root_region: Arc<Region<'static>>,
     pub region: Arc<Region<'static>>,
+
+    // The regions of statements and subprogram bodies within the unit
+    pub local_regions: Vec<(SrcPos, Region<'static>)>,
+
+    // Only for primary units
     pub ent: Option<Arc<NamedEntity>>,
 }
 
@@ -32,6 +40,7 @@ pub(super) type UnitReadGuard<'a> = ReadGuard<'a, AnyDesignUnit, AnalysisData>;
 
 /// Wraps the AST of a [design unit](../../ast/enum.AnyDesignUnit.html) in a thread-safe
 /// r/w-lock for analysis.
+#[derive(Clone)]
 pub(super) struct LockedUnit {
     ident: Ident,
     unit_id: UnitId,
@@ -75,6 +84,7 @@ impl HasIdent for LockedUnit {
 /// Represents a VHDL library containing zero or more design units.
 ///
 /// This struct also keeps track of which source file contained which design units.
+#[derive(Clone)]
 struct Library {
     name: Symbol,
 
@@ -256,6 +266,19 @@ pub struct DesignRoot {
     users_of_library_all: RwLock<FnvHashMap<Symbol, FnvHashSet<UnitId>>>,
 }
 
+/// Copies the design units together with their analysis results
+impl Clone for DesignRoot {
+    fn clone(&self) -> Self {
+        DesignRoot {
+            symbols: self.symbols.clone(),
+            libraries: self.libraries.clone(),
+            users_of: RwLock::new(self.users_of.read().clone()),
+            missing_primary: RwLock::new(self.missing_primary.read().clone()),
+            users_of_library_all: RwLock::new(self.users_of_library_all.read().clone()),
+        }
+    }
+}
+
 impl DesignRoot {
     pub fn new(symbols: Arc<Symbols>) -> DesignRoot {
         DesignRoot {
@@ -282,6 +305,11 @@ impl DesignRoot {
         self.get_or_create_library(name);
     }
 
+    /// Get the names of all libraries
+    pub(super) fn library_names(&self) -> impl Iterator<Item = &Symbol> {
+        self.libraries.keys()
+    }
+
     pub(super) fn get_library_units(
         &self,
         library_name: &Symbol,
@@ -296,6 +324,43 @@ impl DesignRoot {
         self.libraries.get(library_name).map(|library| &library.ent)
     }
 
+    /// Get the design units defined in source in the order they appear in the file
+    /// A source file mapped to several libraries yields the units of every library
+    pub(super) fn get_units_in_source(&self, source: &Source) -> Vec<&LockedUnit> {
+        let mut units = Vec::new();
+        for library in self.libraries.values() {
+            if let Some(unit_ids) = library.units_by_source.get(source) {
+                for unit_id in unit_ids.iter() {
+                    if let Some(unit) = library.units.get(unit_id.key()) {
+                        units.push(unit);
+                    }
+                }
+            }
+        }
+        units.sort_by_key(|unit| unit.ident().pos.start());
+        units
+    }
+
+    /// Get the design unit enclosing the cursor
+    /// The cursor belongs to the first design unit ending after it, which includes a cursor
+    /// within the context clause of the design unit, or to the last design unit
+    pub(super) fn get_unit_at(&self, source: &Source, cursor: Position) -> Option<&LockedUnit> {
+        let units = self.get_units_in_source(source);
+        units
+            .iter()
+            .find(|unit| {
+                let unit = unit.unit.read();
+                let end = match unit.source_range() {
+                    Some(source_range) => source_range.end(),
+                    // The range of a package instantiation is not kept
+                    None => unit.ident().pos.end(),
+                };
+                cursor <= end
+            })
+            .or_else(|| units.last())
+            .copied()
+    }
+
     pub fn add_design_file(&mut self, library_name: Symbol, design_file: DesignFile) {
         self.get_or_create_library(library_name)
             .add_design_file(design_file);
@@ -331,6 +396,21 @@ impl DesignRoot {
         self.symbols.symtab().insert_utf8(name)
     }
 
+    /// The symbol of a name which already occurs in the design, without inserting it
+    pub(super) fn lookup_symbol_utf8(&self, name: &str) -> Option<Symbol> {
+        self.symbols.symtab().lookup_utf8(name)
+    }
+
+    /// The tokens of the source from the start position until the end position
+    pub(super) fn tokens_between(
+        &self,
+        source: &Source,
+        start: Position,
+        end: Position,
+    ) -> Vec<Token> {
+        tokenize_between(&self.symbols, source, start, end)
+    }
+
     pub(super) fn get_analysis<'a>(&self, locked_unit: &'a LockedUnit) -> UnitReadGuard<'a> {
         match locked_unit.unit.entry() {
             AnalysisEntry::Vacant(mut unit) => {
@@ -354,6 +434,7 @@ impl DesignRoot {
                     false
                 };
 
+                let local_regions = context.into_local_regions();
                 let root_region = Arc::new(root_region);
                 let region = Arc::new(region);
 
@@ -389,6 +470,7 @@ impl DesignRoot {
                     diagnostics,
                     root_region,
                     region,
+                    local_regions,
                     ent,
                     has_circular_dependency,
                 };
@@ -405,6 +487,18 @@ impl DesignRoot {
             .and_then(|library| library.units.get(&unit_id.key()))
     }
 
+    /// The design units which directly depend on the unit
+    pub(super) fn get_users_of(&self, unit_id: &UnitId) -> Vec<UnitId> {
+        self.users_of
+            .read()
+            .get(unit_id)
+            .into_iter()
+            .flatten()
+            .filter(|user| self.get_unit(user).is_some())
+            .cloned()
+            .collect()
+    }
+
     fn reset_affected(&self, mut affected: FnvHashSet<UnitId>) {
         // Reset analysis state of all design units
         for unit_id in affected.drain() {
@@ -563,13 +657,31 @@ impl DesignRoot {
         }
     }
 
+    #[cfg(test)]
     pub fn analyze(&mut self, diagnostics: &mut dyn DiagnosticHandler) {
+        self.prepare_analysis(diagnostics);
+        self.analyze_prepared(&AtomicBool::new(false), diagnostics);
+    }
+
+    /// Reset the analysis of the design units affected by the design units
+    /// added or removed since the last analysis
+    pub fn prepare_analysis(&mut self, diagnostics: &mut dyn DiagnosticHandler) {
         self.reset();
 
         for library in self.libraries.values_mut() {
             library.refresh(diagnostics);
         }
+    }
 
+    /// Analyze the design units which have no analysis result
+    ///
+    /// The analysis stops early when cancelled is set, design units which were already
+    /// analyzed keep their result. Returns false without emitting any diagnostics when cancelled.
+    pub fn analyze_prepared(
+        &self,
+        cancelled: &AtomicBool,
+        diagnostics: &mut dyn DiagnosticHandler,
+    ) -> bool {
         use rayon::prelude::*;
         // @TODO run in parallel
         let mut units: Vec<_> = Vec::new();
@@ -581,9 +693,15 @@ impl DesignRoot {
 
         // @TODO compute the best order to process the units in parallel
         units.par_iter().for_each(|unit| {
-            self.get_analysis(*unit);
+            if !cancelled.load(Ordering::Relaxed) {
+                self.get_analysis(*unit);
+            }
         });
 
+        if cancelled.load(Ordering::Relaxed) {
+            return false;
+        }
+
         // Emit diagnostics sorted within a file
         for library in self.libraries.values() {
             for unit_id in library.sorted_unit_ids() {
@@ -591,6 +709,7 @@ impl DesignRoot {
                 diagnostics.append(unit.unit.expect_analyzed().result().diagnostics.clone());
             }
         }
+        true
     }
 }
 
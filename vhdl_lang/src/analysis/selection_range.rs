@@ -0,0 +1,102 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2020, Olof Kraigher olof.kraigher@gmail.com
+
+use super::root::*;
+use crate::ast::search::*;
+use crate::ast::*;
+use crate::data::*;
+
+impl DesignRoot {
+    /// Find the nested ranges enclosing the cursor ordered from the innermost to the outermost
+    ///
+    /// The ranges are those of the names and expressions, the statements and statement lists
+    /// and finally the design unit without and with its context clause.
+    pub fn find_selection_ranges(&self, source: &Source, cursor: Position) -> Vec<SrcPos> {
+        for unit in self.get_units_in_source(source) {
+            let unit = unit.unit.read();
+            let source_range = match unit.source_range() {
+                Some(source_range) => source_range,
+                None => continue,
+            };
+            let unit_range = match unit.context_clause().and_then(|items| items.first()) {
+                Some(item) => item.pos.combine(source_range),
+                None => source_range.clone(),
+            };
+
+            if !is_inside(&unit_range, cursor) {
+                continue;
+            }
+
+            let mut searcher = FindSelectionRanges {
+                cursor,
+                ranges: vec![unit_range, source_range.clone()],
+            };
+            let _ = unit.search(&mut searcher);
+            return nested_ranges(searcher.ranges);
+        }
+        Vec::new()
+    }
+}
+
+/// The cursor is the gap between two characters and is inside of a range it touches
+fn is_inside(pos: &SrcPos, cursor: Position) -> bool {
+    pos.start() <= cursor && cursor <= pos.end()
+}
+
+/// Keep the chain of ranges which each contain the following range from the innermost outwards
+fn nested_ranges(mut ranges: Vec<SrcPos>) -> Vec<SrcPos> {
+    ranges.sort_by_key(|pos| (pos.start(), std::cmp::Reverse(pos.end())));
+
+    let mut nested: Vec<SrcPos> = Vec::new();
+    for pos in ranges {
+        match nested.last() {
+            Some(outer) if outer.range() == pos.range() => {}
+            Some(outer) if pos.end() > outer.end() => {}
+            _ => nested.push(pos),
+        }
+    }
+
+    nested.reverse();
+    nested
+}
+
+/// Find the positions containing the cursor, positions which do not are not searched further
+struct FindSelectionRanges {
+    cursor: Position,
+    ranges: Vec<SrcPos>,
+}
+
+impl FindSelectionRanges {
+    fn add(&mut self, pos: &SrcPos) -> SearchState {
+        if is_inside(pos, self.cursor) {
+            self.ranges.push(pos.clone());
+        }
+        NotFinished
+    }
+}
+
+impl Searcher for FindSelectionRanges {
+    fn search_with_pos(&mut self, pos: &SrcPos) -> SearchState {
+        if is_inside(pos, self.cursor) {
+            self.ranges.push(pos.clone());
+            NotFinished
+        } else {
+            Finished(NotFound)
+        }
+    }
+
+    fn search_decl_pos(&mut self, pos: &SrcPos) -> SearchState {
+        self.add(pos)
+    }
+
+    fn search_pos_with_ref(&mut self, pos: &SrcPos, _reference: &Reference) -> SearchState {
+        self.add(pos)
+    }
+
+    fn search_end_ident_pos(&mut self, _decl_pos: &SrcPos, end_pos: &SrcPos) -> SearchState {
+        self.add(end_pos)
+    }
+}
@@ -0,0 +1,210 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2020, Olof Kraigher olof.kraigher@gmail.com
+
+use super::root::*;
+use crate::ast::search::*;
+use crate::ast::*;
+use crate::data::*;
+use fnv::FnvHashMap;
+
+/// The kind of named entity which an identifier declares or refers to
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum SemanticTokenKind {
+    Signal,
+    Variable,
+    Constant,
+    Port(Mode),
+    Generic,
+    Type,
+    Subtype,
+    EnumLiteral,
+    Function,
+    Procedure,
+    Library,
+    /// A design unit or a component
+    Unit,
+}
+
+/// An identifier classified by the named entity it declares or refers to
+#[derive(PartialEq, Clone, Debug)]
+pub struct SemanticToken {
+    pub pos: SrcPos,
+    pub kind: SemanticTokenKind,
+    /// True for the identifier of the declaration itself including a repeated end identifier
+    pub is_declaration: bool,
+}
+
+impl SemanticTokenKind {
+    fn from_named_entity(ent: &NamedEntity) -> Option<SemanticTokenKind> {
+        let kind = match ent.actual_kind() {
+            NamedEntityKind::Object(ref object) => match object.list_type {
+                Some(InterfaceListType::Port) => {
+                    SemanticTokenKind::Port(object.mode.unwrap_or(Mode::In))
+                }
+                Some(InterfaceListType::Generic) => SemanticTokenKind::Generic,
+                _ => Self::from_object_class(object.class),
+            },
+            NamedEntityKind::DeferredConstant | NamedEntityKind::LoopParameter => {
+                SemanticTokenKind::Constant
+            }
+            NamedEntityKind::File | NamedEntityKind::InterfaceFile(..) => {
+                SemanticTokenKind::Variable
+            }
+            NamedEntityKind::Subtype(..) => SemanticTokenKind::Subtype,
+            NamedEntityKind::TypeDeclaration(..)
+            | NamedEntityKind::IntegerType(..)
+            | NamedEntityKind::AccessType(..)
+            | NamedEntityKind::RecordType(..)
+            | NamedEntityKind::IncompleteType(..)
+            | NamedEntityKind::InterfaceType
+            | NamedEntityKind::ProtectedType(..) => SemanticTokenKind::Type,
+            NamedEntityKind::EnumLiteral(..) => SemanticTokenKind::EnumLiteral,
+            NamedEntityKind::Subprogram(ref signature)
+            | NamedEntityKind::SubprogramDecl(ref signature) => {
+                if signature.return_type().is_some() {
+                    SemanticTokenKind::Function
+                } else {
+                    SemanticTokenKind::Procedure
+                }
+            }
+            NamedEntityKind::Library => SemanticTokenKind::Library,
+            NamedEntityKind::Component(..)
+            | NamedEntityKind::Entity(..)
+            | NamedEntityKind::Configuration(..)
+            | NamedEntityKind::Package(..)
+            | NamedEntityKind::UninstPackage(..)
+            | NamedEntityKind::PackageInstance(..)
+            | NamedEntityKind::LocalPackageInstance(..)
+            | NamedEntityKind::Context(..) => SemanticTokenKind::Unit,
+            NamedEntityKind::AliasOf(..)
+            | NamedEntityKind::UnknownAlias
+            | NamedEntityKind::Attribute
+            | NamedEntityKind::ElementDeclaration(..)
+            | NamedEntityKind::Label
+            | NamedEntityKind::PhysicalLiteral(..) => {
+                return None;
+            }
+        };
+        Some(kind)
+    }
+
+    fn from_declaration(decl: &FoundDeclaration) -> Option<SemanticTokenKind> {
+        let kind = match decl {
+            FoundDeclaration::Object(object) => Self::from_object_class(object.class),
+            FoundDeclaration::InterfaceObject(object) => match object.list_type {
+                InterfaceListType::Port => SemanticTokenKind::Port(object.mode),
+                InterfaceListType::Generic => SemanticTokenKind::Generic,
+                InterfaceListType::Parameter => Self::from_object_class(object.class),
+            },
+            FoundDeclaration::File(..) => SemanticTokenKind::Variable,
+            FoundDeclaration::Type(typ) => match typ.def {
+                TypeDefinition::Subtype(..) => SemanticTokenKind::Subtype,
+                _ => SemanticTokenKind::Type,
+            },
+            FoundDeclaration::Function(..) => SemanticTokenKind::Function,
+            FoundDeclaration::Procedure(..) => SemanticTokenKind::Procedure,
+            FoundDeclaration::Library(..) => SemanticTokenKind::Library,
+            FoundDeclaration::Component(..)
+            | FoundDeclaration::Package(..)
+            | FoundDeclaration::PackageInstance(..)
+            | FoundDeclaration::Configuration(..)
+            | FoundDeclaration::Entity(..)
+            | FoundDeclaration::Context(..) => SemanticTokenKind::Unit,
+            FoundDeclaration::ForIndex(..) | FoundDeclaration::ForGenerateIndex(..) => {
+                SemanticTokenKind::Constant
+            }
+            FoundDeclaration::ElementDeclaration(..) | FoundDeclaration::Alias(..) => {
+                return None;
+            }
+        };
+        Some(kind)
+    }
+
+    fn from_object_class(class: ObjectClass) -> SemanticTokenKind {
+        match class {
+            ObjectClass::Signal => SemanticTokenKind::Signal,
+            ObjectClass::Constant => SemanticTokenKind::Constant,
+            ObjectClass::Variable | ObjectClass::SharedVariable => SemanticTokenKind::Variable,
+        }
+    }
+}
+
+impl DesignRoot {
+    /// Classify the identifiers within the source by the named entity they declare or refer to
+    /// The tokens are sorted by position
+    pub fn find_semantic_tokens(&self, source: &Source) -> Vec<SemanticToken> {
+        let mut searcher = FindSemanticTokens::new(source);
+        let _ = self.search(&mut searcher);
+
+        let mut tokens = searcher.tokens;
+        tokens.sort_by_key(|token| token.pos.start());
+        tokens.dedup_by(|lhs, rhs| lhs.pos == rhs.pos);
+        tokens
+    }
+}
+
+struct FindSemanticTokens {
+    source: Source,
+    /// The kind of declarations within the source to classify end identifiers
+    declarations: FnvHashMap<SrcPos, SemanticTokenKind>,
+    tokens: Vec<SemanticToken>,
+}
+
+impl FindSemanticTokens {
+    fn new(source: &Source) -> FindSemanticTokens {
+        FindSemanticTokens {
+            source: source.clone(),
+            declarations: FnvHashMap::default(),
+            tokens: Vec::new(),
+        }
+    }
+
+    fn add(&mut self, pos: &SrcPos, kind: SemanticTokenKind, is_declaration: bool) {
+        if pos.source == self.source {
+            self.tokens.push(SemanticToken {
+                pos: pos.clone(),
+                kind,
+                is_declaration,
+            });
+        }
+    }
+}
+
+impl Searcher for FindSemanticTokens {
+    fn search_pos_with_ref(&mut self, pos: &SrcPos, reference: &Reference) -> SearchState {
+        if let Some(kind) = reference
+            .as_ref()
+            .and_then(|ent| SemanticTokenKind::from_named_entity(ent))
+        {
+            self.add(pos, kind, false);
+        }
+        NotFinished
+    }
+
+    fn search_decl(&mut self, pos: &SrcPos, decl: FoundDeclaration) -> SearchState {
+        if let Some(kind) = SemanticTokenKind::from_declaration(&decl) {
+            self.declarations.insert(pos.clone(), kind);
+            self.add(pos, kind, true);
+        }
+        NotFinished
+    }
+
+    fn search_end_ident_pos(&mut self, decl_pos: &SrcPos, end_pos: &SrcPos) -> SearchState {
+        if let Some(kind) = self.declarations.get(decl_pos).cloned() {
+            self.add(end_pos, kind, true);
+        }
+        NotFinished
+    }
+
+    // Assume source is searched first to filter out design units in other files
+    fn search_source(&mut self, source: &Source) -> SearchState {
+        if source == &self.source {
+            NotFinished
+        } else {
+            Finished(NotFound)
+        }
+    }
+}
@@ -25,7 +25,7 @@ impl<'a> AnalyzeContext<'a> {
             parent.add(label.clone(), NamedEntityKind::Label, diagnostics);
         }
 
-        match statement.statement {
+        match statement.statement.item {
             SequentialStatement::Return(ref mut ret) => {
                 let ReturnStatement { expression } = ret;
                 if let Some(ref mut expression) = expression {
@@ -92,10 +92,10 @@ impl<'a> AnalyzeContext<'a> {
                 }
             }
             SequentialStatement::If(ref mut ifstmt) => {
-                let IfStatement {
+                let Conditionals {
                     conditionals,
                     else_item,
-                } = ifstmt;
+                } = &mut ifstmt.conds;
 
                 // @TODO write generic function for this
                 for conditional in conditionals {
@@ -112,6 +112,7 @@ impl<'a> AnalyzeContext<'a> {
                     is_matching: _,
                     expression,
                     alternatives,
+                    end_label_pos: _,
                 } = case_stmt;
                 self.analyze_expression(parent, expression, diagnostics)?;
                 for alternative in alternatives.iter_mut() {
@@ -124,6 +125,7 @@ impl<'a> AnalyzeContext<'a> {
                 let LoopStatement {
                     iteration_scheme,
                     statements,
+                    end_label_pos: _,
                 } = loop_stmt;
                 match iteration_scheme {
                     Some(IterationScheme::For(ref mut index, ref mut drange)) => {
@@ -132,6 +134,7 @@ impl<'a> AnalyzeContext<'a> {
                         let designator: WithPos<Designator> = index.clone().into();
                         region.add(designator, NamedEntityKind::LoopParameter, diagnostics);
                         self.analyze_sequential_part(&mut region, statements, diagnostics)?;
+                        self.add_local_region(&statement.statement.pos, &region);
                     }
                     Some(IterationScheme::While(ref mut expr)) => {
                         self.analyze_expression(parent, expr, diagnostics)?;
@@ -0,0 +1,502 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2020, Olof Kraigher olof.kraigher@gmail.com
+
+use super::completion::type_region;
+use super::region::*;
+use super::root::*;
+use crate::ast::search::*;
+use crate::ast::*;
+use crate::data::*;
+use crate::syntax::{Kind, Token, Value};
+use std::sync::Arc;
+
+/// The candidate signatures of the subprogram call or association list enclosing the cursor
+pub struct CallSignatures {
+    pub signatures: Vec<CallSignature>,
+    /// The index of the first signature which may accept the actuals typed so far
+    pub active_signature: usize,
+    /// The index of the parameter at the cursor within the active signature
+    pub active_parameter: Option<usize>,
+}
+
+/// The signature of a subprogram or the interface list of an instantiated entity or component
+pub struct CallSignature {
+    pub label: String,
+    /// The character range of each parameter within the label
+    pub parameters: Vec<(usize, usize)>,
+    formals: Vec<Designator>,
+}
+
+impl CallSignature {
+    fn subprogram(ent: &NamedEntity) -> Option<CallSignature> {
+        let signature = ent.signature()?;
+        let designator = ent.designator().to_string();
+        let (description, ranges) = signature.describe_with_param_ranges();
+        let offset = designator.chars().count();
+
+        Some(CallSignature {
+            label: format!("{}{}", designator, description),
+            parameters: ranges
+                .into_iter()
+                .map(|(start, end)| (start + offset, end + offset))
+                .collect(),
+            formals: signature.formals().cloned().collect(),
+        })
+    }
+
+    fn interface_list(
+        designator: &Designator,
+        list_type: InterfaceListType,
+        list: &[InterfaceDeclaration],
+    ) -> CallSignature {
+        let keyword = match list_type {
+            InterfaceListType::Generic => "generic",
+            _ => "port",
+        };
+
+        let mut label = format!("{} {} (", designator, keyword);
+        let mut parameters = Vec::with_capacity(list.len());
+        for (i, decl) in list.iter().enumerate() {
+            let start = label.chars().count();
+            label.push_str(&decl.to_string());
+            parameters.push((start, label.chars().count()));
+
+            if i + 1 < list.len() {
+                label.push_str("; ");
+            }
+        }
+        label.push(')');
+
+        CallSignature {
+            label,
+            parameters,
+            formals: list.iter().map(InterfaceDeclaration::designator).collect(),
+        }
+    }
+}
+
+impl DesignRoot {
+    /// Find the candidate signatures of the subprogram call or the port or generic map
+    /// of an instantiation enclosing the cursor
+    ///
+    /// The tokens before the cursor are scanned for unclosed parenthesis since the call being
+    /// typed is typically incomplete. When the name before the innermost parenthesis is not a
+    /// subprogram such as for an array index the enclosing parenthesis are tried in turn.
+    pub fn find_call_signatures(
+        &self,
+        source: &Source,
+        cursor: Position,
+    ) -> Option<CallSignatures> {
+        let start = self.token_start(source, cursor);
+        let tokens = self.tokens_between(source, start, cursor);
+        let visible = self.visible_at(source, cursor);
+
+        open_parens(&tokens).iter().rev().find_map(|paren| {
+            let prefix = &tokens[..paren.idx];
+            let signatures = if let Some((list_type, names)) = instantiated_unit(prefix) {
+                vec![self.instantiated_interface_list(&visible, list_type, &names)?]
+            } else {
+                self.called_subprograms(&visible, &called_name(prefix)?)
+            };
+
+            if signatures.is_empty() {
+                return None;
+            }
+
+            let formal = paren.formal.clone().map(Designator::Identifier);
+
+            let active_signature = signatures
+                .iter()
+                .position(|signature| match formal {
+                    Some(ref formal) => signature.formals.contains(formal),
+                    None => paren.commas < signature.formals.len(),
+                })
+                .unwrap_or(0);
+
+            let formals = &signatures[active_signature].formals;
+            let active_parameter = match formal {
+                Some(ref formal) => formals.iter().position(|other| other == formal),
+                None => Some(paren.commas).filter(|&idx| idx < formals.len()),
+            };
+
+            Some(CallSignatures {
+                signatures,
+                active_signature,
+                active_parameter,
+            })
+        })
+    }
+
+    /// The position from which the text before the cursor is tokenized
+    fn token_start(&self, source: &Source, cursor: Position) -> Position {
+        let unit = if let Some(unit) = self.get_unit_at(source, cursor) {
+            unit
+        } else {
+            return Position::default();
+        };
+
+        let local_start = self
+            .get_analysis(unit)
+            .result()
+            .local_regions
+            .iter()
+            .map(|(source_range, _)| source_range)
+            .filter(|source_range| {
+                source_range.source == *source
+                    && source_range.start() <= cursor
+                    && cursor <= source_range.end()
+            })
+            .map(|source_range| source_range.start())
+            .max();
+
+        let unit = unit.unit.read();
+        let start = local_start
+            .or_else(|| unit.source_range().map(|source_range| source_range.start()))
+            .filter(|start| *start <= cursor)
+            .unwrap_or_else(Position::default);
+
+        let mut searcher = FindTokenStart { cursor, start };
+        let _ = unit.search(&mut searcher);
+        searcher.start
+    }
+
+    /// The signatures of the subprograms with the selected name sorted by label
+    fn called_subprograms(
+        &self,
+        visible: &[Arc<NamedEntity>],
+        names: &[Symbol],
+    ) -> Vec<CallSignature> {
+        let (suffix, prefix) = if let Some(split) = names.split_last() {
+            split
+        } else {
+            return Vec::new();
+        };
+        let designator = Designator::Identifier(suffix.clone());
+
+        let candidates: Vec<_> = if prefix.is_empty() {
+            visible
+                .iter()
+                .filter(|ent| ent.designator() == &designator)
+                .cloned()
+                .collect()
+        } else {
+            self.lookup_selected_name(visible, prefix)
+                .and_then(|prefix| lookup_selected_overloaded(&prefix, &designator))
+                .unwrap_or_default()
+        };
+
+        let subprograms: Vec<_> = candidates
+            .into_iter()
+            .filter(|ent| ent.as_actual().is_subprogram() || ent.as_actual().is_subprogram_decl())
+            .collect();
+
+        if subprograms.is_empty() {
+            return Vec::new();
+        }
+
+        // A subprogram declaration and its body have the same signature
+        let overloaded = OverloadedName::new(subprograms);
+        let mut signatures: Vec<_> = overloaded
+            .entities()
+            .filter_map(|ent| CallSignature::subprogram(ent))
+            .collect();
+        signatures.sort_by(|lhs, rhs| lhs.label.cmp(&rhs.label));
+        signatures
+    }
+
+    /// The generic or port list of the entity or component of an instantiation
+    fn instantiated_interface_list(
+        &self,
+        visible: &[Arc<NamedEntity>],
+        list_type: InterfaceListType,
+        names: &[Symbol],
+    ) -> Option<CallSignature> {
+        let ent = self.lookup_selected_name(visible, names)?;
+        let ent = ent.as_actual();
+
+        match ent.kind() {
+            NamedEntityKind::Entity(..) | NamedEntityKind::Component(..) => {}
+            _ => return None,
+        }
+
+        let list = FindInterfaceList::search(self, ent.decl_pos()?, list_type)?;
+        Some(CallSignature::interface_list(
+            ent.designator(),
+            list_type,
+            &list,
+        ))
+    }
+
+    /// Lookup a non-overloaded selected name such as `lib.pkg` among the visible names
+    fn lookup_selected_name(
+        &self,
+        visible: &[Arc<NamedEntity>],
+        names: &[Symbol],
+    ) -> Option<Arc<NamedEntity>> {
+        let mut names = names.iter();
+        let first = Designator::Identifier(names.next()?.clone());
+        let mut ent = self.lookup_completion_prefix(visible, &first)?;
+
+        for name in names {
+            let suffix = Designator::Identifier(name.clone());
+            ent = self.lookup_selected_prefix(&ent, &suffix)?;
+        }
+
+        Some(ent)
+    }
+}
+
+/// Lookup all overloaded named entities with the suffix designator within the prefix
+fn lookup_selected_overloaded(
+    prefix: &NamedEntity,
+    suffix: &Designator,
+) -> Option<Vec<Arc<NamedEntity>>> {
+    let region = match prefix.actual_kind() {
+        NamedEntityKind::Package(ref region)
+        | NamedEntityKind::PackageInstance(ref region)
+        | NamedEntityKind::LocalPackageInstance(ref region) => region,
+        NamedEntityKind::Object(ref object) => type_region(object.subtype.base_type())?,
+        _ => return None,
+    };
+
+    Some(match region.lookup_selected(suffix)? {
+        NamedEntities::Single(ent) => vec![ent.clone()],
+        NamedEntities::Overloaded(overloaded) => overloaded.entities().cloned().collect(),
+    })
+}
+
+/// The identifier of a token
+fn identifier(token: &Token) -> Option<&Symbol> {
+    match token.value {
+        Value::Identifier(ref symbol) if token.kind == Kind::Identifier => Some(symbol),
+        _ => None,
+    }
+}
+
+/// Find the position from which the text before the cursor is tokenized
+///
+/// Calls and association lists do not span several statements so the text before the cursor
+/// is tokenized from the end of the last concurrent statement before the cursor within the
+/// innermost statement, subprogram body or design unit enclosing the cursor.
+struct FindTokenStart {
+    cursor: Position,
+    start: Position,
+}
+
+impl Searcher for FindTokenStart {
+    fn search_concurrent_statement(
+        &mut self,
+        statement: &LabeledConcurrentStatement,
+    ) -> SearchState {
+        let pos = &statement.statement.pos;
+        if pos.start() <= self.cursor && self.cursor <= pos.end() {
+            NotFinished
+        } else {
+            if pos.end() <= self.cursor && self.start < pos.end() {
+                self.start = pos.end();
+            }
+            // The statement does not enclose the cursor
+            Finished(NotFound)
+        }
+    }
+}
+
+/// An unclosed parenthesis before the cursor
+struct OpenParen {
+    /// The index of the parenthesis token
+    idx: usize,
+    /// The number of commas between the parenthesis and the cursor
+    commas: usize,
+    /// The formal of the named association at the cursor
+    formal: Option<Symbol>,
+}
+
+/// Find the unclosed parenthesis before the cursor with the innermost last
+fn open_parens(tokens: &[Token]) -> Vec<OpenParen> {
+    let mut parens: Vec<OpenParen> = Vec::new();
+
+    for (idx, token) in tokens.iter().enumerate() {
+        match token.kind {
+            Kind::LeftPar => parens.push(OpenParen {
+                idx,
+                commas: 0,
+                formal: None,
+            }),
+            Kind::RightPar => {
+                parens.pop();
+            }
+            Kind::Comma => {
+                if let Some(paren) = parens.last_mut() {
+                    paren.commas += 1;
+                    paren.formal = None;
+                }
+            }
+            Kind::RightArrow => {
+                if let Some(paren) = parens.last_mut() {
+                    if idx >= 2 {
+                        let before = tokens[idx - 2].kind;
+                        if before == Kind::LeftPar || before == Kind::Comma {
+                            paren.formal = identifier(&tokens[idx - 1]).cloned();
+                        }
+                    }
+                }
+            }
+            // Calls and association lists do not contain semi colons
+            // so any parenthesis before it are part of something else
+            Kind::SemiColon => parens.clear(),
+            _ => {}
+        }
+    }
+
+    parens
+}
+
+/// The selected name before an opening parenthesis
+///
+/// Example: `x := pkg.fun` => `[pkg, fun]`
+fn called_name(tokens: &[Token]) -> Option<Vec<Symbol>> {
+    let mut names = Vec::new();
+    let mut idx = tokens.len();
+
+    loop {
+        idx = idx.checked_sub(1)?;
+        names.push(identifier(&tokens[idx])?.clone());
+
+        if idx > 0 && tokens[idx - 1].kind == Kind::Dot {
+            idx -= 1;
+        } else {
+            break;
+        }
+    }
+
+    // An attribute such as `x'image` is not a subprogram
+    if idx > 0 && tokens[idx - 1].kind == Kind::Tick {
+        return None;
+    }
+
+    names.reverse();
+    Some(names)
+}
+
+/// The interface list type and the name of the instantiated unit
+/// of a port or generic map before an opening parenthesis
+///
+/// Example: `inst : entity work.ent(rtl) generic map (...) port map` => `(Port, [work, ent])`
+fn instantiated_unit(tokens: &[Token]) -> Option<(InterfaceListType, Vec<Symbol>)> {
+    let list_type = match tokens {
+        [.., list, map] if map.kind == Kind::Map => match list.kind {
+            Kind::Port => InterfaceListType::Port,
+            Kind::Generic => InterfaceListType::Generic,
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    // Find the colon after the instance label skipping any generic map and architecture name
+    let mut idx = tokens.len() - 2;
+    let mut depth = 0;
+    loop {
+        idx = idx.checked_sub(1)?;
+        match tokens[idx].kind {
+            Kind::RightPar => depth += 1,
+            Kind::LeftPar if depth == 0 => return None,
+            Kind::LeftPar => depth -= 1,
+            Kind::Colon if depth == 0 => break,
+            Kind::SemiColon => return None,
+            _ => {}
+        }
+    }
+
+    let mut rest = tokens[idx + 1..].iter().peekable();
+    if let Some(token) = rest.peek() {
+        if token.kind == Kind::Entity || token.kind == Kind::Component {
+            rest.next();
+        }
+    }
+
+    let mut names = Vec::new();
+    loop {
+        names.push(identifier(rest.next()?)?.clone());
+
+        match rest.next() {
+            Some(token) if token.kind == Kind::Dot => {}
+            _ => break,
+        }
+    }
+
+    Some((list_type, names))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::syntax::test::Code;
+
+    fn open_paren_at(code: &Code) -> Option<(Vec<Symbol>, usize, Option<Symbol>)> {
+        let tokens = code.tokenize();
+        let paren = open_parens(&tokens).pop()?;
+        let names = called_name(&tokens[..paren.idx])?;
+        Some((names, paren.commas, paren.formal))
+    }
+
+    #[test]
+    fn finds_innermost_call() {
+        let code = Code::new("x := foo(a, pkg.bar(b, ");
+        assert_eq!(
+            open_paren_at(&code),
+            Some((vec![code.symbol("pkg"), code.symbol("bar")], 1, None))
+        );
+
+        let code = Code::new("x := foo(a, bar(b), ");
+        assert_eq!(
+            open_paren_at(&code),
+            Some((vec![code.symbol("foo")], 2, None))
+        );
+    }
+
+    #[test]
+    fn finds_named_association_formal() {
+        let code = Code::new("proc(a => 1, b => ");
+        assert_eq!(
+            open_paren_at(&code),
+            Some((vec![code.symbol("proc")], 1, Some(code.symbol("b"))))
+        );
+    }
+
+    #[test]
+    fn ignores_comments_strings_and_character_literals() {
+        let code = Code::new("foo(\"(,\", ',', -- (,\n /* (, */ ");
+        assert_eq!(
+            open_paren_at(&code),
+            Some((vec![code.symbol("foo")], 2, None))
+        );
+    }
+
+    #[test]
+    fn ignores_attributes_and_closed_calls() {
+        assert_eq!(open_paren_at(&Code::new("x := integer'image(")), None);
+        assert_eq!(open_paren_at(&Code::new("x := foo(a);")), None);
+        assert_eq!(open_paren_at(&Code::new("x := foo(a); y := ")), None);
+    }
+
+    #[test]
+    fn finds_instantiated_unit() {
+        let code = Code::new("inst : entity work.ent(rtl) generic map (a => 1) port map");
+        assert_eq!(
+            instantiated_unit(&code.tokenize()),
+            Some((
+                InterfaceListType::Port,
+                vec![code.symbol("work"), code.symbol("ent")]
+            ))
+        );
+
+        let code = Code::new("inst : comp generic map");
+        assert_eq!(
+            instantiated_unit(&code.tokenize()),
+            Some((InterfaceListType::Generic, vec![code.symbol("comp")]))
+        );
+    }
+}
@@ -0,0 +1,208 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2020, Olof Kraigher olof.kraigher@gmail.com
+
+use super::*;
+use crate::analysis::{AssociationRewrite, AssociationStyle};
+use crate::data::Position;
+
+/// Apply the edits of the rewrite to the code which is expected to be ascii
+fn apply(code: &str, rewrite: &AssociationRewrite) -> String {
+    let offset = |position: Position| -> usize {
+        let line_start: usize = code
+            .split_inclusive('\n')
+            .take(position.line as usize)
+            .map(|line| line.len())
+            .sum();
+        line_start + position.character as usize
+    };
+
+    let mut result = code.to_owned();
+    for (pos, text) in rewrite.edits.iter().rev() {
+        result.replace_range(offset(pos.start())..offset(pos.end()), text);
+    }
+    result
+}
+
+fn find_rewrites(builder: &LibraryBuilder, code: &Code, cursor: Code) -> Vec<AssociationRewrite> {
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+    root.find_association_rewrites(code.source(), cursor.start())
+}
+
+const ENTITY: &str = "
+entity dma is
+  generic (
+    width : natural;
+    depth : natural
+  );
+  port (
+    clk : in bit;
+    data : out bit_vector(width - 1 downto 0)
+  );
+end entity;
+";
+
+#[test]
+fn port_map_positional_to_named() {
+    let mut builder = LibraryBuilder::new();
+    builder.code("libname", ENTITY);
+    let contents = "
+entity top is
+end entity;
+
+architecture a of top is
+  signal sys_clk : bit;
+  signal sys_data : bit_vector(7 downto 0);
+begin
+  inst : entity work.dma
+    generic map (8, depth => 4)
+    port map (sys_clk, sys_data);
+end architecture;
+";
+    let code = builder.code("libname", contents);
+
+    let result = find_rewrites(&builder, &code, code.s1("sys_data)"));
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].style, AssociationStyle::Named);
+    assert_eq!(
+        apply(contents, &result[0]),
+        contents.replace(
+            "port map (sys_clk, sys_data)",
+            "port map (clk => sys_clk, data => sys_data)"
+        )
+    );
+
+    // The generic map is mixed and may be rewritten in both directions
+    let result = find_rewrites(&builder, &code, code.s1("8"));
+    assert_eq!(result.len(), 2);
+    assert_eq!(result[0].style, AssociationStyle::Named);
+    assert_eq!(
+        apply(contents, &result[0]),
+        contents.replace("(8, depth => 4)", "(width => 8, depth => 4)")
+    );
+    assert_eq!(result[1].style, AssociationStyle::Positional);
+    assert_eq!(
+        apply(contents, &result[1]),
+        contents.replace("(8, depth => 4)", "(8, 4)")
+    );
+}
+
+#[test]
+fn port_map_named_to_positional_requires_formal_order() {
+    let mut builder = LibraryBuilder::new();
+    builder.code("libname", ENTITY);
+    let contents = "
+entity top is
+end entity;
+
+architecture a of top is
+  signal sys_clk : bit;
+  signal sys_data : bit_vector(7 downto 0);
+begin
+  inst : entity work.dma
+    generic map (depth => 4, width => 8)
+    port map (
+      clk => sys_clk,
+      data => sys_data
+    );
+end architecture;
+";
+    let code = builder.code("libname", contents);
+
+    let result = find_rewrites(&builder, &code, code.s1("sys_clk,"));
+    assert_eq!(result.len(), 1);
+    assert_eq!(result[0].style, AssociationStyle::Positional);
+    assert_eq!(
+        apply(contents, &result[0]),
+        contents.replace(
+            "clk => sys_clk,\n      data => sys_data",
+            "sys_clk,\n      sys_data"
+        )
+    );
+
+    // The generics are named out of order
+    assert_eq!(
+        find_rewrites(&builder, &code, code.s1("depth => 4")),
+        vec![]
+    );
+}
+
+#[test]
+fn innermost_subprogram_call() {
+    let mut builder = LibraryBuilder::new();
+    let contents = "
+package pkg is
+  function inner(a, b : natural) return natural;
+  function outer(x : natural; y : natural := 0) return natural;
+  procedure proc(value : natural);
+end package;
+
+package body pkg is
+  function inner(a, b : natural) return natural is
+  begin
+    return a + b;
+  end function;
+
+  function outer(x : natural; y : natural := 0) return natural is
+  begin
+    return x;
+  end function;
+
+  procedure proc(value : natural) is
+  begin
+    proc(outer(inner(1, 2)));
+  end procedure;
+end package body;
+";
+    let code = builder.code("libname", contents);
+
+    let result = find_rewrites(&builder, &code, code.s1("1, 2"));
+    assert_eq!(result.len(), 1);
+    assert_eq!(
+        apply(contents, &result[0]),
+        contents.replace("inner(1, 2)", "inner(a => 1, b => 2)")
+    );
+
+    let result = find_rewrites(&builder, &code, code.s1("outer(inner"));
+    assert_eq!(result.len(), 1);
+    assert_eq!(
+        apply(contents, &result[0]),
+        contents.replace("proc(outer(", "proc(value => outer(")
+    );
+
+    let result = find_rewrites(&builder, &code, code.s1("inner(1").s1("("));
+    assert_eq!(
+        apply(contents, &result[0]),
+        contents.replace("outer(inner(1, 2))", "outer(x => inner(1, 2))")
+    );
+}
+
+#[test]
+fn no_rewrites_of_more_positional_associations_than_formals() {
+    let mut builder = LibraryBuilder::new();
+    builder.code("libname", ENTITY);
+    let code = builder.code(
+        "libname",
+        "
+entity top is
+end entity;
+
+architecture a of top is
+  signal s : bit;
+begin
+  inst : entity work.dma
+    generic map (8, 4)
+    port map (s, s, s, data => s);
+end architecture;
+",
+    );
+    let (root, _) = builder.get_analyzed_root();
+
+    assert_eq!(
+        root.find_association_rewrites(code.source(), code.s1("data =>").start()),
+        Vec::new()
+    );
+}
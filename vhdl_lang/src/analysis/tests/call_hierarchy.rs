@@ -0,0 +1,158 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2020, Olof Kraigher olof.kraigher@gmail.com
+
+use super::*;
+use crate::analysis::{CallItemKind, Calls};
+use crate::data::SrcPos;
+
+fn summarize(calls: Vec<Calls>) -> Vec<(String, CallItemKind, SrcPos, Vec<SrcPos>)> {
+    calls
+        .into_iter()
+        .map(|calls| {
+            (
+                calls.item.name,
+                calls.item.kind,
+                calls.item.pos,
+                calls.positions,
+            )
+        })
+        .collect()
+}
+
+fn code() -> (LibraryBuilder, Code) {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+package bfm is
+  procedure bfm_write(signal bus_sig : out natural; value : natural);
+  function double_it(value : natural) return natural;
+end package;
+
+package body bfm is
+  function double_it(value : natural) return natural is
+  begin
+    return 2 * value;
+  end function;
+
+  procedure bfm_write(signal bus_sig : out natural; value : natural) is
+  begin
+    bus_sig <= double_it(value);
+  end procedure;
+end package body;
+
+use work.bfm.all;
+
+entity tb is
+end entity;
+
+architecture sim of tb is
+  signal bus_sig : natural;
+begin
+  main : process
+  begin
+    bfm_write(bus_sig, 1);
+    bfm_write(bus_sig, double_it(2));
+    wait;
+  end process;
+
+  init : bfm_write(bus_sig, 3);
+end architecture;
+",
+    );
+    (builder, code)
+}
+
+#[test]
+fn prepares_subprogram_item() {
+    let (builder, code) = code();
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    let item = root
+        .prepare_call_hierarchy(&code.s1("double_it").pos())
+        .unwrap();
+    assert_eq!(item.name, "double_it");
+    assert_eq!(item.kind, CallItemKind::Function);
+    assert_eq!(item.pos, code.s1("double_it").pos());
+
+    assert_eq!(root.prepare_call_hierarchy(&code.s1("bus_sig").pos()), None);
+}
+
+#[test]
+fn incoming_calls_from_processes_and_concurrent_procedure_calls() {
+    let (builder, code) = code();
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    assert_eq!(
+        summarize(root.find_incoming_calls(&code.s1("bfm_write").pos())),
+        vec![
+            (
+                "main".to_owned(),
+                CallItemKind::Process,
+                code.s1("main").pos(),
+                vec![code.s("bfm_write", 3).pos(), code.s("bfm_write", 4).pos()]
+            ),
+            (
+                "init".to_owned(),
+                CallItemKind::Process,
+                code.s1("init").pos(),
+                vec![code.s("bfm_write", 5).pos()]
+            ),
+        ]
+    );
+}
+
+#[test]
+fn incoming_calls_to_declaration_and_body() {
+    let (builder, code) = code();
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    // The call within the package body refers to the body
+    // while the call within the process refers to the declaration
+    let expected = vec![
+        (
+            "bfm_write".to_owned(),
+            CallItemKind::Procedure,
+            code.s("bfm_write", 2).pos(),
+            vec![code.s("double_it", 3).pos()],
+        ),
+        (
+            "main".to_owned(),
+            CallItemKind::Process,
+            code.s1("main").pos(),
+            vec![code.s("double_it", 4).pos()],
+        ),
+    ];
+
+    assert_eq!(
+        summarize(root.find_incoming_calls(&code.s1("double_it").pos())),
+        expected
+    );
+    assert_eq!(
+        summarize(root.find_incoming_calls(&code.s("double_it", 2).pos())),
+        expected
+    );
+}
+
+#[test]
+fn outgoing_calls_from_body() {
+    let (builder, code) = code();
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    assert_eq!(
+        summarize(root.find_outgoing_calls(&code.s1("bfm_write").pos())),
+        vec![(
+            "double_it".to_owned(),
+            CallItemKind::Function,
+            code.s("double_it", 2).pos(),
+            vec![code.s("double_it", 3).pos()]
+        )]
+    );
+}
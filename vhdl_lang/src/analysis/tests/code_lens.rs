@@ -0,0 +1,163 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2020, Olof Kraigher olof.kraigher@gmail.com
+
+use super::*;
+use crate::analysis::CodeLens;
+
+#[test]
+fn code_lenses_summarize_usage() {
+    let mut builder = LibraryBuilder::new();
+    let pkg = builder.code(
+        "libname",
+        "
+package pkg is
+  function fun(arg : natural) return natural;
+end package;
+
+package body pkg is
+  function fun(arg : natural) return natural is
+  begin
+    return arg;
+  end function;
+end package body;
+",
+    );
+
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+end entity;
+
+architecture a of ent is
+begin
+end architecture;
+
+architecture b of ent is
+begin
+end architecture;
+
+use work.pkg.all;
+
+entity top is
+end entity;
+
+architecture a of top is
+  component ent is
+  end component;
+  constant c0 : natural := fun(0);
+  constant c1 : natural := fun(c0);
+begin
+  inst0 : entity work.ent(a);
+  inst1 : ent;
+end architecture;
+",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    assert_eq!(
+        root.find_code_lenses(pkg.source()),
+        vec![
+            CodeLens {
+                pos: pkg.s1("pkg").pos(),
+                title: "used by 1 unit".to_owned(),
+                references: vec![code.s1("pkg").pos(), pkg.s("pkg", 2).pos()],
+            },
+            CodeLens {
+                pos: pkg.s("fun(", 1).s1("fun").pos(),
+                title: "2 references".to_owned(),
+                references: vec![
+                    code.s("fun(", 1).s1("fun").pos(),
+                    code.s("fun(", 2).s1("fun").pos()
+                ],
+            },
+            CodeLens {
+                pos: pkg.s("fun(", 2).s1("fun").pos(),
+                title: "2 references".to_owned(),
+                references: vec![
+                    code.s("fun(", 1).s1("fun").pos(),
+                    code.s("fun(", 2).s1("fun").pos()
+                ],
+            },
+        ]
+    );
+
+    let lenses = root.find_code_lenses(code.source());
+    assert_eq!(
+        lenses
+            .iter()
+            .map(|lens| (lens.pos.clone(), lens.title.as_str()))
+            .collect::<Vec<_>>(),
+        vec![
+            (
+                code.s1("entity ent").s("ent", 2).pos(),
+                "2 architectures \u{b7} 2 instantiations"
+            ),
+            (
+                code.s1("top").pos(),
+                "1 architecture \u{b7} 0 instantiations"
+            ),
+        ]
+    );
+}
+
+#[test]
+fn code_lens_counts_component_instances_bound_to_the_entity() {
+    let mut builder = LibraryBuilder::new();
+    let lib1 = builder.code(
+        "lib1",
+        "
+entity ent is
+end entity;
+",
+    );
+
+    let lib2 = builder.code(
+        "lib2",
+        "
+entity ent is
+end entity;
+
+entity top is
+end entity;
+
+architecture a of top is
+  component ent is
+  end component;
+begin
+  inst0 : ent;
+  inst1 : component ent;
+end architecture;
+",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    assert_eq!(
+        root.find_code_lenses(lib1.source()),
+        vec![CodeLens {
+            pos: lib1.s1("entity ent").s("ent", 2).pos(),
+            title: "0 architectures \u{b7} 0 instantiations".to_owned(),
+            references: vec![],
+        }]
+    );
+
+    let lenses = root.find_code_lenses(lib2.source());
+    assert_eq!(
+        lenses[0],
+        CodeLens {
+            pos: lib2.s1("entity ent").s("ent", 2).pos(),
+            title: "0 architectures \u{b7} 2 instantiations".to_owned(),
+            references: vec![
+                lib2.s1("inst0 : ent").s1("ent").pos(),
+                lib2.s1("component ent;").s("ent", 2).pos()
+            ],
+        }
+    );
+}
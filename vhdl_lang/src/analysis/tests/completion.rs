@@ -0,0 +1,295 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2020, Olof Kraigher olof.kraigher@gmail.com
+
+use super::*;
+use crate::data::Position;
+
+fn completion_names(builder: &LibraryBuilder, code: &Code, cursor: Position) -> Vec<String> {
+    let (root, _) = builder.get_analyzed_root();
+    let mut names: Vec<String> = root
+        .list_completion_options(code.source(), cursor)
+        .iter()
+        .map(|ent| ent.designator().to_string())
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn completes_names_visible_in_architecture() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+  port (clk : in bit);
+end entity;
+
+architecture a of ent is
+  signal sig_foo : natural;
+begin
+  sig_foo <= 0;
+end architecture;
+",
+    );
+
+    let names = completion_names(&builder, &code, code.s1("sig_foo <=").start());
+    for name in &["clk", "sig_foo", "ent", "a", "NATURAL", "work", "std"] {
+        assert!(names.contains(&(*name).to_owned()), "{}", name);
+    }
+}
+
+#[test]
+fn completes_primary_units_after_library_prefix() {
+    let mut builder = LibraryBuilder::new();
+    builder.code(
+        "libname",
+        "
+package pkg is
+end package;
+
+entity ent is
+end entity;
+
+architecture a of ent is
+begin
+end architecture;
+",
+    );
+    let code = builder.code(
+        "libname",
+        "
+use work.pkg.all;
+
+entity ent2 is
+end entity;
+",
+    );
+
+    let names = completion_names(&builder, &code, code.s1("work.").end());
+    assert_eq!(names, vec!["ent", "ent2", "pkg"]);
+}
+
+#[test]
+fn completes_package_declarations_after_package_prefix() {
+    let mut builder = LibraryBuilder::new();
+    builder.code(
+        "libname",
+        "
+package pkg is
+  constant c0 : natural := 0;
+  type enum_t is (alpha, beta);
+  function fun return natural;
+end package;
+",
+    );
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+end entity;
+
+architecture a of ent is
+  constant c1 : natural := work.pkg.c0;
+begin
+end architecture;
+",
+    );
+
+    let names = completion_names(&builder, &code, code.s1("work.pkg.").end());
+    assert_eq!(names, vec!["alpha", "beta", "c0", "enum_t", "fun"]);
+}
+
+#[test]
+fn completes_record_elements_after_object_prefix() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+end entity;
+
+architecture a of ent is
+  type rec_t is record
+    field1 : natural;
+    field2 : bit;
+  end record;
+  signal rec : rec_t;
+begin
+  rec.field1 <= 0;
+end architecture;
+",
+    );
+
+    let names = completion_names(&builder, &code, code.s1("rec.").end());
+    assert_eq!(names, vec!["field1", "field2"]);
+
+    // Partially typed element name
+    let names = completion_names(&builder, &code, code.s1("rec.fie").end());
+    assert_eq!(names, vec!["field1", "field2"]);
+}
+
+#[test]
+fn completes_nothing_after_unknown_prefix() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+end entity;
+
+architecture a of ent is
+  signal sig : natural;
+begin
+  sig <= missing.foo;
+end architecture;
+",
+    );
+
+    let names = completion_names(&builder, &code, code.s1("missing.").end());
+    assert_eq!(names, Vec::<String>::new());
+}
+
+#[test]
+fn completes_declarations_local_to_enclosing_statements() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+end entity;
+
+architecture a of ent is
+  signal sig : natural;
+begin
+  gen : for gen_idx in 0 to 1 generate
+    signal gen_sig : natural;
+  begin
+    main : process
+      variable proc_var : natural;
+
+      procedure proc(proc_arg : natural) is
+        variable sub_var : natural;
+      begin
+        sub_var := proc_arg;
+      end procedure;
+    begin
+      for loop_idx in 0 to 1 loop
+        proc_var := loop_idx;
+      end loop;
+    end process;
+  end generate;
+
+  other : process
+    variable other_var : natural;
+  begin
+  end process;
+end architecture;
+",
+    );
+
+    let names = completion_names(&builder, &code, code.s1("proc_var := loop_idx").start());
+    for name in &["sig", "gen_idx", "gen_sig", "proc_var", "proc", "loop_idx"] {
+        assert!(names.contains(&(*name).to_owned()), "{}", name);
+    }
+    for name in &["sub_var", "proc_arg", "other_var"] {
+        assert!(!names.contains(&(*name).to_owned()), "{}", name);
+    }
+
+    let names = completion_names(&builder, &code, code.s1("sub_var := proc_arg").start());
+    for name in &["sub_var", "proc_arg", "proc_var", "gen_sig"] {
+        assert!(names.contains(&(*name).to_owned()), "{}", name);
+    }
+    assert!(!names.contains(&"loop_idx".to_owned()));
+}
+
+#[test]
+fn completes_record_elements_after_local_object_prefix() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+end entity;
+
+architecture a of ent is
+  type rec_t is record
+    field1 : natural;
+    field2 : bit;
+  end record;
+begin
+  main : process
+    variable v : rec_t;
+  begin
+    v.field1 := 0;
+  end process;
+end architecture;
+",
+    );
+
+    let names = completion_names(&builder, &code, code.s1("v.").end());
+    assert_eq!(names, vec!["field1", "field2"]);
+}
+
+#[test]
+fn completes_names_visible_in_context_clause_of_following_unit() {
+    let mut builder = LibraryBuilder::new();
+    builder.code(
+        "libname",
+        "
+package pkg is
+  constant c0 : natural := 0;
+end package;
+",
+    );
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+end entity;
+
+use work.pkg.all;
+
+architecture a of ent is
+  signal sig : natural;
+begin
+end architecture;
+",
+    );
+
+    // The context clause does not belong to the preceding entity
+    let names = completion_names(&builder, &code, code.s1("use").start());
+    assert!(names.contains(&"sig".to_owned()));
+}
+
+#[test]
+fn typed_unknown_prefix_is_not_added_to_symbols() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+end entity;
+
+architecture a of ent is
+  signal sig_foo : natural;
+begin
+  sig_foo <= 0;
+end architecture;
+",
+    );
+    let (root, _) = builder.get_analyzed_root();
+
+    // The prefix is typed after the analysis
+    let range = code.s1("sig_foo <= 0;").pos().range();
+    let cursor = Position::new(range.start.line, range.start.character + 8);
+    code.source().change(Some(&range), "unknown.");
+
+    assert!(root
+        .list_completion_options(code.source(), cursor)
+        .is_empty());
+    assert_eq!(root.lookup_symbol_utf8("unknown"), None);
+}
@@ -0,0 +1,210 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2020, Olof Kraigher olof.kraigher@gmail.com
+
+use super::*;
+use crate::analysis::HierarchyNode;
+
+/// Format the tree as one indented line per node such as `inst: lib.ent(arch)`
+fn format_tree(node: &HierarchyNode) -> Vec<String> {
+    fn format_node(node: &HierarchyNode, indent: usize, lines: &mut Vec<String>) {
+        let label = match node.label {
+            Some(ref label) => format!("{}: ", label),
+            None => String::new(),
+        };
+        let architecture = match node.architecture_name {
+            Some(ref name) => format!("({})", name),
+            None => String::new(),
+        };
+        lines.push(format!(
+            "{}{}{}.{}{}",
+            "  ".repeat(indent),
+            label,
+            node.library_name,
+            node.entity_name,
+            architecture
+        ));
+        for child in node.children.iter() {
+            format_node(child, indent + 1, lines);
+        }
+    }
+
+    let mut lines = Vec::new();
+    format_node(node, 0, &mut lines);
+    lines
+}
+
+#[test]
+fn follows_entity_and_component_instantiations() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity leaf is
+end entity;
+
+architecture rtl of leaf is
+begin
+end architecture;
+
+architecture sim of leaf is
+begin
+end architecture;
+
+entity mid is
+end entity;
+
+architecture rtl of mid is
+  component leaf is
+  end component;
+
+  component missing is
+  end component;
+begin
+  leaf_inst : leaf;
+
+  gen : for i in 0 to 1 generate
+    rtl_inst : entity work.leaf(rtl);
+  end generate;
+
+  missing_inst : missing;
+end architecture;
+
+entity top is
+end entity;
+
+architecture a of top is
+begin
+  mid_inst : entity work.mid;
+end architecture;
+",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    let top = root
+        .find_design_hierarchy(None, &root.symbol_utf8("top"))
+        .unwrap();
+
+    assert_eq!(
+        format_tree(&top),
+        vec![
+            "libname.top(a)",
+            "  mid_inst: libname.mid(rtl)",
+            // The last architecture is bound by default
+            "    leaf_inst: libname.leaf(sim)",
+            "    rtl_inst: libname.leaf(rtl)",
+            "    missing_inst: libname.missing",
+        ]
+    );
+
+    assert_eq!(top.pos, code.s1("entity top").s1("top").pos());
+    let mid = &top.children[0];
+    assert_eq!(mid.pos, code.s1("mid_inst").pos());
+    assert_eq!(mid.children[0].unit_pos, Some(code.s1("sim").pos()));
+    assert_eq!(mid.children[2].unit_pos, None);
+
+    assert_eq!(
+        root.find_design_hierarchy(None, &root.symbol_utf8("missing")),
+        None
+    );
+}
+
+#[test]
+fn follows_configurations() {
+    let mut builder = LibraryBuilder::new();
+    builder.code(
+        "libname",
+        "
+entity leaf is
+end entity;
+
+architecture rtl of leaf is
+begin
+end architecture;
+
+architecture sim of leaf is
+begin
+end architecture;
+
+entity other is
+end entity;
+
+architecture beh of other is
+begin
+end architecture;
+
+entity top is
+end entity;
+
+architecture a of top is
+  component leaf is
+  end component;
+
+  for spec_inst : leaf use entity work.leaf(rtl);
+begin
+  spec_inst : leaf;
+  cfg_inst : leaf;
+  open_inst : leaf;
+end architecture;
+
+configuration cfg of top is
+  for a
+    for cfg_inst : leaf
+      use entity work.other;
+    end for;
+    for open_inst : leaf
+      use open;
+    end for;
+  end for;
+end configuration;
+
+entity wrapper is
+end entity;
+
+architecture a of wrapper is
+begin
+  top_inst : configuration work.cfg;
+end architecture;
+",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    assert_eq!(
+        format_tree(
+            &root
+                .find_design_hierarchy(
+                    Some(&root.symbol_utf8("libname")),
+                    &root.symbol_utf8("wrapper")
+                )
+                .unwrap()
+        ),
+        vec![
+            "libname.wrapper(a)",
+            "  top_inst: libname.top(a)",
+            "    spec_inst: libname.leaf(rtl)",
+            "    cfg_inst: libname.other(beh)",
+            "    open_inst: libname.leaf",
+        ]
+    );
+
+    // Without the configuration the configuration specification still applies
+    assert_eq!(
+        format_tree(
+            &root
+                .find_design_hierarchy(None, &root.symbol_utf8("top"))
+                .unwrap()
+        ),
+        vec![
+            "libname.top(a)",
+            "  spec_inst: libname.leaf(rtl)",
+            "  cfg_inst: libname.leaf(sim)",
+            "  open_inst: libname.leaf(sim)",
+        ]
+    );
+}
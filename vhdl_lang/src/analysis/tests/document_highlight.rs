@@ -0,0 +1,133 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2020, Olof Kraigher olof.kraigher@gmail.com
+
+use super::*;
+use crate::analysis::OccurrenceKind::*;
+use crate::analysis::{Occurrence, OccurrenceKind};
+use crate::data::SrcPos;
+
+fn occurrences(builder: &LibraryBuilder, code: &Code, decl: Code) -> Vec<(SrcPos, OccurrenceKind)> {
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+    root.find_occurrences(code.source(), &decl.pos())
+        .into_iter()
+        .map(|Occurrence { pos, kind }| (pos, kind))
+        .collect()
+}
+
+#[test]
+fn assignment_targets_are_written() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+end entity;
+
+architecture a of ent is
+  type arr_t is array (natural range <>) of natural;
+  signal cnt : natural;
+  signal arr : arr_t(0 to 3);
+begin
+  cnt <= cnt + 1;
+  arr(cnt) <= cnt;
+
+  main : process
+    variable tmp : natural;
+  begin
+    tmp := cnt;
+    arr(0 to 1) <= (tmp, tmp);
+    tmp := arr(tmp);
+    wait;
+  end process;
+end architecture;
+",
+    );
+
+    assert_eq!(
+        occurrences(&builder, &code, code.s1("cnt")),
+        vec![
+            (code.s("cnt", 1).pos(), Read),
+            (code.s("cnt", 2).pos(), Write),
+            (code.s("cnt", 3).pos(), Read),
+            (code.s("cnt", 4).pos(), Read),
+            (code.s("cnt", 5).pos(), Read),
+            (code.s("cnt", 6).pos(), Read),
+        ]
+    );
+
+    assert_eq!(
+        occurrences(&builder, &code, code.s1("arr :").s1("arr")),
+        vec![
+            (code.s1("arr :").s1("arr").pos(), Read),
+            (code.s("arr(", 1).s1("arr").pos(), Write),
+            (code.s("arr(", 2).s1("arr").pos(), Write),
+            (code.s("arr(", 3).s1("arr").pos(), Read),
+        ]
+    );
+
+    assert_eq!(
+        occurrences(&builder, &code, code.s1("tmp")),
+        vec![
+            (code.s("tmp", 1).pos(), Read),
+            (code.s("tmp", 2).pos(), Write),
+            (code.s("tmp", 3).pos(), Read),
+            (code.s("tmp", 4).pos(), Read),
+            (code.s("tmp", 5).pos(), Write),
+            (code.s("tmp", 6).pos(), Read),
+        ]
+    );
+}
+
+#[test]
+fn selected_element_of_target_is_written() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+end entity;
+
+architecture a of ent is
+  type rec_t is record
+    field : natural;
+  end record;
+  signal rec : rec_t;
+begin
+  rec.field <= rec.field;
+end architecture;
+",
+    );
+
+    assert_eq!(
+        occurrences(&builder, &code, code.s1("rec :").s1("rec")),
+        vec![
+            (code.s1("rec :").s1("rec").pos(), Read),
+            (code.s("rec.", 1).s1("rec").pos(), Write),
+            (code.s("rec.", 2).s1("rec").pos(), Read),
+        ]
+    );
+}
+
+#[test]
+fn end_identifier_is_an_occurrence() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+end entity ent;
+",
+    );
+
+    assert_eq!(
+        occurrences(&builder, &code, code.s1("ent is").s1("ent")),
+        vec![
+            (code.s1("ent is").s1("ent").pos(), Read),
+            (code.s1("ent;").s1("ent").pos(), Read)
+        ]
+    );
+}
@@ -0,0 +1,207 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2020, Olof Kraigher olof.kraigher@gmail.com
+
+use super::*;
+
+#[test]
+fn architecture_inserted_after_entity() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity dma is
+  port (
+    clk : in bit
+  );
+end entity;
+",
+    );
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    let skeletons = root
+        .find_entity_skeletons(code.source(), code.s1("clk").start())
+        .unwrap();
+
+    let end = code.s1("end entity;").end();
+    assert_eq!(skeletons.library_name, root.symbol_utf8("libname"));
+    assert_eq!(skeletons.entity_name, root.symbol_utf8("dma"));
+    assert_eq!(skeletons.entity_end, code.source().pos(end, end));
+    assert_eq!(
+        skeletons.architecture,
+        "
+
+architecture rtl of dma is
+begin
+end architecture;"
+    );
+}
+
+#[test]
+fn testbench_is_analyzed_without_diagnostics() {
+    let mut builder = LibraryBuilder::new();
+    builder.code(
+        "libname",
+        "
+package types is
+  subtype word_t is bit_vector(7 downto 0);
+end package;
+",
+    );
+    let code = builder.code(
+        "libname",
+        "
+use work.types.all;
+
+entity dma is
+  generic (
+    depth : natural := 4
+  );
+  port (
+    wr : in bit;
+    data : in word_t;
+    full : out bit
+  );
+end entity;
+",
+    );
+    let (root, _) = builder.get_analyzed_root();
+
+    let skeletons = root
+        .find_entity_skeletons(code.source(), code.s1("entity dma").start())
+        .unwrap();
+    assert!(skeletons
+        .testbench
+        .starts_with("library libname;\nuse libname.types.all;\n\nentity dma_tb is"));
+
+    builder.code("libname", &skeletons.testbench);
+    let diagnostics = builder.analyze();
+    check_no_diagnostics(&diagnostics);
+}
+
+#[test]
+fn testbench_constrains_ports_of_unconstrained_array_types() {
+    let mut builder = LibraryBuilder::new();
+    builder.code(
+        "libname",
+        "
+package types is
+  subtype word_t is bit_vector;
+  type matrix_t is array (natural range <>, character range <>) of bit;
+end package;
+",
+    );
+    let code = builder.code(
+        "libname",
+        "
+use work.types.all;
+
+entity dma is
+  generic (
+    width : natural
+  );
+  port (
+    clk : out bit;
+    data : in word_t;
+    matrix : in matrix_t;
+    fixed : in bit_vector(width - 1 downto 0)
+  );
+end entity;
+",
+    );
+    let (root, _) = builder.get_analyzed_root();
+
+    let skeletons = root
+        .find_entity_skeletons(code.source(), code.s1("entity dma").start())
+        .unwrap();
+    let testbench = &skeletons.testbench;
+    assert!(testbench.contains(
+        "  -- TODO: value of the generic\n  constant width : natural := natural'left;\n"
+    ));
+    assert!(testbench.contains(
+        "  -- TODO: constraint of the unconstrained port\n  signal data   : word_t(NATURAL'low to NATURAL'low);\n"
+    ));
+    assert!(testbench.contains(
+        "  signal matrix : matrix_t(natural'low to natural'low, character'low to character'low);\n"
+    ));
+    assert!(testbench.contains("  signal fixed  : bit_vector(width - 1 downto 0);\n"));
+
+    // The clock port of mode out is not driven by the testbench
+    assert!(testbench.contains("  signal tb_clk : bit;\n"));
+    assert!(testbench.contains("    tb_clk <= '0';\n"));
+
+    builder.code("libname", testbench);
+    let diagnostics = builder.analyze();
+    check_no_diagnostics(&diagnostics);
+}
+
+#[test]
+fn testbench_of_entity_with_generic_type_boolean_clock_and_descending_index() {
+    let mut builder = LibraryBuilder::new();
+    builder.code(
+        "libname",
+        "
+package types is
+  subtype index_t is integer range 7 downto 0;
+  type word_t is array (index_t range <>) of bit;
+end package;
+",
+    );
+    let code = builder.code(
+        "libname",
+        "
+use work.types.all;
+
+entity dma is
+  generic (
+    type element_t;
+    init : element_t
+  );
+  port (
+    clk : in boolean;
+    data : in word_t
+  );
+end entity;
+",
+    );
+    let (root, _) = builder.get_analyzed_root();
+
+    let skeletons = root
+        .find_entity_skeletons(code.source(), code.s1("entity dma").start())
+        .unwrap();
+    let testbench = &skeletons.testbench;
+    assert!(testbench
+        .contains("  -- TODO: type of the generic type\n  subtype element_t is integer;\n"));
+    assert!(testbench.contains("  signal data : word_t(index_t'high downto index_t'high);\n"));
+    assert!(testbench.contains("    clk <= false;\n"));
+    assert!(testbench.contains("    clk <= true;\n"));
+
+    builder.code("libname", testbench);
+    let diagnostics = builder.analyze();
+    check_no_diagnostics(&diagnostics);
+}
+
+#[test]
+fn no_entity_skeletons_outside_of_entity() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity dma is
+end entity;
+
+architecture a of dma is
+begin
+end architecture;
+",
+    );
+    let (root, _) = builder.get_analyzed_root();
+
+    assert_eq!(
+        root.find_entity_skeletons(code.source(), code.s1("architecture").start()),
+        None
+    );
+}
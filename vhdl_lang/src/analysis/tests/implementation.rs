@@ -0,0 +1,163 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2020, Olof Kraigher olof.kraigher@gmail.com
+
+use super::*;
+
+#[test]
+fn entity_is_implemented_by_architectures() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity myent is
+end entity;
+
+architecture rtl of myent is
+begin
+end architecture;
+
+architecture sim of myent is
+begin
+end architecture;
+
+entity other is
+end entity;
+
+architecture rtl of other is
+begin
+end architecture;
+",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    assert_eq!(
+        root.find_implementation(&code.s1("myent").pos()),
+        vec![code.s1("rtl").pos(), code.s1("sim").pos()]
+    );
+    assert_eq!(
+        root.find_definition(&code.s1("myent").pos()),
+        code.s1("myent").pos()
+    );
+}
+
+#[test]
+fn component_is_implemented_by_entity() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity myent is
+end entity;
+
+architecture rtl of myent is
+begin
+end architecture;
+
+entity top is
+end entity;
+
+architecture rtl of top is
+  component myent is
+  end component;
+begin
+  inst : myent;
+end architecture;
+",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    assert_eq!(
+        root.find_implementation(&code.s("myent", 3).pos()),
+        vec![code.s1("myent").pos()]
+    );
+}
+
+#[test]
+fn package_items_are_implemented_by_package_body() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+package pkg is
+  constant deferred : natural;
+  function calc(arg : natural) return natural;
+  function calc(arg : boolean) return natural;
+  type prot_t is protected
+    procedure update;
+  end protected;
+end package;
+
+package body pkg is
+  constant deferred : natural := 0;
+
+  function calc(arg : natural) return natural is
+  begin
+    return arg;
+  end function;
+
+  function calc(arg : boolean) return natural is
+  begin
+    return 0;
+  end function;
+
+  type prot_t is protected body
+    procedure update is
+    begin
+    end procedure;
+  end protected body;
+end package body;
+",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    assert_eq!(
+        root.find_implementation(&code.s1("pkg").pos()),
+        vec![code.s("pkg", 2).pos()]
+    );
+    assert_eq!(
+        root.find_implementation(&code.s1("deferred").pos()),
+        vec![code.s("deferred", 2).pos()]
+    );
+    assert_eq!(
+        root.find_implementation(&code.s("calc", 2).pos()),
+        vec![code.s("calc", 4).pos()]
+    );
+    assert_eq!(
+        root.find_implementation(&code.s1("prot_t").pos()),
+        vec![code.s("prot_t", 2).pos()]
+    );
+    assert_eq!(
+        root.find_implementation(&code.s1("update").pos()),
+        vec![code.s("update", 2).pos()]
+    );
+
+    // Bodies are not implemented elsewhere
+    assert_eq!(root.find_implementation(&code.s("calc", 3).pos()), vec![]);
+
+    assert_eq!(
+        root.find_definition(&code.s1("calc").pos()),
+        code.s("calc", 3).pos()
+    );
+    assert_eq!(
+        root.find_definition(&code.s1("deferred").pos()),
+        code.s("deferred", 2).pos()
+    );
+    assert_eq!(
+        root.find_definition(&code.s1("prot_t").pos()),
+        code.s("prot_t", 2).pos()
+    );
+    // A package is its own definition
+    assert_eq!(
+        root.find_definition(&code.s1("pkg").pos()),
+        code.s1("pkg").pos()
+    );
+}
@@ -0,0 +1,167 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2020, Olof Kraigher olof.kraigher@gmail.com
+
+use super::*;
+use crate::analysis::InlayHint;
+use crate::analysis::InlayHintKind::*;
+use crate::data::Position;
+
+fn hints(builder: &LibraryBuilder, code: &Code) -> Vec<(Position, String)> {
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+    root.find_inlay_hints(code.source())
+        .into_iter()
+        .map(
+            |InlayHint {
+                 position, label, ..
+             }| (position, label),
+        )
+        .collect()
+}
+
+#[test]
+fn hints_formals_of_positional_port_and_generic_maps() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity sub is
+  generic (width : natural);
+  port (clk : bit; rst : bit; data : bit);
+end entity;
+
+architecture a of sub is
+begin
+end architecture;
+
+entity top is
+end entity;
+
+architecture a of top is
+  signal clk, reset, value : bit;
+begin
+  inst : entity work.sub
+    generic map (8)
+    port map (clk, reset, data => value);
+end architecture;
+",
+    );
+
+    assert_eq!(
+        hints(&builder, &code),
+        vec![
+            (code.s1("8").start(), "width =>".to_owned()),
+            // The hint is redundant for clk => clk
+            (
+                code.s1("(clk, reset").s1("reset").start(),
+                "rst =>".to_owned()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn hints_formals_of_resolved_subprogram_calls() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+package pkg is
+  function add(lhs, rhs : natural) return natural;
+  procedure clear(signal target : out bit);
+end package;
+
+package body pkg is
+  function add(lhs, rhs : natural) return natural is
+  begin
+    return lhs + rhs;
+  end function;
+
+  procedure clear(signal target : out bit) is
+  begin
+    target <= '0';
+  end procedure;
+
+  procedure user(signal sig : out bit) is
+    type arr_t is array (0 to 1) of natural;
+    variable arr : arr_t;
+    variable sum : natural;
+  begin
+    sum := add(1, arr(0));
+    clear(sig);
+  end procedure;
+end package body;
+",
+    );
+
+    assert_eq!(
+        hints(&builder, &code),
+        vec![
+            (code.s1("add(1").s1("1").start(), "lhs =>".to_owned()),
+            (code.s1("arr(0)").start(), "rhs =>".to_owned()),
+            (
+                code.s1("clear(sig)").s1("sig").start(),
+                "target =>".to_owned()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn hints_subtype_of_aliases_and_loop_parameters() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+end entity;
+
+architecture a of ent is
+  type state_t is (idle, busy);
+  signal flag : boolean;
+  alias flag_alias is flag;
+  alias typed_alias : boolean is flag;
+begin
+  gen : for i in 0 to 3 generate
+  end generate;
+
+  main : process
+  begin
+    for state in idle to busy loop
+    end loop;
+    for j in natural range 0 to 1 loop
+    end loop;
+    wait;
+  end process;
+end architecture;
+",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+    let hints = root.find_inlay_hints(code.source());
+
+    assert_eq!(
+        hints,
+        vec![
+            InlayHint {
+                position: code.s1("flag_alias").end(),
+                label: ": BOOLEAN".to_owned(),
+                kind: Type,
+            },
+            InlayHint {
+                position: code.s1("i in").s1("i").end(),
+                label: ": integer".to_owned(),
+                kind: Type,
+            },
+            InlayHint {
+                position: code.s1("state in").s1("state").end(),
+                label: ": state_t".to_owned(),
+                kind: Type,
+            },
+        ]
+    );
+}
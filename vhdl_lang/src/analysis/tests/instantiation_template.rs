@@ -0,0 +1,140 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2020, Olof Kraigher olof.kraigher@gmail.com
+
+use super::*;
+use crate::data::Position;
+
+#[test]
+fn instantiation_template_of_entity_named_by_statement() {
+    let mut builder = LibraryBuilder::new();
+    builder.code(
+        "libname",
+        "
+entity dma is
+  generic (
+    width : natural
+  );
+  port (
+    clk : in bit;
+    data_out : out bit_vector(width-1 downto 0)
+  );
+end entity;
+",
+    );
+    let code = builder.code(
+        "libname",
+        "
+entity top is
+end entity;
+
+architecture a of top is
+begin
+  dma;
+end architecture;
+",
+    );
+
+    let (root, _) = builder.get_analyzed_root();
+    let template = root
+        .find_instantiation_template(code.source(), code.s1("dma").start())
+        .unwrap();
+
+    assert_eq!(template.library_name, root.symbol_utf8("libname"));
+    assert_eq!(template.entity_name, root.symbol_utf8("dma"));
+    assert_eq!(template.pos, code.s1("dma;").pos());
+    assert_eq!(
+        template.instantiation,
+        "\
+dma_inst : entity libname.dma
+    generic map (
+      width => width
+    )
+    port map (
+      clk      => clk,
+      data_out => data_out
+    );"
+    );
+    assert_eq!(
+        template.component,
+        "  component dma
+    generic (
+      width : natural
+    );
+    port (
+      clk : in bit;
+      data_out : out bit_vector(width - 1 downto 0)
+    );
+  end component;
+"
+    );
+    assert_eq!(template.component_position, Position::new(5, 0));
+}
+
+#[test]
+fn instantiation_template_keeps_label_and_library() {
+    let mut builder = LibraryBuilder::new();
+    builder.code(
+        "otherlib",
+        "
+entity dma is
+end entity;
+",
+    );
+    let code = builder.code(
+        "libname",
+        "
+library otherlib;
+
+entity top is
+end entity;
+
+architecture a of top is
+begin
+  u_dma : otherlib.dma;
+end architecture;
+",
+    );
+
+    let (root, _) = builder.get_analyzed_root();
+    let template = root
+        .find_instantiation_template(code.source(), code.s1("u_dma").start())
+        .unwrap();
+
+    assert_eq!(template.library_name, root.symbol_utf8("otherlib"));
+    assert_eq!(template.pos, code.s1("u_dma : otherlib.dma;").pos());
+    assert_eq!(template.instantiation, "u_dma : entity otherlib.dma;");
+}
+
+#[test]
+fn no_instantiation_template_without_entity() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity top is
+end entity;
+
+architecture a of top is
+  procedure proc is
+  begin
+  end procedure;
+begin
+  proc;
+  missing;
+end architecture;
+",
+    );
+
+    let (root, _) = builder.get_analyzed_root();
+    assert_eq!(
+        root.find_instantiation_template(code.source(), code.s1("proc;").start()),
+        None
+    );
+    assert_eq!(
+        root.find_instantiation_template(code.source(), code.s1("missing").start()),
+        None
+    );
+}
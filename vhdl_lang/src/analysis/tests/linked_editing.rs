@@ -0,0 +1,127 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2020, Olof Kraigher olof.kraigher@gmail.com
+
+use super::*;
+use crate::data::SrcPos;
+
+fn linked_editing_ranges(builder: &LibraryBuilder, code: &Code, cursor: Code) -> Vec<SrcPos> {
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+    root.find_linked_editing_ranges(code.source(), cursor.start())
+}
+
+#[test]
+fn links_design_unit_names_with_end_names() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity top is
+end entity top;
+
+architecture rtl of top is
+begin
+end architecture rtl;
+",
+    );
+
+    let top = vec![code.s("top", 1).pos(), code.s("top", 2).pos()];
+    assert_eq!(
+        linked_editing_ranges(&builder, &code, code.s("top", 1)),
+        top
+    );
+    assert_eq!(
+        linked_editing_ranges(&builder, &code, code.s("top", 2)),
+        top
+    );
+
+    let rtl = vec![code.s("rtl", 1).pos(), code.s("rtl", 2).pos()];
+    assert_eq!(
+        linked_editing_ranges(&builder, &code, code.s("rtl", 1)),
+        rtl
+    );
+    assert_eq!(
+        linked_editing_ranges(&builder, &code, code.s("rtl", 2)),
+        rtl
+    );
+
+    // The entity name of the architecture is not repeated after end
+    assert_eq!(
+        linked_editing_ranges(&builder, &code, code.s("top", 3)),
+        vec![]
+    );
+}
+
+#[test]
+fn links_labels_and_subprogram_names_with_end_names() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+end entity;
+
+architecture a of ent is
+  function calc return natural is
+  begin
+    return 0;
+  end function calc;
+begin
+  blk : block
+  begin
+    p_main : process
+    begin
+      lp : loop
+        wait;
+      end loop lp;
+    end process p_main;
+  end block blk;
+end architecture;
+",
+    );
+
+    for name in ["calc", "blk", "p_main", "lp"].iter() {
+        let expected = vec![code.s(name, 1).pos(), code.s(name, 2).pos()];
+        assert_eq!(
+            linked_editing_ranges(&builder, &code, code.s(name, 1)),
+            expected
+        );
+        assert_eq!(
+            linked_editing_ranges(&builder, &code, code.s(name, 2)),
+            expected
+        );
+    }
+
+    assert_eq!(
+        linked_editing_ranges(&builder, &code, code.s1("wait")),
+        vec![]
+    );
+}
+
+#[test]
+fn does_not_link_names_without_end_name() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+end entity;
+
+architecture a of ent is
+begin
+  main : process
+  begin
+    wait;
+  end process;
+end architecture;
+",
+    );
+
+    assert_eq!(
+        linked_editing_ranges(&builder, &code, code.s1("main")),
+        vec![]
+    );
+}
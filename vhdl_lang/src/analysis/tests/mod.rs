@@ -5,21 +5,40 @@
 // Copyright (c) 2019, Olof Kraigher olof.kraigher@gmail.com
 
 mod assignment_typecheck;
+mod association_rewrite;
+mod call_hierarchy;
 mod circular_dependencies;
+mod code_lens;
+mod completion;
 mod context_clause;
 mod deferred_constant;
+mod design_hierarchy;
+mod document_highlight;
+mod entity_skeletons;
 mod homographs;
+mod implementation;
 mod implicit;
 mod incomplete_type;
 mod incremental_analysis;
+mod inlay_hint;
+mod instantiation_template;
+mod linked_editing;
+mod package_body_stub;
 mod package_instance;
+mod port_map;
 mod protected_type;
 mod resolves_design_units;
 mod resolves_names;
 mod resolves_type_mark;
+mod selection_range;
+mod semantic_tokens;
+mod signature_help;
+mod type_definition;
 mod typecheck_expression;
+mod use_clause_fix;
 mod util;
 mod visibility;
+mod workspace_symbol;
 
 pub use self::util::*;
 pub use crate::data::Diagnostic;
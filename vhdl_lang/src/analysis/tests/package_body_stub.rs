@@ -0,0 +1,387 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2020, Olof Kraigher olof.kraigher@gmail.com
+
+use super::*;
+use crate::analysis::PackageBodyStub;
+use crate::data::Position;
+
+const PACKAGE: &str = "
+package pkg is
+  constant width : natural;
+  constant depth : natural := 4;
+  function parity(value : bit_vector) return bit;
+  procedure reset(signal value : out bit_vector);
+  type counter_t is protected
+    procedure increment;
+  end protected;
+end package;
+";
+
+#[test]
+fn new_package_body_after_package() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code("libname", PACKAGE);
+    let (root, _) = builder.get_analyzed_root();
+
+    let stub = root
+        .find_package_body_stub(code.source(), code.s1("parity").start())
+        .unwrap();
+
+    let end = code.s1("end package;").end();
+    assert_eq!(
+        stub,
+        PackageBodyStub {
+            library_name: root.symbol_utf8("libname"),
+            package_name: root.symbol_utf8("pkg"),
+            is_new: true,
+            edits: vec![(
+                code.source().pos(end, end),
+                "
+
+package body pkg is
+  -- TODO: value of the deferred constant
+  constant width : natural := natural'left;
+
+  function parity(
+    constant value : in bit_vector
+  ) return bit is
+  begin
+  end function;
+
+  procedure reset(
+    signal value : out bit_vector
+  ) is
+  begin
+  end procedure;
+
+  type counter_t is protected body
+    procedure increment is
+    begin
+    end procedure;
+  end protected body;
+end package body;"
+                    .to_owned()
+            )]
+        }
+    );
+
+    // The stub is inserted after the package declaration which ends the code
+    let mut builder = LibraryBuilder::new();
+    builder.code(
+        "libname",
+        &format!("{}{}", PACKAGE.trim_end(), stub.edits[0].1),
+    );
+    check_no_diagnostics(&builder.analyze());
+}
+
+#[test]
+fn missing_declarations_of_existing_package_body() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code("libname", PACKAGE);
+    let body = builder.code(
+        "libname",
+        "
+package body pkg is
+  constant width : natural := 8;
+
+  function PARITY(value : bit_vector) return bit is
+  begin
+    return '0';
+  end function;
+end package body;
+",
+    );
+    let (root, _) = builder.get_analyzed_root();
+
+    let stub = root
+        .find_package_body_stub(code.source(), code.s1("package pkg").start())
+        .unwrap();
+
+    let start = Position::new(body.s1("end package body").start().line, 0);
+    assert!(!stub.is_new);
+    assert_eq!(
+        stub.edits,
+        vec![(
+            body.source().pos(start, start),
+            "
+  procedure reset(
+    signal value : out bit_vector
+  ) is
+  begin
+  end procedure;
+
+  type counter_t is protected body
+    procedure increment is
+    begin
+    end procedure;
+  end protected body;
+"
+            .to_owned()
+        )]
+    );
+}
+
+#[test]
+fn no_package_body_stub_of_complete_package_body() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+package pkg is
+  constant width : natural;
+  procedure reset;
+end package;
+
+package body pkg is
+  constant width : natural := 8;
+
+  procedure reset is
+  begin
+  end procedure;
+end package body;
+",
+    );
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    assert_eq!(
+        root.find_package_body_stub(code.source(), code.s1("reset").start()),
+        None
+    );
+}
+
+#[test]
+fn no_package_body_stub_outside_of_package() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+package pkg is
+  procedure reset;
+end package;
+
+entity ent is
+end entity;
+",
+    );
+    let (root, _) = builder.get_analyzed_root();
+
+    assert!(root
+        .find_package_body_stub(code.source(), code.s1("reset").start())
+        .is_some());
+    assert_eq!(
+        root.find_package_body_stub(code.source(), code.s1("ent").start()),
+        None
+    );
+}
+
+#[test]
+fn missing_declarations_before_end_sharing_a_line() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+package pkg is
+  procedure reset;
+end package;
+",
+    );
+    let body = builder.code(
+        "libname",
+        "
+package body pkg is end package body;
+",
+    );
+    let (root, _) = builder.get_analyzed_root();
+
+    let stub = root
+        .find_package_body_stub(code.source(), code.s1("reset").start())
+        .unwrap();
+
+    let end = body.s1("end package body").start();
+    assert_eq!(
+        stub.edits,
+        vec![(
+            body.source().pos(end, end),
+            "
+
+  procedure reset is
+  begin
+  end procedure;
+"
+            .to_owned()
+        )]
+    );
+}
+
+#[test]
+fn missing_methods_of_existing_protected_type_body() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+package pkg is
+  type counter_t is protected
+    procedure increment;
+    impure function value return natural;
+  end protected;
+end package;
+",
+    );
+    let body = builder.code(
+        "libname",
+        "
+package body pkg is
+  type counter_t is protected body
+    variable count : natural := 0;
+
+    procedure increment is
+    begin
+      count := count + 1;
+    end procedure;
+  end protected body;
+end package body;
+",
+    );
+    let (root, _) = builder.get_analyzed_root();
+
+    let stub = root
+        .find_package_body_stub(code.source(), code.s1("counter_t").start())
+        .unwrap();
+
+    let start = Position::new(body.s1("end protected body").start().line, 0);
+    assert!(!stub.is_new);
+    assert_eq!(
+        stub.edits,
+        vec![(
+            body.source().pos(start, start),
+            "
+    impure function value return natural is
+    begin
+    end function;
+"
+            .to_owned()
+        )]
+    );
+}
+
+#[test]
+fn conforming_bodies_written_differently_are_not_missing() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+package pkg is
+  function parity(value : bit_vector) return bit;
+  procedure reset(value : natural);
+  procedure reset(value : bit);
+  type counter_t is protected
+    procedure add(amount : natural);
+    procedure add(amount : bit);
+  end protected;
+end package;
+",
+    );
+    let body = builder.code(
+        "libname",
+        "
+package body pkg is
+  function Parity(constant VALUE : in BIT_VECTOR) return Bit is
+  begin
+    return '0';
+  end function;
+
+  procedure reset(constant value : in NATURAL) is
+  begin
+  end procedure;
+
+  type counter_t is protected body
+    procedure ADD(constant amount : in Natural) is
+    begin
+    end procedure;
+  end protected body;
+end package body;
+",
+    );
+    let (root, _) = builder.get_analyzed_root();
+
+    let stub = root
+        .find_package_body_stub(code.source(), code.s1("package pkg").start())
+        .unwrap();
+
+    let protected_end = Position::new(body.s1("end protected body").start().line, 0);
+    let package_end = Position::new(body.s1("end package body").start().line, 0);
+    assert_eq!(
+        stub.edits,
+        vec![
+            (
+                body.source().pos(protected_end, protected_end),
+                "
+    procedure add(
+      constant amount : in bit
+    ) is
+    begin
+    end procedure;
+"
+                .to_owned()
+            ),
+            (
+                body.source().pos(package_end, package_end),
+                "
+  procedure reset(
+    constant value : in bit
+  ) is
+  begin
+  end procedure;
+"
+                .to_owned()
+            )
+        ]
+    );
+}
+
+#[test]
+fn extended_identifiers_differing_in_case_are_distinct() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+package pkg is
+  procedure \\reset\\;
+  procedure \\Reset\\;
+end package;
+",
+    );
+    let body = builder.code(
+        "libname",
+        "
+package body pkg is
+  procedure \\reset\\ is
+  begin
+  end procedure;
+end package body;
+",
+    );
+    let (root, _) = builder.get_analyzed_root();
+
+    let stub = root
+        .find_package_body_stub(code.source(), code.s1("package pkg").start())
+        .unwrap();
+
+    let start = Position::new(body.s1("end package body").start().line, 0);
+    assert_eq!(
+        stub.edits,
+        vec![(
+            body.source().pos(start, start),
+            "
+  procedure \\Reset\\ is
+  begin
+  end procedure;
+"
+            .to_owned()
+        )]
+    );
+}
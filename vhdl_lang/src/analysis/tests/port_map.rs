@@ -0,0 +1,332 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2020, Olof Kraigher olof.kraigher@gmail.com
+
+use super::*;
+use crate::analysis::{PortMapFix, PortMapFixKind};
+use crate::data::Position;
+
+/// Apply the edits of the fix to the code which is expected to be ascii
+fn apply(code: &str, fix: &PortMapFix) -> String {
+    let offset = |position: Position| -> usize {
+        let line_start: usize = code
+            .split_inclusive('\n')
+            .take(position.line as usize)
+            .map(|line| line.len())
+            .sum();
+        line_start + position.character as usize
+    };
+
+    let mut result = code.to_owned();
+    for (pos, text) in fix.edits.iter().rev() {
+        result.replace_range(offset(pos.start())..offset(pos.end()), text);
+    }
+    result
+}
+
+const ENTITY: &str = "
+entity dma is
+  port (
+    clk : in bit;
+    rst : in bit := '0';
+    req : in bit;
+    ack : out bit
+  );
+end entity;
+";
+
+#[test]
+fn missing_port_of_mode_in_without_default() {
+    let mut builder = LibraryBuilder::new();
+    let ent = builder.code("libname", ENTITY);
+    let code = builder.code(
+        "libname",
+        "
+entity top is
+end entity;
+
+architecture a of top is
+  signal clk : bit;
+begin
+  inst : entity work.dma
+    port map (clk => clk);
+end architecture;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![Diagnostic::error(
+            code.s1("work.dma"),
+            "Missing association of port 'req' of mode in without a default value",
+        )
+        .related(ent.s1("req"), "Defined here")],
+    );
+}
+
+#[test]
+fn missing_port_of_component() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity top is
+end entity;
+
+architecture a of top is
+  component fifo is
+    port (
+      wr : in bit;
+      full : out bit
+    );
+  end component;
+begin
+  inst : fifo
+    port map (full => open);
+end architecture;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![Diagnostic::error(
+            code.s("fifo", 2),
+            "Missing association of port 'wr' of mode in without a default value",
+        )
+        .related(code.s1("wr"), "Defined here")],
+    );
+}
+
+#[test]
+fn association_of_formal_which_is_not_a_port() {
+    let mut builder = LibraryBuilder::new();
+    builder.code("libname", ENTITY);
+    let code = builder.code(
+        "libname",
+        "
+entity top is
+end entity;
+
+architecture a of top is
+  signal clk, req, valid : bit;
+begin
+  inst : entity work.dma
+    port map (clk => clk, req => req, valid => valid);
+end architecture;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![Diagnostic::error(
+            code.s1("valid =>").s1("valid"),
+            "No declaration of 'valid' within entity 'dma'",
+        )],
+    );
+}
+
+#[test]
+fn positional_and_partial_associations_are_not_missing() {
+    let mut builder = LibraryBuilder::new();
+    builder.code(
+        "libname",
+        "
+entity fifo is
+  port (
+    wr : in bit;
+    data : in bit_vector(7 downto 0)
+  );
+end entity;
+
+entity top is
+end entity;
+
+architecture a of top is
+  signal wr : bit;
+  signal data : bit_vector(7 downto 0);
+begin
+  inst0 : entity work.fifo
+    port map (wr, data);
+
+  inst1 : entity work.fifo
+    port map (wr => wr, data(7 downto 4) => data(3 downto 0), data(3 downto 0) => data(7 downto 4));
+end architecture;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_no_diagnostics(&diagnostics);
+}
+
+#[test]
+fn fix_adds_missing_ports() {
+    let mut builder = LibraryBuilder::new();
+    builder.code("libname", ENTITY);
+    let contents = "
+entity top is
+end entity;
+
+architecture a of top is
+  signal clk, req : bit;
+begin
+  inst : entity work.dma
+    port map (
+      clk => clk
+    );
+
+  inst2 : entity work.dma port map (clk => clk);
+
+  inst3 : entity work.dma;
+end architecture;
+";
+    let code = builder.code("libname", contents);
+    let (root, _) = builder.get_analyzed_root();
+
+    let fixes = root.find_port_map_fixes(code.source(), code.s1("inst :").start());
+    assert_eq!(fixes.len(), 1);
+    assert_eq!(fixes[0].kind, PortMapFixKind::AddMissing);
+    assert_eq!(
+        apply(contents, &fixes[0]),
+        contents.replace(
+            "      clk => clk\n",
+            "      clk => clk,\n      req => req,\n      ack => open\n"
+        )
+    );
+
+    let fixes = root.find_port_map_fixes(code.source(), code.s1("inst2").start());
+    assert_eq!(fixes.len(), 1);
+    assert_eq!(
+        apply(contents, &fixes[0]),
+        contents.replace(
+            "port map (clk => clk);",
+            "port map (clk => clk, req => req, ack => open);"
+        )
+    );
+
+    let fixes = root.find_port_map_fixes(code.source(), code.s1("inst3").start());
+    assert_eq!(fixes.len(), 1);
+    assert_eq!(
+        apply(contents, &fixes[0]),
+        contents.replace(
+            "  inst3 : entity work.dma;",
+            "  inst3 : entity work.dma
+    port map (
+      clk => clk,
+      req => req,
+      ack => open
+    );"
+        )
+    );
+}
+
+#[test]
+fn fix_removes_associations_of_formals_which_are_not_ports() {
+    let mut builder = LibraryBuilder::new();
+    builder.code("libname", ENTITY);
+    let contents = "
+entity top is
+end entity;
+
+architecture a of top is
+  signal clk, req, ack, valid, ready : bit;
+begin
+  inst : entity work.dma
+    port map (valid => valid, clk => clk, ready => ready, req => req, ack => ack);
+
+  inst2 : entity work.dma
+    port map (valid => valid, ready => ready);
+end architecture;
+";
+    let code = builder.code("libname", contents);
+    let (root, _) = builder.get_analyzed_root();
+
+    let fixes = root.find_port_map_fixes(code.source(), code.s1("inst :").start());
+    assert_eq!(fixes.len(), 1);
+    assert_eq!(fixes[0].kind, PortMapFixKind::RemoveUnknown);
+    assert_eq!(
+        apply(contents, &fixes[0]),
+        contents.replace(
+            "port map (valid => valid, clk => clk, ready => ready, req => req, ack => ack)",
+            "port map (clk => clk, req => req, ack => ack)"
+        )
+    );
+
+    // Both fixes are offered when no association is kept
+    let fixes = root.find_port_map_fixes(code.source(), code.s1("inst2").start());
+    assert_eq!(fixes.len(), 2);
+    assert_eq!(fixes[0].kind, PortMapFixKind::RemoveUnknown);
+    assert_eq!(
+        apply(contents, &fixes[0]),
+        contents.replace("(valid => valid, ready => ready)", "()")
+    );
+    assert_eq!(fixes[1].kind, PortMapFixKind::AddMissing);
+}
+
+#[test]
+fn no_fixes_of_complete_port_map() {
+    let mut builder = LibraryBuilder::new();
+    builder.code("libname", ENTITY);
+    let code = builder.code(
+        "libname",
+        "
+entity top is
+end entity;
+
+architecture a of top is
+  signal clk, req : bit;
+begin
+  inst : entity work.dma
+    port map (clk, open, req, open);
+end architecture;
+",
+    );
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    let fixes = root.find_port_map_fixes(code.source(), code.s1("inst").start());
+    assert_eq!(fixes, Vec::new());
+}
+
+#[test]
+fn association_of_port_with_unresolved_type() {
+    let mut builder = LibraryBuilder::new();
+    let ent = builder.code(
+        "libname",
+        "
+entity fifo is
+  port (
+    wr : in bit;
+    data : in missing_t
+  );
+end entity;
+",
+    );
+    builder.code(
+        "libname",
+        "
+entity top is
+end entity;
+
+architecture a of top is
+  signal wr, data : bit;
+begin
+  inst : entity work.fifo
+    port map (wr => wr, data => data);
+end architecture;
+",
+    );
+
+    let diagnostics = builder.analyze();
+    check_diagnostics(
+        diagnostics,
+        vec![Diagnostic::error(
+            ent.s1("missing_t"),
+            "No declaration of 'missing_t'",
+        )],
+    );
+}
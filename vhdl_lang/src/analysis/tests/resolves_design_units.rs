@@ -252,6 +252,49 @@ end package body;
     );
 }
 
+#[test]
+fn search_design_unit_end_identifiers() {
+    check_search_reference(
+        "
+entity decl is
+end entity decl;
+
+architecture a of decl is
+begin
+end architecture;
+",
+    );
+
+    check_search_reference(
+        "
+package decl is
+end package decl;
+
+package body decl is
+end package body decl;
+",
+    );
+
+    check_search_reference(
+        "
+entity ent is
+end entity;
+
+configuration decl of ent is
+for rtl
+end for;
+end configuration decl;
+",
+    );
+
+    check_search_reference(
+        "
+context decl is
+end context decl;
+",
+    );
+}
+
 #[test]
 fn resolves_reference_to_entity_instance() {
     let mut builder = LibraryBuilder::new();
@@ -964,6 +964,161 @@ end architecture;
     );
 }
 
+#[test]
+fn search_generate_alternate_end_labels() {
+    check_search_reference(
+        "
+entity ent is
+end entity;
+
+architecture a of ent is
+begin
+ gen: if decl: true generate
+ end decl;
+ end generate;
+end architecture;
+",
+    );
+}
+
+#[test]
+fn search_concurrent_statement_end_labels() {
+    for statement in [
+        "
+ decl: process
+ begin
+ end process decl;",
+        "
+ decl: block
+ begin
+ end block decl;",
+        "
+ decl: for i in 0 to 1 generate
+ end generate decl;",
+        "
+ decl: if true generate
+ else generate
+ end generate decl;",
+        "
+ decl: case 0 generate
+   when others =>
+ end generate decl;",
+    ]
+    .iter()
+    {
+        check_search_reference(&format!(
+            "
+entity ent is
+end entity;
+
+architecture a of ent is
+begin
+{}
+end architecture;
+",
+            statement
+        ));
+    }
+}
+
+#[test]
+fn search_sequential_statement_end_labels() {
+    for statement in [
+        "
+    decl: loop
+    end loop decl;",
+        "
+    decl: if true then
+    end if decl;",
+        "
+    decl: case 0 is
+      when others =>
+    end case decl;",
+    ]
+    .iter()
+    {
+        check_search_reference(&format!(
+            "
+entity ent is
+end entity;
+
+architecture a of ent is
+begin
+  main : process
+  begin
+{}
+  end process;
+end architecture;
+",
+            statement
+        ));
+    }
+}
+
+#[test]
+fn search_declaration_end_identifiers() {
+    check_search_reference(
+        "
+package pkg is
+  type decl is record
+    field : natural;
+  end record decl;
+end package;
+",
+    );
+
+    check_search_reference(
+        "
+package pkg is
+  type decl is range 0 to 1
+    units
+      primary;
+    end units decl;
+end package;
+",
+    );
+
+    check_search_reference(
+        "
+package pkg is
+  component decl is
+  end component decl;
+end package;
+",
+    );
+
+    check_search_reference(
+        "
+entity ent is
+end entity;
+
+architecture a of ent is
+  procedure decl is
+  begin
+  end procedure decl;
+begin
+end architecture;
+",
+    );
+}
+
+#[test]
+fn search_protected_type_end_identifiers() {
+    check_search_reference(
+        "
+package pkg is
+  type decl is protected
+  end protected decl;
+end package;
+
+package body pkg is
+  type decl is protected body
+  end protected body decl;
+end package body;
+",
+    );
+}
+
 #[test]
 fn resolves_missing_name_in_alias() {
     check_missing(
@@ -0,0 +1,108 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2020, Olof Kraigher olof.kraigher@gmail.com
+
+use super::*;
+use crate::data::SrcPos;
+
+fn selection_ranges(builder: &LibraryBuilder, code: &Code, cursor: Code) -> Vec<SrcPos> {
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+    root.find_selection_ranges(code.source(), cursor.start())
+}
+
+#[test]
+fn expands_from_expression_to_design_unit() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+end entity;
+
+architecture a of ent is
+  signal cnt : natural;
+begin
+  main : process
+    variable tmp : natural;
+  begin
+    tmp := cnt + 1;
+    wait;
+  end process;
+end architecture;
+",
+    );
+
+    assert_eq!(
+        selection_ranges(&builder, &code, code.s("cnt", 2)),
+        vec![
+            code.s("cnt", 2).pos(),
+            code.s1("cnt + 1").pos(),
+            code.s1("tmp := cnt + 1;").pos(),
+            code.between("tmp := cnt + 1;", "wait;").pos(),
+            code.between("main : process", "end process;").pos(),
+            code.between("architecture", "end architecture;").pos(),
+        ]
+    );
+}
+
+#[test]
+fn expands_through_name_and_context_clause() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+package pkg is
+  type arr_t is array (natural range <>) of natural;
+end package;
+
+entity ent is
+end entity;
+
+use work.pkg.all;
+
+architecture a of ent is
+  signal arr : arr_t(0 to 3);
+  signal idx : natural;
+begin
+  arr(idx + 1) <= 0;
+  arr(idx) <= 1;
+end architecture;
+",
+    );
+
+    assert_eq!(
+        selection_ranges(&builder, &code, code.s("idx", 2)),
+        vec![
+            code.s("idx", 2).pos(),
+            code.s1("idx + 1").pos(),
+            code.s1("arr(idx + 1)").pos(),
+            code.s1("arr(idx + 1) <= 0;").pos(),
+            code.between("arr(idx + 1)", "<= 1;").pos(),
+            code.between("architecture", "end architecture;").pos(),
+            code.between("use work", "end architecture;").pos(),
+        ]
+    );
+}
+
+#[test]
+fn no_selection_ranges_outside_of_design_units() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+end entity;
+
+entity ent2 is
+end entity;
+",
+    );
+
+    assert_eq!(
+        selection_ranges(&builder, &code, code.s1("\nentity ent2")),
+        vec![]
+    );
+}
@@ -0,0 +1,139 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2020, Olof Kraigher olof.kraigher@gmail.com
+
+use super::*;
+use crate::analysis::{SemanticToken, SemanticTokenKind};
+use crate::ast::Mode;
+use crate::data::SrcPos;
+use SemanticTokenKind::*;
+
+fn kind_at(tokens: &[SemanticToken], pos: SrcPos) -> Option<(SemanticTokenKind, bool)> {
+    tokens
+        .iter()
+        .find(|token| token.pos == pos)
+        .map(|token| (token.kind, token.is_declaration))
+}
+
+#[test]
+fn classifies_references_by_named_entity_kind() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+library libname;
+
+package pkg is
+  type state_t is (idle, busy);
+  subtype small_t is natural range 0 to 3;
+  function calc(arg : natural) return natural;
+  procedure update(constant arg : in natural);
+end package;
+
+use work.pkg.all;
+
+entity top is
+  generic (width : natural);
+  port (
+    clk : in bit;
+    dout : out natural);
+end entity;
+
+architecture a of top is
+  signal fsm : state_t;
+  constant limit : small_t := 0;
+begin
+  main : process
+    variable acc : natural;
+  begin
+    acc := calc(width) + limit;
+    update(acc);
+    fsm <= idle;
+    dout <= acc;
+    wait until clk = '1';
+  end process;
+end architecture;
+",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+    let tokens = root.find_semantic_tokens(code.source());
+
+    let refs = vec![
+        ("state_t", 2, Type),
+        ("small_t", 2, Subtype),
+        ("idle", 2, EnumLiteral),
+        ("calc", 2, Function),
+        ("update", 2, Procedure),
+        ("pkg", 2, Unit),
+        ("work", 1, Library),
+        ("top", 2, Unit),
+        ("width", 2, Generic),
+        ("clk", 2, Port(Mode::In)),
+        ("dout", 2, Port(Mode::Out)),
+        ("fsm", 2, Signal),
+        ("limit", 2, Constant),
+        ("acc", 2, Variable),
+    ];
+
+    for (name, occurrence, kind) in refs {
+        assert_eq!(
+            kind_at(&tokens, code.s(name, occurrence).pos()),
+            Some((kind, false)),
+            "{}",
+            name
+        );
+    }
+
+    let decls = vec![
+        ("libname", Library),
+        ("state_t", Type),
+        ("small_t", Subtype),
+        ("calc", Function),
+        ("update", Procedure),
+        ("top", Unit),
+        ("width", Generic),
+        ("clk", Port(Mode::In)),
+        ("dout", Port(Mode::Out)),
+        ("fsm", Signal),
+        ("limit", Constant),
+        ("acc", Variable),
+    ];
+
+    for (name, kind) in decls {
+        assert_eq!(
+            kind_at(&tokens, code.s1(name).pos()),
+            Some((kind, true)),
+            "{}",
+            name
+        );
+    }
+
+    assert!(tokens
+        .iter()
+        .all(|token| token.pos.source == *code.source()));
+    assert!(tokens
+        .windows(2)
+        .all(|pair| pair[0].pos.start() < pair[1].pos.start()));
+}
+
+#[test]
+fn classifies_end_identifiers_as_declarations() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+package pkg is
+end package pkg;
+",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+    let tokens = root.find_semantic_tokens(code.source());
+
+    assert_eq!(kind_at(&tokens, code.s("pkg", 2).pos()), Some((Unit, true)));
+}
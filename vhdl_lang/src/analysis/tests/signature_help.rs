@@ -0,0 +1,300 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2020, Olof Kraigher olof.kraigher@gmail.com
+
+use super::*;
+use crate::analysis::CallSignatures;
+use crate::data::Position;
+
+fn call_signatures(builder: &LibraryBuilder, code: &Code, cursor: Position) -> CallSignatures {
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+    root.find_call_signatures(code.source(), cursor)
+        .expect("Expected call signatures")
+}
+
+fn labels(signatures: &CallSignatures) -> Vec<&str> {
+    signatures
+        .signatures
+        .iter()
+        .map(|signature| signature.label.as_str())
+        .collect()
+}
+
+fn active_parameter_label(signatures: &CallSignatures) -> Option<String> {
+    let signature = &signatures.signatures[signatures.active_signature];
+    let (start, end) = signature.parameters[signatures.active_parameter?];
+    Some(
+        signature
+            .label
+            .chars()
+            .skip(start)
+            .take(end - start)
+            .collect(),
+    )
+}
+
+#[test]
+fn signature_help_lists_overloaded_subprograms() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+package pkg is
+  function fun(arg : natural) return natural;
+  function fun(arg : natural; flag : boolean) return natural;
+end package;
+
+use work.pkg.all;
+
+entity ent is
+end entity;
+
+architecture a of ent is
+  constant c0 : natural := fun(0, false);
+begin
+end architecture;
+",
+    );
+
+    let signatures = call_signatures(&builder, &code, code.s1("fun(0, ").end());
+    assert_eq!(
+        labels(&signatures),
+        vec![
+            "fun[NATURAL return NATURAL]",
+            "fun[NATURAL, BOOLEAN return NATURAL]"
+        ]
+    );
+    assert_eq!(signatures.active_signature, 1);
+    assert_eq!(
+        active_parameter_label(&signatures),
+        Some("BOOLEAN".to_owned())
+    );
+
+    let signatures = call_signatures(&builder, &code, code.s1("fun(").end());
+    assert_eq!(signatures.active_signature, 0);
+    assert_eq!(
+        active_parameter_label(&signatures),
+        Some("NATURAL".to_owned())
+    );
+}
+
+#[test]
+fn signature_help_of_selected_name_with_named_association() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+package pkg is
+  procedure proc(first : natural; second : boolean);
+end package;
+
+entity ent is
+end entity;
+
+architecture a of ent is
+begin
+  main : process
+  begin
+    work.pkg.proc(second => true, first => 0);
+  end process;
+end architecture;
+",
+    );
+
+    let signatures = call_signatures(&builder, &code, code.s1("second => ").end());
+    assert_eq!(labels(&signatures), vec!["proc[NATURAL, BOOLEAN]"]);
+    assert_eq!(
+        active_parameter_label(&signatures),
+        Some("BOOLEAN".to_owned())
+    );
+
+    let signatures = call_signatures(&builder, &code, code.s1("first => ").end());
+    assert_eq!(
+        active_parameter_label(&signatures),
+        Some("NATURAL".to_owned())
+    );
+}
+
+#[test]
+fn signature_help_of_enclosing_call() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+end entity;
+
+architecture a of ent is
+  type arr_t is array (natural range <>) of natural;
+  constant arr : arr_t(0 to 1) := (0, 1);
+  function fun(arg : natural) return natural;
+  constant c0 : natural := fun(arr(0));
+begin
+end architecture;
+",
+    );
+
+    let signatures = call_signatures(&builder, &code, code.s1("arr(").end());
+    assert_eq!(labels(&signatures), vec!["fun[NATURAL return NATURAL]"]);
+    assert_eq!(
+        active_parameter_label(&signatures),
+        Some("NATURAL".to_owned())
+    );
+}
+
+#[test]
+fn no_signature_help_outside_of_call() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+end entity;
+
+architecture a of ent is
+  function fun(arg : natural) return natural;
+  constant c0 : natural := fun(0);
+  constant c1 : natural := 0;
+begin
+end architecture;
+",
+    );
+
+    let (root, _) = builder.get_analyzed_root();
+    assert!(root
+        .find_call_signatures(code.source(), code.s1("0;").end())
+        .is_none());
+    assert!(root
+        .find_call_signatures(code.source(), code.s1(":= 0").end())
+        .is_none());
+}
+
+#[test]
+fn signature_help_of_entity_instance_port_map() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+  generic (width : natural := 8);
+  port (
+    clk : in bit;
+    data : out bit_vector(width - 1 downto 0));
+end entity;
+
+architecture a of ent is
+begin
+end architecture;
+
+entity top is
+end entity;
+
+architecture a of top is
+  signal clk : bit;
+  signal data : bit_vector(7 downto 0);
+begin
+  inst : entity work.ent(a)
+    generic map (width => 8)
+    port map (clk, data => data);
+end architecture;
+",
+    );
+
+    let signatures = call_signatures(&builder, &code, code.s1("port map (clk, ").end());
+    assert_eq!(
+        labels(&signatures),
+        vec!["ent port (clk : in bit; data : out bit_vector(width - 1 downto 0))"]
+    );
+    assert_eq!(
+        active_parameter_label(&signatures),
+        Some("data : out bit_vector(width - 1 downto 0)".to_owned())
+    );
+
+    let signatures = call_signatures(&builder, &code, code.s1("generic map (").end());
+    assert_eq!(
+        labels(&signatures),
+        vec!["ent generic (width : natural := 8)"]
+    );
+    assert_eq!(
+        active_parameter_label(&signatures),
+        Some("width : natural := 8".to_owned())
+    );
+}
+
+#[test]
+fn signature_help_of_component_instance_port_map() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity top is
+end entity;
+
+architecture a of top is
+  component comp is
+    port (
+      clk : in bit;
+      rst : in bit);
+  end component;
+
+  signal clk, rst : bit;
+begin
+  inst0 : component comp port map (clk => clk, rst => rst);
+  inst1 : comp port map (clk, rst);
+end architecture;
+",
+    );
+
+    let signatures = call_signatures(&builder, &code, code.s1("rst => ").end());
+    assert_eq!(
+        labels(&signatures),
+        vec!["comp port (clk : in bit; rst : in bit)"]
+    );
+    assert_eq!(
+        active_parameter_label(&signatures),
+        Some("rst : in bit".to_owned())
+    );
+
+    let signatures = call_signatures(&builder, &code, code.s1("port map (clk").end());
+    assert_eq!(
+        active_parameter_label(&signatures),
+        Some("clk : in bit".to_owned())
+    );
+}
+
+#[test]
+fn signature_help_of_subprogram_local_to_process() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+entity ent is
+end entity;
+
+architecture a of ent is
+begin
+  main : process
+    variable count : natural;
+
+    procedure incr(amount : natural; wrap : boolean) is
+    begin
+      count := count + amount;
+    end procedure;
+  begin
+    incr(1, false);
+    wait;
+  end process;
+end architecture;
+",
+    );
+
+    let signatures = call_signatures(&builder, &code, code.s1("incr(1, ").end());
+    assert_eq!(labels(&signatures), vec!["incr[NATURAL, BOOLEAN]"]);
+    assert_eq!(
+        active_parameter_label(&signatures),
+        Some("BOOLEAN".to_owned())
+    );
+}
@@ -0,0 +1,106 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2020, Olof Kraigher olof.kraigher@gmail.com
+
+use super::*;
+
+#[test]
+fn object_type_definition_is_type_mark_and_base_type() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+package pkg is
+  type word_t is array (natural range <>) of bit;
+  subtype byte_t is word_t(7 downto 0);
+  constant zero : word_t(0 to 3) := \"0000\";
+  signal data : byte_t;
+  shared variable cnt : natural;
+end package;
+",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    assert_eq!(
+        root.find_type_definition(&code.s1("zero").pos()),
+        vec![code.s1("word_t").pos()]
+    );
+    assert_eq!(
+        root.find_type_definition(&code.s1("data").pos()),
+        vec![code.s1("byte_t").pos(), code.s1("word_t").pos()]
+    );
+
+    // The base type of natural is declared within the standard package
+    let cnt = root.find_type_definition(&code.s1("cnt").pos());
+    assert_eq!(cnt.len(), 2);
+    assert_eq!(cnt[0].source, cnt[1].source);
+    assert_ne!(cnt[0].source, *code.source());
+
+    // A type has no type definition
+    assert_eq!(root.find_type_definition(&code.s1("byte_t").pos()), vec![]);
+}
+
+#[test]
+fn port_and_record_element_type_definition() {
+    let mut builder = LibraryBuilder::new();
+    let code = builder.code(
+        "libname",
+        "
+package pkg is
+  type req_t is record
+    valid : boolean;
+  end record;
+
+  type bus_t is record
+    req : req_t;
+  end record;
+end package;
+
+use work.pkg.all;
+
+entity ent is
+  port (
+    bus_i : in bus_t
+  );
+end entity;
+
+architecture a of ent is
+begin
+  main : process
+  begin
+    assert bus_i.req.valid;
+    wait;
+  end process;
+end architecture;
+",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_no_diagnostics(&diagnostics);
+
+    assert_eq!(
+        root.find_type_definition(&code.s1("bus_i").pos()),
+        vec![code.s1("bus_t").pos()]
+    );
+
+    // Names within expressions refer to the declarations of the port and the record element
+    let decl_pos = root
+        .search_reference(code.source(), code.s("bus_i", 2).start())
+        .unwrap();
+    assert_eq!(
+        root.find_type_definition(&decl_pos),
+        vec![code.s1("bus_t").pos()]
+    );
+
+    let decl_pos = root
+        .search_reference(code.source(), code.s1("bus_i.req.valid").s1("req").start())
+        .unwrap();
+    assert_eq!(
+        root.find_type_definition(&decl_pos),
+        vec![code.s1("req_t").pos()]
+    );
+}
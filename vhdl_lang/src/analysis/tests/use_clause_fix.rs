@@ -0,0 +1,110 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2020, Olof Kraigher olof.kraigher@gmail.com
+
+use super::*;
+use crate::analysis::{DesignRoot, UseClauseFix};
+use crate::data::Position;
+
+fn fixes(root: &DesignRoot, code: &Code) -> Vec<(String, Position)> {
+    root.find_use_clause_fixes(code.source(), code.start())
+        .into_iter()
+        .map(|UseClauseFix { text, position, .. }| (text, position))
+        .collect()
+}
+
+#[test]
+fn finds_packages_declaring_unresolved_name() {
+    let mut builder = LibraryBuilder::new();
+    builder.code(
+        "lib2",
+        "
+package other_pkg is
+  type state_t is (idle, busy);
+end package;
+",
+    );
+    let code = builder.code(
+        "libname",
+        "
+package local_pkg is
+  type state_t is (idle, busy);
+end package;
+
+package gen_pkg is
+  generic (width : natural);
+  type state_t is (idle, busy);
+end package;
+
+entity ent is
+end entity;
+
+architecture a of ent is
+  signal state : state_t;
+begin
+end architecture;
+
+library lib2;
+
+entity ent2 is
+end entity;
+
+architecture a of ent2 is
+  signal state : state_t;
+begin
+end architecture;
+",
+    );
+
+    let (root, diagnostics) = builder.get_analyzed_root();
+    check_diagnostics(
+        diagnostics,
+        vec![
+            Diagnostic::error(
+                code.s(": state_t", 1).s1("state_t"),
+                "No declaration of 'state_t'",
+            ),
+            Diagnostic::error(
+                code.s(": state_t", 2).s1("state_t"),
+                "No declaration of 'state_t'",
+            ),
+        ],
+    );
+
+    let line = code.s1("architecture a of ent ").start().line;
+    assert_eq!(
+        fixes(&root, &code.s(": state_t", 1).s1("state_t")),
+        vec![
+            (
+                "use work.local_pkg.all;\n".to_owned(),
+                Position::new(line, 0)
+            ),
+            (
+                "library lib2;\nuse lib2.other_pkg.all;\n".to_owned(),
+                Position::new(line, 0)
+            ),
+        ]
+    );
+
+    // The library clause of the entity applies to the architecture
+    let line = code.s1("architecture a of ent2").start().line;
+    assert_eq!(
+        fixes(&root, &code.s(": state_t", 2).s1("state_t")),
+        vec![
+            (
+                "use work.local_pkg.all;\n".to_owned(),
+                Position::new(line, 0)
+            ),
+            (
+                "use lib2.other_pkg.all;\n".to_owned(),
+                Position::new(line, 0)
+            ),
+        ]
+    );
+
+    // Resolved names and declarations are not fixed
+    assert_eq!(fixes(&root, &code.s1("state :")), vec![]);
+    assert_eq!(fixes(&root, &code.s1("natural")), vec![]);
+}
@@ -0,0 +1,120 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2020, Olof Kraigher olof.kraigher@gmail.com
+
+use super::*;
+
+fn workspace_symbols(builder: &LibraryBuilder, query: &str) -> Vec<(String, String, String)> {
+    let (root, _) = builder.get_analyzed_root();
+    root.find_workspace_symbols(query)
+        .into_iter()
+        // Ignore the standard library
+        .filter(|symbol| !symbol.container_name.starts_with("std"))
+        .map(|symbol| {
+            (
+                symbol.name,
+                symbol.container_name,
+                symbol.kind.describe().to_owned(),
+            )
+        })
+        .collect()
+}
+
+fn symbol(name: &str, container_name: &str, kind: &str) -> (String, String, String) {
+    (name.to_owned(), container_name.to_owned(), kind.to_owned())
+}
+
+#[test]
+fn finds_design_units_in_all_libraries() {
+    let mut builder = LibraryBuilder::new();
+    builder.code(
+        "lib1",
+        "
+entity uart_tx is
+end entity;
+
+architecture rtl of uart_tx is
+begin
+end architecture;
+",
+    );
+    builder.code(
+        "lib2",
+        "
+package uart_pkg is
+end package;
+
+package body uart_pkg is
+end package body;
+",
+    );
+
+    assert_eq!(
+        workspace_symbols(&builder, "uart"),
+        vec![
+            symbol("uart_pkg", "lib2", "package"),
+            symbol("uart_pkg", "lib2", "package body"),
+            symbol("uart_tx", "lib1", "entity"),
+        ]
+    );
+
+    assert_eq!(
+        workspace_symbols(&builder, "rtl"),
+        vec![symbol("rtl", "lib1.uart_tx", "architecture")]
+    );
+}
+
+#[test]
+fn finds_package_declarations() {
+    let mut builder = LibraryBuilder::new();
+    builder.code(
+        "libname",
+        "
+package pkg is
+  constant data_width : natural := 8;
+  type data_t is array (0 to data_width - 1) of bit;
+  function get_data return data_t;
+end package;
+",
+    );
+
+    assert_eq!(
+        workspace_symbols(&builder, "data"),
+        vec![
+            symbol("data_t", "libname.pkg", "type"),
+            symbol("data_width", "libname.pkg", "constant"),
+            symbol("get_data", "libname.pkg", "function"),
+        ]
+    );
+}
+
+#[test]
+fn matches_fuzzy_and_case_insensitive() {
+    let mut builder = LibraryBuilder::new();
+    builder.code(
+        "libname",
+        "
+entity axi_stream_fifo is
+end entity;
+
+entity fifo is
+end entity;
+",
+    );
+
+    // Exact matches first, then contiguous matches
+    assert_eq!(
+        workspace_symbols(&builder, "FIFO"),
+        vec![
+            symbol("fifo", "libname", "entity"),
+            symbol("axi_stream_fifo", "libname", "entity"),
+        ]
+    );
+
+    assert_eq!(
+        workspace_symbols(&builder, "asf"),
+        vec![symbol("axi_stream_fifo", "libname", "entity")]
+    );
+}
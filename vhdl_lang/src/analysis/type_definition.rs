@@ -0,0 +1,73 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2020, Olof Kraigher olof.kraigher@gmail.com
+
+use super::root::*;
+use crate::ast::search::*;
+use crate::ast::*;
+use crate::data::*;
+
+impl DesignRoot {
+    /// Find the type definition of the object, interface object, file or record element
+    /// declared at decl_pos
+    ///
+    /// The result is the declaration of the type mark of the declaration followed by the
+    /// declaration of its base type when the type mark is a subtype or an alias.
+    pub fn find_type_definition(&self, decl_pos: &SrcPos) -> Vec<SrcPos> {
+        let mut searcher = FindTypeMark::new(decl_pos);
+        for unit in self.get_units_in_source(&decl_pos.source) {
+            if let Found = unit.unit.read().search(&mut searcher) {
+                break;
+            }
+        }
+
+        let mut result = Vec::new();
+        if let Some(type_mark) = searcher.result {
+            result.extend(type_mark.decl_pos().cloned());
+            let base_type = type_mark.base_type();
+            if base_type.id() != type_mark.id() {
+                result.extend(base_type.decl_pos().cloned());
+            }
+        }
+        result
+    }
+}
+
+/// Find the named entity referenced by the type mark of the declaration at decl_pos
+struct FindTypeMark<'a> {
+    decl_pos: &'a SrcPos,
+    result: Reference,
+}
+
+impl<'a> FindTypeMark<'a> {
+    fn new(decl_pos: &'a SrcPos) -> FindTypeMark<'a> {
+        FindTypeMark {
+            decl_pos,
+            result: None,
+        }
+    }
+}
+
+impl<'a> Searcher for FindTypeMark<'a> {
+    fn search_decl(&mut self, pos: &SrcPos, decl: FoundDeclaration) -> SearchState {
+        if pos != self.decl_pos {
+            return NotFinished;
+        }
+
+        let subtype_indication = match decl {
+            FoundDeclaration::Object(object) => &object.subtype_indication,
+            FoundDeclaration::InterfaceObject(object) => &object.subtype_indication,
+            FoundDeclaration::File(file) => &file.subtype_indication,
+            FoundDeclaration::ElementDeclaration(elem) => &elem.subtype,
+            _ => return Finished(NotFound),
+        };
+
+        self.result = match subtype_indication.type_mark.item {
+            SelectedName::Designator(ref designator) => designator.reference.clone(),
+            SelectedName::Selected(_, ref suffix) => suffix.item.reference.clone(),
+        };
+        Finished(Found)
+    }
+}
@@ -0,0 +1,186 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2020, Olof Kraigher olof.kraigher@gmail.com
+
+use super::named_entity::*;
+use super::root::*;
+use crate::ast::search::*;
+use crate::ast::*;
+use crate::data::*;
+
+/// Library and use clauses which make a declaration within a package visible
+/// when inserted into the context clause of a design unit
+#[derive(PartialEq, Clone, Debug)]
+pub struct UseClauseFix {
+    pub library_name: Symbol,
+    pub package_name: Symbol,
+    /// The clauses to insert such as `library ieee;\nuse ieee.numeric_std.all;\n`
+    pub text: String,
+    /// Where the clauses are inserted which is the start of the line of the design unit
+    pub position: Position,
+}
+
+impl DesignRoot {
+    /// Find the packages declaring the unresolved name at the cursor
+    /// and the library and use clauses which would make it visible
+    ///
+    /// Packages within the library of the design unit enclosing the cursor are listed
+    /// first, then packages within other libraries in alphabetical order.
+    pub fn find_use_clause_fixes(&self, source: &Source, cursor: Position) -> Vec<UseClauseFix> {
+        let unit = match self.get_unit_at(source, cursor) {
+            Some(unit) => unit,
+            None => return Vec::new(),
+        };
+
+        let designator = {
+            let mut searcher = FindUnresolvedName::new(source, cursor);
+            let _ = unit.unit.read().search(&mut searcher);
+            match searcher.result {
+                Some(designator) => designator,
+                None => return Vec::new(),
+            }
+        };
+
+        let work_library_name = unit.unit_id().library_name();
+        let visible_libraries = self.visible_libraries(unit);
+        let position = Position::new(unit.ident().pos.start().line, 0);
+
+        let mut packages = self.find_packages_declaring(&designator);
+        packages.sort_by_key(|(library_name, package_name)| {
+            (
+                library_name != work_library_name,
+                library_name.name_utf8(),
+                package_name.name_utf8(),
+            )
+        });
+
+        packages
+            .into_iter()
+            .map(|(library_name, package_name)| {
+                let mut text = String::new();
+                if library_name == *work_library_name {
+                    text.push_str(&format!("use work.{}.all;\n", package_name));
+                } else {
+                    if !visible_libraries.contains(&library_name) {
+                        text.push_str(&format!("library {};\n", library_name));
+                    }
+                    text.push_str(&format!("use {}.{}.all;\n", library_name, package_name));
+                }
+
+                UseClauseFix {
+                    library_name,
+                    package_name,
+                    text,
+                    position,
+                }
+            })
+            .collect()
+    }
+
+    /// The library and name of the packages which immediately declare the designator
+    /// Generic packages are excluded since they cannot be used without being instantiated
+    fn find_packages_declaring(&self, designator: &Designator) -> Vec<(Symbol, Symbol)> {
+        let mut result = Vec::new();
+
+        for library_name in self.library_names() {
+            let units = match self.get_library_units(library_name) {
+                Some(units) => units,
+                None => continue,
+            };
+
+            for unit in units.values() {
+                let is_package = match *unit.unit.read() {
+                    AnyDesignUnit::Primary(AnyPrimaryUnit::Package(ref package)) => {
+                        package.generic_clause.is_none()
+                    }
+                    AnyDesignUnit::Primary(AnyPrimaryUnit::PackageInstance(..)) => true,
+                    _ => false,
+                };
+
+                if !is_package {
+                    continue;
+                }
+
+                let declares = match self.get_analysis(unit).result().ent {
+                    Some(ref ent) => match ent.kind() {
+                        NamedEntityKind::Package(ref region)
+                        | NamedEntityKind::PackageInstance(ref region) => {
+                            region.lookup_immediate(designator).is_some()
+                        }
+                        _ => false,
+                    },
+                    None => false,
+                };
+
+                if declares {
+                    result.push((library_name.clone(), unit.ident().item.clone()));
+                }
+            }
+        }
+
+        result
+    }
+
+    /// The libraries made visible by library clauses in the context clause of the unit
+    /// or of its primary unit, the std library is always visible
+    fn visible_libraries(&self, unit: &LockedUnit) -> Vec<Symbol> {
+        let mut result = vec![self.symbol_utf8("std")];
+        add_library_clauses(&unit.unit.read(), &mut result);
+
+        if let AnyKind::Secondary(..) = unit.kind() {
+            let unit_id = unit.unit_id();
+            if let Some(primary) = self
+                .get_library_units(unit_id.library_name())
+                .and_then(|units| units.get(&UnitKey::Primary(unit_id.primary_name().clone())))
+            {
+                add_library_clauses(&primary.unit.read(), &mut result);
+            }
+        }
+
+        result
+    }
+}
+
+fn add_library_clauses(unit: &AnyDesignUnit, result: &mut Vec<Symbol>) {
+    for item in unit.context_clause().into_iter().flatten() {
+        if let ContextItem::Library(ref clause) = item.item {
+            result.extend(clause.name_list.iter().map(|ident| ident.item.clone()));
+        }
+    }
+}
+
+/// Find the designator without a reference at the cursor
+struct FindUnresolvedName {
+    source: Source,
+    cursor: Position,
+    result: Option<Designator>,
+}
+
+impl FindUnresolvedName {
+    fn new(source: &Source, cursor: Position) -> FindUnresolvedName {
+        FindUnresolvedName {
+            source: source.clone(),
+            cursor,
+            result: None,
+        }
+    }
+}
+
+impl Searcher for FindUnresolvedName {
+    fn search_designator_ref(
+        &mut self,
+        pos: &SrcPos,
+        designator: &WithRef<Designator>,
+    ) -> SearchState {
+        if pos.source != self.source || pos.start() > self.cursor || self.cursor > pos.end() {
+            NotFinished
+        } else if designator.reference.is_none() {
+            self.result = Some(designator.item.clone());
+            Finished(Found)
+        } else {
+            Finished(NotFound)
+        }
+    }
+}
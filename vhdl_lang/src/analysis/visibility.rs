@@ -119,6 +119,19 @@ impl Visibility {
         }
     }
 
+    /// Iterate over all potentially visible entities and the name they are visible as
+    pub fn entities(&self) -> impl Iterator<Item = (&Designator, &Arc<NamedEntity>)> {
+        self.all_in_regions
+            .iter()
+            .flat_map(|visible_region| visible_region.region.immediates())
+            .map(|ent| (ent.designator(), ent))
+            .chain(self.visible.iter().flat_map(|(designator, visible)| {
+                visible
+                    .values()
+                    .map(move |visible_ent| (designator, &visible_ent.entity))
+            }))
+    }
+
     /// Helper function lookup a visible declaration within the region
     pub fn lookup_into<'a>(&'a self, designator: &Designator, visible: &mut Visible<'a>) {
         for visible_region in self.all_in_regions.iter() {
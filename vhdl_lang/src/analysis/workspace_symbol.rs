@@ -0,0 +1,169 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2020, Olof Kraigher olof.kraigher@gmail.com
+
+use super::root::*;
+use crate::ast::*;
+use crate::data::*;
+use std::sync::Arc;
+
+/// A design unit or package declaration found by a workspace wide symbol search
+pub struct WorkspaceSymbol {
+    pub name: String,
+    /// The library of a design unit or the library and package of a declaration
+    pub container_name: String,
+    pub kind: WorkspaceSymbolKind,
+    pub decl_pos: SrcPos,
+}
+
+pub enum WorkspaceSymbolKind {
+    /// A primary unit or a declaration within a package
+    Named(Arc<NamedEntity>),
+    /// Secondary units have no named entity
+    Secondary(SecondaryKind),
+}
+
+impl WorkspaceSymbolKind {
+    pub fn describe(&self) -> &str {
+        match self {
+            WorkspaceSymbolKind::Named(ent) => ent.kind().describe(),
+            WorkspaceSymbolKind::Secondary(kind) => kind.describe(),
+        }
+    }
+}
+
+impl DesignRoot {
+    /// Find the design units and package declarations in all libraries matching the query
+    /// The query is matched case-insensitively as a possibly non-contiguous subsequence
+    /// of the name. The result is sorted with the best matches first.
+    pub fn find_workspace_symbols(&self, query: &str) -> Vec<WorkspaceSymbol> {
+        let query = query.to_lowercase();
+        let mut matches = Vec::new();
+
+        let mut add = |symbol: WorkspaceSymbol| {
+            if let Some(score) = fuzzy_match(&query, &symbol.name.to_lowercase()) {
+                matches.push((score, symbol));
+            }
+        };
+
+        for library_name in self.library_names() {
+            let units = self.get_library_units(library_name).into_iter();
+            for unit in units.flat_map(|units| units.values()) {
+                let data = self.get_analysis(unit);
+
+                match data.result().ent {
+                    Some(ref ent) => {
+                        add(WorkspaceSymbol {
+                            name: ent.designator().to_string(),
+                            container_name: library_name.to_string(),
+                            kind: WorkspaceSymbolKind::Named(ent.clone()),
+                            decl_pos: unit.ident().pos.clone(),
+                        });
+
+                        if let NamedEntityKind::Package(ref region)
+                        | NamedEntityKind::UninstPackage(ref region) = ent.kind()
+                        {
+                            let container_name = format!("{}.{}", library_name, ent.designator());
+                            for decl in region.immediates() {
+                                if decl.is_implicit() {
+                                    continue;
+                                }
+
+                                if let (Designator::Identifier(..), Some(decl_pos)) =
+                                    (decl.designator(), decl.decl_pos())
+                                {
+                                    add(WorkspaceSymbol {
+                                        name: decl.designator().to_string(),
+                                        container_name: container_name.clone(),
+                                        kind: WorkspaceSymbolKind::Named(decl.clone()),
+                                        decl_pos: decl_pos.clone(),
+                                    });
+                                }
+                            }
+                        }
+                    }
+                    None => {
+                        if let AnyKind::Secondary(kind) = unit.kind() {
+                            let container_name = match kind {
+                                SecondaryKind::Architecture => {
+                                    format!("{}.{}", library_name, unit.unit_id().primary_name())
+                                }
+                                SecondaryKind::PackageBody => library_name.to_string(),
+                            };
+
+                            add(WorkspaceSymbol {
+                                name: unit.name().to_string(),
+                                container_name,
+                                kind: WorkspaceSymbolKind::Secondary(kind),
+                                decl_pos: unit.ident().pos.clone(),
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        matches.sort_by(|(score, symbol), (other_score, other_symbol)| {
+            score
+                .cmp(other_score)
+                .then_with(|| symbol.name.cmp(&other_symbol.name))
+                .then_with(|| symbol.container_name.cmp(&other_symbol.container_name))
+        });
+        matches.into_iter().map(|(_, symbol)| symbol).collect()
+    }
+}
+
+/// Match the lower case query against the lower case name
+/// Returns None when the query is not a subsequence of the name,
+/// otherwise a score where lower is better.
+/// Exact matches are best followed by contiguous matches closest to the start
+fn fuzzy_match(query: &str, name: &str) -> Option<usize> {
+    if query == name {
+        return Some(0);
+    } else if let Some(idx) = name.find(query) {
+        return Some(1 + idx);
+    }
+
+    let mut score = 1 + name.len();
+    let mut query_chars = query.chars().peekable();
+    for (idx, chr) in name.chars().enumerate() {
+        match query_chars.peek() {
+            Some(query_chr) if *query_chr == chr => {
+                query_chars.next();
+            }
+            Some(..) => {
+                // Penalize gaps before and within the match
+                score += idx.min(1);
+            }
+            None => break,
+        }
+    }
+
+    if query_chars.peek().is_none() {
+        Some(score)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fuzzy_match_ranks_exact_then_contiguous_then_subsequence() {
+        assert_eq!(fuzzy_match("axi_pkg", "axi_pkg"), Some(0));
+        assert_eq!(fuzzy_match("axi", "axi_pkg"), Some(1));
+        assert_eq!(fuzzy_match("pkg", "axi_pkg"), Some(5));
+        assert!(fuzzy_match("apkg", "axi_pkg").unwrap() > fuzzy_match("pkg", "axi_pkg").unwrap());
+        assert_eq!(fuzzy_match("", "axi_pkg"), Some(1));
+    }
+
+    #[test]
+    fn fuzzy_match_requires_subsequence() {
+        assert_eq!(fuzzy_match("pkga", "axi_pkg"), None);
+        assert_eq!(fuzzy_match("axi_pkg_", "axi_pkg"), None);
+    }
+}
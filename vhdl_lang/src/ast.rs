@@ -12,6 +12,7 @@
 use std::sync::Arc;
 mod display;
 mod name_util;
+mod template;
 
 #[macro_use]
 mod any_design_unit;
@@ -23,7 +24,7 @@ pub use self::display::*;
 pub use self::name_util::*;
 pub use any_design_unit::*;
 
-pub use crate::analysis::NamedEntity;
+pub use crate::analysis::{NamedEntity, NamedEntityKind};
 use crate::data::*;
 
 /// LRM 15.8 Bit string literals
@@ -500,6 +501,8 @@ pub enum TypeDefinition {
 pub struct TypeDeclaration {
     pub ident: Ident,
     pub def: TypeDefinition,
+    /// The repeated name of a record, physical or protected type such as `end record name;`
+    pub end_ident_pos: Option<SrcPos>,
 }
 
 /// LRM 6.4.2 Object Declarations
@@ -562,6 +565,9 @@ pub struct SubprogramBody {
     pub specification: SubprogramDeclaration,
     pub declarations: Vec<Declaration>,
     pub statements: Vec<LabeledSequentialStatement>,
+    pub end_ident_pos: Option<SrcPos>,
+    /// From the initial keyword to the final semicolon
+    pub source_range: SrcPos,
 }
 
 /// LRM 4.5.3 Signatures
@@ -646,6 +652,7 @@ pub struct ComponentDeclaration {
     pub ident: Ident,
     pub generic_list: Vec<InterfaceDeclaration>,
     pub port_list: Vec<InterfaceDeclaration>,
+    pub end_ident_pos: Option<SrcPos>,
 }
 
 #[derive(PartialEq, Debug, Clone)]
@@ -773,7 +780,11 @@ pub type ConditionalExpression = Conditional<WithPos<Expression>>;
 pub type ConditionalExpressions = Conditionals<WithPos<Expression>>;
 
 /// LRM 10.8 If statement
-pub type IfStatement = Conditionals<Vec<LabeledSequentialStatement>>;
+#[derive(PartialEq, Debug, Clone)]
+pub struct IfStatement {
+    pub conds: Conditionals<Vec<LabeledSequentialStatement>>,
+    pub end_label_pos: Option<SrcPos>,
+}
 
 #[derive(PartialEq, Debug, Clone)]
 pub struct Alternative<T> {
@@ -793,6 +804,7 @@ pub struct CaseStatement {
     pub is_matching: bool,
     pub expression: WithPos<Expression>,
     pub alternatives: Vec<Alternative<Vec<LabeledSequentialStatement>>>,
+    pub end_label_pos: Option<SrcPos>,
 }
 
 /// LRM 10.10 Loop statement
@@ -807,6 +819,7 @@ pub enum IterationScheme {
 pub struct LoopStatement {
     pub iteration_scheme: Option<IterationScheme>,
     pub statements: Vec<LabeledSequentialStatement>,
+    pub end_label_pos: Option<SrcPos>,
 }
 
 /// LRM 10.11 Next statement
@@ -853,7 +866,7 @@ pub enum SequentialStatement {
 #[derive(PartialEq, Debug, Clone)]
 pub struct LabeledSequentialStatement {
     pub label: Option<Ident>,
-    pub statement: SequentialStatement,
+    pub statement: WithPos<SequentialStatement>,
 }
 
 /// LRM 11.2 Block statement
@@ -863,6 +876,7 @@ pub struct BlockStatement {
     pub header: BlockHeader,
     pub decl: Vec<Declaration>,
     pub statements: Vec<LabeledConcurrentStatement>,
+    pub end_label_pos: Option<SrcPos>,
 }
 
 /// LRM 11.2 Block statement
@@ -887,6 +901,7 @@ pub struct ProcessStatement {
     pub sensitivity_list: Option<SensitivityList>,
     pub decl: Vec<Declaration>,
     pub statements: Vec<LabeledSequentialStatement>,
+    pub end_label_pos: Option<SrcPos>,
 }
 
 /// LRM 11.4 Concurrent procedure call statements
@@ -935,6 +950,9 @@ pub struct GenerateBody {
     pub alternative_label: Option<Ident>,
     pub decl: Option<Vec<Declaration>>,
     pub statements: Vec<LabeledConcurrentStatement>,
+    pub end_label_pos: Option<SrcPos>,
+    /// From the generate keyword or arrow before the body to the token ending the body
+    pub source_range: SrcPos,
 }
 
 /// 11.8 Generate statements
@@ -943,11 +961,22 @@ pub struct ForGenerateStatement {
     pub index_name: Ident,
     pub discrete_range: DiscreteRange,
     pub body: GenerateBody,
+    pub end_label_pos: Option<SrcPos>,
+}
+
+/// 11.8 Generate statements
+#[derive(PartialEq, Debug, Clone)]
+pub struct IfGenerateStatement {
+    pub conds: Conditionals<GenerateBody>,
+    pub end_label_pos: Option<SrcPos>,
 }
 
 /// 11.8 Generate statements
-pub type IfGenerateStatement = Conditionals<GenerateBody>;
-pub type CaseGenerateStatement = Selection<GenerateBody>;
+#[derive(PartialEq, Debug, Clone)]
+pub struct CaseGenerateStatement {
+    pub sels: Selection<GenerateBody>,
+    pub end_label_pos: Option<SrcPos>,
+}
 
 /// LRM 11. Concurrent statements
 #[derive(PartialEq, Debug, Clone)]
@@ -967,7 +996,7 @@ pub enum ConcurrentStatement {
 #[derive(PartialEq, Debug, Clone)]
 pub struct LabeledConcurrentStatement {
     pub label: Option<Ident>,
-    pub statement: ConcurrentStatement,
+    pub statement: WithPos<ConcurrentStatement>,
 }
 
 /// LRM 13. Design units and their analysis
@@ -1001,6 +1030,9 @@ pub enum ContextItem {
 pub struct ContextDeclaration {
     pub ident: Ident,
     pub items: ContextClause,
+    pub end_ident_pos: Option<SrcPos>,
+    /// From the initial keyword to the final semicolon
+    pub source_range: SrcPos,
 }
 
 /// LRM 4.9 Package instatiation declaration
@@ -1097,6 +1129,9 @@ pub struct ConfigurationDeclaration {
     pub decl: Vec<ConfigurationDeclarativeItem>,
     pub vunit_bind_inds: Vec<VUnitBindingIndication>,
     pub block_config: BlockConfiguration,
+    pub end_ident_pos: Option<SrcPos>,
+    /// From the initial keyword to the final semicolon excluding the context clause
+    pub source_range: SrcPos,
 }
 
 /// LRM 3.2 Entity declarations
@@ -1108,6 +1143,9 @@ pub struct EntityDeclaration {
     pub port_clause: Option<Vec<InterfaceDeclaration>>,
     pub decl: Vec<Declaration>,
     pub statements: Vec<LabeledConcurrentStatement>,
+    pub end_ident_pos: Option<SrcPos>,
+    /// From the initial keyword to the final semicolon excluding the context clause
+    pub source_range: SrcPos,
 }
 
 /// LRM 3.3 Architecture bodies
@@ -1118,6 +1156,9 @@ pub struct ArchitectureBody {
     pub entity_name: WithRef<Ident>,
     pub decl: Vec<Declaration>,
     pub statements: Vec<LabeledConcurrentStatement>,
+    pub end_ident_pos: Option<SrcPos>,
+    /// From the initial keyword to the final semicolon excluding the context clause
+    pub source_range: SrcPos,
 }
 
 /// LRM 4.7 Package declarations
@@ -1127,6 +1168,9 @@ pub struct PackageDeclaration {
     pub ident: Ident,
     pub generic_clause: Option<Vec<InterfaceDeclaration>>,
     pub decl: Vec<Declaration>,
+    pub end_ident_pos: Option<SrcPos>,
+    /// From the initial keyword to the final semicolon excluding the context clause
+    pub source_range: SrcPos,
 }
 
 /// LRM 4.8 Package bodies
@@ -1135,6 +1179,9 @@ pub struct PackageBody {
     pub context_clause: ContextClause,
     pub ident: WithRef<Ident>,
     pub decl: Vec<Declaration>,
+    pub end_ident_pos: Option<SrcPos>,
+    /// From the initial keyword to the final semicolon excluding the context clause
+    pub source_range: SrcPos,
 }
 
 /// LRM 13.1 Design units
@@ -216,6 +216,42 @@ impl AnyDesignUnit {
             None
         }
     }
+
+    /// From the initial keyword to the final semicolon excluding the context clause
+    /// The range of a package instantiation is not kept
+    pub fn source_range(&self) -> Option<&SrcPos> {
+        match self {
+            AnyDesignUnit::Primary(unit) => match unit {
+                AnyPrimaryUnit::Entity(ref unit) => Some(&unit.source_range),
+                AnyPrimaryUnit::Configuration(ref unit) => Some(&unit.source_range),
+                AnyPrimaryUnit::Package(ref unit) => Some(&unit.source_range),
+                AnyPrimaryUnit::PackageInstance(..) => None,
+                AnyPrimaryUnit::Context(ref unit) => Some(&unit.source_range),
+            },
+            AnyDesignUnit::Secondary(unit) => match unit {
+                AnySecondaryUnit::Architecture(ref unit) => Some(&unit.source_range),
+                AnySecondaryUnit::PackageBody(ref unit) => Some(&unit.source_range),
+            },
+        }
+    }
+
+    /// The context clause preceding the design unit
+    /// A context declaration is itself a context clause and has none
+    pub fn context_clause(&self) -> Option<&ContextClause> {
+        match self {
+            AnyDesignUnit::Primary(unit) => match unit {
+                AnyPrimaryUnit::Entity(ref unit) => Some(&unit.context_clause),
+                AnyPrimaryUnit::Configuration(ref unit) => Some(&unit.context_clause),
+                AnyPrimaryUnit::Package(ref unit) => Some(&unit.context_clause),
+                AnyPrimaryUnit::PackageInstance(ref unit) => Some(&unit.context_clause),
+                AnyPrimaryUnit::Context(..) => None,
+            },
+            AnyDesignUnit::Secondary(unit) => match unit {
+                AnySecondaryUnit::Architecture(ref unit) => Some(&unit.context_clause),
+                AnySecondaryUnit::PackageBody(ref unit) => Some(&unit.context_clause),
+            },
+        }
+    }
 }
 
 /// Upper case first letter
@@ -996,6 +996,64 @@ impl Display for ForGenerateStatement {
     }
 }
 
+impl Display for LibraryClause {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "library")?;
+        let mut first = true;
+        for name in &self.name_list {
+            if first {
+                write!(f, " {}", name)?;
+            } else {
+                write!(f, ", {}", name)?;
+            }
+            first = false;
+        }
+        write!(f, ";")
+    }
+}
+
+impl Display for UseClause {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "use")?;
+        let mut first = true;
+        for name in &self.name_list {
+            if first {
+                write!(f, " {}", name)?;
+            } else {
+                write!(f, ", {}", name)?;
+            }
+            first = false;
+        }
+        write!(f, ";")
+    }
+}
+
+impl Display for ContextReference {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "context")?;
+        let mut first = true;
+        for name in &self.name_list {
+            if first {
+                write!(f, " {}", name)?;
+            } else {
+                write!(f, ", {}", name)?;
+            }
+            first = false;
+        }
+        write!(f, ";")
+    }
+}
+
+impl Display for ContextItem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            ContextItem::Use(ref use_clause) => write!(f, "{}", use_clause),
+            ContextItem::Library(ref library_clause) => write!(f, "{}", library_clause),
+            ContextItem::Context(ref context_reference) => write!(f, "{}", context_reference),
+        }
+    }
+}
+
 impl Display for ContextDeclaration {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         // Not used: items
@@ -1117,6 +1175,16 @@ mod tests {
         assert_format_eq(code, code, code_fun);
     }
 
+    #[test]
+    fn test_library_clause() {
+        assert_format("library ieee, lib;", Code::library_clause);
+    }
+
+    #[test]
+    fn test_use_clause() {
+        assert_format("use ieee.std_logic_1164.all, lib.pkg;", Code::use_clause);
+    }
+
     #[test]
     fn test_selected_name_single() {
         assert_format("foo", Code::selected_name);
@@ -2033,7 +2101,7 @@ end generate;",
             "for idx in 0 to 1 generate",
             |code| {
                 assert_matches!(
-                    code.concurrent_statement().statement,
+                    code.concurrent_statement().statement.item,
                     ConcurrentStatement::ForGenerate(gen) => gen
                 )
             },
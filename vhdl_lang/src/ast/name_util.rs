@@ -275,6 +275,23 @@ impl SubprogramDeclaration {
     }
 }
 
+impl InterfaceDeclaration {
+    /// The designator by which the interface declaration is associated
+    pub fn designator(&self) -> Designator {
+        match self {
+            InterfaceDeclaration::Object(ref object) => {
+                Designator::Identifier(object.ident.item.clone())
+            }
+            InterfaceDeclaration::File(ref file) => Designator::Identifier(file.ident.item.clone()),
+            InterfaceDeclaration::Type(ref ident) => Designator::Identifier(ident.item.clone()),
+            InterfaceDeclaration::Subprogram(ref decl, ..) => decl.designator().item,
+            InterfaceDeclaration::Package(ref package) => {
+                Designator::Identifier(package.ident.item.clone())
+            }
+        }
+    }
+}
+
 impl EnumerationLiteral {
     pub fn into_designator(self) -> Designator {
         match self {
@@ -307,3 +324,29 @@ impl Name {
         }
     }
 }
+
+impl SequentialStatement {
+    /// The position of the label repeated at the end of a compound statement
+    pub fn end_label_pos(&self) -> Option<&SrcPos> {
+        match self {
+            SequentialStatement::If(ref stmt) => stmt.end_label_pos.as_ref(),
+            SequentialStatement::Case(ref stmt) => stmt.end_label_pos.as_ref(),
+            SequentialStatement::Loop(ref stmt) => stmt.end_label_pos.as_ref(),
+            _ => None,
+        }
+    }
+}
+
+impl ConcurrentStatement {
+    /// The position of the label repeated at the end of a compound statement
+    pub fn end_label_pos(&self) -> Option<&SrcPos> {
+        match self {
+            ConcurrentStatement::Block(ref stmt) => stmt.end_label_pos.as_ref(),
+            ConcurrentStatement::Process(ref stmt) => stmt.end_label_pos.as_ref(),
+            ConcurrentStatement::ForGenerate(ref stmt) => stmt.end_label_pos.as_ref(),
+            ConcurrentStatement::IfGenerate(ref stmt) => stmt.end_label_pos.as_ref(),
+            ConcurrentStatement::CaseGenerate(ref stmt) => stmt.end_label_pos.as_ref(),
+            _ => None,
+        }
+    }
+}
@@ -94,6 +94,51 @@ pub trait Searcher {
         self.search_decl_pos(pos)
     }
 
+    /// Search the repeated identifier or label at the end of a declaration or statement
+    /// such as `end entity name;` where decl_pos is the position of the declared name
+    fn search_end_ident_pos(&mut self, _decl_pos: &SrcPos, _end_pos: &SrcPos) -> SearchState {
+        NotFinished
+    }
+
+    /// Search the target of an assignment before the names within it are searched
+    fn search_assignment_target(&mut self, _target: &WithPos<Target>) -> SearchState {
+        NotFinished
+    }
+
+    /// Search a function or procedure call before its name and parameters are searched
+    fn search_function_call(&mut self, _call: &FunctionCall) -> SearchState {
+        NotFinished
+    }
+
+    /// Search an instantiation before its unit name and association lists are searched
+    fn search_instantiation(&mut self, _inst: &InstantiationStatement) -> SearchState {
+        NotFinished
+    }
+
+    /// Search a subprogram body before its specification is searched
+    /// The body is skipped when the search is finished without being found
+    fn search_subprogram_body(&mut self, _body: &SubprogramBody) -> SearchState {
+        NotFinished
+    }
+
+    /// Search a concurrent statement before its label and contents are searched
+    /// The statement is skipped when the search is finished without being found
+    fn search_concurrent_statement(
+        &mut self,
+        _statement: &LabeledConcurrentStatement,
+    ) -> SearchState {
+        NotFinished
+    }
+
+    /// Search a protected type body where ident_pos is the position of its name
+    fn search_protected_body(
+        &mut self,
+        _ident_pos: &SrcPos,
+        _body: &ProtectedTypeBody,
+    ) -> SearchState {
+        NotFinished
+    }
+
     fn search_with_pos(&mut self, _pos: &SrcPos) -> SearchState {
         NotFinished
     }
@@ -142,6 +187,57 @@ impl<T: Search> Search for Option<T> {
     }
 }
 
+fn search_end_ident(
+    decl_pos: &SrcPos,
+    end_pos: Option<&SrcPos>,
+    searcher: &mut impl Searcher,
+) -> SearchResult {
+    if let Some(end_pos) = end_pos {
+        return_if_found!(searcher
+            .search_end_ident_pos(decl_pos, end_pos)
+            .or_not_found());
+    }
+    NotFound
+}
+
+/// The position of a statement including its label
+trait StatementPos {
+    fn statement_pos(&self) -> SrcPos;
+}
+
+impl StatementPos for LabeledSequentialStatement {
+    fn statement_pos(&self) -> SrcPos {
+        match self.label {
+            Some(ref label) => label.pos.combine(&self.statement.pos),
+            None => self.statement.pos.clone(),
+        }
+    }
+}
+
+impl StatementPos for LabeledConcurrentStatement {
+    fn statement_pos(&self) -> SrcPos {
+        match self.label {
+            Some(ref label) => label.pos.combine(&self.statement.pos),
+            None => self.statement.pos.clone(),
+        }
+    }
+}
+
+/// Search a list of statements after the position spanning from the first to the last statement
+fn search_statements<T: Search + StatementPos>(
+    statements: &[T],
+    searcher: &mut impl Searcher,
+) -> SearchResult {
+    if let (Some(first), Some(last)) = (statements.first(), statements.last()) {
+        let pos = first.statement_pos().combine(&last.statement_pos());
+        return_if_finished!(searcher.search_with_pos(&pos));
+    }
+    for statement in statements.iter() {
+        return_if_found!(statement.search(searcher));
+    }
+    NotFound
+}
+
 fn search_conditionals<T: Search>(
     conditionals: &Conditionals<T>,
     item_before_cond: bool,
@@ -249,6 +345,8 @@ impl Search for Choice {
 
 impl Search for WithPos<Target> {
     fn search(&self, searcher: &mut impl Searcher) -> SearchResult {
+        return_if_finished!(searcher.search_with_pos(&self.pos));
+        return_if_finished!(searcher.search_assignment_target(self));
         match self.item {
             Target::Name(ref name) => search_pos_name(&self.pos, name, searcher),
             Target::Aggregate(ref assocs) => assocs.search(searcher),
@@ -258,10 +356,16 @@ impl Search for WithPos<Target> {
 
 impl Search for LabeledSequentialStatement {
     fn search(&self, searcher: &mut impl Searcher) -> SearchResult {
+        return_if_finished!(searcher.search_with_pos(&self.statement_pos()));
         if let Some(ref label) = self.label {
             return_if_found!(searcher.search_decl_pos(label.pos()).or_not_found());
+            return_if_found!(search_end_ident(
+                label.pos(),
+                self.statement.item.end_label_pos(),
+                searcher
+            ));
         }
-        match self.statement {
+        match self.statement.item {
             SequentialStatement::Return(ReturnStatement { ref expression }) => {
                 return_if_found!(expression.search(searcher));
             }
@@ -269,7 +373,18 @@ impl Search for LabeledSequentialStatement {
                 return_if_found!(pcall.search(searcher));
             }
             SequentialStatement::If(ref ifstmt) => {
-                return_if_found!(search_conditionals(ifstmt, false, searcher));
+                let Conditionals {
+                    conditionals,
+                    else_item,
+                } = &ifstmt.conds;
+                for conditional in conditionals {
+                    let Conditional { condition, item } = conditional;
+                    return_if_found!(condition.search(searcher));
+                    return_if_found!(search_statements(item, searcher));
+                }
+                if let Some(else_item) = else_item {
+                    return_if_found!(search_statements(else_item, searcher));
+                }
             }
             SequentialStatement::Wait(ref wait_stmt) => {
                 let WaitStatement {
@@ -313,6 +428,7 @@ impl Search for LabeledSequentialStatement {
                 let LoopStatement {
                     iteration_scheme,
                     statements,
+                    end_label_pos: _,
                 } = loop_stmt;
                 match iteration_scheme {
                     Some(IterationScheme::For(ref index, ref drange)) => {
@@ -320,14 +436,14 @@ impl Search for LabeledSequentialStatement {
                             .search_decl(index.pos(), FoundDeclaration::ForIndex(&index, &drange))
                             .or_not_found());
                         return_if_found!(drange.search(searcher));
-                        return_if_found!(statements.search(searcher));
+                        return_if_found!(search_statements(statements, searcher));
                     }
                     Some(IterationScheme::While(ref expr)) => {
                         return_if_found!(expr.search(searcher));
-                        return_if_found!(statements.search(searcher));
+                        return_if_found!(search_statements(statements, searcher));
                     }
                     None => {
-                        return_if_found!(statements.search(searcher));
+                        return_if_found!(search_statements(statements, searcher));
                     }
                 }
             }
@@ -367,17 +483,25 @@ impl Search for GenerateBody {
             alternative_label,
             decl,
             statements,
+            end_label_pos,
+            source_range: _,
         } = self;
         if let Some(ref label) = alternative_label {
             return_if_found!(searcher.search_decl_pos(label.pos()).or_not_found());
+            return_if_found!(search_end_ident(
+                label.pos(),
+                end_label_pos.as_ref(),
+                searcher
+            ));
         }
         return_if_found!(decl.search(searcher));
-        statements.search(searcher)
+        search_statements(statements, searcher)
     }
 }
 
 impl Search for InstantiationStatement {
     fn search(&self, searcher: &mut impl Searcher) -> SearchResult {
+        return_if_found!(searcher.search_instantiation(self).or_not_found());
         match self.unit {
             InstantiatedUnit::Entity(ref ent_name, _) => {
                 return_if_found!(ent_name.search(searcher));
@@ -407,14 +531,21 @@ impl Search for SensitivityList {
 
 impl Search for LabeledConcurrentStatement {
     fn search(&self, searcher: &mut impl Searcher) -> SearchResult {
+        return_if_finished!(searcher.search_concurrent_statement(self));
+        return_if_finished!(searcher.search_with_pos(&self.statement_pos()));
         if let Some(ref label) = self.label {
             return_if_found!(searcher.search_decl_pos(label.pos()).or_not_found());
+            return_if_found!(search_end_ident(
+                label.pos(),
+                self.statement.item.end_label_pos(),
+                searcher
+            ));
         }
-        match self.statement {
+        match self.statement.item {
             ConcurrentStatement::Block(ref block) => {
                 // @TODO guard condition
                 return_if_found!(block.decl.search(searcher));
-                block.statements.search(searcher)
+                search_statements(&block.statements, searcher)
             }
             ConcurrentStatement::Process(ref process) => {
                 let ProcessStatement {
@@ -422,16 +553,18 @@ impl Search for LabeledConcurrentStatement {
                     sensitivity_list,
                     decl,
                     statements,
+                    end_label_pos: _,
                 } = process;
                 return_if_found!(sensitivity_list.search(searcher));
                 return_if_found!(decl.search(searcher));
-                statements.search(searcher)
+                search_statements(statements, searcher)
             }
             ConcurrentStatement::ForGenerate(ref gen) => {
                 let ForGenerateStatement {
                     index_name,
                     discrete_range,
                     body,
+                    end_label_pos: _,
                 } = gen;
                 return_if_found!(searcher
                     .search_decl(
@@ -442,8 +575,12 @@ impl Search for LabeledConcurrentStatement {
                 return_if_found!(discrete_range.search(searcher));
                 body.search(searcher)
             }
-            ConcurrentStatement::IfGenerate(ref gen) => search_conditionals(gen, false, searcher),
-            ConcurrentStatement::CaseGenerate(ref gen) => search_selection(gen, false, searcher),
+            ConcurrentStatement::IfGenerate(ref gen) => {
+                search_conditionals(&gen.conds, false, searcher)
+            }
+            ConcurrentStatement::CaseGenerate(ref gen) => {
+                search_selection(&gen.sels, false, searcher)
+            }
             ConcurrentStatement::Instance(ref inst) => inst.search(searcher),
             ConcurrentStatement::Assignment(ref assign) => {
                 let ConcurrentSignalAssignment { target, rhs, .. } = assign;
@@ -652,16 +789,29 @@ impl Search for TypeDeclaration {
     fn search(&self, searcher: &mut impl Searcher) -> SearchResult {
         match self.def {
             TypeDefinition::ProtectedBody(ref body) => {
+                return_if_found!(searcher
+                    .search_protected_body(self.ident.pos(), body)
+                    .or_not_found());
                 // Protected type body is not considered a declaration
                 return_if_found!(searcher
                     .search_pos_with_ref(self.ident.pos(), &body.type_reference)
                     .or_not_found());
+                if let Some(ref end_ident_pos) = self.end_ident_pos {
+                    return_if_found!(searcher
+                        .search_pos_with_ref(end_ident_pos, &body.type_reference)
+                        .or_not_found());
+                }
                 return_if_found!(body.decl.search(searcher));
             }
             TypeDefinition::Protected(ref prot_decl) => {
                 return_if_found!(searcher
                     .search_decl(self.ident.pos(), FoundDeclaration::Type(&self))
                     .or_not_found());
+                return_if_found!(search_end_ident(
+                    self.ident.pos(),
+                    self.end_ident_pos.as_ref(),
+                    searcher
+                ));
                 for item in prot_decl.items.iter() {
                     match item {
                         ProtectedTypeDeclarativeItem::Subprogram(ref subprogram) => {
@@ -674,6 +824,11 @@ impl Search for TypeDeclaration {
                 return_if_found!(searcher
                     .search_decl(self.ident.pos(), FoundDeclaration::Type(&self))
                     .or_not_found());
+                return_if_found!(search_end_ident(
+                    self.ident.pos(),
+                    self.end_ident_pos.as_ref(),
+                    searcher
+                ));
                 for elem in element_decls {
                     return_if_found!(searcher
                         .search_decl(elem.ident.pos(), FoundDeclaration::ElementDeclaration(elem))
@@ -741,6 +896,11 @@ impl Search for TypeDeclaration {
                 return_if_found!(searcher
                     .search_decl(self.ident.pos(), FoundDeclaration::Type(&self))
                     .or_not_found());
+                return_if_found!(search_end_ident(
+                    self.ident.pos(),
+                    self.end_ident_pos.as_ref(),
+                    searcher
+                ));
             }
         }
         NotFound
@@ -809,6 +969,7 @@ impl Search for AssociationElement {
 
 impl Search for FunctionCall {
     fn search(&self, searcher: &mut impl Searcher) -> SearchResult {
+        return_if_found!(searcher.search_function_call(self).or_not_found());
         let FunctionCall { name, parameters } = self;
         return_if_found!(name.search(searcher));
         return_if_found!(parameters.search(searcher));
@@ -874,9 +1035,15 @@ impl Search for Declaration {
             }
             Declaration::Type(typ) => return_if_found!(typ.search(searcher)),
             Declaration::SubprogramBody(body) => {
+                return_if_finished!(searcher.search_subprogram_body(body));
                 return_if_found!(body.specification.search(searcher));
+                return_if_found!(search_end_ident(
+                    body.specification.pos(),
+                    body.end_ident_pos.as_ref(),
+                    searcher
+                ));
                 return_if_found!(body.declarations.search(searcher));
-                return_if_found!(body.statements.search(searcher));
+                return_if_found!(search_statements(&body.statements, searcher));
             }
             Declaration::SubprogramDeclaration(decl) => {
                 return_if_found!(decl.search(searcher));
@@ -908,10 +1075,16 @@ impl Search for Declaration {
                     ident,
                     generic_list,
                     port_list,
+                    end_ident_pos,
                 } = component;
                 return_if_found!(searcher
                     .search_decl(ident.pos(), FoundDeclaration::Component(&component))
                     .or_not_found());
+                return_if_found!(search_end_ident(
+                    ident.pos(),
+                    end_ident_pos.as_ref(),
+                    searcher
+                ));
                 return_if_found!(generic_list.search(searcher));
                 return_if_found!(port_list.search(searcher));
             }
@@ -1040,10 +1213,15 @@ impl Search for EntityDeclaration {
         return_if_found!(searcher
             .search_decl(self.ident().pos(), FoundDeclaration::Entity(&self))
             .or_not_found());
+        return_if_found!(search_end_ident(
+            self.ident().pos(),
+            self.end_ident_pos.as_ref(),
+            searcher
+        ));
         return_if_found!(self.generic_clause.search(searcher));
         return_if_found!(self.port_clause.search(searcher));
         return_if_found!(self.decl.search(searcher));
-        self.statements.search(searcher)
+        search_statements(&self.statements, searcher)
     }
 }
 
@@ -1052,8 +1230,13 @@ impl Search for ArchitectureBody {
         return_if_finished!(searcher.search_source(self.source()));
         return_if_found!(self.context_clause.search(searcher));
         return_if_found!(searcher.search_ident_ref(&self.entity_name).or_not_found());
+        return_if_found!(search_end_ident(
+            self.ident().pos(),
+            self.end_ident_pos.as_ref(),
+            searcher
+        ));
         return_if_found!(self.decl.search(searcher));
-        self.statements.search(searcher)
+        search_statements(&self.statements, searcher)
     }
 }
 
@@ -1064,6 +1247,11 @@ impl Search for PackageDeclaration {
         return_if_found!(searcher
             .search_decl(self.ident().pos(), FoundDeclaration::Package(&self))
             .or_not_found());
+        return_if_found!(search_end_ident(
+            self.ident().pos(),
+            self.end_ident_pos.as_ref(),
+            searcher
+        ));
         return_if_found!(self.generic_clause.search(searcher));
         self.decl.search(searcher)
     }
@@ -1074,6 +1262,11 @@ impl Search for PackageBody {
         return_if_finished!(searcher.search_source(self.source()));
         return_if_found!(self.context_clause.search(searcher));
         return_if_found!(searcher.search_ident_ref(&self.ident).or_not_found());
+        if let Some(ref end_ident_pos) = self.end_ident_pos {
+            return_if_found!(searcher
+                .search_pos_with_ref(end_ident_pos, &self.ident.reference)
+                .or_not_found());
+        }
         self.decl.search(searcher)
     }
 }
@@ -1096,6 +1289,11 @@ impl Search for ConfigurationDeclaration {
         return_if_found!(searcher
             .search_decl(self.ident().pos(), FoundDeclaration::Configuration(&self))
             .or_not_found());
+        return_if_found!(search_end_ident(
+            self.ident().pos(),
+            self.end_ident_pos.as_ref(),
+            searcher
+        ));
         self.entity_name.search(searcher)
     }
 }
@@ -1106,6 +1304,11 @@ impl Search for ContextDeclaration {
         return_if_found!(searcher
             .search_decl(self.ident().pos(), FoundDeclaration::Context(&self))
             .or_not_found());
+        return_if_found!(search_end_ident(
+            self.ident().pos(),
+            self.end_ident_pos.as_ref(),
+            searcher
+        ));
         self.items.search(searcher)
     }
 }
@@ -1116,9 +1319,14 @@ impl Search for CaseStatement {
             is_matching: _,
             expression,
             alternatives,
+            end_label_pos: _,
         } = self;
         return_if_found!(expression.search(searcher));
-        return_if_found!(search_alternatives(alternatives, false, searcher));
+        for alternative in alternatives.iter() {
+            let Alternative { choices, item } = alternative;
+            return_if_found!(choices.search(searcher));
+            return_if_found!(search_statements(item, searcher));
+        }
         NotFound
     }
 }
@@ -1172,6 +1380,15 @@ impl Searcher for ItemAtCursor {
         }
     }
 
+    fn search_end_ident_pos(&mut self, decl_pos: &SrcPos, end_pos: &SrcPos) -> SearchState {
+        if self.is_inside(end_pos) {
+            self.result = Some(decl_pos.clone());
+            Finished(Found)
+        } else {
+            NotFinished
+        }
+    }
+
     fn search_pos_with_ref(&mut self, pos: &SrcPos, reference: &Reference) -> SearchState {
         if !self.is_inside(pos) {
             Finished(NotFound)
@@ -1312,6 +1529,13 @@ impl Searcher for FindAllReferences {
         NotFinished
     }
 
+    fn search_end_ident_pos(&mut self, decl_pos: &SrcPos, end_pos: &SrcPos) -> SearchState {
+        if decl_pos == &self.decl_pos {
+            self.references.push(end_pos.clone());
+        }
+        NotFinished
+    }
+
     fn search_pos_with_ref(&mut self, pos: &SrcPos, reference: &Reference) -> SearchState {
         if let Some(ref_pos) = reference
             .as_ref()
@@ -1324,3 +1548,55 @@ impl Searcher for FindAllReferences {
         NotFinished
     }
 }
+
+// Search for the generic or port list of an entity or component declaration
+pub struct FindInterfaceList {
+    decl_pos: SrcPos,
+    list_type: InterfaceListType,
+    result: Option<Vec<InterfaceDeclaration>>,
+}
+
+impl FindInterfaceList {
+    pub fn new(decl_pos: &SrcPos, list_type: InterfaceListType) -> FindInterfaceList {
+        FindInterfaceList {
+            decl_pos: decl_pos.clone(),
+            list_type,
+            result: None,
+        }
+    }
+
+    pub fn search(
+        searchable: &impl Search,
+        decl_pos: &SrcPos,
+        list_type: InterfaceListType,
+    ) -> Option<Vec<InterfaceDeclaration>> {
+        let mut searcher = Self::new(decl_pos, list_type);
+        let _ = searchable.search(&mut searcher);
+        searcher.result
+    }
+}
+
+impl Searcher for FindInterfaceList {
+    fn search_decl(&mut self, pos: &SrcPos, decl: FoundDeclaration) -> SearchState {
+        if pos == &self.decl_pos {
+            self.result = match (decl, self.list_type) {
+                (FoundDeclaration::Entity(ent), InterfaceListType::Generic) => {
+                    Some(ent.generic_clause.clone().unwrap_or_default())
+                }
+                (FoundDeclaration::Entity(ent), InterfaceListType::Port) => {
+                    Some(ent.port_clause.clone().unwrap_or_default())
+                }
+                (FoundDeclaration::Component(comp), InterfaceListType::Generic) => {
+                    Some(comp.generic_list.clone())
+                }
+                (FoundDeclaration::Component(comp), InterfaceListType::Port) => {
+                    Some(comp.port_list.clone())
+                }
+                _ => None,
+            };
+            Finished(Found)
+        } else {
+            NotFinished
+        }
+    }
+}
@@ -0,0 +1,642 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2020, Olof Kraigher olof.kraigher@gmail.com
+
+//! Code generated from declarations to be inserted by the user
+
+use super::*;
+
+impl EntityDeclaration {
+    /// A named instantiation of the entity with one association per line for each generic
+    /// and port where each formal is associated with an actual of the same name
+    ///
+    /// The arrows of the associations are aligned within each map aspect.
+    pub fn instantiation_template(&self, label: &str, library_name: &Symbol) -> String {
+        let mut result = format!("{} : entity {}.{}", label, library_name, self.ident);
+        if let Some(ref generic_clause) = self.generic_clause {
+            push_map_aspect(&mut result, "generic", generic_clause);
+        }
+        if let Some(ref port_clause) = self.port_clause {
+            push_map_aspect(&mut result, "port", port_clause);
+        }
+        result.push(';');
+        result
+    }
+
+    /// An architecture of the entity with empty declarative and statement parts
+    pub fn architecture_stub(&self, name: &str) -> String {
+        format!(
+            "architecture {} of {} is\nbegin\nend architecture;",
+            name, self.ident
+        )
+    }
+
+    /// A testbench entity with the name of the entity suffixed by `_tb` and an architecture
+    /// instantiating the entity of the library as `dut`
+    ///
+    /// The testbench has a library clause for the library of the entity, the context clause
+    /// of the entity with work replaced by the library of the entity, a constant for each generic object with its default value, a signal for
+    /// each port, a clock process and a stimulus process. The clock process drives the port of
+    /// mode in named clk or clock or a signal clk of type bit when there is no such port.
+    /// Generics without a default value, generic types, subprograms and packages and ports of
+    /// an unconstrained array type, whose index subtypes and their directions are given by
+    /// `index_subtypes`, get a placeholder preceded by a TODO comment.
+    pub fn testbench(
+        &self,
+        library_name: &Symbol,
+        index_subtypes: impl Fn(&SubtypeIndication) -> Option<Vec<(String, Direction)>>,
+    ) -> String {
+        let generics = self.generic_clause.as_deref().unwrap_or(&[]);
+        let ports = interface_objects(&self.port_clause);
+        let name = format!("{}_tb", self.ident);
+
+        let mut result = String::new();
+        let has_library_clause = self.context_clause.iter().any(|item| match item.item {
+            ContextItem::Library(ref clause) => clause
+                .name_list
+                .iter()
+                .any(|name| &name.item == library_name),
+            _ => false,
+        });
+        if !has_library_clause {
+            result.push_str(&format!("library {};\n", library_name));
+        }
+        for item in self.context_clause.iter() {
+            let mut item = item.item.clone();
+            match item {
+                ContextItem::Use(UseClause { ref mut name_list })
+                | ContextItem::Context(ContextReference { ref mut name_list }) => {
+                    for name in name_list.iter_mut() {
+                        replace_work(&mut name.item, library_name);
+                    }
+                }
+                ContextItem::Library(..) => {}
+            }
+            result.push_str(&format!("{}\n", item));
+        }
+        result.push('\n');
+
+        result.push_str(&format!("entity {} is\nend entity;\n\n", name));
+        result.push_str(&format!("architecture tb of {} is\n", name));
+        for generic in generics.iter() {
+            match generic {
+                InterfaceDeclaration::Object(ref generic) => {
+                    let default = match generic.expression {
+                        Some(ref expr) => expr.to_string(),
+                        None => {
+                            result.push_str("  -- TODO: value of the generic\n");
+                            format!("{}'left", generic.subtype_indication.type_mark)
+                        }
+                    };
+                    result.push_str(&format!(
+                        "  constant {} : {} := {};\n",
+                        generic.ident, generic.subtype_indication, default
+                    ));
+                }
+                InterfaceDeclaration::Type(ref ident) => {
+                    result.push_str("  -- TODO: type of the generic type\n");
+                    result.push_str(&format!("  subtype {} is integer;\n", ident));
+                }
+                InterfaceDeclaration::Subprogram(ref decl, _) => {
+                    result.push_str("  -- TODO: implementation of the generic subprogram\n");
+                    for line in decl.body_stub().lines() {
+                        result.push_str(&format!("  {}\n", line));
+                    }
+                }
+                InterfaceDeclaration::Package(ref package) => {
+                    result.push_str("  -- TODO: generic map of the generic package\n");
+                    result.push_str(&format!(
+                        "  package {} is new {};\n",
+                        package.ident, package.package_name
+                    ));
+                }
+                // A file cannot be a generic of an entity
+                InterfaceDeclaration::File(..) => {}
+            }
+        }
+        if !generics.is_empty() {
+            result.push('\n');
+        }
+
+        let is_clock = |name: &str| {
+            let name = name.to_lowercase();
+            name == "clk" || name == "clock"
+        };
+        let clock = ports
+            .iter()
+            .filter(|port| port.mode == Mode::In)
+            .find(|port| is_clock(&port.ident.item.name_utf8()));
+
+        // A signal, its subtype and whether the subtype is a placeholder
+        let mut signals: Vec<(String, String, bool)> = ports
+            .iter()
+            .map(|port| {
+                let (subtype, is_placeholder) = match index_subtypes(&port.subtype_indication) {
+                    Some(index_subtypes) => {
+                        // A single index of the index subtype in the direction of its range
+                        let ranges: Vec<String> = index_subtypes
+                            .iter()
+                            .map(|(index, direction)| match direction {
+                                Direction::Ascending => format!("{}'low to {}'low", index, index),
+                                Direction::Descending => {
+                                    format!("{}'high downto {}'high", index, index)
+                                }
+                            })
+                            .collect();
+                        (
+                            format!("{}({})", port.subtype_indication, ranges.join(", ")),
+                            true,
+                        )
+                    }
+                    None => (port.subtype_indication.to_string(), false),
+                };
+                let subtype = match port.expression {
+                    Some(ref expr) => format!("{} := {}", subtype, expr),
+                    None => subtype,
+                };
+                (port.ident.to_string(), subtype, is_placeholder)
+            })
+            .collect();
+        let (clock, values) = match clock {
+            Some(clock) => (
+                clock.ident.item.name_utf8(),
+                clock_values(&clock.subtype_indication.type_mark),
+            ),
+            None => {
+                // A port named clk which is not of mode in is not driven by the testbench
+                let clock = if ports
+                    .iter()
+                    .any(|port| port.ident.item.name_utf8().eq_ignore_ascii_case("clk"))
+                {
+                    "tb_clk"
+                } else {
+                    "clk"
+                };
+                signals.push((clock.to_owned(), "bit".to_owned(), false));
+                (
+                    clock.to_owned(),
+                    ("'0'".to_owned(), "'1'".to_owned(), false),
+                )
+            }
+        };
+
+        let width = signals
+            .iter()
+            .map(|(name, ..)| name.chars().count())
+            .max()
+            .unwrap_or(0);
+        for (signal, subtype, is_placeholder) in signals.iter() {
+            if *is_placeholder {
+                result.push_str("  -- TODO: constraint of the unconstrained port\n");
+            }
+            result.push_str(&format!(
+                "  signal {:width$} : {};\n",
+                signal,
+                subtype,
+                width = width
+            ));
+        }
+        result.push_str("\n  constant clk_period : time := 10 ns;\n");
+        result.push_str("begin\n");
+
+        for line in self.instantiation_template("dut", library_name).lines() {
+            result.push_str(&format!("  {}\n", line));
+        }
+
+        let (low, high, is_placeholder) = values;
+        let todo = if is_placeholder {
+            "    -- TODO: values of the clock\n"
+        } else {
+            ""
+        };
+        result.push_str(&format!(
+            "
+  clock : process
+  begin
+{todo}    {clock} <= {low};
+    wait for clk_period / 2;
+    {clock} <= {high};
+    wait for clk_period / 2;
+  end process;
+
+  stimulus : process
+  begin
+    wait for 10 * clk_period;
+    wait;
+  end process;
+end architecture;",
+            todo = todo,
+            clock = clock,
+            low = low,
+            high = high
+        ));
+        result
+    }
+
+    /// A component declaration with the generics and ports of the entity
+    pub fn component_declaration(&self) -> ComponentDeclaration {
+        ComponentDeclaration {
+            ident: self.ident.clone(),
+            generic_list: self.generic_clause.clone().unwrap_or_default(),
+            port_list: self.port_clause.clone().unwrap_or_default(),
+            end_ident_pos: None,
+        }
+    }
+}
+
+impl SubprogramDeclaration {
+    /// A subprogram body with the specification of the declaration and empty declarative
+    /// and statement parts
+    pub fn body_stub(&self) -> String {
+        let kind = match self {
+            SubprogramDeclaration::Procedure(..) => "procedure",
+            SubprogramDeclaration::Function(..) => "function",
+        };
+        format!("{} is\nbegin\nend {};", self, kind)
+    }
+}
+
+impl ObjectDeclaration {
+    /// The full constant declaration of a deferred constant with a placeholder value
+    /// preceded by a comment reminding the user to write the value
+    pub fn full_constant_stub(&self) -> String {
+        format!(
+            "-- TODO: value of the deferred constant\nconstant {} : {} := {}'left;",
+            self.ident, self.subtype_indication, self.subtype_indication.type_mark
+        )
+    }
+}
+
+impl TypeDeclaration {
+    /// A protected type body with a body stub of each method of a protected type
+    pub fn protected_body_stub(&self) -> Option<String> {
+        let items = match self.def {
+            TypeDefinition::Protected(ref protected) => &protected.items,
+            _ => return None,
+        };
+
+        let mut result = format!("type {} is protected body\n", self.ident);
+        for (i, item) in items.iter().enumerate() {
+            let ProtectedTypeDeclarativeItem::Subprogram(ref subprogram) = item;
+            if i > 0 {
+                result.push('\n');
+            }
+            for line in subprogram.body_stub().lines() {
+                result.push_str(&format!("  {}\n", line));
+            }
+        }
+        result.push_str("end protected body;");
+        Some(result)
+    }
+}
+
+/// Replace the library name work by the library name since the testbench may be analyzed
+/// into another library
+fn replace_work(name: &mut Name, library_name: &Symbol) {
+    match name {
+        Name::Designator(ref mut designator) => {
+            if let Designator::Identifier(ref symbol) = designator.item {
+                if symbol.name_utf8().eq_ignore_ascii_case("work") {
+                    designator.item = Designator::Identifier(library_name.clone());
+                }
+            }
+        }
+        Name::Selected(ref mut prefix, _) | Name::SelectedAll(ref mut prefix) => {
+            replace_work(&mut prefix.item, library_name)
+        }
+        _ => {}
+    }
+}
+
+/// The values alternately driven onto a clock of the type and whether they are placeholders
+fn clock_values(type_mark: &WithPos<SelectedName>) -> (String, String, bool) {
+    let designator = match type_mark.item {
+        SelectedName::Designator(ref designator) => designator,
+        SelectedName::Selected(_, ref suffix) => &suffix.item,
+    };
+    let name = designator.item.to_string().to_lowercase();
+    match name.as_str() {
+        "bit" | "std_logic" | "std_ulogic" => ("'0'".to_owned(), "'1'".to_owned(), false),
+        "boolean" => ("false".to_owned(), "true".to_owned(), false),
+        _ => (
+            format!("{}'low", type_mark),
+            format!("{}'high", type_mark),
+            true,
+        ),
+    }
+}
+
+fn interface_objects(list: &Option<Vec<InterfaceDeclaration>>) -> Vec<&InterfaceObjectDeclaration> {
+    list.iter()
+        .flatten()
+        .filter_map(|decl| match decl {
+            InterfaceDeclaration::Object(ref object) => Some(object),
+            _ => None,
+        })
+        .collect()
+}
+
+fn push_map_aspect(result: &mut String, kind: &str, list: &[InterfaceDeclaration]) {
+    if list.is_empty() {
+        return;
+    }
+
+    let formals: Vec<_> = list
+        .iter()
+        .map(|decl| decl.designator().to_string())
+        .collect();
+    let width = formals
+        .iter()
+        .map(|formal| formal.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    result.push_str(&format!("\n  {} map (", kind));
+    for (i, formal) in formals.iter().enumerate() {
+        let separator = if i + 1 < formals.len() { "," } else { "" };
+        result.push_str(&format!(
+            "\n    {:width$} => {}{}",
+            formal,
+            formal,
+            separator,
+            width = width
+        ));
+    }
+    result.push_str("\n  )");
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ast::{AnyDesignUnit, AnyPrimaryUnit};
+    use crate::syntax::test::Code;
+
+    #[test]
+    fn instantiation_template_aligns_generics_and_ports() {
+        let code = Code::new(
+            "\
+entity dma is
+  generic (
+    width : natural := 8;
+    g : boolean
+  );
+  port (
+    clk : in bit;
+    data_out : out bit_vector(width-1 downto 0)
+  );
+end entity;",
+        );
+        let entity = code.entity_decl();
+        let library_name = code.symbol("lib");
+
+        assert_eq!(
+            entity.instantiation_template("dma_inst", &library_name),
+            "\
+dma_inst : entity lib.dma
+  generic map (
+    width => width,
+    g     => g
+  )
+  port map (
+    clk      => clk,
+    data_out => data_out
+  );"
+        );
+    }
+
+    #[test]
+    fn instantiation_template_without_generics_and_ports() {
+        let code = Code::new(
+            "\
+entity ent is
+  port (
+  );
+end entity;",
+        );
+        let entity = code.entity_decl();
+        let library_name = code.symbol("work");
+
+        assert_eq!(
+            entity.instantiation_template("ent_inst", &library_name),
+            "ent_inst : entity work.ent;"
+        );
+    }
+
+    #[test]
+    fn testbench_of_entity() {
+        let code = Code::new(
+            "\
+entity dma is
+  generic (
+    width : natural := 8
+  );
+  port (
+    clk : in bit;
+    enable : in bit := '1';
+    data_out : out bit_vector(width-1 downto 0)
+  );
+end entity;",
+        );
+        let library_name = code.symbol("lib");
+
+        assert_eq!(
+            code.entity_decl().testbench(&library_name, |_| None),
+            "\
+library lib;
+
+entity dma_tb is
+end entity;
+
+architecture tb of dma_tb is
+  constant width : natural := 8;
+
+  signal clk      : bit;
+  signal enable   : bit := '1';
+  signal data_out : bit_vector(width - 1 downto 0);
+
+  constant clk_period : time := 10 ns;
+begin
+  dut : entity lib.dma
+    generic map (
+      width => width
+    )
+    port map (
+      clk      => clk,
+      enable   => enable,
+      data_out => data_out
+    );
+
+  clock : process
+  begin
+    clk <= '0';
+    wait for clk_period / 2;
+    clk <= '1';
+    wait for clk_period / 2;
+  end process;
+
+  stimulus : process
+  begin
+    wait for 10 * clk_period;
+    wait;
+  end process;
+end architecture;"
+        );
+    }
+
+    #[test]
+    fn testbench_of_entity_with_generic_type_subprogram_and_package() {
+        let code = Code::new(
+            "\
+library lib;
+use work.pkg.all;
+
+entity fifo is
+  generic (
+    type element_t;
+    function to_string(value : element_t) return string;
+    package queue_pkg is new lib.queue generic map (<>)
+  );
+  port (
+    clock : in boolean
+  );
+end entity;",
+        );
+        let library_name = code.symbol("lib");
+        let entity = match code.design_file().design_units.pop() {
+            Some(AnyDesignUnit::Primary(AnyPrimaryUnit::Entity(entity))) => entity,
+            _ => panic!("Expected an entity"),
+        };
+
+        assert_eq!(
+            entity.testbench(&library_name, |_| None),
+            "\
+library lib;
+use lib.pkg.all;
+
+entity fifo_tb is
+end entity;
+
+architecture tb of fifo_tb is
+  -- TODO: type of the generic type
+  subtype element_t is integer;
+  -- TODO: implementation of the generic subprogram
+  function to_string(
+    constant value : in element_t
+  ) return string is
+  begin
+  end function;
+  -- TODO: generic map of the generic package
+  package queue_pkg is new lib.queue;
+
+  signal clock : boolean;
+
+  constant clk_period : time := 10 ns;
+begin
+  dut : entity lib.fifo
+    generic map (
+      element_t => element_t,
+      to_string => to_string,
+      queue_pkg => queue_pkg
+    )
+    port map (
+      clock => clock
+    );
+
+  clock : process
+  begin
+    clock <= false;
+    wait for clk_period / 2;
+    clock <= true;
+    wait for clk_period / 2;
+  end process;
+
+  stimulus : process
+  begin
+    wait for 10 * clk_period;
+    wait;
+  end process;
+end architecture;"
+        );
+    }
+
+    #[test]
+    fn testbench_drives_clock_of_unknown_type_with_placeholders() {
+        let code = Code::new(
+            "\
+entity ent is
+  port (
+    clk : in clk_t
+  );
+end entity;",
+        );
+        let library_name = code.symbol("lib");
+
+        assert!(code
+            .entity_decl()
+            .testbench(&library_name, |_| None)
+            .contains(
+                "    -- TODO: values of the clock
+    clk <= clk_t'low;
+    wait for clk_period / 2;
+    clk <= clk_t'high;"
+            ));
+    }
+
+    #[test]
+    fn protected_body_stub_with_method_body_stubs() {
+        let code = Code::new(
+            "\
+type counter_t is protected
+  procedure increment(amount : natural);
+  impure function value return natural;
+end protected;",
+        );
+
+        assert_eq!(
+            code.type_decl().protected_body_stub().unwrap(),
+            "\
+type counter_t is protected body
+  procedure increment(
+    constant amount : in natural
+  ) is
+  begin
+  end procedure;
+
+  impure function value return natural is
+  begin
+  end function;
+end protected body;"
+        );
+    }
+
+    #[test]
+    fn component_declaration_of_entity() {
+        let code = Code::new(
+            "\
+entity ent is
+  generic (
+    width : natural := 8
+  );
+  port (
+    clk : in bit;
+    data : out bit_vector(width-1 downto 0)
+  );
+end entity;",
+        );
+
+        assert_eq!(
+            code.entity_decl().component_declaration().to_string(),
+            "\
+component ent
+  generic (
+    width : natural := 8
+  );
+  port (
+    clk : in bit;
+    data : out bit_vector(width - 1 downto 0)
+  );
+end component;"
+        );
+    }
+}
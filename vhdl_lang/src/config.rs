@@ -25,6 +25,8 @@ pub struct Config {
 pub struct LibraryConfig {
     name: String,
     patterns: Vec<String>,
+    /// The library is installed rather than part of the user design and may not be modified
+    is_read_only: bool,
 }
 
 impl LibraryConfig {
@@ -122,6 +124,10 @@ impl LibraryConfig {
     pub fn name(&self) -> &str {
         self.name.as_str()
     }
+
+    pub fn is_read_only(&self) -> bool {
+        self.is_read_only
+    }
 }
 
 impl Config {
@@ -156,11 +162,19 @@ impl Config {
                 patterns.push(path);
             }
 
+            let is_read_only = match lib.get("read_only") {
+                Some(read_only) => read_only
+                    .as_bool()
+                    .ok_or_else(|| format!("read_only for library {} is not a boolean", name))?,
+                None => false,
+            };
+
             libraries.insert(
                 name.to_owned(),
                 LibraryConfig {
                     name: name.to_owned(),
                     patterns,
+                    is_read_only,
                 },
             );
         }
@@ -204,6 +218,7 @@ impl Config {
                     LibraryConfig {
                         name: library.name.clone(),
                         patterns: library.patterns.clone(),
+                        is_read_only: library.is_read_only,
                     },
                 );
             }
@@ -365,6 +380,7 @@ lib1.files = [
   'pkg1.vhd',
   'tb_ent.vhd'
 ]
+lib1.read_only = true
 ",
                 absolute_vhd.to_str().unwrap()
             ),
@@ -377,6 +393,8 @@ lib1.files = [
 
         let lib1 = config.get_library("lib1").unwrap();
         let lib2 = config.get_library("lib2").unwrap();
+        assert!(lib1.is_read_only());
+        assert!(!lib2.is_read_only());
 
         let pkg1_path = touch(&parent, "pkg1.vhd");
         let pkg2_path = touch(&parent, "pkg2.vhd");
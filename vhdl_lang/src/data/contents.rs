@@ -11,6 +11,7 @@ use std::io;
 use std::io::prelude::Read;
 use std::path::Path;
 
+#[derive(PartialEq)]
 pub struct Contents {
     lines: Vec<String>,
 }
@@ -307,7 +308,6 @@ impl<'a> ContentReader<'a> {
         self.state.pos()
     }
 
-    #[cfg(test)]
     pub fn seek_pos(&mut self, pos: Position) {
         self.state = ReaderState {
             pos: Position {
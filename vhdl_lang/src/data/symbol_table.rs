@@ -108,6 +108,18 @@ impl SymbolTable {
         }
     }
 
+    /// Looks up a basic identifier regardless of its case without inserting it.
+    ///
+    /// Returns `None` if no equivalent identifier exists.
+    pub fn lookup_utf8(&self, name: &str) -> Option<Symbol> {
+        let name = Latin1String::from_utf8(name).ok()?;
+        if name.bytes.first() == Some(&b'\\') {
+            return self.lookup(&name);
+        }
+        self.lookup(&name)
+            .or_else(|| self.lookup(&name.to_lowercase()))
+    }
+
     /// Inserts a basic identifier and returns a corresponding `Symbol` instance.
     pub fn insert(&self, name: &Latin1String) -> Symbol {
         if let Some(symbol) = self.lookup(name) {
@@ -232,6 +244,15 @@ mod tests {
         assert_eq!(sym2.name_utf8(), "\\hello\\");
     }
 
+    #[test]
+    fn lookup_ignores_case_and_does_not_insert() {
+        let symtab = SymbolTable::default();
+        let sym0 = symtab.insert_utf8("hello");
+        assert_eq!(symtab.lookup_utf8("HeLLo"), Some(sym0));
+        assert_eq!(symtab.lookup_utf8("abc"), None);
+        assert_eq!(symtab.lookup_utf8("abc"), None);
+    }
+
     #[test]
     fn symbols_are_not_equal() {
         let symtab = SymbolTable::default();
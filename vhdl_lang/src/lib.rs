@@ -13,6 +13,12 @@ mod data;
 mod project;
 mod syntax;
 
+pub use crate::analysis::{
+    AssociationRewrite, AssociationStyle, CallItem, CallItemKind, CallSignature, CallSignatures,
+    Calls, CodeLens, EntitySkeletons, HierarchyNode, InlayHint, InlayHintKind,
+    InstantiationTemplate, Occurrence, OccurrenceKind, PackageBodyStub, PortMapFix, PortMapFixKind,
+    SemanticToken, SemanticTokenKind, UseClauseFix, WorkspaceSymbol, WorkspaceSymbolKind,
+};
 pub use crate::config::Config;
 pub use crate::data::{
     Diagnostic, Latin1String, Message, MessageHandler, MessagePrinter, MessageType, Position,
@@ -20,4 +26,4 @@ pub use crate::data::{
 };
 
 pub use crate::project::{Project, SourceFile};
-pub use crate::syntax::{ParserResult, VHDLParser};
+pub use crate::syntax::{FoldingRange, FoldingRangeKind, ParserResult, VHDLParser};
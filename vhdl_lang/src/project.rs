@@ -4,20 +4,29 @@
 //
 // Copyright (c) 2018, Olof Kraigher olof.kraigher@gmail.com
 
-use crate::analysis::DesignRoot;
-use crate::ast::DesignFile;
+use crate::analysis::{
+    AssociationRewrite, CallItem, CallSignatures, Calls, CodeLens, DesignRoot, EntitySkeletons,
+    HierarchyNode, InlayHint, InstantiationTemplate, Occurrence, PackageBodyStub, PortMapFix,
+    SemanticToken, UseClauseFix, WorkspaceSymbol,
+};
+use crate::ast::{DesignFile, NamedEntity};
 use crate::config::Config;
 use crate::data::*;
-use crate::syntax::VHDLParser;
+use crate::syntax::{FoldingRange, VHDLParser};
 use fnv::{FnvHashMap, FnvHashSet};
 use std::collections::hash_map::Entry;
+use std::io;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
 
+#[derive(Clone)]
 pub struct Project {
     parser: VHDLParser,
     root: DesignRoot,
     files: FnvHashMap<PathBuf, SourceFile>,
     empty_libraries: FnvHashSet<Symbol>,
+    read_only_libraries: FnvHashSet<Symbol>,
 }
 
 impl Project {
@@ -27,6 +36,7 @@ impl Project {
             root: DesignRoot::new(parser.symbols.clone()),
             files: FnvHashMap::default(),
             empty_libraries: FnvHashSet::default(),
+            read_only_libraries: FnvHashSet::default(),
             parser,
         }
     }
@@ -66,6 +76,7 @@ impl Project {
             if let Some(source_file) = self.files.get_mut(&file_name) {
                 source_file.parser_diagnostics.clear();
                 source_file.library_names = library_names;
+                source_file.is_mapped = true;
                 source_file.design_file = self
                     .parser
                     .parse_design_source(&source_file.source, &mut source_file.parser_diagnostics);
@@ -82,11 +93,15 @@ impl Project {
     ) -> FnvHashMap<PathBuf, FnvHashSet<Symbol>> {
         let mut files: FnvHashMap<PathBuf, FnvHashSet<Symbol>> = FnvHashMap::default();
         self.empty_libraries.clear();
+        self.read_only_libraries.clear();
 
         for library in config.iter_libraries() {
             let library_name =
                 Latin1String::from_utf8(library.name()).expect("Library name not latin-1 encoded");
             let library_name = self.parser.symbol(&library_name);
+            if library.is_read_only() {
+                self.read_only_libraries.insert(library_name.clone());
+            }
 
             let mut empty_library = true;
             for file_name in library.file_names(messages) {
@@ -146,6 +161,8 @@ impl Project {
                     library_names,
                     parser_diagnostics,
                     design_file,
+                    is_changed: false,
+                    is_mapped: true,
                 },
             );
         }
@@ -155,14 +172,25 @@ impl Project {
         self.files.get(file_name).map(|file| file.source.clone())
     }
 
+    /// Parse the current contents of the source without analysis
+    /// Parse errors are ignored and only the successfully parsed design units are returned
+    pub fn parse_source(&self, source: &Source) -> DesignFile {
+        let mut diagnostics = Vec::new();
+        self.parser.parse_design_source(source, &mut diagnostics)
+    }
+
+    /// Find the ranges of lines within the source which may be folded
+    pub fn folding_ranges(&self, source: &Source) -> Vec<FoldingRange> {
+        self.parser.folding_ranges(source)
+    }
+
     pub fn update_source(&mut self, source: &Source) {
         let mut source_file = {
             if let Some(mut source_file) = self.files.remove(source.file_name()) {
                 // File is already part of the project
-                for library_name in source_file.library_names.iter() {
-                    self.root.remove_source(library_name.clone(), source);
-                }
+                // The design units of the file are replaced when preparing the next analysis
                 source_file.source = source.clone();
+                source_file.is_changed = true;
                 source_file
             } else {
                 // File is not part of the project
@@ -178,6 +206,8 @@ impl Project {
                     library_names,
                     parser_diagnostics: vec![],
                     design_file: DesignFile::default(),
+                    is_changed: false,
+                    is_mapped: false,
                 }
             }
         };
@@ -189,10 +219,62 @@ impl Project {
             .insert(source.file_name().to_owned(), source_file);
     }
 
+    /// Replace the contents of a source of the project by the contents of its file
+    /// The source is not updated when the contents are the same, other files are ignored
+    pub fn reload_source(&mut self, file_name: &Path) -> io::Result<()> {
+        let current = match self.get_source(file_name) {
+            Some(source) => source,
+            None => return Ok(()),
+        };
+
+        let source = Source::from_latin1_file(file_name)?;
+        if *source.contents() != *current.contents() {
+            self.update_source(&source);
+        }
+        Ok(())
+    }
+
+    /// Remove a source which was added by `update_source` without being part of the configuration
+    /// Returns false when the file is part of the configuration and was kept
+    pub fn remove_unmapped_source(&mut self, file_name: &Path) -> bool {
+        match self.files.get(file_name) {
+            Some(source_file) if !source_file.is_mapped => {}
+            _ => return false,
+        }
+
+        if let Some(source_file) = self.files.remove(file_name) {
+            for library_name in source_file.library_names.iter() {
+                self.root
+                    .remove_source(library_name.clone(), &source_file.source);
+            }
+        }
+        true
+    }
+
     pub fn analyse(&mut self) -> Vec<Diagnostic> {
+        let mut diagnostics = self.prepare_analysis();
+        if let Some(analysis_diagnostics) = self.analyse_prepared(&AtomicBool::new(false)) {
+            diagnostics.extend(analysis_diagnostics);
+        }
+        diagnostics
+    }
+
+    /// Replace the design units of the sources updated since the last analysis
+    /// and reset the analysis of the design units affected by the changes
+    ///
+    /// Returns the parser diagnostics and the diagnostics of duplicate design units.
+    /// Until the analysis is prepared the project is searched as of the last analysis.
+    pub fn prepare_analysis(&mut self) -> Vec<Diagnostic> {
         let mut diagnostics = Vec::new();
 
         for source_file in self.files.values_mut() {
+            if std::mem::take(&mut source_file.is_changed) {
+                for library_name in source_file.library_names.iter() {
+                    self.root
+                        .remove_source(library_name.clone(), &source_file.source);
+                }
+            }
+
             let design_file = source_file.take_design_file();
             // Avoid cloning design files for single library
             let mut design_files = multiply(design_file, source_file.library_names.len());
@@ -211,10 +293,24 @@ impl Project {
             self.root.ensure_library(library_name.clone());
         }
 
-        self.root.analyze(&mut diagnostics);
+        self.root.prepare_analysis(&mut diagnostics);
         diagnostics
     }
 
+    /// Analyse the design units prepared by `prepare_analysis`
+    ///
+    /// Only a shared reference is needed such that the project may be searched while the
+    /// analysis runs on another thread. The analysis stops early and returns None when
+    /// cancelled is set, the design units analysed until then keep their result.
+    pub fn analyse_prepared(&self, cancelled: &AtomicBool) -> Option<Vec<Diagnostic>> {
+        let mut diagnostics = Vec::new();
+        if self.root.analyze_prepared(cancelled, &mut diagnostics) {
+            Some(diagnostics)
+        } else {
+            None
+        }
+    }
+
     /// Search for reference at position
     /// Character offset on a line in a document (zero-based). Assuming that the line is
     /// represented as a string, the `character` value represents the gap between the
@@ -236,6 +332,220 @@ impl Project {
         self.root.find_all_references(decl_pos)
     }
 
+    /// Find all occurrences within the source of the declaration referenced at the cursor
+    pub fn find_occurrences(&self, source: &Source, cursor: Position) -> Vec<Occurrence> {
+        match self.search_reference(source, cursor) {
+            Some(decl_pos) => self.root.find_occurrences(source, &decl_pos),
+            None => Vec::new(),
+        }
+    }
+
+    /// Find the implementations of the declaration referenced at the cursor
+    pub fn find_implementation(&self, source: &Source, cursor: Position) -> Vec<SrcPos> {
+        match self.search_reference(source, cursor) {
+            Some(decl_pos) => self.root.find_implementation(&decl_pos),
+            None => Vec::new(),
+        }
+    }
+
+    /// Find the declarations of the type mark and base type of the object referenced at the cursor
+    pub fn find_type_definition(&self, source: &Source, cursor: Position) -> Vec<SrcPos> {
+        match self.search_reference(source, cursor) {
+            Some(decl_pos) => self.root.find_type_definition(&decl_pos),
+            None => Vec::new(),
+        }
+    }
+
+    /// Find the definition of the declaration referenced at the cursor
+    pub fn find_definition(&self, source: &Source, cursor: Position) -> Option<SrcPos> {
+        self.search_reference(source, cursor)
+            .map(|decl_pos| self.root.find_definition(&decl_pos))
+    }
+
+    /// Find the identifier at the cursor which would be replaced when renaming
+    /// An error message is returned when there is nothing at the cursor which may be renamed
+    pub fn prepare_rename(&self, source: &Source, cursor: Position) -> Result<SrcPos, String> {
+        let decl_pos = self.search_renameable_declaration(source, cursor)?;
+        self.find_all_references(&decl_pos)
+            .into_iter()
+            .find(|pos| pos.source == *source && pos.start() <= cursor && cursor <= pos.end())
+            .ok_or_else(|| "No identifier to rename at the cursor".to_owned())
+    }
+
+    /// Find the positions of all identifiers to replace by the new name when renaming
+    /// the declaration referenced at the cursor. This includes repeated end identifiers.
+    pub fn rename(
+        &self,
+        source: &Source,
+        cursor: Position,
+        new_name: &str,
+    ) -> Result<Vec<SrcPos>, String> {
+        if !VHDLParser::is_identifier(new_name) {
+            return Err(format!("'{}' is not a valid identifier", new_name));
+        }
+        let decl_pos = self.search_renameable_declaration(source, cursor)?;
+        Ok(self.find_all_references(&decl_pos))
+    }
+
+    fn search_renameable_declaration(
+        &self,
+        source: &Source,
+        cursor: Position,
+    ) -> Result<SrcPos, String> {
+        let decl_pos = self
+            .search_reference(source, cursor)
+            .ok_or_else(|| "No identifier to rename at the cursor".to_owned())?;
+
+        if let Some(library_name) = self.read_only_library_of(&decl_pos) {
+            return Err(format!(
+                "Cannot rename a declaration within the read-only library '{}'",
+                library_name
+            ));
+        }
+
+        Ok(decl_pos)
+    }
+
+    /// Libraries configured as read-only are not part of the user design and may not be modified
+    fn read_only_library_of(&self, pos: &SrcPos) -> Option<&Symbol> {
+        let source_file = self.files.get(pos.file_name())?;
+        source_file
+            .library_names
+            .iter()
+            .find(|library_name| self.read_only_libraries.contains(*library_name))
+    }
+
+    /// List the named entities which may complete the identifier at the cursor
+    pub fn list_completion_options(
+        &self,
+        source: &Source,
+        cursor: Position,
+    ) -> Vec<Arc<NamedEntity>> {
+        self.root.list_completion_options(source, cursor)
+    }
+
+    /// Find the candidate signatures of the subprogram call or port/generic map enclosing the cursor
+    pub fn find_call_signatures(
+        &self,
+        source: &Source,
+        cursor: Position,
+    ) -> Option<CallSignatures> {
+        self.root.find_call_signatures(source, cursor)
+    }
+
+    /// Classify the identifiers within the source by the named entity they declare or refer to
+    pub fn find_semantic_tokens(&self, source: &Source) -> Vec<SemanticToken> {
+        self.root.find_semantic_tokens(source)
+    }
+
+    /// Get the call hierarchy item of the subprogram referenced at the cursor
+    pub fn prepare_call_hierarchy(&self, source: &Source, cursor: Position) -> Option<CallItem> {
+        let decl_pos = self.search_reference(source, cursor)?;
+        self.root.prepare_call_hierarchy(&decl_pos)
+    }
+
+    /// Find the callers of the subprogram referenced at the cursor
+    pub fn find_incoming_calls(&self, source: &Source, cursor: Position) -> Vec<Calls> {
+        match self.search_reference(source, cursor) {
+            Some(decl_pos) => self.root.find_incoming_calls(&decl_pos),
+            None => Vec::new(),
+        }
+    }
+
+    /// Find the subprograms called by the subprogram referenced at the cursor
+    pub fn find_outgoing_calls(&self, source: &Source, cursor: Position) -> Vec<Calls> {
+        match self.search_reference(source, cursor) {
+            Some(decl_pos) => self.root.find_outgoing_calls(&decl_pos),
+            None => Vec::new(),
+        }
+    }
+
+    /// Find the hints of formals and implicit subtypes to show inline within the source
+    pub fn find_inlay_hints(&self, source: &Source) -> Vec<InlayHint> {
+        self.root.find_inlay_hints(source)
+    }
+
+    /// Find the library and use clauses which would make the unresolved name at the cursor visible
+    pub fn find_use_clause_fixes(&self, source: &Source, cursor: Position) -> Vec<UseClauseFix> {
+        self.root.find_use_clause_fixes(source, cursor)
+    }
+
+    /// Find the nested ranges enclosing the cursor ordered from the innermost to the outermost
+    pub fn find_selection_ranges(&self, source: &Source, cursor: Position) -> Vec<SrcPos> {
+        self.root.find_selection_ranges(source, cursor)
+    }
+
+    /// Find the name and the name repeated after `end` when the cursor is within either of them
+    pub fn find_linked_editing_ranges(&self, source: &Source, cursor: Position) -> Vec<SrcPos> {
+        self.root.find_linked_editing_ranges(source, cursor)
+    }
+
+    /// Find the rewrites between positional and named associations of the innermost port map,
+    /// generic map or subprogram call enclosing the cursor
+    pub fn find_association_rewrites(
+        &self,
+        source: &Source,
+        cursor: Position,
+    ) -> Vec<AssociationRewrite> {
+        self.root.find_association_rewrites(source, cursor)
+    }
+
+    /// Find the architecture and testbench skeletons of the entity declaration
+    /// enclosing the cursor
+    pub fn find_entity_skeletons(
+        &self,
+        source: &Source,
+        cursor: Position,
+    ) -> Option<EntitySkeletons> {
+        self.root.find_entity_skeletons(source, cursor)
+    }
+
+    /// Find the missing declarations of the package body of the package declaration
+    /// enclosing the cursor
+    pub fn find_package_body_stub(
+        &self,
+        source: &Source,
+        cursor: Position,
+    ) -> Option<PackageBodyStub> {
+        self.root.find_package_body_stub(source, cursor)
+    }
+
+    /// Find the fixes of the port map of the instance at the cursor
+    pub fn find_port_map_fixes(&self, source: &Source, cursor: Position) -> Vec<PortMapFix> {
+        self.root.find_port_map_fixes(source, cursor)
+    }
+
+    /// Find the instantiation template of the entity named by the statement at the cursor
+    pub fn find_instantiation_template(
+        &self,
+        source: &Source,
+        cursor: Position,
+    ) -> Option<InstantiationTemplate> {
+        self.root.find_instantiation_template(source, cursor)
+    }
+
+    /// Find the usage summaries shown above the entities, packages and subprograms of the source
+    pub fn find_code_lenses(&self, source: &Source) -> Vec<CodeLens> {
+        self.root.find_code_lenses(source)
+    }
+
+    /// Find the tree of instances below the top level entity or configuration
+    /// All libraries are searched when no library name is given
+    pub fn find_design_hierarchy(
+        &self,
+        library_name: Option<&str>,
+        name: &str,
+    ) -> Option<HierarchyNode> {
+        let library_name = library_name.map(|library_name| self.root.symbol_utf8(library_name));
+        self.root
+            .find_design_hierarchy(library_name.as_ref(), &self.root.symbol_utf8(name))
+    }
+
+    /// Find the design units and package declarations in all libraries matching the query
+    pub fn find_workspace_symbols(&self, query: &str) -> Vec<WorkspaceSymbol> {
+        self.root.find_workspace_symbols(query)
+    }
+
     pub fn files(&self) -> impl Iterator<Item = &SourceFile> {
         self.files.values()
     }
@@ -264,11 +574,16 @@ impl Default for Project {
     }
 }
 
+#[derive(Clone)]
 pub struct SourceFile {
     library_names: FnvHashSet<Symbol>,
     source: Source,
     design_file: DesignFile,
     parser_diagnostics: Vec<Diagnostic>,
+    /// The design units of the source have to be replaced by the next analysis
+    is_changed: bool,
+    /// The file is part of a library of the configuration
+    is_mapped: bool,
 }
 
 impl SourceFile {
@@ -284,7 +599,7 @@ impl SourceFile {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::syntax::test::check_no_diagnostics;
+    use crate::syntax::test::{check_no_diagnostics, Code};
 
     /// Test that an empty library is created
     /// Thus test case was added when fixing a bug
@@ -567,4 +882,284 @@ lib.files = ['file2.vhd']
         assert_eq!(diagnostics[0].pos.source, source2); // No such library
         assert_eq!(diagnostics[1].pos.source, source2); // No declaration
     }
+
+    #[test]
+    fn search_uses_previous_analysis_until_prepared() {
+        let contents = "
+package pkg is
+  type enum_t is (alpha, beta);
+  constant c : enum_t := alpha;
+  constant d : enum_t := c;
+end package;
+";
+        let mut project = Project::new();
+        let code = Code::new(contents);
+        project.update_source(code.source());
+        check_no_diagnostics(&project.analyse());
+
+        let cursor = code.s1("c;").start();
+        let decl_pos = code.s1("c :").s1("c").pos();
+        assert_eq!(
+            project.search_reference(code.source(), cursor),
+            Some(decl_pos.clone())
+        );
+
+        // The design units of the updated source are not replaced until the analysis is prepared
+        code.source().change(None, contents);
+        project.update_source(code.source());
+        assert_eq!(
+            project.search_reference(code.source(), cursor),
+            Some(decl_pos.clone())
+        );
+
+        // A cancelled analysis is completed by the next analysis
+        check_no_diagnostics(&project.prepare_analysis());
+        assert_eq!(project.analyse_prepared(&AtomicBool::new(true)), None);
+        check_no_diagnostics(&project.analyse());
+        assert_eq!(
+            project.search_reference(code.source(), cursor),
+            Some(decl_pos)
+        );
+    }
+
+    #[test]
+    fn clone_keeps_analysis_while_original_is_analysed() {
+        let code = Code::new(
+            "
+package pkg is
+  type enum_t is (alpha, beta);
+  constant c : enum_t := alpha;
+  constant d : enum_t := c;
+end package;
+",
+        );
+        let mut project = Project::new();
+        project.update_source(code.source());
+        check_no_diagnostics(&project.analyse());
+        let analysed = project.clone();
+
+        let cursor = code.s1("c;").start();
+        let decl_pos = code.s1("c :").s1("c").pos();
+        code.source().change(
+            None,
+            "
+package pkg is
+end package;
+",
+        );
+        project.update_source(code.source());
+        check_no_diagnostics(&project.prepare_analysis());
+        assert_eq!(project.analyse_prepared(&AtomicBool::new(true)), None);
+
+        assert_eq!(
+            analysed.search_reference(code.source(), cursor),
+            Some(decl_pos)
+        );
+    }
+
+    #[test]
+    fn reload_and_remove_unmapped_source() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let root = dunce::canonicalize(tempdir.path()).unwrap();
+
+        let path = root.join("file.vhd");
+        std::fs::write(
+            &path,
+            "
+package pkg is
+end package;
+        ",
+        )
+        .unwrap();
+
+        let config_str = "
+[libraries]
+lib.files = ['file.vhd']
+        ";
+
+        let config = Config::from_str(config_str, &root).unwrap();
+        let mut messages = Vec::new();
+        let mut project = Project::from_config(&config, &mut messages);
+        assert_eq!(messages, vec![]);
+        check_no_diagnostics(&project.analyse());
+
+        // Unsaved changes are discarded when reloading the file
+        let source = project.get_source(&path).unwrap();
+        source.change(None, "package is");
+        project.update_source(&source);
+        assert_eq!(project.analyse().len(), 1);
+        project.reload_source(&path).unwrap();
+        check_no_diagnostics(&project.analyse());
+
+        // Files of the configuration are kept
+        assert!(!project.remove_unmapped_source(&path));
+        assert!(project.get_source(&path).is_some());
+
+        let unmapped_path = root.join("unmapped.vhd");
+        project.update_source(&Source::inline(&unmapped_path, "package is"));
+        assert_eq!(project.analyse().len(), 1);
+        assert!(project.remove_unmapped_source(&unmapped_path));
+        assert!(project.get_source(&unmapped_path).is_none());
+        check_no_diagnostics(&project.analyse());
+    }
+
+    /// Create a project with a user design using a package within the read-only ieee library
+    fn rename_project() -> (tempfile::TempDir, Project, Code, Code) {
+        rename_project_with_config(
+            "
+[libraries]
+ieee.files = ['ieee_pkg.vhd']
+ieee.read_only = true
+lib.files = ['file.vhd']
+        ",
+        )
+    }
+
+    fn rename_project_with_config(config_str: &str) -> (tempfile::TempDir, Project, Code, Code) {
+        let tempdir = tempfile::tempdir().unwrap();
+        let root = dunce::canonicalize(tempdir.path()).unwrap();
+
+        let ieee_file_name = root.join("ieee_pkg.vhd");
+        let ieee_contents = "
+package pkg is
+  type bit_t is ('0', '1');
+  constant zero : bit_t := '0';
+end package;
+";
+
+        let file_name = root.join("file.vhd");
+        let contents = "
+library ieee;
+use ieee.pkg.all;
+
+entity ent is
+end entity ent;
+
+architecture a of ENT is
+  constant \\Ext id\\ : bit_t := zero;
+  constant other : bit_t := \\Ext id\\;
+begin
+  main : process
+  begin
+  end process MAIN;
+end architecture;
+";
+
+        std::fs::write(&ieee_file_name, ieee_contents).unwrap();
+        std::fs::write(&file_name, contents).unwrap();
+        let ieee_code = Code::new_with_file_name(&ieee_file_name, ieee_contents);
+        let code = Code::new_with_file_name(&file_name, contents);
+
+        let config = Config::from_str(config_str, &root).unwrap();
+        let mut messages = Vec::new();
+        let mut project = Project::from_config(&config, &mut messages);
+        assert_eq!(messages, vec![]);
+        project.update_source(ieee_code.source());
+        project.update_source(code.source());
+        check_no_diagnostics(&project.analyse());
+
+        (tempdir, project, ieee_code, code)
+    }
+
+    #[test]
+    fn rename_includes_end_identifiers_of_any_case() {
+        let (_tempdir, project, _, code) = rename_project();
+
+        assert_eq!(
+            project.prepare_rename(code.source(), code.s1("ent;").start()),
+            Ok(code.s1("ent;").s1("ent").pos())
+        );
+
+        let mut positions = project
+            .rename(code.source(), code.s1("ENT").start(), "new_ent")
+            .unwrap();
+        positions.sort();
+        assert_eq!(
+            positions,
+            vec![
+                code.s1("ent is").s1("ent").pos(),
+                code.s1("ent;").s1("ent").pos(),
+                code.s1("ENT").pos()
+            ]
+        );
+
+        let mut positions = project
+            .rename(code.source(), code.s1("MAIN").start(), "new_main")
+            .unwrap();
+        positions.sort();
+        assert_eq!(
+            positions,
+            vec![code.s1("main").pos(), code.s1("MAIN").pos()]
+        );
+    }
+
+    #[test]
+    fn rename_extended_identifier() {
+        let (_tempdir, project, _, code) = rename_project();
+
+        assert_eq!(
+            project.prepare_rename(code.source(), code.s("\\Ext id\\", 2).start()),
+            Ok(code.s("\\Ext id\\", 2).pos())
+        );
+
+        let mut positions = project
+            .rename(code.source(), code.s1("\\Ext id\\").start(), "\\new id\\")
+            .unwrap();
+        positions.sort();
+        assert_eq!(
+            positions,
+            vec![code.s("\\Ext id\\", 1).pos(), code.s("\\Ext id\\", 2).pos()]
+        );
+    }
+
+    #[test]
+    fn rename_refuses_read_only_library() {
+        let (_tempdir, project, ieee_code, code) = rename_project();
+
+        let message = "Cannot rename a declaration within the read-only library 'ieee'";
+        assert_eq!(
+            project.prepare_rename(code.source(), code.s1(":= zero").end()),
+            Err(message.to_owned())
+        );
+        assert_eq!(
+            project.rename(ieee_code.source(), ieee_code.s1("zero").start(), "one"),
+            Err(message.to_owned())
+        );
+    }
+
+    #[test]
+    fn rename_within_library_which_is_not_read_only() {
+        let (_tempdir, project, ieee_code, code) = rename_project_with_config(
+            "
+[libraries]
+ieee.files = ['ieee_pkg.vhd']
+lib.files = ['file.vhd']
+        ",
+        );
+
+        assert_eq!(
+            project.prepare_rename(code.source(), code.s1(":= zero").end()),
+            Ok(code.s1("zero").pos())
+        );
+        assert_eq!(
+            project
+                .rename(ieee_code.source(), ieee_code.s1("zero").start(), "one")
+                .map(|positions| positions.len()),
+            Ok(2)
+        );
+    }
+
+    #[test]
+    fn rename_refuses_invalid_identifier() {
+        let (_tempdir, project, _, code) = rename_project();
+
+        assert_eq!(
+            project.rename(code.source(), code.s1("other").start(), "end"),
+            Err("'end' is not a valid identifier".to_owned())
+        );
+        assert_eq!(
+            project.prepare_rename(code.source(), code.s1("architecture").start()),
+            Err("No identifier to rename at the cursor".to_owned())
+        );
+    }
 }
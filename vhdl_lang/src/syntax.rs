@@ -17,6 +17,7 @@ mod context;
 mod declarative_part;
 mod design_unit;
 mod expression;
+mod folding;
 mod interface_declaration;
 mod names;
 mod object_declaration;
@@ -31,5 +32,6 @@ mod waveform;
 #[cfg(test)]
 pub mod test;
 
+pub use folding::{FoldingRange, FoldingRangeKind};
 pub use parser::{ParserResult, VHDLParser};
-pub use tokens::Symbols;
+pub use tokens::{tokenize_between, Kind, Symbols, Token, Value};
@@ -6,7 +6,7 @@
 
 use super::tokens::{Kind, TokenStream};
 use crate::ast::Ident;
-use crate::data::Diagnostic;
+use crate::data::{Diagnostic, DiagnosticHandler, SrcPos};
 
 /// Parse optional part followed by optional keyword
 pub fn parse_optional<F, R>(
@@ -43,4 +43,38 @@ pub fn error_on_end_identifier_mismatch(
     None
 }
 
+/// The position of the end identifier when it repeats the declared identifier
+pub fn matching_end_ident_pos(ident: &Ident, end_ident: Option<Ident>) -> Option<SrcPos> {
+    end_ident
+        .filter(|end_ident| end_ident.item == ident.item)
+        .map(|end_ident| end_ident.pos)
+}
+
+/// Check the label repeated at the end of a statement against the label of the statement
+/// The position of the end label is kept when it repeats the label
+pub fn check_end_label(
+    label: Option<&Ident>,
+    end_ident: Option<Ident>,
+    diagnostics: &mut dyn DiagnosticHandler,
+) -> Option<SrcPos> {
+    let end_ident = end_ident?;
+    if let Some(label) = label {
+        if label.item == end_ident.item {
+            Some(end_ident.pos)
+        } else {
+            diagnostics.push_some(error_on_end_identifier_mismatch(label, &Some(end_ident)));
+            None
+        }
+    } else {
+        diagnostics.push(Diagnostic::error(
+            &end_ident.pos,
+            format!(
+                "End label '{}' found for unlabeled statement",
+                end_ident.item.name()
+            ),
+        ));
+        None
+    }
+}
+
 pub type ParseResult<T> = Result<T, Diagnostic>;
@@ -4,8 +4,8 @@
 //
 // Copyright (c) 2018, Olof Kraigher olof.kraigher@gmail.com
 
-use super::common::error_on_end_identifier_mismatch;
 use super::common::ParseResult;
+use super::common::{error_on_end_identifier_mismatch, matching_end_ident_pos};
 use super::interface_declaration::{parse_generic_interface_list, parse_port_interface_list};
 use super::tokens::{Kind::*, TokenStream};
 use crate::ast::{ComponentDeclaration, InterfaceDeclaration};
@@ -82,18 +82,16 @@ pub fn parse_component_declaration(
     let port_list = parse_optional_port_list(stream, diagnostics)?;
     stream.expect_kind(End)?;
     stream.expect_kind(Component)?;
-    if let Some(token) = stream.pop_if_kind(Identifier)? {
-        diagnostics.push_some(error_on_end_identifier_mismatch(
-            &ident,
-            &Some(token.expect_ident()?),
-        ));
-    }
+    let end_ident = stream.pop_optional_ident()?;
+    diagnostics.push_some(error_on_end_identifier_mismatch(&ident, &end_ident));
+    let end_ident_pos = matching_end_ident_pos(&ident, end_ident);
     stream.expect_kind(SemiColon)?;
 
     Ok(ComponentDeclaration {
         ident,
         generic_list: generic_list.unwrap_or_default(),
         port_list: port_list.unwrap_or_default(),
+        end_ident_pos,
     })
 }
 
@@ -113,6 +111,7 @@ mod tests {
             ident,
             generic_list,
             port_list,
+            end_ident_pos: None,
         }
     }
 
@@ -151,7 +150,10 @@ end component foo;
         let component = code.with_stream_no_diagnostics(parse_component_declaration);
         assert_eq!(
             component,
-            to_component(code.s1("foo").ident(), vec![], vec![])
+            ComponentDeclaration {
+                end_ident_pos: Some(code.s("foo", 2).pos()),
+                ..to_component(code.s1("foo").ident(), vec![], vec![])
+            }
         );
     }
 
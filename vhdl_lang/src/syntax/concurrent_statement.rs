@@ -4,8 +4,8 @@
 //
 // Copyright (c) 2018, Olof Kraigher olof.kraigher@gmail.com
 
-use super::common::error_on_end_identifier_mismatch;
 use super::common::ParseResult;
+use super::common::{check_end_label, error_on_end_identifier_mismatch, matching_end_ident_pos};
 use super::declarative_part::{is_declarative_part, parse_declarative_part};
 use super::expression::parse_aggregate_leftpar_known;
 use super::expression::{parse_choices, parse_expression};
@@ -27,6 +27,7 @@ use crate::data::*;
 /// LRM 11.2 Block statement
 pub fn parse_block_statement(
     stream: &mut TokenStream,
+    label: Option<&Ident>,
     diagnostics: &mut dyn DiagnosticHandler,
 ) -> ParseResult<BlockStatement> {
     let token = stream.peek_expect()?;
@@ -46,14 +47,14 @@ pub fn parse_block_statement(
     let decl = parse_declarative_part(stream, diagnostics, true)?;
     let statements = parse_labeled_concurrent_statements(stream, diagnostics)?;
     stream.expect_kind(Block)?;
-    // @TODO check name
-    stream.pop_if_kind(Identifier)?;
+    let end_label_pos = check_end_label(label, stream.pop_optional_ident()?, diagnostics);
     stream.expect_kind(SemiColon)?;
     Ok(BlockStatement {
         guard_condition,
         header,
         decl,
         statements,
+        end_label_pos,
     })
 }
 
@@ -153,6 +154,7 @@ fn parse_block_header(
 /// LRM 11.3 Process statement
 pub fn parse_process_statement(
     stream: &mut TokenStream,
+    label: Option<&Ident>,
     postponed: bool,
     diagnostics: &mut dyn DiagnosticHandler,
 ) -> ParseResult<ProcessStatement> {
@@ -205,14 +207,14 @@ pub fn parse_process_statement(
         }
     }
     stream.expect_kind(Process)?;
-    // @TODO check name
-    stream.pop_if_kind(Identifier)?;
+    let end_label_pos = check_end_label(label, stream.pop_optional_ident()?, diagnostics);
     stream.expect_kind(SemiColon)?;
     Ok(ProcessStatement {
         postponed,
         sensitivity_list,
         decl,
         statements,
+        end_label_pos,
     })
 }
 
@@ -359,14 +361,17 @@ fn parse_optional_declarative_part(
     }
 }
 
+/// The generate body starting after the generate keyword or arrow at start
 fn parse_generate_body_end_token(
     stream: &mut TokenStream,
+    start: &SrcPos,
     alternative_label: Option<Ident>,
     diagnostics: &mut dyn DiagnosticHandler,
 ) -> ParseResult<(GenerateBody, Token)> {
     let decl = parse_optional_declarative_part(stream, diagnostics)?;
     let (statements, mut end_token) =
         parse_labeled_concurrent_statements_end_token(stream, diagnostics)?;
+    let mut end_label_pos = None;
 
     // Potential inner end [ alternative_label ];
     if end_token.kind == End {
@@ -385,9 +390,10 @@ fn parse_generate_body_end_token(
         Identifier => {
             stream.move_after(&token);
             // Inner with identifier
-            let end_ident = token.expect_ident()?;
+            let end_ident = Some(token.expect_ident()?);
             if let Some(ref ident) = alternative_label {
-                diagnostics.push_some(error_on_end_identifier_mismatch(ident, &Some(end_ident)));
+                diagnostics.push_some(error_on_end_identifier_mismatch(ident, &end_ident));
+                end_label_pos = matching_end_ident_pos(ident, end_ident);
             };
             stream.expect_kind(SemiColon)?;
             end_token = stream.expect()?;
@@ -398,6 +404,8 @@ fn parse_generate_body_end_token(
         alternative_label,
         decl,
         statements,
+        end_label_pos,
+        source_range: start.combine(&end_token.pos),
     };
 
     Ok((body, end_token))
@@ -405,10 +413,12 @@ fn parse_generate_body_end_token(
 
 fn parse_generate_body(
     stream: &mut TokenStream,
+    start: &SrcPos,
     alternative_label: Option<Ident>,
     diagnostics: &mut dyn DiagnosticHandler,
 ) -> ParseResult<GenerateBody> {
-    let (body, end_token) = parse_generate_body_end_token(stream, alternative_label, diagnostics)?;
+    let (body, end_token) =
+        parse_generate_body_end_token(stream, start, alternative_label, diagnostics)?;
     end_token.expect_kind(End)?;
     Ok(body)
 }
@@ -416,28 +426,30 @@ fn parse_generate_body(
 /// 11.8 Generate statements
 fn parse_for_generate_statement(
     stream: &mut TokenStream,
+    label: Option<&Ident>,
     diagnostics: &mut dyn DiagnosticHandler,
 ) -> ParseResult<ForGenerateStatement> {
     let index_name = stream.expect_ident()?;
     stream.expect_kind(In)?;
     let discrete_range = parse_discrete_range(stream)?;
+    let generate_token = stream.expect_kind(Generate)?;
+    let body = parse_generate_body(stream, &generate_token.pos, None, diagnostics)?;
     stream.expect_kind(Generate)?;
-    let body = parse_generate_body(stream, None, diagnostics)?;
-    stream.expect_kind(Generate)?;
-    // @TODO check identifier
-    stream.pop_if_kind(Identifier)?;
+    let end_label_pos = check_end_label(label, stream.pop_optional_ident()?, diagnostics);
     stream.expect_kind(SemiColon)?;
 
     Ok(ForGenerateStatement {
         index_name,
         discrete_range,
         body,
+        end_label_pos,
     })
 }
 
 /// 11.8 Generate statements
 fn parse_if_generate_statement(
     stream: &mut TokenStream,
+    label: Option<&Ident>,
     diagnostics: &mut dyn DiagnosticHandler,
 ) -> ParseResult<IfGenerateStatement> {
     let mut conditionals = Vec::new();
@@ -450,9 +462,13 @@ fn parse_if_generate_statement(
             alternative_label = Some(expression_to_ident(condition)?);
             condition = parse_expression(stream)?;
         }
-        stream.expect_kind(Generate)?;
-        let (body, end_token) =
-            parse_generate_body_end_token(stream, alternative_label, diagnostics)?;
+        let generate_token = stream.expect_kind(Generate)?;
+        let (body, end_token) = parse_generate_body_end_token(
+            stream,
+            &generate_token.pos,
+            alternative_label,
+            diagnostics,
+        )?;
 
         let conditional = Conditional {
             condition,
@@ -472,18 +488,19 @@ fn parse_if_generate_statement(
             },
             Else => {
                 let token = stream.expect()?;
-                let alternative_label = try_token_kind!(
+                let (alternative_label, generate_token) = try_token_kind!(
                     token,
                     Generate => {
-                        None
+                        (None, token)
                     },
                     Identifier => {
                         stream.expect_kind(Colon)?;
-                        stream.expect_kind(Generate)?;
-                        Some(token.expect_ident()?)
+                        let generate_token = stream.expect_kind(Generate)?;
+                        (Some(token.expect_ident()?), generate_token)
                     }
                 );
-                let body = parse_generate_body(stream, alternative_label, diagnostics)?;
+                let body =
+                    parse_generate_body(stream, &generate_token.pos, alternative_label, diagnostics)?;
                 else_branch = Some(body);
                 break;
             }
@@ -491,19 +508,22 @@ fn parse_if_generate_statement(
     }
 
     stream.expect_kind(Generate)?;
-    // @TODO check identifier
-    stream.pop_if_kind(Identifier)?;
+    let end_label_pos = check_end_label(label, stream.pop_optional_ident()?, diagnostics);
     stream.expect_kind(SemiColon)?;
 
     Ok(IfGenerateStatement {
-        conditionals,
-        else_item: else_branch,
+        conds: Conditionals {
+            conditionals,
+            else_item: else_branch,
+        },
+        end_label_pos,
     })
 }
 
 /// 11.8 Generate statements
 fn parse_case_generate_statement(
     stream: &mut TokenStream,
+    label: Option<&Ident>,
     diagnostics: &mut dyn DiagnosticHandler,
 ) -> ParseResult<CaseGenerateStatement> {
     let expression = parse_expression(stream)?;
@@ -522,9 +542,13 @@ fn parse_case_generate_statement(
             }
         };
         let choices = parse_choices(stream)?;
-        stream.expect_kind(RightArrow)?;
-        let (body, end_token) =
-            parse_generate_body_end_token(stream, alternative_label, diagnostics)?;
+        let arrow_token = stream.expect_kind(RightArrow)?;
+        let (body, end_token) = parse_generate_body_end_token(
+            stream,
+            &arrow_token.pos,
+            alternative_label,
+            diagnostics,
+        )?;
         alternatives.push(Alternative {
             choices,
             item: body,
@@ -538,29 +562,32 @@ fn parse_case_generate_statement(
     }
 
     stream.expect_kind(Generate)?;
-    // @TODO check identifier
-    stream.pop_if_kind(Identifier)?;
+    let end_label_pos = check_end_label(label, stream.pop_optional_ident()?, diagnostics);
     stream.expect_kind(SemiColon)?;
 
     Ok(CaseGenerateStatement {
-        expression,
-        alternatives,
+        sels: Selection {
+            expression,
+            alternatives,
+        },
+        end_label_pos,
     })
 }
 
 pub fn parse_concurrent_statement(
     stream: &mut TokenStream,
     token: Token,
+    label: Option<&Ident>,
     diagnostics: &mut dyn DiagnosticHandler,
 ) -> ParseResult<ConcurrentStatement> {
     let statement = {
         try_token_kind!(
             token,
             Block => {
-                ConcurrentStatement::Block(parse_block_statement(stream, diagnostics)?)
+                ConcurrentStatement::Block(parse_block_statement(stream, label, diagnostics)?)
             },
             Process => {
-                ConcurrentStatement::Process(parse_process_statement(stream, false, diagnostics)?)
+                ConcurrentStatement::Process(parse_process_statement(stream, label, false, diagnostics)?)
             },
             Component => {
                 let unit = InstantiatedUnit::Component(parse_selected_name(stream)?);
@@ -584,14 +611,14 @@ pub fn parse_concurrent_statement(
                 let unit = InstantiatedUnit::Entity(name, arch);
                 ConcurrentStatement::Instance(parse_instantiation_statement(stream, unit)?)
             },
-            For => ConcurrentStatement::ForGenerate(parse_for_generate_statement(stream, diagnostics)?),
-            If => ConcurrentStatement::IfGenerate(parse_if_generate_statement(stream, diagnostics)?),
-            Case => ConcurrentStatement::CaseGenerate(parse_case_generate_statement(stream, diagnostics)?),
+            For => ConcurrentStatement::ForGenerate(parse_for_generate_statement(stream, label, diagnostics)?),
+            If => ConcurrentStatement::IfGenerate(parse_if_generate_statement(stream, label, diagnostics)?),
+            Case => ConcurrentStatement::CaseGenerate(parse_case_generate_statement(stream, label, diagnostics)?),
             Assert => ConcurrentStatement::Assert(parse_concurrent_assert_statement(stream, false)?),
             Postponed => {
                 let token = stream.expect()?;
                 match token.kind {
-                    Process => ConcurrentStatement::Process(parse_process_statement(stream, true, diagnostics)?),
+                    Process => ConcurrentStatement::Process(parse_process_statement(stream, label, true, diagnostics)?),
                     Assert => ConcurrentStatement::Assert(parse_concurrent_assert_statement(stream, true)?),
                     With => ConcurrentStatement::Assignment(parse_selected_signal_assignment(stream, true)?),
                     _ => {
@@ -666,27 +693,32 @@ pub fn parse_labeled_concurrent_statement_initial_token(
     token: Token,
     diagnostics: &mut dyn DiagnosticHandler,
 ) -> ParseResult<LabeledConcurrentStatement> {
+    let start = token.pos.clone();
     if token.kind == Identifier {
         let name = parse_name_initial_token(stream, token)?;
         let token = stream.expect()?;
         if token.kind == Colon {
             let label = Some(to_simple_name(name)?);
             let token = stream.expect()?;
-            let statement = parse_concurrent_statement(stream, token, diagnostics)?;
-            Ok(LabeledConcurrentStatement { label, statement })
+            let start = token.pos.clone();
+            let statement = parse_concurrent_statement(stream, token, label.as_ref(), diagnostics)?;
+            Ok(LabeledConcurrentStatement {
+                label,
+                statement: WithPos::from(statement, stream.pos_since(&start)),
+            })
         } else {
             let target = name.map_into(Target::Name);
             let statement = parse_assignment_or_procedure_call(stream, &token, target)?;
             Ok(LabeledConcurrentStatement {
                 label: None,
-                statement,
+                statement: WithPos::from(statement, stream.pos_since(&start)),
             })
         }
     } else {
-        let statement = parse_concurrent_statement(stream, token, diagnostics)?;
+        let statement = parse_concurrent_statement(stream, token, None, diagnostics)?;
         Ok(LabeledConcurrentStatement {
             label: None,
-            statement,
+            statement: WithPos::from(statement, stream.pos_since(&start)),
         })
     }
 }
@@ -719,7 +751,10 @@ mod tests {
         };
         let stmt = code.with_stream_no_diagnostics(parse_labeled_concurrent_statement);
         assert_eq!(stmt.label, None);
-        assert_eq!(stmt.statement, ConcurrentStatement::ProcedureCall(call));
+        assert_eq!(
+            stmt.statement.item,
+            ConcurrentStatement::ProcedureCall(call)
+        );
     }
 
     #[test]
@@ -735,7 +770,10 @@ mod tests {
         };
         let stmt = code.with_stream_no_diagnostics(parse_labeled_concurrent_statement);
         assert_eq!(stmt.label, None);
-        assert_eq!(stmt.statement, ConcurrentStatement::ProcedureCall(call));
+        assert_eq!(
+            stmt.statement.item,
+            ConcurrentStatement::ProcedureCall(call)
+        );
     }
 
     #[test]
@@ -751,7 +789,11 @@ mod tests {
         };
         let stmt = code.with_stream_no_diagnostics(parse_labeled_concurrent_statement);
         assert_eq!(stmt.label, Some(code.s1("name").ident()));
-        assert_eq!(stmt.statement, ConcurrentStatement::ProcedureCall(call));
+        assert_eq!(
+            stmt.statement.item,
+            ConcurrentStatement::ProcedureCall(call)
+        );
+        assert_eq!(stmt.statement.pos, code.s1("foo(clk);").pos());
     }
 
     #[test]
@@ -767,7 +809,10 @@ mod tests {
         };
         let stmt = code.with_stream_no_diagnostics(parse_labeled_concurrent_statement);
         assert_eq!(stmt.label, None);
-        assert_eq!(stmt.statement, ConcurrentStatement::ProcedureCall(call));
+        assert_eq!(
+            stmt.statement.item,
+            ConcurrentStatement::ProcedureCall(call)
+        );
     }
 
     #[test]
@@ -797,12 +842,16 @@ end block;
             decl: code.s1("constant const : natural := 0;").declarative_part(),
             statements: vec![LabeledConcurrentStatement {
                 label: Some(code.s1("name2").ident()),
-                statement: ConcurrentStatement::ProcedureCall(call),
+                statement: WithPos::new(
+                    ConcurrentStatement::ProcedureCall(call),
+                    code.s1("foo(clk);"),
+                ),
             }],
+            end_label_pos: None,
         };
         let stmt = code.with_stream_no_diagnostics(parse_labeled_concurrent_statement);
         assert_eq!(stmt.label, Some(code.s1("name").ident()));
-        assert_eq!(stmt.statement, ConcurrentStatement::Block(block));
+        assert_eq!(stmt.statement.item, ConcurrentStatement::Block(block));
     }
 
     #[test]
@@ -824,10 +873,11 @@ end block name;
             },
             decl: vec![],
             statements: vec![],
+            end_label_pos: Some(code.s("name", 2).pos()),
         };
         let stmt = code.with_stream_no_diagnostics(parse_labeled_concurrent_statement);
         assert_eq!(stmt.label, Some(code.s1("name").ident()));
-        assert_eq!(stmt.statement, ConcurrentStatement::Block(block));
+        assert_eq!(stmt.statement.item, ConcurrentStatement::Block(block));
     }
 
     #[test]
@@ -849,10 +899,11 @@ end block;
             },
             decl: vec![],
             statements: vec![],
+            end_label_pos: None,
         };
         let stmt = code.with_stream_no_diagnostics(parse_labeled_concurrent_statement);
         assert_eq!(stmt.label, Some(code.s1("name").ident()));
-        assert_eq!(stmt.statement, ConcurrentStatement::Block(block));
+        assert_eq!(stmt.statement.item, ConcurrentStatement::Block(block));
     }
 
     #[test]
@@ -874,10 +925,11 @@ end block;
             },
             decl: vec![],
             statements: vec![],
+            end_label_pos: None,
         };
         let stmt = code.with_stream_no_diagnostics(parse_labeled_concurrent_statement);
         assert_eq!(stmt.label, Some(code.s1("name").ident()));
-        assert_eq!(stmt.statement, ConcurrentStatement::Block(block));
+        assert_eq!(stmt.statement.item, ConcurrentStatement::Block(block));
     }
 
     #[test]
@@ -903,10 +955,11 @@ end block;
             },
             decl: vec![],
             statements: vec![],
+            end_label_pos: None,
         };
         let stmt = code.with_stream_no_diagnostics(parse_labeled_concurrent_statement);
         assert_eq!(stmt.label, Some(code.s1("name").ident()));
-        assert_eq!(stmt.statement, ConcurrentStatement::Block(block));
+        assert_eq!(stmt.statement.item, ConcurrentStatement::Block(block));
     }
 
     #[test]
@@ -923,10 +976,11 @@ end process;
             sensitivity_list: None,
             decl: vec![],
             statements: vec![],
+            end_label_pos: None,
         };
         let stmt = code.with_stream_no_diagnostics(parse_labeled_concurrent_statement);
         assert_eq!(stmt.label, None);
-        assert_eq!(stmt.statement, ConcurrentStatement::Process(process));
+        assert_eq!(stmt.statement.item, ConcurrentStatement::Process(process));
     }
 
     #[test]
@@ -943,10 +997,11 @@ end process name;
             sensitivity_list: None,
             decl: vec![],
             statements: vec![],
+            end_label_pos: Some(code.s("name", 2).pos()),
         };
         let stmt = code.with_stream_no_diagnostics(parse_labeled_concurrent_statement);
         assert_eq!(stmt.label, Some(code.s1("name").ident()));
-        assert_eq!(stmt.statement, ConcurrentStatement::Process(process));
+        assert_eq!(stmt.statement.item, ConcurrentStatement::Process(process));
     }
 
     #[test]
@@ -963,10 +1018,11 @@ end process;
             sensitivity_list: None,
             decl: vec![],
             statements: vec![],
+            end_label_pos: None,
         };
         let stmt = code.with_stream_no_diagnostics(parse_labeled_concurrent_statement);
         assert_eq!(stmt.label, None);
-        assert_eq!(stmt.statement, ConcurrentStatement::Process(process));
+        assert_eq!(stmt.statement.item, ConcurrentStatement::Process(process));
     }
 
     #[test]
@@ -983,10 +1039,11 @@ end postponed process;
             sensitivity_list: None,
             decl: vec![],
             statements: vec![],
+            end_label_pos: None,
         };
         let stmt = code.with_stream_no_diagnostics(parse_labeled_concurrent_statement);
         assert_eq!(stmt.label, None);
-        assert_eq!(stmt.statement, ConcurrentStatement::Process(process));
+        assert_eq!(stmt.statement.item, ConcurrentStatement::Process(process));
     }
 
     #[test]
@@ -1004,6 +1061,7 @@ end postponed process;
             sensitivity_list: None,
             decl: Vec::new(),
             statements: Vec::new(),
+            end_label_pos: None,
         };
         assert_eq!(
             diagnostics,
@@ -1012,7 +1070,66 @@ end postponed process;
                 "'postponed' at the end of non-postponed process."
             )]
         );
-        assert_eq!(stmt.statement, ConcurrentStatement::Process(process));
+        assert_eq!(stmt.statement.item, ConcurrentStatement::Process(process));
+    }
+
+    #[test]
+    fn test_process_statement_end_label_mismatch() {
+        let code = Code::new(
+            "\
+name : process is
+begin
+end process other;
+",
+        );
+        let (stmt, diagnostics) = code.with_stream_diagnostics(parse_labeled_concurrent_statement);
+        let process = ProcessStatement {
+            postponed: false,
+            sensitivity_list: None,
+            decl: Vec::new(),
+            statements: Vec::new(),
+            end_label_pos: None,
+        };
+        assert_eq!(
+            diagnostics,
+            vec![Diagnostic::error(
+                code.s1("other"),
+                "End identifier mismatch, expected name"
+            )]
+        );
+        assert_eq!(stmt.statement.item, ConcurrentStatement::Process(process));
+    }
+
+    #[test]
+    fn test_block_end_label_for_unlabeled_statement() {
+        let code = Code::new(
+            "\
+block is
+begin
+end block name;
+",
+        );
+        let (stmt, diagnostics) = code.with_stream_diagnostics(parse_labeled_concurrent_statement);
+        let block = BlockStatement {
+            guard_condition: None,
+            header: BlockHeader {
+                generic_clause: None,
+                generic_map: None,
+                port_clause: None,
+                port_map: None,
+            },
+            decl: Vec::new(),
+            statements: Vec::new(),
+            end_label_pos: None,
+        };
+        assert_eq!(
+            diagnostics,
+            vec![Diagnostic::error(
+                code.s1("name"),
+                "End label 'name' found for unlabeled statement"
+            )]
+        );
+        assert_eq!(stmt.statement.item, ConcurrentStatement::Block(block));
     }
 
     #[test]
@@ -1032,10 +1149,11 @@ end process;
             ])),
             decl: vec![],
             statements: vec![],
+            end_label_pos: None,
         };
         let stmt = code.with_stream_no_diagnostics(parse_labeled_concurrent_statement);
         assert_eq!(stmt.label, None);
-        assert_eq!(stmt.statement, ConcurrentStatement::Process(process));
+        assert_eq!(stmt.statement.item, ConcurrentStatement::Process(process));
     }
 
     #[test]
@@ -1053,6 +1171,7 @@ end process;
             sensitivity_list: Some(SensitivityList::Names(Vec::new())),
             decl: Vec::new(),
             statements: Vec::new(),
+            end_label_pos: None,
         };
         assert_eq!(
             diagnostics,
@@ -1061,7 +1180,7 @@ end process;
                 "Processes with sensitivity lists must contain at least one element."
             )]
         );
-        assert_eq!(stmt.statement, ConcurrentStatement::Process(process));
+        assert_eq!(stmt.statement.item, ConcurrentStatement::Process(process));
     }
 
     #[test]
@@ -1084,10 +1203,11 @@ end process;
                 code.s1("foo <= true;").sequential_statement(),
                 code.s1("wait;").sequential_statement(),
             ],
+            end_label_pos: None,
         };
         let stmt = code.with_stream_no_diagnostics(parse_labeled_concurrent_statement);
         assert_eq!(stmt.label, None);
-        assert_eq!(stmt.statement, ConcurrentStatement::Process(process));
+        assert_eq!(stmt.statement.item, ConcurrentStatement::Process(process));
     }
 
     #[test]
@@ -1107,7 +1227,7 @@ assert cond = true;
         };
         let stmt = code.with_stream_no_diagnostics(parse_labeled_concurrent_statement);
         assert_eq!(stmt.label, None);
-        assert_eq!(stmt.statement, ConcurrentStatement::Assert(assert));
+        assert_eq!(stmt.statement.item, ConcurrentStatement::Assert(assert));
     }
 
     #[test]
@@ -1127,7 +1247,7 @@ postponed assert cond = true;
         };
         let stmt = code.with_stream_no_diagnostics(parse_labeled_concurrent_statement);
         assert_eq!(stmt.label, None);
-        assert_eq!(stmt.statement, ConcurrentStatement::Assert(assert));
+        assert_eq!(stmt.statement.item, ConcurrentStatement::Assert(assert));
     }
 
     #[test]
@@ -1146,7 +1266,7 @@ foo <= bar(2 to 3);
         };
         let stmt = code.with_stream_no_diagnostics(parse_labeled_concurrent_statement);
         assert_eq!(stmt.label, None);
-        assert_eq!(stmt.statement, ConcurrentStatement::Assignment(assign));
+        assert_eq!(stmt.statement.item, ConcurrentStatement::Assignment(assign));
     }
 
     #[test]
@@ -1168,7 +1288,7 @@ foo <= bar(2 to 3);
         };
         let stmt = code.with_stream_no_diagnostics(parse_labeled_concurrent_statement);
         assert_eq!(stmt.label, None);
-        assert_eq!(stmt.statement, ConcurrentStatement::Assignment(assign));
+        assert_eq!(stmt.statement.item, ConcurrentStatement::Assignment(assign));
     }
 
     #[test]
@@ -1190,7 +1310,7 @@ with x(0) + 1 select
         let stmt = code.with_stream_no_diagnostics(parse_labeled_concurrent_statement);
         assert_eq!(stmt.label, None);
         assert_eq!(
-            stmt.statement,
+            stmt.statement.item,
             ConcurrentStatement::Assignment(ConcurrentSignalAssignment {
                 postponed: false,
                 guarded: false,
@@ -1216,7 +1336,7 @@ inst: component lib.foo.bar;
         };
         let stmt = code.with_stream_no_diagnostics(parse_labeled_concurrent_statement);
         assert_eq!(stmt.label, Some(code.s1("inst").ident()));
-        assert_eq!(stmt.statement, ConcurrentStatement::Instance(inst));
+        assert_eq!(stmt.statement.item, ConcurrentStatement::Instance(inst));
     }
 
     #[test]
@@ -1234,7 +1354,7 @@ inst: configuration lib.foo.bar;
         };
         let stmt = code.with_stream_no_diagnostics(parse_labeled_concurrent_statement);
         assert_eq!(stmt.label, Some(code.s1("inst").ident()));
-        assert_eq!(stmt.statement, ConcurrentStatement::Instance(inst));
+        assert_eq!(stmt.statement.item, ConcurrentStatement::Instance(inst));
     }
 
     #[test]
@@ -1252,7 +1372,7 @@ inst: entity lib.foo.bar;
         };
         let stmt = code.with_stream_no_diagnostics(parse_labeled_concurrent_statement);
         assert_eq!(stmt.label, Some(code.s1("inst").ident()));
-        assert_eq!(stmt.statement, ConcurrentStatement::Instance(inst));
+        assert_eq!(stmt.statement.item, ConcurrentStatement::Instance(inst));
     }
 
     #[test]
@@ -1273,7 +1393,7 @@ inst: entity lib.foo.bar(arch);
         };
         let stmt = code.with_stream_no_diagnostics(parse_labeled_concurrent_statement);
         assert_eq!(stmt.label, Some(code.s1("inst").ident()));
-        assert_eq!(stmt.statement, ConcurrentStatement::Instance(inst));
+        assert_eq!(stmt.statement.item, ConcurrentStatement::Instance(inst));
     }
 
     #[test]
@@ -1305,7 +1425,7 @@ inst: component lib.foo.bar
         };
         let stmt = code.with_stream_no_diagnostics(parse_labeled_concurrent_statement);
         assert_eq!(stmt.label, Some(code.s1("inst").ident()));
-        assert_eq!(stmt.statement, ConcurrentStatement::Instance(inst));
+        assert_eq!(stmt.statement.item, ConcurrentStatement::Instance(inst));
     }
 
     #[test]
@@ -1330,7 +1450,7 @@ inst: lib.foo.bar
         };
         let stmt = code.with_stream_no_diagnostics(parse_labeled_concurrent_statement);
         assert_eq!(stmt.label, Some(code.s1("inst").ident()));
-        assert_eq!(stmt.statement, ConcurrentStatement::Instance(inst));
+        assert_eq!(stmt.statement.item, ConcurrentStatement::Instance(inst));
     }
 
     #[test]
@@ -1355,7 +1475,7 @@ inst: lib.foo.bar
         };
         let stmt = code.with_stream_no_diagnostics(parse_labeled_concurrent_statement);
         assert_eq!(stmt.label, Some(code.s1("inst").ident()));
-        assert_eq!(stmt.statement, ConcurrentStatement::Instance(inst));
+        assert_eq!(stmt.statement.item, ConcurrentStatement::Instance(inst));
     }
 
     #[test]
@@ -1373,11 +1493,14 @@ end generate;
                 alternative_label: None,
                 decl: None,
                 statements: vec![],
+                end_label_pos: None,
+                source_range: code.between("generate", "end").pos(),
             },
+            end_label_pos: None,
         };
         let stmt = code.with_stream_no_diagnostics(parse_labeled_concurrent_statement);
         assert_eq!(stmt.label, Some(code.s1("gen").ident()));
-        assert_eq!(stmt.statement, ConcurrentStatement::ForGenerate(gen));
+        assert_eq!(stmt.statement.item, ConcurrentStatement::ForGenerate(gen));
     }
 
     #[test]
@@ -1396,11 +1519,14 @@ end generate;
                 alternative_label: None,
                 decl: None,
                 statements: vec![code.s1("foo <= bar;").concurrent_statement()],
+                end_label_pos: None,
+                source_range: code.between("generate", "end").pos(),
             },
+            end_label_pos: None,
         };
         let stmt = code.with_stream_no_diagnostics(parse_labeled_concurrent_statement);
         assert_eq!(stmt.label, Some(code.s1("gen").ident()));
-        assert_eq!(stmt.statement, ConcurrentStatement::ForGenerate(gen));
+        assert_eq!(stmt.statement.item, ConcurrentStatement::ForGenerate(gen));
     }
 
     #[test]
@@ -1420,11 +1546,14 @@ end generate;
                 alternative_label: None,
                 decl: Some(vec![]),
                 statements: vec![code.s1("foo <= bar;").concurrent_statement()],
+                end_label_pos: None,
+                source_range: code.between("generate", "end").pos(),
             },
+            end_label_pos: None,
         };
         let stmt = code.with_stream_no_diagnostics(parse_labeled_concurrent_statement);
         assert_eq!(stmt.label, Some(code.s1("gen").ident()));
-        assert_eq!(stmt.statement, ConcurrentStatement::ForGenerate(gen));
+        assert_eq!(stmt.statement.item, ConcurrentStatement::ForGenerate(gen));
     }
 
     #[test]
@@ -1445,11 +1574,14 @@ end generate;
                 alternative_label: None,
                 decl: Some(code.s1("signal foo : natural;").declarative_part()),
                 statements: vec![code.s1("foo <= bar;").concurrent_statement()],
+                end_label_pos: None,
+                source_range: code.between("generate", "end").pos(),
             },
+            end_label_pos: None,
         };
         let stmt = code.with_stream_no_diagnostics(parse_labeled_concurrent_statement);
         assert_eq!(stmt.label, Some(code.s1("gen").ident()));
-        assert_eq!(stmt.statement, ConcurrentStatement::ForGenerate(gen));
+        assert_eq!(stmt.statement.item, ConcurrentStatement::ForGenerate(gen));
     }
 
     #[test]
@@ -1461,19 +1593,24 @@ end generate;
 ",
         );
         let gen = IfGenerateStatement {
-            conditionals: vec![Conditional {
-                condition: code.s1("cond = true").expr(),
-                item: GenerateBody {
-                    alternative_label: None,
-                    decl: None,
-                    statements: vec![],
-                },
-            }],
-            else_item: None,
+            conds: Conditionals {
+                conditionals: vec![Conditional {
+                    condition: code.s1("cond = true").expr(),
+                    item: GenerateBody {
+                        alternative_label: None,
+                        decl: None,
+                        statements: vec![],
+                        end_label_pos: None,
+                        source_range: code.between("generate", "end").pos(),
+                    },
+                }],
+                else_item: None,
+            },
+            end_label_pos: None,
         };
         let stmt = code.with_stream_no_diagnostics(parse_labeled_concurrent_statement);
         assert_eq!(stmt.label, Some(code.s1("gen").ident()));
-        assert_eq!(stmt.statement, ConcurrentStatement::IfGenerate(gen));
+        assert_eq!(stmt.statement.item, ConcurrentStatement::IfGenerate(gen));
     }
 
     #[test]
@@ -1486,19 +1623,24 @@ end generate;
 ",
         );
         let gen = IfGenerateStatement {
-            conditionals: vec![Conditional {
-                condition: code.s1("cond = true").expr(),
-                item: GenerateBody {
-                    alternative_label: None,
-                    decl: Some(vec![]),
-                    statements: vec![],
-                },
-            }],
-            else_item: None,
+            conds: Conditionals {
+                conditionals: vec![Conditional {
+                    condition: code.s1("cond = true").expr(),
+                    item: GenerateBody {
+                        alternative_label: None,
+                        decl: Some(vec![]),
+                        statements: vec![],
+                        end_label_pos: None,
+                        source_range: code.between("generate", "end").pos(),
+                    },
+                }],
+                else_item: None,
+            },
+            end_label_pos: None,
         };
         let stmt = code.with_stream_no_diagnostics(parse_labeled_concurrent_statement);
         assert_eq!(stmt.label, Some(code.s1("gen").ident()));
-        assert_eq!(stmt.statement, ConcurrentStatement::IfGenerate(gen));
+        assert_eq!(stmt.statement.item, ConcurrentStatement::IfGenerate(gen));
     }
 
     #[test]
@@ -1512,33 +1654,42 @@ end generate;
 ",
         );
         let gen = IfGenerateStatement {
-            conditionals: vec![
-                Conditional {
-                    condition: code.s1("cond = true").expr(),
-                    item: GenerateBody {
-                        alternative_label: None,
-                        decl: None,
-                        statements: vec![],
+            conds: Conditionals {
+                conditionals: vec![
+                    Conditional {
+                        condition: code.s1("cond = true").expr(),
+                        item: GenerateBody {
+                            alternative_label: None,
+                            decl: None,
+                            statements: vec![],
+                            end_label_pos: None,
+                            source_range: code.between("generate", "elsif").pos(),
+                        },
                     },
-                },
-                Conditional {
-                    condition: code.s1("cond2 = true").expr(),
-                    item: GenerateBody {
-                        alternative_label: None,
-                        decl: None,
-                        statements: vec![],
+                    Conditional {
+                        condition: code.s1("cond2 = true").expr(),
+                        item: GenerateBody {
+                            alternative_label: None,
+                            decl: None,
+                            statements: vec![],
+                            end_label_pos: None,
+                            source_range: code.s("generate", 2).pos().combine(&code.s1("else")),
+                        },
                     },
-                },
-            ],
-            else_item: Some(GenerateBody {
-                alternative_label: None,
-                decl: None,
-                statements: vec![],
-            }),
+                ],
+                else_item: Some(GenerateBody {
+                    alternative_label: None,
+                    decl: None,
+                    statements: vec![],
+                    end_label_pos: None,
+                    source_range: code.s("generate", 3).pos().combine(&code.s1("end")),
+                }),
+            },
+            end_label_pos: None,
         };
         let stmt = code.with_stream_no_diagnostics(parse_labeled_concurrent_statement);
         assert_eq!(stmt.label, Some(code.s1("gen").ident()));
-        assert_eq!(stmt.statement, ConcurrentStatement::IfGenerate(gen));
+        assert_eq!(stmt.statement.item, ConcurrentStatement::IfGenerate(gen));
     }
     #[test]
     fn test_if_elseif_else_generate() {
@@ -1560,33 +1711,42 @@ end generate;
 ",
         );
         let gen = IfGenerateStatement {
-            conditionals: vec![
-                Conditional {
-                    condition: code.s1("cond = true").expr(),
-                    item: GenerateBody {
-                        alternative_label: None,
-                        decl: Some(code.s1("variable v1 : boolean;").declarative_part()),
-                        statements: vec![code.s1("foo1(clk);").concurrent_statement()],
+            conds: Conditionals {
+                conditionals: vec![
+                    Conditional {
+                        condition: code.s1("cond = true").expr(),
+                        item: GenerateBody {
+                            alternative_label: None,
+                            decl: Some(code.s1("variable v1 : boolean;").declarative_part()),
+                            statements: vec![code.s1("foo1(clk);").concurrent_statement()],
+                            end_label_pos: None,
+                            source_range: code.between("generate", "elsif").pos(),
+                        },
                     },
-                },
-                Conditional {
-                    condition: code.s1("cond2 = true").expr(),
-                    item: GenerateBody {
-                        alternative_label: None,
-                        decl: Some(code.s1("variable v2 : boolean;").declarative_part()),
-                        statements: vec![code.s1("foo2(clk);").concurrent_statement()],
+                    Conditional {
+                        condition: code.s1("cond2 = true").expr(),
+                        item: GenerateBody {
+                            alternative_label: None,
+                            decl: Some(code.s1("variable v2 : boolean;").declarative_part()),
+                            statements: vec![code.s1("foo2(clk);").concurrent_statement()],
+                            end_label_pos: None,
+                            source_range: code.s("generate", 2).pos().combine(&code.s1("else")),
+                        },
                     },
-                },
-            ],
-            else_item: Some(GenerateBody {
-                alternative_label: None,
-                decl: Some(code.s1("variable v3 : boolean;").declarative_part()),
-                statements: vec![code.s1("foo3(clk);").concurrent_statement()],
-            }),
+                ],
+                else_item: Some(GenerateBody {
+                    alternative_label: None,
+                    decl: Some(code.s1("variable v3 : boolean;").declarative_part()),
+                    statements: vec![code.s1("foo3(clk);").concurrent_statement()],
+                    end_label_pos: None,
+                    source_range: code.s("generate", 3).pos().combine(&code.s1("end")),
+                }),
+            },
+            end_label_pos: None,
         };
         let stmt = code.with_stream_no_diagnostics(parse_labeled_concurrent_statement);
         assert_eq!(stmt.label, Some(code.s1("gen").ident()));
-        assert_eq!(stmt.statement, ConcurrentStatement::IfGenerate(gen));
+        assert_eq!(stmt.statement.item, ConcurrentStatement::IfGenerate(gen));
     }
 
     #[test]
@@ -1601,33 +1761,42 @@ end generate;
 ",
         );
         let gen = IfGenerateStatement {
-            conditionals: vec![
-                Conditional {
-                    condition: code.s1("cond = true").expr(),
-                    item: GenerateBody {
-                        alternative_label: Some(code.s1("alt1").ident()),
-                        decl: None,
-                        statements: vec![],
+            conds: Conditionals {
+                conditionals: vec![
+                    Conditional {
+                        condition: code.s1("cond = true").expr(),
+                        item: GenerateBody {
+                            alternative_label: Some(code.s1("alt1").ident()),
+                            decl: None,
+                            statements: vec![],
+                            end_label_pos: None,
+                            source_range: code.between("generate", "elsif").pos(),
+                        },
                     },
-                },
-                Conditional {
-                    condition: code.s1("cond2 = true").expr(),
-                    item: GenerateBody {
-                        alternative_label: Some(code.s1("alt2").ident()),
-                        decl: None,
-                        statements: vec![],
+                    Conditional {
+                        condition: code.s1("cond2 = true").expr(),
+                        item: GenerateBody {
+                            alternative_label: Some(code.s1("alt2").ident()),
+                            decl: None,
+                            statements: vec![],
+                            end_label_pos: None,
+                            source_range: code.s("generate", 2).pos().combine(&code.s1("else")),
+                        },
                     },
-                },
-            ],
-            else_item: Some(GenerateBody {
-                alternative_label: Some(code.s1("alt3").ident()),
-                decl: None,
-                statements: vec![],
-            }),
+                ],
+                else_item: Some(GenerateBody {
+                    alternative_label: Some(code.s1("alt3").ident()),
+                    decl: None,
+                    statements: vec![],
+                    end_label_pos: None,
+                    source_range: code.s("generate", 3).pos().combine(&code.s("end", 2)),
+                }),
+            },
+            end_label_pos: None,
         };
         let (stmt, diagnostics) = code.with_stream_diagnostics(parse_labeled_concurrent_statement);
         assert_eq!(stmt.label, Some(code.s1("gen").ident()));
-        assert_eq!(stmt.statement, ConcurrentStatement::IfGenerate(gen));
+        assert_eq!(stmt.statement.item, ConcurrentStatement::IfGenerate(gen));
         assert_eq!(
             diagnostics,
             vec![Diagnostic::error(
@@ -1650,33 +1819,42 @@ end generate;
 ",
         );
         let gen = IfGenerateStatement {
-            conditionals: vec![
-                Conditional {
-                    condition: code.s1("cond = true").expr(),
-                    item: GenerateBody {
-                        alternative_label: Some(code.s1("alt1").ident()),
-                        decl: None,
-                        statements: vec![],
+            conds: Conditionals {
+                conditionals: vec![
+                    Conditional {
+                        condition: code.s1("cond = true").expr(),
+                        item: GenerateBody {
+                            alternative_label: Some(code.s1("alt1").ident()),
+                            decl: None,
+                            statements: vec![],
+                            end_label_pos: Some(code.s("alt1", 2).pos()),
+                            source_range: code.between("generate", "elsif").pos(),
+                        },
                     },
-                },
-                Conditional {
-                    condition: code.s1("cond2 = true").expr(),
-                    item: GenerateBody {
-                        alternative_label: Some(code.s1("alt2").ident()),
-                        decl: None,
-                        statements: vec![],
+                    Conditional {
+                        condition: code.s1("cond2 = true").expr(),
+                        item: GenerateBody {
+                            alternative_label: Some(code.s1("alt2").ident()),
+                            decl: None,
+                            statements: vec![],
+                            end_label_pos: Some(code.s("alt2", 2).pos()),
+                            source_range: code.s("generate", 2).pos().combine(&code.s1("else")),
+                        },
                     },
-                },
-            ],
-            else_item: Some(GenerateBody {
-                alternative_label: Some(code.s1("alt3").ident()),
-                decl: None,
-                statements: vec![],
-            }),
+                ],
+                else_item: Some(GenerateBody {
+                    alternative_label: Some(code.s1("alt3").ident()),
+                    decl: None,
+                    statements: vec![],
+                    end_label_pos: Some(code.s("alt3", 2).pos()),
+                    source_range: code.s("generate", 3).pos().combine(&code.s("end", 4)),
+                }),
+            },
+            end_label_pos: None,
         };
         let stmt = code.with_stream_no_diagnostics(parse_labeled_concurrent_statement);
         assert_eq!(stmt.label, Some(code.s1("gen").ident()));
-        assert_eq!(stmt.statement, ConcurrentStatement::IfGenerate(gen));
+        assert_eq!(stmt.statement.item, ConcurrentStatement::IfGenerate(gen));
     }
 
     #[test]
@@ -1692,29 +1870,36 @@ end generate;
 ",
         );
         let gen = CaseGenerateStatement {
-            expression: code.s1("expr(0) + 2").expr(),
-            alternatives: vec![
-                Alternative {
-                    choices: code.s1("1 | 2").choices(),
-                    item: GenerateBody {
-                        alternative_label: None,
-                        decl: None,
-                        statements: vec![code.s1("sig <= value;").concurrent_statement()],
+            sels: Selection {
+                expression: code.s1("expr(0) + 2").expr(),
+                alternatives: vec![
+                    Alternative {
+                        choices: code.s1("1 | 2").choices(),
+                        item: GenerateBody {
+                            alternative_label: None,
+                            decl: None,
+                            statements: vec![code.s1("sig <= value;").concurrent_statement()],
+                            end_label_pos: None,
+                            source_range: code.s1("=>").pos().combine(&code.s("when", 2)),
+                        },
                     },
-                },
-                Alternative {
-                    choices: code.s1("others").choices(),
-                    item: GenerateBody {
-                        alternative_label: None,
-                        decl: None,
-                        statements: vec![code.s1("foo(clk);").concurrent_statement()],
+                    Alternative {
+                        choices: code.s1("others").choices(),
+                        item: GenerateBody {
+                            alternative_label: None,
+                            decl: None,
+                            statements: vec![code.s1("foo(clk);").concurrent_statement()],
+                            end_label_pos: None,
+                            source_range: code.s("=>", 2).pos().combine(&code.s1("end")),
+                        },
                     },
-                },
-            ],
+                ],
+            },
+            end_label_pos: None,
         };
         let stmt = code.with_stream_no_diagnostics(parse_labeled_concurrent_statement);
         assert_eq!(stmt.label, Some(code.s1("gen").ident()));
-        assert_eq!(stmt.statement, ConcurrentStatement::CaseGenerate(gen));
+        assert_eq!(stmt.statement.item, ConcurrentStatement::CaseGenerate(gen));
     }
 
     #[test]
@@ -1730,28 +1915,35 @@ end generate;
 ",
         );
         let gen = CaseGenerateStatement {
-            expression: code.s1("expr(0) + 2").expr(),
-            alternatives: vec![
-                Alternative {
-                    choices: code.s1("1 | 2").choices(),
-                    item: GenerateBody {
-                        alternative_label: Some(code.s1("alt1").ident()),
-                        decl: None,
-                        statements: vec![code.s1("sig <= value;").concurrent_statement()],
+            sels: Selection {
+                expression: code.s1("expr(0) + 2").expr(),
+                alternatives: vec![
+                    Alternative {
+                        choices: code.s1("1 | 2").choices(),
+                        item: GenerateBody {
+                            alternative_label: Some(code.s1("alt1").ident()),
+                            decl: None,
+                            statements: vec![code.s1("sig <= value;").concurrent_statement()],
+                            end_label_pos: None,
+                            source_range: code.s1("=>").pos().combine(&code.s("when", 2)),
+                        },
                     },
-                },
-                Alternative {
-                    choices: code.s1("others").choices(),
-                    item: GenerateBody {
-                        alternative_label: Some(code.s1("alt2").ident()),
-                        decl: None,
-                        statements: vec![code.s1("foo(clk);").concurrent_statement()],
+                    Alternative {
+                        choices: code.s1("others").choices(),
+                        item: GenerateBody {
+                            alternative_label: Some(code.s1("alt2").ident()),
+                            decl: None,
+                            statements: vec![code.s1("foo(clk);").concurrent_statement()],
+                            end_label_pos: None,
+                            source_range: code.s("=>", 2).pos().combine(&code.s1("end")),
+                        },
                     },
-                },
-            ],
+                ],
+            },
+            end_label_pos: None,
         };
         let stmt = code.with_stream_no_diagnostics(parse_labeled_concurrent_statement);
         assert_eq!(stmt.label, Some(code.s1("gen").ident()));
-        assert_eq!(stmt.statement, ConcurrentStatement::CaseGenerate(gen));
+        assert_eq!(stmt.statement.item, ConcurrentStatement::CaseGenerate(gen));
     }
 }
@@ -4,8 +4,8 @@
 //
 // Copyright (c) 2018, Olof Kraigher olof.kraigher@gmail.com
 
-use super::common::error_on_end_identifier_mismatch;
 use super::common::ParseResult;
+use super::common::{error_on_end_identifier_mismatch, matching_end_ident_pos};
 use super::concurrent_statement::parse_generic_and_port_map;
 use super::context::parse_use_clause_no_keyword;
 use super::names::{parse_name, parse_name_initial_token, parse_selected_name};
@@ -268,7 +268,7 @@ pub fn parse_configuration_declaration(
     stream: &mut TokenStream,
     diagnostics: &mut dyn DiagnosticHandler,
 ) -> ParseResult<ConfigurationDeclaration> {
-    stream.expect_kind(Configuration)?;
+    let start_token = stream.expect_kind(Configuration)?;
     let ident = stream.expect_ident()?;
     stream.expect_kind(Of)?;
     let entity_name = parse_selected_name(stream)?;
@@ -301,7 +301,8 @@ pub fn parse_configuration_declaration(
     if let Some(diagnostic) = error_on_end_identifier_mismatch(&ident, &end_ident) {
         diagnostics.push(diagnostic)
     }
-    stream.expect_kind(SemiColon)?;
+    let end_ident_pos = matching_end_ident_pos(&ident, end_ident);
+    let semi_token = stream.expect_kind(SemiColon)?;
     Ok(ConfigurationDeclaration {
         context_clause: ContextClause::default(),
         ident,
@@ -309,6 +310,8 @@ pub fn parse_configuration_declaration(
         decl,
         vunit_bind_inds,
         block_config,
+        end_ident_pos,
+        source_range: start_token.pos.combine_into(&semi_token),
     })
 }
 
@@ -375,7 +378,9 @@ end;
                     block_spec: code.s1("rtl(0)").name(),
                     use_clauses: vec![],
                     items: vec![],
-                }
+                },
+                end_ident_pos: None,
+                source_range: code.between("configuration", "end;").pos(),
             }
         );
     }
@@ -402,7 +407,11 @@ end configuration cfg;
                     block_spec: code.s1("rtl(0)").name(),
                     use_clauses: vec![],
                     items: vec![],
-                }
+                },
+                source_range: code
+                    .between("configuration", "end configuration cfg;")
+                    .pos(),
+                end_ident_pos: Some(code.s("cfg", 2).pos()),
             }
         );
     }
@@ -433,7 +442,11 @@ end configuration cfg;
                     block_spec: code.s1("rtl(0)").name(),
                     use_clauses: vec![],
                     items: vec![],
-                }
+                },
+                source_range: code
+                    .between("configuration", "end configuration cfg;")
+                    .pos(),
+                end_ident_pos: Some(code.s("cfg", 2).pos()),
             }
         );
     }
@@ -466,7 +479,11 @@ end configuration cfg;
                     block_spec: code.s1("rtl(0)").name(),
                     use_clauses: vec![],
                     items: vec![],
-                }
+                },
+                source_range: code
+                    .between("configuration", "end configuration cfg;")
+                    .pos(),
+                end_ident_pos: Some(code.s("cfg", 2).pos()),
             }
         );
     }
@@ -493,7 +510,11 @@ end configuration cfg;
                     block_spec: code.s1("rtl(0)").name(),
                     use_clauses: vec![],
                     items: vec![],
-                }
+                },
+                source_range: code
+                    .between("configuration", "end configuration cfg;")
+                    .pos(),
+                end_ident_pos: Some(code.s("cfg", 2).pos()),
             }
         );
     }
@@ -535,7 +556,11 @@ end configuration cfg;
                             items: vec![],
                         })
                     ],
-                }
+                },
+                source_range: code
+                    .between("configuration", "end configuration cfg;")
+                    .pos(),
+                end_ident_pos: Some(code.s("cfg", 2).pos()),
             }
         );
     }
@@ -580,7 +605,11 @@ end configuration cfg;
                             items: vec![],
                         }),
                     }),],
-                }
+                },
+                source_range: code
+                    .between("configuration", "end configuration cfg;")
+                    .pos(),
+                end_ident_pos: Some(code.s("cfg", 2).pos()),
             }
         );
     }
@@ -636,7 +665,11 @@ end configuration cfg;
                             items: vec![],
                         }),
                     }),],
-                }
+                },
+                source_range: code
+                    .between("configuration", "end configuration cfg;")
+                    .pos(),
+                end_ident_pos: Some(code.s("cfg", 2).pos()),
             }
         );
     }
@@ -683,7 +716,11 @@ end configuration cfg;
                         vunit_bind_inds: Vec::new(),
                         block_config: None,
                     }),],
-                }
+                },
+                source_range: code
+                    .between("configuration", "end configuration cfg;")
+                    .pos(),
+                end_ident_pos: Some(code.s("cfg", 2).pos()),
             }
         );
     }
@@ -761,7 +798,11 @@ end configuration cfg;
                             block_config: None,
                         })
                     ],
-                }
+                },
+                source_range: code
+                    .between("configuration", "end configuration cfg;")
+                    .pos(),
+                end_ident_pos: Some(code.s("cfg", 2).pos()),
             }
         );
     }
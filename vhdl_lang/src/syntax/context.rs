@@ -4,8 +4,8 @@
 //
 // Copyright (c) 2018, Olof Kraigher olof.kraigher@gmail.com
 
-use super::common::error_on_end_identifier_mismatch;
 use super::common::ParseResult;
+use super::common::{error_on_end_identifier_mismatch, matching_end_ident_pos};
 use super::names::parse_name;
 use super::tokens::{Kind::*, Token, TokenStream};
 use crate::ast::*;
@@ -94,6 +94,7 @@ pub fn parse_context(
     if stream.skip_if_kind(Is)? {
         let mut items = Vec::with_capacity(16);
         let end_ident;
+        let semi_token;
         loop {
             let token = stream.expect()?;
             try_token_kind!(
@@ -104,7 +105,7 @@ pub fn parse_context(
                 End => {
                     stream.pop_if_kind(Context)?;
                     end_ident = stream.pop_optional_ident()?;
-                    stream.expect_kind(SemiColon)?;
+                    semi_token = stream.expect_kind(SemiColon)?;
                     break;
                 }
             )
@@ -113,10 +114,13 @@ pub fn parse_context(
         let ident = to_simple_name(name)?;
 
         diagnostics.push_some(error_on_end_identifier_mismatch(&ident, &end_ident));
+        let end_ident_pos = matching_end_ident_pos(&ident, end_ident);
 
         Ok(DeclarationOrReference::Declaration(ContextDeclaration {
             ident,
             items,
+            end_ident_pos,
+            source_range: context_token.pos.combine_into(&semi_token),
         }))
     } else {
         // Context reference
@@ -229,30 +233,49 @@ mod tests {
     #[test]
     fn test_context_clause() {
         let variants = vec![
-            &"\
+            (
+                &"\
 context ident is
 end;
 ",
-            &"\
+                false,
+            ),
+            (
+                &"\
 context ident is
 end context;
 ",
-            &"\
+                false,
+            ),
+            (
+                &"\
 context ident is
 end ident;
 ",
-            &"\
+                true,
+            ),
+            (
+                &"\
 context ident is
 end context ident;
 ",
+                true,
+            ),
         ];
-        for variant in variants {
+        for (variant, has_end_ident) in variants {
             let code = Code::new(variant);
+            let end_ident_pos = if has_end_ident {
+                Some(code.s("ident", 2).pos())
+            } else {
+                None
+            };
             assert_eq!(
                 code.with_stream_no_diagnostics(parse_context),
                 DeclarationOrReference::Declaration(ContextDeclaration {
                     ident: code.s1("ident").ident(),
-                    items: vec![]
+                    items: vec![],
+                    end_ident_pos,
+                    source_range: code.s1(variant.trim_end()).pos(),
                 })
             );
         }
@@ -278,7 +301,9 @@ end context ident2;
             context,
             DeclarationOrReference::Declaration(ContextDeclaration {
                 ident: code.s1("ident").ident(),
-                items: vec![]
+                items: vec![],
+                end_ident_pos: None,
+                source_range: code.between("context", "ident2;").pos(),
             })
         );
     }
@@ -317,7 +342,9 @@ end context;
                         }),
                         code.s1("context foo.ctx;")
                     ),
-                ]
+                ],
+                end_ident_pos: None,
+                source_range: code.between("context ident", "end context;").pos(),
             })
         )
     }
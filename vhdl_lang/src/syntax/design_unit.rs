@@ -6,8 +6,8 @@
 
 use super::tokens::{Kind::*, TokenStream};
 
-use super::common::error_on_end_identifier_mismatch;
 use super::common::ParseResult;
+use super::common::{error_on_end_identifier_mismatch, matching_end_ident_pos};
 use super::component_declaration::{parse_optional_generic_list, parse_optional_port_list};
 use super::concurrent_statement::parse_labeled_concurrent_statements;
 use super::configuration::parse_configuration_declaration;
@@ -27,7 +27,7 @@ pub fn parse_entity_declaration(
     stream: &mut TokenStream,
     diagnostics: &mut dyn DiagnosticHandler,
 ) -> ParseResult<EntityDeclaration> {
-    stream.expect_kind(Entity)?;
+    let start_token = stream.expect_kind(Entity)?;
 
     let ident = stream.expect_ident()?;
     stream.expect_kind(Is)?;
@@ -48,7 +48,8 @@ pub fn parse_entity_declaration(
     if let Some(diagnostic) = error_on_end_identifier_mismatch(&ident, &end_ident) {
         diagnostics.push(diagnostic);
     }
-    stream.expect_kind(SemiColon)?;
+    let end_ident_pos = matching_end_ident_pos(&ident, end_ident);
+    let semi_token = stream.expect_kind(SemiColon)?;
     Ok(EntityDeclaration {
         context_clause: ContextClause::default(),
         ident,
@@ -56,6 +57,8 @@ pub fn parse_entity_declaration(
         port_clause,
         decl,
         statements,
+        end_ident_pos,
+        source_range: start_token.pos.combine_into(&semi_token),
     })
 }
 
@@ -64,7 +67,7 @@ pub fn parse_architecture_body(
     stream: &mut TokenStream,
     diagnostics: &mut dyn DiagnosticHandler,
 ) -> ParseResult<ArchitectureBody> {
-    stream.expect_kind(Architecture)?;
+    let start_token = stream.expect_kind(Architecture)?;
     let ident = stream.expect_ident()?;
     stream.expect_kind(Of)?;
     let entity_name = stream.expect_ident()?;
@@ -79,8 +82,9 @@ pub fn parse_architecture_body(
     if let Some(diagnostic) = error_on_end_identifier_mismatch(&ident, &end_ident) {
         diagnostics.push(diagnostic);
     }
+    let end_ident_pos = matching_end_ident_pos(&ident, end_ident);
 
-    stream.expect_kind(SemiColon)?;
+    let semi_token = stream.expect_kind(SemiColon)?;
 
     Ok(ArchitectureBody {
         context_clause: ContextClause::default(),
@@ -88,6 +92,8 @@ pub fn parse_architecture_body(
         entity_name: entity_name.into_ref(),
         decl,
         statements,
+        end_ident_pos,
+        source_range: start_token.pos.combine_into(&semi_token),
     })
 }
 
@@ -96,7 +102,7 @@ pub fn parse_package_declaration(
     stream: &mut TokenStream,
     diagnostics: &mut dyn DiagnosticHandler,
 ) -> ParseResult<PackageDeclaration> {
-    stream.expect_kind(Package)?;
+    let start_token = stream.expect_kind(Package)?;
     let ident = stream.expect_ident()?;
 
     stream.expect_kind(Is)?;
@@ -115,13 +121,16 @@ pub fn parse_package_declaration(
     if let Some(diagnostic) = error_on_end_identifier_mismatch(&ident, &end_ident) {
         diagnostics.push(diagnostic);
     }
+    let end_ident_pos = matching_end_ident_pos(&ident, end_ident);
     stream.pop_if_kind(Identifier)?;
-    stream.expect_kind(SemiColon)?;
+    let semi_token = stream.expect_kind(SemiColon)?;
     Ok(PackageDeclaration {
         context_clause: ContextClause::default(),
         ident,
         generic_clause,
         decl,
+        end_ident_pos,
+        source_range: start_token.pos.combine_into(&semi_token),
     })
 }
 
@@ -130,7 +139,7 @@ pub fn parse_package_body(
     stream: &mut TokenStream,
     diagnostics: &mut dyn DiagnosticHandler,
 ) -> ParseResult<PackageBody> {
-    stream.expect_kind(Package)?;
+    let start_token = stream.expect_kind(Package)?;
     stream.expect_kind(Body)?;
     let ident = stream.expect_ident()?;
 
@@ -143,12 +152,15 @@ pub fn parse_package_body(
     if let Some(diagnostic) = error_on_end_identifier_mismatch(&ident, &end_ident) {
         diagnostics.push(diagnostic);
     }
-    stream.expect_kind(SemiColon)?;
+    let end_ident_pos = matching_end_ident_pos(&ident, end_ident);
+    let semi_token = stream.expect_kind(SemiColon)?;
 
     Ok(PackageBody {
         context_clause: ContextClause::default(),
         ident: ident.into_ref(),
         decl,
+        end_ident_pos,
+        source_range: start_token.pos.combine_into(&semi_token),
     })
 }
 
@@ -309,7 +321,11 @@ mod tests {
     }
 
     /// An simple entity with only a name
-    fn simple_entity(ident: Ident) -> AnyDesignUnit {
+    fn simple_entity(
+        ident: Ident,
+        source_range: SrcPos,
+        end_ident_pos: Option<SrcPos>,
+    ) -> AnyDesignUnit {
         AnyDesignUnit::Primary(AnyPrimaryUnit::Entity(EntityDeclaration {
             context_clause: ContextClause::default(),
             ident,
@@ -317,6 +333,8 @@ mod tests {
             port_clause: None,
             decl: vec![],
             statements: vec![],
+            end_ident_pos,
+            source_range,
         }))
     }
 
@@ -330,7 +348,11 @@ end entity;
         );
         assert_eq!(
             design_file.design_units,
-            [simple_entity(code.s1("myent").ident())]
+            [simple_entity(
+                code.s1("myent").ident(),
+                code.between("entity", ";").pos(),
+                None
+            )]
         );
 
         let (code, design_file) = parse_ok(
@@ -341,7 +363,11 @@ end entity myent;
         );
         assert_eq!(
             design_file.design_units,
-            [simple_entity(code.s1("myent").ident())]
+            [simple_entity(
+                code.s1("myent").ident(),
+                code.between("entity", ";").pos(),
+                Some(code.s("myent", 2).pos())
+            )]
         );
     }
 
@@ -363,6 +389,8 @@ end entity;
                 port_clause: None,
                 decl: vec![],
                 statements: vec![],
+                end_ident_pos: None,
+                source_range: code.between("entity", "end entity;").pos(),
             }
         );
     }
@@ -390,6 +418,8 @@ end entity;
                 port_clause: None,
                 decl: vec![],
                 statements: vec![],
+                end_ident_pos: None,
+                source_range: code.between("entity", "end entity;").pos(),
             }
         );
     }
@@ -412,6 +442,8 @@ end entity;
                 port_clause: Some(vec![]),
                 decl: vec![],
                 statements: vec![],
+                end_ident_pos: None,
+                source_range: code.between("entity", "end entity;").pos(),
             }
         );
     }
@@ -434,6 +466,8 @@ end entity;
                 port_clause: None,
                 decl: vec![],
                 statements: vec![],
+                end_ident_pos: None,
+                source_range: code.between("entity", "end entity;").pos(),
             }
         );
     }
@@ -456,6 +490,8 @@ end entity;
                 port_clause: None,
                 decl: code.s1("constant foo : natural := 0;").declarative_part(),
                 statements: vec![],
+                end_ident_pos: None,
+                source_range: code.between("entity", "end entity;").pos(),
             }
         );
     }
@@ -479,6 +515,8 @@ end entity;
                 port_clause: None,
                 decl: vec![],
                 statements: vec![code.s1("check(clk, valid);").concurrent_statement()],
+                end_ident_pos: None,
+                source_range: code.between("entity", "end entity;").pos(),
             }
         );
     }
@@ -503,22 +541,45 @@ end;
         assert_eq!(
             design_file.design_units,
             [
-                simple_entity(code.s1("myent").ident()),
-                simple_entity(code.s1("myent2").ident()),
-                simple_entity(code.s1("myent3").ident()),
-                simple_entity(code.s1("myent4").ident())
+                simple_entity(
+                    code.s1("myent").ident(),
+                    code.between("entity myent is", ";").pos(),
+                    None
+                ),
+                simple_entity(
+                    code.s1("myent2").ident(),
+                    code.between("entity myent2", ";").pos(),
+                    Some(code.s("myent2", 2).pos())
+                ),
+                simple_entity(
+                    code.s1("myent3").ident(),
+                    code.between("entity myent3", ";").pos(),
+                    Some(code.s("myent3", 2).pos())
+                ),
+                simple_entity(
+                    code.s1("myent4").ident(),
+                    code.between("entity myent4", ";").pos(),
+                    None
+                )
             ]
         );
     }
 
     // An simple entity with only a name
-    fn simple_architecture(ident: Ident, entity_name: Ident) -> AnyDesignUnit {
+    fn simple_architecture(
+        ident: Ident,
+        entity_name: Ident,
+        source_range: SrcPos,
+        end_ident_pos: Option<SrcPos>,
+    ) -> AnyDesignUnit {
         AnyDesignUnit::Secondary(AnySecondaryUnit::Architecture(ArchitectureBody {
             context_clause: ContextClause::default(),
             ident,
             entity_name: entity_name.into_ref(),
             decl: Vec::new(),
             statements: vec![],
+            end_ident_pos,
+            source_range,
         }))
     }
 
@@ -535,7 +596,9 @@ end architecture;
             design_file.design_units,
             [simple_architecture(
                 code.s1("arch_name").ident(),
-                code.s1("myent").ident()
+                code.s1("myent").ident(),
+                code.between("architecture", ";").pos(),
+                None
             )]
         );
     }
@@ -553,7 +616,9 @@ end architecture arch_name;
             design_file.design_units,
             [simple_architecture(
                 code.s1("arch_name").ident(),
-                code.s1("myent").ident()
+                code.s1("myent").ident(),
+                code.between("architecture", ";").pos(),
+                Some(code.s("arch_name", 2).pos())
             )]
         );
     }
@@ -571,7 +636,9 @@ end;
             design_file.design_units,
             [simple_architecture(
                 code.s1("arch_name").ident(),
-                code.s1("myent").ident()
+                code.s1("myent").ident(),
+                code.between("architecture", ";").pos(),
+                None
             )]
         );
     }
@@ -591,6 +658,8 @@ end package;
                 ident: code.s1("pkg_name").ident(),
                 generic_clause: None,
                 decl: vec![],
+                end_ident_pos: None,
+                source_range: code.between("package", "end package;").pos(),
             }
         );
     }
@@ -617,6 +686,8 @@ end package;
   constant bar : natural := 0;
 ")
                     .declarative_part(),
+                end_ident_pos: None,
+                source_range: code.between("package", "end package;").pos(),
             }
         );
     }
@@ -642,7 +713,9 @@ end package;
                     code.s1("type foo").generic(),
                     code.s1("type bar").generic()
                 ]),
-                decl: vec![]
+                decl: vec![],
+                end_ident_pos: None,
+                source_range: code.between("package", "end package;").pos(),
             }
         );
     }
@@ -676,6 +749,8 @@ end entity;
                         port_clause: None,
                         decl: vec![],
                         statements: vec![],
+                        end_ident_pos: None,
+                        source_range: code.between("entity", "end entity;").pos(),
                     }
                 ))]
             }
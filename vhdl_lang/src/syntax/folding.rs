@@ -0,0 +1,522 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2020, Olof Kraigher olof.kraigher@gmail.com
+
+//! Folding ranges of a source computed from its tokens
+//!
+//! Constructs are matched by their initial keyword and the `end` which closes them
+//! such that folding also works for code which does not parse while being edited.
+
+use super::tokens::{Comment, Kind, Kind::*, Symbols, Token, Tokenizer};
+use crate::data::*;
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum FoldingRangeKind {
+    Comment,
+    Region,
+}
+
+/// A range of lines which may be folded, the start line remains visible when folded
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct FoldingRange {
+    pub start_line: u32,
+    pub end_line: u32,
+    pub kind: FoldingRangeKind,
+}
+
+pub fn folding_ranges(symbols: &Symbols, source: &Source) -> Vec<FoldingRange> {
+    let contents = source.contents();
+    let mut tokenizer = Tokenizer::new(symbols, source, ContentReader::new(&contents));
+    let mut tokens = Vec::new();
+
+    loop {
+        match tokenizer.pop() {
+            Ok(Some(token)) => tokens.push(token),
+            Ok(None) => break,
+            // Ignore illegal tokens, the rest of the source is still folded
+            Err(_) => {}
+        }
+    }
+
+    let mut folder = Folder::new(&tokens);
+    folder.fold();
+
+    if let Some(comments) = tokenizer.get_final_comments() {
+        folder.add_comments(&comments);
+    }
+    folder.finish_comments();
+
+    let mut ranges = folder.ranges;
+    ranges.sort_by_key(|range| (range.start_line, std::cmp::Reverse(range.end_line)));
+    ranges
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+enum Construct {
+    /// A design unit, subprogram body or statement with a declarative part
+    WithDeclarativePart,
+    /// An if or case statement which may turn out to be a generate statement
+    IfOrCase,
+    Other,
+}
+
+struct Open {
+    construct: Construct,
+    kind: Kind,
+    line: u32,
+    /// The line of the first declaration within the declarative part
+    decl_line: Option<u32>,
+    begun: bool,
+}
+
+struct Folder<'a> {
+    tokens: &'a [Token],
+    stack: Vec<Open>,
+    /// The start line of an interface list and the parenthesis depth within it
+    interface_lists: Vec<(u32, usize)>,
+    paren_depth: usize,
+    /// The line of a for or while iteration scheme preceding a loop or generate
+    iteration_line: Option<u32>,
+    /// The first and last line of consecutive comments
+    comment_run: Option<(u32, u32)>,
+    ranges: Vec<FoldingRange>,
+}
+
+impl<'a> Folder<'a> {
+    fn new(tokens: &'a [Token]) -> Folder<'a> {
+        Folder {
+            tokens,
+            stack: Vec::new(),
+            interface_lists: Vec::new(),
+            paren_depth: 0,
+            iteration_line: None,
+            comment_run: None,
+            ranges: Vec::new(),
+        }
+    }
+
+    fn kind_at(&self, idx: usize) -> Option<Kind> {
+        self.tokens.get(idx).map(|token| token.kind)
+    }
+
+    fn add(&mut self, start_line: u32, end_line: u32, kind: FoldingRangeKind) {
+        if end_line > start_line {
+            self.ranges.push(FoldingRange {
+                start_line,
+                end_line,
+                kind,
+            });
+        }
+    }
+
+    fn push(&mut self, construct: Construct, kind: Kind, line: u32) {
+        self.stack.push(Open {
+            construct,
+            kind,
+            line,
+            decl_line: None,
+            begun: false,
+        });
+    }
+
+    fn in_configuration(&self) -> bool {
+        self.stack
+            .last()
+            .map(|open| matches!(open.kind, Configuration | For))
+            .unwrap_or(false)
+    }
+
+    fn in_declarative_part(&self) -> bool {
+        self.stack
+            .last()
+            .map(|open| open.construct == Construct::WithDeclarativePart && !open.begun)
+            .unwrap_or(false)
+    }
+
+    fn fold(&mut self) {
+        for (idx, token) in self.tokens.iter().enumerate() {
+            if let Some(ref comments) = token.comments {
+                self.add_comments(&comments.leading);
+            }
+            // Comments separated by code are not consecutive
+            self.finish_comments();
+            self.fold_token(idx, token);
+        }
+    }
+
+    fn fold_token(&mut self, idx: usize, token: &Token) {
+        let line = token.pos.start().line;
+        let prev = if idx > 0 { self.kind_at(idx - 1) } else { None };
+        let after_end = prev == Some(End);
+        // Keywords used as entity classes, instantiated units or bindings do not start a construct
+        let is_reference = matches!(prev, Some(End) | Some(Colon) | Some(Comma) | Some(Use));
+
+        if self.in_declarative_part() && self.paren_depth == 0 && is_declaration_keyword(token.kind)
+        {
+            if let Some(open) = self.stack.last_mut() {
+                open.decl_line.get_or_insert(line);
+            }
+        }
+
+        match token.kind {
+            Entity | Architecture if !is_reference => {
+                self.push(Construct::WithDeclarativePart, token.kind, line)
+            }
+            Configuration if !is_reference => self.push(Construct::Other, token.kind, line),
+            Package if !is_reference => {
+                let is_instance =
+                    self.kind_at(idx + 2) == Some(Is) && self.kind_at(idx + 3) == Some(New);
+                if !is_instance {
+                    self.push(Construct::WithDeclarativePart, token.kind, line);
+                }
+            }
+            Context if !is_reference && self.kind_at(idx + 2) == Some(Is) => {
+                self.push(Construct::Other, token.kind, line)
+            }
+            Component if !is_reference => self.push(Construct::Other, token.kind, line),
+            Function | Procedure if !is_reference && self.is_subprogram_body(idx) => {
+                self.push(Construct::WithDeclarativePart, token.kind, line)
+            }
+            Process | Block if !after_end => {
+                self.push(Construct::WithDeclarativePart, token.kind, line)
+            }
+            Record | Units | Protected if !after_end => {
+                self.push(Construct::Other, token.kind, line)
+            }
+            If | Case if !after_end => self.push(Construct::IfOrCase, token.kind, line),
+            Then | Is => {
+                if let Some(open) = self.stack.last_mut() {
+                    if open.construct == Construct::IfOrCase {
+                        open.construct = Construct::Other;
+                    }
+                }
+            }
+            For if self.in_configuration() => self.push(Construct::Other, token.kind, line),
+            For | While => self.iteration_line = Some(line),
+            Loop if !after_end => {
+                let line = self.iteration_line.take().unwrap_or(line);
+                self.push(Construct::Other, token.kind, line);
+            }
+            Generate if !after_end => {
+                if let Some(line) = self.iteration_line.take() {
+                    self.push(Construct::WithDeclarativePart, token.kind, line);
+                } else if let Some(open) = self.stack.last_mut() {
+                    // An if or case generate statement, alternatives do not start a construct
+                    if open.construct == Construct::IfOrCase {
+                        open.construct = Construct::WithDeclarativePart;
+                        open.kind = Generate;
+                    }
+                }
+            }
+            Begin => {
+                if let Some(open) = self.stack.last_mut() {
+                    if open.construct == Construct::WithDeclarativePart && !open.begun {
+                        open.begun = true;
+                        if let Some(decl_line) = open.decl_line {
+                            let end_line = line.saturating_sub(1);
+                            self.add(decl_line, end_line, FoldingRangeKind::Region);
+                        }
+                    }
+                }
+            }
+            End => self.fold_end(idx, line),
+            Generic | Port if self.kind_at(idx + 1) == Some(LeftPar) => {
+                self.interface_lists.push((line, self.paren_depth));
+            }
+            LeftPar => self.paren_depth += 1,
+            RightPar => {
+                self.paren_depth = self.paren_depth.saturating_sub(1);
+                if let Some((start_line, depth)) = self.interface_lists.last().cloned() {
+                    if depth == self.paren_depth {
+                        self.interface_lists.pop();
+                        self.add(start_line, line, FoldingRangeKind::Region);
+                    }
+                }
+            }
+            SemiColon => self.iteration_line = None,
+            _ => {}
+        }
+    }
+
+    fn fold_end(&mut self, idx: usize, line: u32) {
+        if let Some(open) = self.stack.last() {
+            // The alternatives of a generate statement may be closed by `end [label];`
+            if open.kind == Generate && self.kind_at(idx + 1) != Some(Generate) {
+                return;
+            }
+        }
+
+        if let Some(open) = self.stack.pop() {
+            // The declarative part of a package or an entity without statements ends here
+            if open.construct == Construct::WithDeclarativePart && !open.begun {
+                if let Some(decl_line) = open.decl_line {
+                    self.add(decl_line, line.saturating_sub(1), FoldingRangeKind::Region);
+                }
+            }
+            self.add(open.line, line, FoldingRangeKind::Region);
+        }
+    }
+
+    /// A subprogram specification followed by `is` outside of an interface list
+    /// excluding subprogram instantiations and interface subprogram defaults
+    fn is_subprogram_body(&self, idx: usize) -> bool {
+        if self.paren_depth > 0 {
+            return false;
+        }
+
+        let mut depth = 0;
+        for (offset, token) in self.tokens[idx + 1..].iter().enumerate() {
+            match token.kind {
+                LeftPar => depth += 1,
+                RightPar => depth -= 1,
+                SemiColon | Begin | End => return false,
+                Is if depth == 0 => {
+                    return !matches!(self.kind_at(idx + offset + 2), Some(New) | Some(BOX));
+                }
+                _ => {}
+            }
+        }
+        false
+    }
+
+    fn add_comments(&mut self, comments: &[Comment]) {
+        for comment in comments.iter() {
+            let start_line = comment.range.start.line;
+            let end_line = comment.range.end.line;
+
+            match self.comment_run {
+                Some((first_line, last_line)) if start_line <= last_line + 1 => {
+                    self.comment_run = Some((first_line, end_line));
+                }
+                _ => {
+                    self.finish_comments();
+                    self.comment_run = Some((start_line, end_line));
+                }
+            }
+        }
+    }
+
+    fn finish_comments(&mut self) {
+        if let Some((start_line, end_line)) = self.comment_run.take() {
+            self.add(start_line, end_line, FoldingRangeKind::Comment);
+        }
+    }
+}
+
+fn is_declaration_keyword(kind: Kind) -> bool {
+    matches!(
+        kind,
+        Type | Subtype
+            | Component
+            | Impure
+            | Pure
+            | Function
+            | Procedure
+            | Package
+            | For
+            | File
+            | Shared
+            | Constant
+            | Signal
+            | Variable
+            | Attribute
+            | Use
+            | Alias
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::syntax::test::Code;
+    use pretty_assertions::assert_eq;
+    use FoldingRangeKind::*;
+
+    fn folds(code: &str) -> Vec<(u32, u32, FoldingRangeKind)> {
+        let code = Code::new(code);
+        folding_ranges(&code.symbols, code.source())
+            .into_iter()
+            .map(|range| (range.start_line, range.end_line, range.kind))
+            .collect()
+    }
+
+    #[test]
+    fn folds_design_units_and_interface_lists() {
+        assert_eq!(
+            folds(
+                "\
+entity ent is
+  generic (
+    width : natural);
+  port (
+    clk : in bit;
+    dout : out bit);
+end entity;
+
+package pkg is new work.gpkg generic map (
+  width => 8);
+
+architecture a of ent is
+begin
+end architecture;
+"
+            ),
+            vec![
+                (0, 6, Region),
+                (1, 2, Region),
+                (3, 5, Region),
+                (11, 13, Region)
+            ]
+        );
+    }
+
+    #[test]
+    fn folds_declarative_parts() {
+        assert_eq!(
+            folds(
+                "\
+architecture a of ent is
+  signal s0 : bit;
+  signal s1 : bit;
+
+  function fun(arg : bit) return bit is
+    variable v : bit;
+  begin
+    return v;
+  end function;
+begin
+end architecture;
+"
+            ),
+            vec![(0, 10, Region), (1, 8, Region), (4, 8, Region)]
+        );
+    }
+
+    #[test]
+    fn folds_statements() {
+        assert_eq!(
+            folds(
+                "\
+architecture a of ent is
+begin
+  main : process (clk)
+  begin
+    if rising_edge(clk) then
+      case state is
+        when idle =>
+          null;
+        when others =>
+          null;
+      end case;
+    end if;
+    for i in 0 to 3 loop
+      wait for 1 ns;
+    end loop;
+  end process;
+
+  gen : for i in 0 to 3 generate
+    inst : entity work.ent
+      port map (
+        clk => clk);
+  end generate;
+
+  gen2 : if cond generate
+  elsif cond2 generate
+  else generate
+  end generate;
+
+  blk : block is
+    signal s : bit;
+  begin
+  end block;
+end architecture;
+"
+            ),
+            vec![
+                (0, 32, Region),
+                (2, 15, Region),
+                (4, 11, Region),
+                (5, 10, Region),
+                (12, 14, Region),
+                (17, 21, Region),
+                (23, 26, Region),
+                (28, 31, Region),
+            ]
+        );
+    }
+
+    #[test]
+    fn folds_record_types_and_subprogram_declarations() {
+        assert_eq!(
+            folds(
+                "\
+package pkg is
+  type rec_t is record
+    field : natural;
+  end record;
+
+  procedure proc(
+    arg : natural);
+  component comp is
+    port (
+      clk : in bit);
+  end component;
+end package;
+"
+            ),
+            vec![
+                (0, 11, Region),
+                (1, 10, Region),
+                (1, 3, Region),
+                (7, 10, Region),
+                (8, 9, Region),
+            ]
+        );
+    }
+
+    #[test]
+    fn folds_consecutive_comments() {
+        assert_eq!(
+            folds(
+                "\
+-- Header
+-- comment
+
+-- Not consecutive
+entity ent is -- Trailing
+  -- Inner
+  -- comment
+end entity;
+/* multi
+   line */
+"
+            ),
+            vec![
+                (0, 1, Comment),
+                (4, 7, Region),
+                (5, 6, Comment),
+                (8, 9, Comment),
+            ]
+        );
+    }
+
+    #[test]
+    fn folds_source_which_does_not_parse() {
+        assert_eq!(
+            folds(
+                "\
+architecture a of ent is
+begin
+  main : process
+  begin
+    if then
+    end if;
+  end process;
+"
+            ),
+            vec![(2, 6, Region), (4, 5, Region)]
+        );
+    }
+}
@@ -5,13 +5,14 @@
 // Copyright (c) 2018, Olof Kraigher olof.kraigher@gmail.com
 
 use super::design_unit::parse_design_file;
-use super::tokens::{Symbols, TokenStream, Tokenizer};
+use super::folding::{folding_ranges, FoldingRange};
+use super::tokens::{Kind, Symbols, TokenStream, Tokenizer};
 use crate::ast::DesignFile;
 use crate::data::*;
 use std::io;
 use std::sync::Arc;
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct VHDLParser {
     pub symbols: Arc<Symbols>,
 }
@@ -50,4 +51,52 @@ impl VHDLParser {
         let design_file = self.parse_design_source(&source, diagnostics);
         Ok((source, design_file))
     }
+
+    /// Find the ranges of lines which may be folded, the source does not need to parse
+    pub fn folding_ranges(&self, source: &Source) -> Vec<FoldingRange> {
+        folding_ranges(&self.symbols, source)
+    }
+
+    /// Returns true if the name is a single basic or extended identifier
+    /// Reserved words and surrounding whitespace or comments are not accepted
+    pub fn is_identifier(name: &str) -> bool {
+        // The name is tokenized with symbols of its own to not add it to the symbols of the design
+        let symbols = Symbols::default();
+        let source = Source::inline(Path::new("<identifier>"), name);
+        let contents = source.contents();
+        let mut tokenizer = Tokenizer::new(&symbols, &source, ContentReader::new(&contents));
+
+        match tokenizer.pop() {
+            Ok(Some(token)) => {
+                token.kind == Kind::Identifier
+                    && token.comments.is_none()
+                    && token.pos.range()
+                        == Range::new(
+                            Position::new(0, 0),
+                            Position::new(0, name.chars().count() as u32),
+                        )
+                    && matches!(tokenizer.pop(), Ok(None))
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_identifier() {
+        assert!(VHDLParser::is_identifier("foo"));
+        assert!(VHDLParser::is_identifier("Foo_1"));
+        assert!(VHDLParser::is_identifier("\\foo bar\\"));
+        assert!(!VHDLParser::is_identifier(""));
+        assert!(!VHDLParser::is_identifier("entity"));
+        assert!(!VHDLParser::is_identifier("1foo"));
+        assert!(!VHDLParser::is_identifier("foo bar"));
+        assert!(!VHDLParser::is_identifier(" foo"));
+        assert!(!VHDLParser::is_identifier("foo -- comment"));
+        assert!(!VHDLParser::is_identifier("lib.foo"));
+    }
 }
@@ -4,8 +4,8 @@
 //
 // Copyright (c) 2018, Olof Kraigher olof.kraigher@gmail.com
 
-use super::common::parse_optional;
 use super::common::ParseResult;
+use super::common::{check_end_label, parse_optional};
 use super::expression::{parse_aggregate_leftpar_known, parse_choices, parse_expression};
 use super::names::{parse_name, parse_name_initial_token};
 use super::range::parse_discrete_range;
@@ -87,10 +87,12 @@ pub fn parse_labeled_sequential_statements(
 /// LRM 10.8 If statement
 fn parse_if_statement_known_keyword(
     stream: &mut TokenStream,
+    label: Option<&Ident>,
     diagnostics: &mut dyn DiagnosticHandler,
 ) -> ParseResult<IfStatement> {
     let mut conditionals = Vec::new();
     let mut else_branch = None;
+    let end_label_pos;
 
     loop {
         let condition = parse_expression(stream)?;
@@ -117,8 +119,7 @@ fn parse_if_statement_known_keyword(
                     end_token,
                     End => {
                         stream.expect_kind(If)?;
-                        // @TODO check end label
-                        stream.pop_if_kind(Identifier)?;
+                        end_label_pos = check_end_label(label, stream.pop_optional_ident()?, diagnostics);
                         else_branch = Some(statements);
                         break;
                     }
@@ -126,7 +127,7 @@ fn parse_if_statement_known_keyword(
             },
             End => {
                 stream.expect_kind(If)?;
-                stream.pop_if_kind(Identifier)?;
+                end_label_pos = check_end_label(label, stream.pop_optional_ident()?, diagnostics);
                 conditionals.push(conditional);
                 break;
             }
@@ -135,14 +136,18 @@ fn parse_if_statement_known_keyword(
 
     stream.expect_kind(SemiColon)?;
     Ok(IfStatement {
-        conditionals,
-        else_item: else_branch,
+        conds: Conditionals {
+            conditionals,
+            else_item: else_branch,
+        },
+        end_label_pos,
     })
 }
 
 /// LRM 10.9 Case statement
 fn parse_case_statement_known_keyword(
     stream: &mut TokenStream,
+    label: Option<&Ident>,
     diagnostics: &mut dyn DiagnosticHandler,
 ) -> ParseResult<CaseStatement> {
     let is_matching = stream.pop_if_kind(Que)?.is_some();
@@ -150,6 +155,7 @@ fn parse_case_statement_known_keyword(
     stream.expect_kind(Is)?;
     stream.expect_kind(When)?;
     let mut alternatives = Vec::new();
+    let end_label_pos;
 
     loop {
         let choices = parse_choices(stream)?;
@@ -170,8 +176,7 @@ fn parse_case_statement_known_keyword(
                 if is_matching {
                     stream.expect_kind(Que)?;
                 }
-                // @TODO check end label
-                stream.pop_if_kind(Identifier)?;
+                end_label_pos = check_end_label(label, stream.pop_optional_ident()?, diagnostics);
                 alternatives.push(alternative);
                 break;
             }
@@ -183,6 +188,7 @@ fn parse_case_statement_known_keyword(
         is_matching,
         expression,
         alternatives,
+        end_label_pos,
     })
 }
 
@@ -190,6 +196,7 @@ fn parse_case_statement_known_keyword(
 fn parse_loop_statement_initial_token(
     stream: &mut TokenStream,
     token: &Token,
+    label: Option<&Ident>,
     diagnostics: &mut dyn DiagnosticHandler,
 ) -> ParseResult<LoopStatement> {
     let iteration_scheme = {
@@ -212,12 +219,11 @@ fn parse_loop_statement_initial_token(
     };
 
     let (statements, end_token) = parse_labeled_sequential_statements(stream, diagnostics)?;
-    try_token_kind!(
+    let end_label_pos = try_token_kind!(
         end_token,
         End => {
             stream.expect_kind(Loop)?;
-            // @TODO check end label
-            stream.pop_if_kind(Identifier)?;
+            check_end_label(label, stream.pop_optional_ident()?, diagnostics)
         }
     );
 
@@ -225,6 +231,7 @@ fn parse_loop_statement_initial_token(
     Ok(LoopStatement {
         iteration_scheme,
         statements,
+        end_label_pos,
     })
 }
 
@@ -525,6 +532,7 @@ fn parse_selected_assignment(stream: &mut TokenStream) -> ParseResult<Sequential
 fn parse_unlabeled_sequential_statement(
     stream: &mut TokenStream,
     token: Token,
+    label: Option<&Ident>,
     diagnostics: &mut dyn DiagnosticHandler,
 ) -> ParseResult<SequentialStatement> {
     let statement = {
@@ -533,10 +541,10 @@ fn parse_unlabeled_sequential_statement(
             Wait => SequentialStatement::Wait(parse_wait_statement_known_keyword(stream)?),
             Assert => SequentialStatement::Assert(parse_assert_statement_known_keyword(stream)?),
             Report => SequentialStatement::Report(parse_report_statement_known_keyword(stream)?),
-            If => SequentialStatement::If(parse_if_statement_known_keyword(stream, diagnostics)?),
-            Case => SequentialStatement::Case(parse_case_statement_known_keyword(stream, diagnostics)?),
+            If => SequentialStatement::If(parse_if_statement_known_keyword(stream, label, diagnostics)?),
+            Case => SequentialStatement::Case(parse_case_statement_known_keyword(stream, label, diagnostics)?),
             For | Loop | While => {
-                SequentialStatement::Loop(parse_loop_statement_initial_token(stream, &token, diagnostics)?)
+                SequentialStatement::Loop(parse_loop_statement_initial_token(stream, &token, label, diagnostics)?)
             },
             Next => SequentialStatement::Next(parse_next_statement_known_keyword(stream)?),
             Exit => SequentialStatement::Exit(parse_exit_statement_known_keyword(stream)?),
@@ -572,27 +580,33 @@ pub fn parse_sequential_statement_initial_token(
     token: Token,
     diagnostics: &mut dyn DiagnosticHandler,
 ) -> ParseResult<LabeledSequentialStatement> {
+    let start = token.pos.clone();
     if token.kind == Identifier {
         let name = parse_name_initial_token(stream, token)?;
         let token = stream.expect()?;
         if token.kind == Colon {
             let label = Some(to_simple_name(name)?);
             let token = stream.expect()?;
-            let statement = parse_unlabeled_sequential_statement(stream, token, diagnostics)?;
-            Ok(LabeledSequentialStatement { label, statement })
+            let start = token.pos.clone();
+            let statement =
+                parse_unlabeled_sequential_statement(stream, token, label.as_ref(), diagnostics)?;
+            Ok(LabeledSequentialStatement {
+                label,
+                statement: WithPos::from(statement, stream.pos_since(&start)),
+            })
         } else {
             let target = name.map_into(Target::Name);
             let statement = parse_assignment_or_procedure_call(stream, &token, target)?;
             Ok(LabeledSequentialStatement {
                 label: None,
-                statement,
+                statement: WithPos::from(statement, stream.pos_since(&start)),
             })
         }
     } else {
-        let statement = parse_unlabeled_sequential_statement(stream, token, diagnostics)?;
+        let statement = parse_unlabeled_sequential_statement(stream, token, None, diagnostics)?;
         Ok(LabeledSequentialStatement {
             label: None,
-            statement,
+            statement: WithPos::from(statement, stream.pos_since(&start)),
         })
     }
 }
@@ -610,19 +624,32 @@ mod tests {
         (code, stmt)
     }
 
+    /// The statement after the optional label extends to the final semicolon of the code
     fn with_label(
+        code: &Code,
         label: Option<Ident>,
         statement: SequentialStatement,
     ) -> LabeledSequentialStatement {
-        LabeledSequentialStatement { label, statement }
+        let tokens = code.tokenize();
+        let first = if label.is_some() {
+            &tokens[2]
+        } else {
+            &tokens[0]
+        };
+        let last = tokens.last().unwrap();
+        LabeledSequentialStatement {
+            label,
+            statement: WithPos::new(statement, first.pos.combine(&last.pos)),
+        }
     }
 
     #[test]
     fn parse_simple_wait_statement() {
-        let (_, statement) = parse("wait;");
+        let (code, statement) = parse("wait;");
         assert_eq!(
             statement,
             with_label(
+                &code,
                 None,
                 SequentialStatement::Wait(WaitStatement {
                     sensitivity_clause: vec![],
@@ -639,6 +666,7 @@ mod tests {
         assert_eq!(
             statement,
             with_label(
+                &code,
                 Some(code.s1("foo").ident()),
                 SequentialStatement::Wait(WaitStatement {
                     sensitivity_clause: vec![],
@@ -655,6 +683,7 @@ mod tests {
         assert_eq!(
             statement,
             with_label(
+                &code,
                 None,
                 SequentialStatement::Wait(WaitStatement {
                     sensitivity_clause: vec![code.s1("foo").name(), code.s1("bar").name()],
@@ -671,6 +700,7 @@ mod tests {
         assert_eq!(
             statement,
             with_label(
+                &code,
                 None,
                 SequentialStatement::Wait(WaitStatement {
                     sensitivity_clause: vec![],
@@ -687,6 +717,7 @@ mod tests {
         assert_eq!(
             statement,
             with_label(
+                &code,
                 None,
                 SequentialStatement::Wait(WaitStatement {
                     sensitivity_clause: vec![],
@@ -703,6 +734,7 @@ mod tests {
         assert_eq!(
             statement,
             with_label(
+                &code,
                 None,
                 SequentialStatement::Wait(WaitStatement {
                     sensitivity_clause: vec![code.s1("foo").name()],
@@ -719,6 +751,7 @@ mod tests {
         assert_eq!(
             statement,
             with_label(
+                &code,
                 None,
                 SequentialStatement::Assert(AssertStatement {
                     condition: code.s1("false").expr(),
@@ -735,6 +768,7 @@ mod tests {
         assert_eq!(
             statement,
             with_label(
+                &code,
                 None,
                 SequentialStatement::Assert(AssertStatement {
                     condition: code.s1("false").expr(),
@@ -751,6 +785,7 @@ mod tests {
         assert_eq!(
             statement,
             with_label(
+                &code,
                 None,
                 SequentialStatement::Report(ReportStatement {
                     report: code.s1("\"message\"").expr(),
@@ -767,6 +802,7 @@ mod tests {
         assert_eq!(
             statement,
             with_label(
+                &code,
                 None,
                 SequentialStatement::SignalAssignment(SignalAssignment {
                     target: code.s1("foo(0)").name().map_into(Target::Name),
@@ -784,6 +820,7 @@ mod tests {
         assert_eq!(
             statement,
             with_label(
+                &code,
                 None,
                 SequentialStatement::SignalForceAssignment(SignalForceAssignment {
                     target: code.s1("foo(0)").name().map_into(Target::Name),
@@ -799,6 +836,7 @@ mod tests {
         assert_eq!(
             statement,
             with_label(
+                &code,
                 None,
                 SequentialStatement::SignalForceAssignment(SignalForceAssignment {
                     target: code.s1("foo(0)").name().map_into(Target::Name),
@@ -814,6 +852,7 @@ mod tests {
         assert_eq!(
             statement,
             with_label(
+                &code,
                 None,
                 SequentialStatement::SignalForceAssignment(SignalForceAssignment {
                     target: code.s1("foo(0)").name().map_into(Target::Name),
@@ -831,6 +870,7 @@ mod tests {
         assert_eq!(
             statement,
             with_label(
+                &code,
                 None,
                 SequentialStatement::SignalReleaseAssignment(SignalReleaseAssignment {
                     target: code.s1("foo(0)").name().map_into(Target::Name),
@@ -847,6 +887,7 @@ mod tests {
         assert_eq!(
             statement,
             with_label(
+                &code,
                 None,
                 SequentialStatement::SignalAssignment(SignalAssignment {
                     target: code
@@ -867,6 +908,7 @@ mod tests {
         assert_eq!(
             statement,
             with_label(
+                &code,
                 None,
                 SequentialStatement::SignalAssignment(SignalAssignment {
                     target: code.s1("foo(0)").name().map_into(Target::Name),
@@ -883,6 +925,7 @@ mod tests {
         assert_eq!(
             statement,
             with_label(
+                &code,
                 None,
                 SequentialStatement::VariableAssignment(VariableAssignment {
                     target: code.s1("foo(0)").name().map_into(Target::Name),
@@ -898,6 +941,7 @@ mod tests {
         assert_eq!(
             statement,
             with_label(
+                &code,
                 None,
                 SequentialStatement::VariableAssignment(VariableAssignment {
                     target: code
@@ -916,6 +960,7 @@ mod tests {
         assert_eq!(
             statement,
             with_label(
+                &code,
                 None,
                 SequentialStatement::VariableAssignment(VariableAssignment {
                     target: code
@@ -934,6 +979,7 @@ mod tests {
         assert_eq!(
             statement,
             with_label(
+                &code,
                 Some(code.s1("name").ident()),
                 SequentialStatement::VariableAssignment(VariableAssignment {
                     target: code
@@ -952,6 +998,7 @@ mod tests {
         assert_eq!(
             statement,
             with_label(
+                &code,
                 Some(code.s1("name").ident()),
                 SequentialStatement::VariableAssignment(VariableAssignment {
                     target: code.s1("foo(0)").name().map_into(Target::Name),
@@ -967,6 +1014,7 @@ mod tests {
         assert_eq!(
             statement,
             with_label(
+                &code,
                 None,
                 SequentialStatement::VariableAssignment(VariableAssignment {
                     target: code.s1("foo(0)").name().map_into(Target::Name),
@@ -1008,6 +1056,7 @@ with x(0) + 1 select
         assert_eq!(
             statement,
             with_label(
+                &code,
                 None,
                 SequentialStatement::VariableAssignment(VariableAssignment {
                     target: code.s1("foo(0)").name().map_into(Target::Name),
@@ -1023,6 +1072,7 @@ with x(0) + 1 select
         assert_eq!(
             statement,
             with_label(
+                &code,
                 None,
                 SequentialStatement::VariableAssignment(VariableAssignment {
                     target: code.s1("foo(0)").name().map_into(Target::Name),
@@ -1049,6 +1099,7 @@ with x(0) + 1 select
         assert_eq!(
             statement,
             with_label(
+                &code,
                 None,
                 SequentialStatement::VariableAssignment(VariableAssignment {
                     target: code.s1("foo(0)").name().map_into(Target::Name),
@@ -1079,6 +1130,7 @@ with x(0) + 1 select
         assert_eq!(
             statement,
             with_label(
+                &code,
                 None,
                 SequentialStatement::SignalAssignment(SignalAssignment {
                     target: code.s1("foo(0)").name().map_into(Target::Name),
@@ -1104,6 +1156,7 @@ with x(0) + 1 select
         assert_eq!(
             statement,
             with_label(
+                &code,
                 None,
                 SequentialStatement::SignalForceAssignment(SignalForceAssignment {
                     target: code.s1("foo(0)").name().map_into(Target::Name),
@@ -1140,6 +1193,7 @@ with x(0) + 1 select
         assert_eq!(
             statement,
             with_label(
+                &code,
                 None,
                 SequentialStatement::SignalAssignment(SignalAssignment {
                     target: code.s1("foo(0)").name().map_into(Target::Name),
@@ -1176,6 +1230,7 @@ with x(0) + 1 select
         assert_eq!(
             statement,
             with_label(
+                &code,
                 None,
                 SequentialStatement::SignalForceAssignment(SignalForceAssignment {
                     target: code.s1("foo(0)").name().map_into(Target::Name),
@@ -1193,6 +1248,7 @@ with x(0) + 1 select
         assert_eq!(
             statement,
             with_label(
+                &code,
                 None,
                 SequentialStatement::ProcedureCall(code.s1("foo(1,2)").function_call())
             )
@@ -1206,6 +1262,7 @@ with x(0) + 1 select
         assert_eq!(
             statement,
             with_label(
+                &code,
                 None,
                 SequentialStatement::ProcedureCall(code.s1("foo").function_call())
             )
@@ -1225,16 +1282,20 @@ end if;
         assert_eq!(
             statement,
             with_label(
+                &code,
                 None,
                 SequentialStatement::If(IfStatement {
-                    conditionals: vec![Conditional {
-                        condition: code.s1("cond = true").expr(),
-                        item: vec![
-                            code.s1("foo(1,2);").sequential_statement(),
-                            code.s1("x := 1;").sequential_statement()
-                        ]
-                    }],
-                    else_item: None
+                    conds: Conditionals {
+                        conditionals: vec![Conditional {
+                            condition: code.s1("cond = true").expr(),
+                            item: vec![
+                                code.s1("foo(1,2);").sequential_statement(),
+                                code.s1("x := 1;").sequential_statement()
+                            ]
+                        }],
+                        else_item: None
+                    },
+                    end_label_pos: None
                 })
             )
         );
@@ -1252,16 +1313,20 @@ end if mylabel;
         assert_eq!(
             statement,
             with_label(
+                &code,
                 Some(code.s1("mylabel").ident()),
                 SequentialStatement::If(IfStatement {
-                    conditionals: vec![Conditional {
-                        condition: code.s1("cond = true").expr(),
-                        item: vec![
-                            code.s1("foo(1,2);").sequential_statement(),
-                            code.s1("x := 1;").sequential_statement()
-                        ]
-                    }],
-                    else_item: None
+                    conds: Conditionals {
+                        conditionals: vec![Conditional {
+                            condition: code.s1("cond = true").expr(),
+                            item: vec![
+                                code.s1("foo(1,2);").sequential_statement(),
+                                code.s1("x := 1;").sequential_statement()
+                            ]
+                        }],
+                        else_item: None
+                    },
+                    end_label_pos: Some(code.s("mylabel", 2).pos())
                 })
             )
         );
@@ -1281,13 +1346,17 @@ end if;
         assert_eq!(
             statement,
             with_label(
+                &code,
                 None,
                 SequentialStatement::If(IfStatement {
-                    conditionals: vec![Conditional {
-                        condition: code.s1("cond = true").expr(),
-                        item: vec![code.s1("foo(1,2);").sequential_statement()]
-                    }],
-                    else_item: Some(vec![code.s1("x := 1;").sequential_statement()])
+                    conds: Conditionals {
+                        conditionals: vec![Conditional {
+                            condition: code.s1("cond = true").expr(),
+                            item: vec![code.s1("foo(1,2);").sequential_statement()]
+                        }],
+                        else_item: Some(vec![code.s1("x := 1;").sequential_statement()])
+                    },
+                    end_label_pos: None
                 })
             )
         );
@@ -1307,13 +1376,17 @@ end if mylabel;
         assert_eq!(
             statement,
             with_label(
+                &code,
                 Some(code.s1("mylabel").ident()),
                 SequentialStatement::If(IfStatement {
-                    conditionals: vec![Conditional {
-                        condition: code.s1("cond = true").expr(),
-                        item: vec![code.s1("foo(1,2);").sequential_statement()]
-                    }],
-                    else_item: Some(vec![code.s1("x := 1;").sequential_statement()])
+                    conds: Conditionals {
+                        conditionals: vec![Conditional {
+                            condition: code.s1("cond = true").expr(),
+                            item: vec![code.s1("foo(1,2);").sequential_statement()]
+                        }],
+                        else_item: Some(vec![code.s1("x := 1;").sequential_statement()])
+                    },
+                    end_label_pos: Some(code.s("mylabel", 2).pos())
                 })
             )
         );
@@ -1334,19 +1407,23 @@ end if;
         assert_eq!(
             statement,
             with_label(
+                &code,
                 None,
                 SequentialStatement::If(IfStatement {
-                    conditionals: vec![
-                        Conditional {
-                            condition: code.s1("cond = true").expr(),
-                            item: vec![code.s1("foo(1,2);").sequential_statement()]
-                        },
-                        Conditional {
-                            condition: code.s1("cond2 = false").expr(),
-                            item: vec![code.s1("y := 2;").sequential_statement()]
-                        }
-                    ],
-                    else_item: Some(vec![code.s1("x := 1;").sequential_statement()])
+                    conds: Conditionals {
+                        conditionals: vec![
+                            Conditional {
+                                condition: code.s1("cond = true").expr(),
+                                item: vec![code.s1("foo(1,2);").sequential_statement()]
+                            },
+                            Conditional {
+                                condition: code.s1("cond2 = false").expr(),
+                                item: vec![code.s1("y := 2;").sequential_statement()]
+                            }
+                        ],
+                        else_item: Some(vec![code.s1("x := 1;").sequential_statement()])
+                    },
+                    end_label_pos: None
                 })
             )
         );
@@ -1367,19 +1444,23 @@ end if mylabel;
         assert_eq!(
             statement,
             with_label(
+                &code,
                 Some(code.s1("mylabel").ident()),
                 SequentialStatement::If(IfStatement {
-                    conditionals: vec![
-                        Conditional {
-                            condition: code.s1("cond = true").expr(),
-                            item: vec![code.s1("foo(1,2);").sequential_statement()]
-                        },
-                        Conditional {
-                            condition: code.s1("cond2 = false").expr(),
-                            item: vec![code.s1("y := 2;").sequential_statement()]
-                        }
-                    ],
-                    else_item: Some(vec![code.s1("x := 1;").sequential_statement()])
+                    conds: Conditionals {
+                        conditionals: vec![
+                            Conditional {
+                                condition: code.s1("cond = true").expr(),
+                                item: vec![code.s1("foo(1,2);").sequential_statement()]
+                            },
+                            Conditional {
+                                condition: code.s1("cond2 = false").expr(),
+                                item: vec![code.s1("y := 2;").sequential_statement()]
+                            }
+                        ],
+                        else_item: Some(vec![code.s1("x := 1;").sequential_statement()])
+                    },
+                    end_label_pos: Some(code.s("mylabel", 2).pos())
                 })
             )
         );
@@ -1401,6 +1482,7 @@ end case;
         assert_eq!(
             statement,
             with_label(
+                &code,
                 None,
                 SequentialStatement::Case(CaseStatement {
                     is_matching: false,
@@ -1421,6 +1503,7 @@ end case;
                             ]
                         }
                     ],
+                    end_label_pos: None,
                 })
             )
         );
@@ -1438,6 +1521,7 @@ end case?;
         assert_eq!(
             statement,
             with_label(
+                &code,
                 None,
                 SequentialStatement::Case(CaseStatement {
                     is_matching: true,
@@ -1446,6 +1530,7 @@ end case?;
                         choices: code.s1("others").choices(),
                         item: vec![code.s1("null;").sequential_statement(),]
                     }],
+                    end_label_pos: None,
                 })
             )
         );
@@ -1464,6 +1549,7 @@ end loop;
         assert_eq!(
             statement,
             with_label(
+                &code,
                 None,
                 SequentialStatement::Loop(LoopStatement {
                     iteration_scheme: None,
@@ -1471,6 +1557,7 @@ end loop;
                         code.s1("stmt1;").sequential_statement(),
                         code.s1("stmt2;").sequential_statement()
                     ],
+                    end_label_pos: None,
                 })
             )
         );
@@ -1489,6 +1576,7 @@ end loop;
         assert_eq!(
             statement,
             with_label(
+                &code,
                 None,
                 SequentialStatement::Loop(LoopStatement {
                     iteration_scheme: Some(IterationScheme::While(code.s1("foo = true").expr())),
@@ -1496,10 +1584,61 @@ end loop;
                         code.s1("stmt1;").sequential_statement(),
                         code.s1("stmt2;").sequential_statement()
                     ],
+                    end_label_pos: None,
                 })
             )
         );
     }
+    #[test]
+    fn parse_loop_statement_end_label_mismatch() {
+        let code = Code::new(
+            "\
+lbl: loop
+  stmt1;
+end loop other;
+",
+        );
+        let (statement, diagnostics) = code.with_stream_diagnostics(parse_sequential_statement);
+        assert_eq!(
+            diagnostics,
+            vec![Diagnostic::error(
+                code.s1("other"),
+                "End identifier mismatch, expected lbl"
+            )]
+        );
+        assert_eq!(
+            statement,
+            with_label(
+                &code,
+                Some(code.s1("lbl").ident()),
+                SequentialStatement::Loop(LoopStatement {
+                    iteration_scheme: None,
+                    statements: vec![code.s1("stmt1;").sequential_statement()],
+                    end_label_pos: None,
+                })
+            )
+        );
+    }
+
+    #[test]
+    fn parse_if_statement_end_label_for_unlabeled_statement() {
+        let code = Code::new(
+            "\
+if cond = true then
+  foo(1,2);
+end if lbl;
+",
+        );
+        let (_, diagnostics) = code.with_stream_diagnostics(parse_sequential_statement);
+        assert_eq!(
+            diagnostics,
+            vec![Diagnostic::error(
+                code.s1("lbl"),
+                "End label 'lbl' found for unlabeled statement"
+            )]
+        );
+    }
+
     #[test]
     fn parse_for_loop_statement() {
         let (code, statement) = parse(
@@ -1513,6 +1652,7 @@ end loop;
         assert_eq!(
             statement,
             with_label(
+                &code,
                 None,
                 SequentialStatement::Loop(LoopStatement {
                     iteration_scheme: Some(IterationScheme::For(
@@ -1523,6 +1663,7 @@ end loop;
                         code.s1("stmt1;").sequential_statement(),
                         code.s1("stmt2;").sequential_statement()
                     ],
+                    end_label_pos: None,
                 })
             )
         );
@@ -1530,10 +1671,11 @@ end loop;
 
     #[test]
     fn parse_next_statement() {
-        let (_, statement) = parse("next;");
+        let (code, statement) = parse("next;");
         assert_eq!(
             statement,
             with_label(
+                &code,
                 None,
                 SequentialStatement::Next(NextStatement {
                     loop_label: None,
@@ -1549,6 +1691,7 @@ end loop;
         assert_eq!(
             statement,
             with_label(
+                &code,
                 None,
                 SequentialStatement::Next(NextStatement {
                     loop_label: Some(code.s1("foo").ident()),
@@ -1564,6 +1707,7 @@ end loop;
         assert_eq!(
             statement,
             with_label(
+                &code,
                 None,
                 SequentialStatement::Next(NextStatement {
                     loop_label: None,
@@ -1579,6 +1723,7 @@ end loop;
         assert_eq!(
             statement,
             with_label(
+                &code,
                 None,
                 SequentialStatement::Next(NextStatement {
                     loop_label: Some(code.s1("foo").ident()),
@@ -1590,10 +1735,11 @@ end loop;
 
     #[test]
     fn parse_exit_statement() {
-        let (_, statement) = parse("exit;");
+        let (code, statement) = parse("exit;");
         assert_eq!(
             statement,
             with_label(
+                &code,
                 None,
                 SequentialStatement::Exit(ExitStatement {
                     loop_label: None,
@@ -1609,6 +1755,7 @@ end loop;
         assert_eq!(
             statement,
             with_label(
+                &code,
                 None,
                 SequentialStatement::Exit(ExitStatement {
                     loop_label: Some(code.s1("foo").ident()),
@@ -1624,6 +1771,7 @@ end loop;
         assert_eq!(
             statement,
             with_label(
+                &code,
                 None,
                 SequentialStatement::Exit(ExitStatement {
                     loop_label: None,
@@ -1639,6 +1787,7 @@ end loop;
         assert_eq!(
             statement,
             with_label(
+                &code,
                 None,
                 SequentialStatement::Exit(ExitStatement {
                     loop_label: Some(code.s1("foo").ident()),
@@ -1650,10 +1799,11 @@ end loop;
 
     #[test]
     fn parse_return_statement() {
-        let (_, statement) = parse("return;");
+        let (code, statement) = parse("return;");
         assert_eq!(
             statement,
             with_label(
+                &code,
                 None,
                 SequentialStatement::Return(ReturnStatement { expression: None })
             )
@@ -1666,6 +1816,7 @@ end loop;
         assert_eq!(
             statement,
             with_label(
+                &code,
                 None,
                 SequentialStatement::Return(ReturnStatement {
                     expression: Some(code.s1("1 + 2").expr()),
@@ -1676,7 +1827,10 @@ end loop;
 
     #[test]
     fn parse_null_statement() {
-        let (_, statement) = parse("null;");
-        assert_eq!(statement, with_label(None, SequentialStatement::Null));
+        let (code, statement) = parse("null;");
+        assert_eq!(
+            statement,
+            with_label(&code, None, SequentialStatement::Null)
+        );
     }
 }
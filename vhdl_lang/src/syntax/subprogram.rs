@@ -143,8 +143,10 @@ pub fn parse_subprogram_declaration(
 }
 
 /// LRM 4.3 Subprogram bodies
+/// The subprogram body of the specification starting at start
 pub fn parse_subprogram_body(
     stream: &mut TokenStream,
+    start: &SrcPos,
     specification: SubprogramDeclaration,
     diagnostics: &mut dyn DiagnosticHandler,
 ) -> ParseResult<SubprogramBody> {
@@ -157,19 +159,28 @@ pub fn parse_subprogram_body(
     let declarations = parse_declarative_part(stream, diagnostics, true)?;
 
     let (statements, end_token) = parse_labeled_sequential_statements(stream, diagnostics)?;
-    try_token_kind!(
+    let end_ident_pos = try_token_kind!(
         end_token,
         End => {
             stream.pop_if_kind(end_kind)?;
-            stream.pop_if_kind(Identifier)?;
+            let end_ident = stream.pop_optional_ident()?;
             stream.pop_if_kind(StringLiteral)?;
             stream.expect_kind(SemiColon)?;
+
+            // Operator symbols repeated at the end are not tracked
+            end_ident
+                .filter(|end_ident| {
+                    specification.designator().item == Designator::Identifier(end_ident.item.clone())
+                })
+                .map(|end_ident| end_ident.pos)
         }
     );
     Ok(SubprogramBody {
         specification,
         declarations,
         statements,
+        end_ident_pos,
+        source_range: stream.pos_since(start),
     })
 }
 
@@ -177,11 +188,12 @@ pub fn parse_subprogram(
     stream: &mut TokenStream,
     diagnostics: &mut dyn DiagnosticHandler,
 ) -> ParseResult<Declaration> {
+    let start = stream.peek_expect()?.pos;
     let specification = parse_subprogram_declaration_no_semi(stream, diagnostics)?;
     match_token_kind!(
         stream.expect()?,
         Is => {
-            Ok(Declaration::SubprogramBody(parse_subprogram_body(stream, specification, diagnostics)?))
+            Ok(Declaration::SubprogramBody(parse_subprogram_body(stream, &start, specification, diagnostics)?))
         },
         SemiColon => {
             Ok(Declaration::SubprogramDeclaration(specification))
@@ -431,6 +443,8 @@ end function;
             specification,
             declarations,
             statements,
+            end_ident_pos: None,
+            source_range: code.between("function", "end function;").pos(),
         };
         assert_eq!(
             code.with_stream_no_diagnostics(parse_subprogram),
@@ -112,6 +112,17 @@ impl Code {
         self.s(substr, 1)
     }
 
+    /// Create new Code from the first occurence of start_substr
+    /// until the end of the first subsequent occurence of end_substr
+    pub fn between(&self, start_substr: &str, end_substr: &str) -> Code {
+        let start = self.s1(start_substr).start();
+        let end = self
+            .in_range(Range::new(start, self.end()))
+            .s1(end_substr)
+            .end();
+        self.in_range(Range::new(start, end))
+    }
+
     pub fn pos(&self) -> SrcPos {
         self.pos.clone()
     }
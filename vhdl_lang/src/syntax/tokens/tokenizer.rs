@@ -667,6 +667,7 @@ fn parse_exponent(reader: &mut ContentReader) -> Result<i32, TokenError> {
 #[derive(Clone, Copy)]
 pub struct TokenState {
     last_token_kind: Option<Kind>,
+    last_token_end: Position,
     start: ReaderState,
 }
 
@@ -674,6 +675,7 @@ impl TokenState {
     pub fn new(start: ReaderState) -> TokenState {
         TokenState {
             last_token_kind: None,
+            last_token_end: start.pos(),
             start,
         }
     }
@@ -681,6 +683,7 @@ impl TokenState {
     /// Set state to after token
     pub fn set_after(&mut self, token: &Token) {
         self.last_token_kind = Some(token.kind);
+        self.last_token_end = token.pos.end();
         self.start = token.next_state;
     }
 }
@@ -1324,6 +1327,11 @@ impl<'a> Tokenizer<'a> {
         self.state
     }
 
+    /// The lexical position from the start of the given position until the end of the last consumed token
+    pub fn pos_since(&self, start: &SrcPos) -> SrcPos {
+        self.source.pos(start.start(), self.state.last_token_end)
+    }
+
     pub fn eof_error(&self) -> Diagnostic {
         Diagnostic::error(
             self.source
@@ -1625,6 +1633,32 @@ impl<'a> Tokenizer<'a> {
     }
 }
 
+/// The tokens of the source from the start position until the first token which starts at or
+/// after the end position
+///
+/// Illegal tokens are skipped such that code which is being edited is also tokenized.
+pub fn tokenize_between(
+    symbols: &Symbols,
+    source: &Source,
+    start: Position,
+    end: Position,
+) -> Vec<Token> {
+    let contents = source.contents();
+    let mut reader = ContentReader::new(&contents);
+    reader.seek_pos(start);
+    let mut tokenizer = Tokenizer::new(symbols, source, reader);
+
+    let mut tokens = Vec::new();
+    loop {
+        match tokenizer.pop() {
+            Ok(Some(token)) if token.pos.start() < end => tokens.push(token),
+            Ok(_) => break,
+            Err(_) => {}
+        }
+    }
+    tokens
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
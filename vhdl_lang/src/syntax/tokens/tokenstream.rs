@@ -7,7 +7,7 @@
 use super::tokenizer::Kind::*;
 use super::tokenizer::*;
 use crate::ast::Ident;
-use crate::data::{DiagnosticHandler, DiagnosticResult, Symbol};
+use crate::data::{DiagnosticHandler, DiagnosticResult, SrcPos, Symbol};
 
 pub struct TokenStream<'a> {
     tokenizer: Tokenizer<'a>,
@@ -34,6 +34,11 @@ impl<'a> TokenStream<'a> {
         self.tokenizer.set_state(state);
     }
 
+    /// The lexical position from the start of the given position until the end of the last consumed token
+    pub fn pos_since(&self, start: &SrcPos) -> SrcPos {
+        self.tokenizer.pos_since(start)
+    }
+
     pub fn move_after(&mut self, token: &Token) {
         self.tokenizer.move_after(token);
     }
@@ -4,8 +4,8 @@
 //
 // Copyright (c) 2018, Olof Kraigher olof.kraigher@gmail.com
 
-use super::common::error_on_end_identifier_mismatch;
 use super::common::ParseResult;
+use super::common::{error_on_end_identifier_mismatch, matching_end_ident_pos};
 use super::declarative_part::parse_declarative_part;
 use super::names::{parse_identifier_list, parse_selected_name};
 use super::range::{parse_array_index_constraint, parse_range};
@@ -110,6 +110,7 @@ pub fn parse_subtype_declaration(stream: &mut TokenStream) -> ParseResult<TypeDe
     Ok(TypeDeclaration {
         ident,
         def: TypeDefinition::Subtype(subtype_indication),
+        end_ident_pos: None,
     })
 }
 
@@ -214,6 +215,7 @@ pub fn parse_type_declaration(
     );
 
     let ident = stream.expect_ident()?;
+    let mut end_ident_pos = None;
 
     try_token_kind!(
         stream.expect()?,
@@ -222,6 +224,7 @@ pub fn parse_type_declaration(
             return Ok(TypeDeclaration {
                 ident,
                 def: TypeDefinition::Incomplete(Reference::default()),
+                end_ident_pos: None,
             });
         }
     );
@@ -237,6 +240,7 @@ pub fn parse_type_declaration(
                 Units => {
                     let (def, end_ident) = parse_physical_type_definition(stream, constraint)?;
                     diagnostics.push_some(error_on_end_identifier_mismatch(&ident, &end_ident));
+                    end_ident_pos = matching_end_ident_pos(&ident, end_ident);
                     def
                 }
             )
@@ -253,13 +257,15 @@ pub fn parse_type_declaration(
                 let decl = parse_declarative_part(stream, diagnostics, false)?;
                 stream.expect_kind(Protected)?;
                 stream.expect_kind(Body)?;
-                // @TODO check name
-                stream.pop_if_kind(Identifier)?;
+                let end_ident = stream.pop_optional_ident()?;
+                diagnostics.push_some(error_on_end_identifier_mismatch(&ident, &end_ident));
+                end_ident_pos = matching_end_ident_pos(&ident, end_ident);
                 stream.expect_kind(SemiColon)?;
                 TypeDefinition::ProtectedBody(ProtectedTypeBody {type_reference: Reference::default(), decl})
             } else {
                 let (protected_type_decl, end_ident) = parse_protected_type_declaration(stream, diagnostics)?;
                 diagnostics.push_some(error_on_end_identifier_mismatch(&ident, &end_ident));
+                end_ident_pos = matching_end_ident_pos(&ident, end_ident);
                 stream.expect_kind(SemiColon)?;
                 TypeDefinition::Protected(protected_type_decl)
             }
@@ -274,13 +280,18 @@ pub fn parse_type_declaration(
         Record =>  {
             let (def, end_ident) = parse_record_type_definition(stream)?;
             diagnostics.push_some(error_on_end_identifier_mismatch(&ident, &end_ident));
+            end_ident_pos = matching_end_ident_pos(&ident, end_ident);
             def
         },
         // Enumeration
         LeftPar => parse_enumeration_type_definition(stream)?
     );
 
-    Ok(TypeDeclaration { ident, def })
+    Ok(TypeDeclaration {
+        ident,
+        def,
+        end_ident_pos,
+    })
 }
 
 #[cfg(test)]
@@ -288,6 +299,7 @@ mod tests {
     use super::*;
 
     use crate::ast::{DiscreteRange, Ident};
+    use crate::data::Diagnostic;
     use crate::syntax::test::Code;
 
     #[test]
@@ -297,6 +309,7 @@ mod tests {
         let type_decl = TypeDeclaration {
             ident: code.s1("foo").ident(),
             def: TypeDefinition::Integer(code.s1("0 to 1").range()),
+            end_ident_pos: None,
         };
         assert_eq!(
             code.with_stream_no_diagnostics(parse_type_declaration),
@@ -318,6 +331,7 @@ mod tests {
                     .ident()
                     .map_into(EnumerationLiteral::Identifier),
             ]),
+            end_ident_pos: None,
         };
         assert_eq!(
             code.with_stream_no_diagnostics(parse_type_declaration),
@@ -339,6 +353,7 @@ mod tests {
                     .character()
                     .map_into(EnumerationLiteral::Character),
             ]),
+            end_ident_pos: None,
         };
         assert_eq!(
             code.with_stream_no_diagnostics(parse_type_declaration),
@@ -360,6 +375,7 @@ mod tests {
                     .character()
                     .map_into(EnumerationLiteral::Character),
             ]),
+            end_ident_pos: None,
         };
         assert_eq!(
             code.with_stream_no_diagnostics(parse_type_declaration),
@@ -379,6 +395,7 @@ mod tests {
                 )],
                 code.s1("boolean").subtype_indication(),
             ),
+            end_ident_pos: None,
         };
 
         assert_eq!(
@@ -400,6 +417,7 @@ mod tests {
                 ))],
                 code.s1("boolean").subtype_indication(),
             ),
+            end_ident_pos: None,
         };
 
         assert_eq!(
@@ -421,6 +439,7 @@ mod tests {
                 ))],
                 code.s1("boolean").subtype_indication(),
             ),
+            end_ident_pos: None,
         };
 
         assert_eq!(
@@ -441,6 +460,7 @@ mod tests {
                 ))],
                 code.s1("boolean").subtype_indication(),
             ),
+            end_ident_pos: None,
         };
 
         assert_eq!(
@@ -458,6 +478,7 @@ mod tests {
         let type_decl = TypeDeclaration {
             ident: code.s1("foo").ident(),
             def: TypeDefinition::Array(vec![index], code.s1("boolean").subtype_indication()),
+            end_ident_pos: None,
         };
 
         assert_eq!(
@@ -480,6 +501,7 @@ mod tests {
                 vec![index0, index1],
                 code.s1("boolean").subtype_indication(),
             ),
+            end_ident_pos: None,
         };
 
         assert_eq!(
@@ -505,6 +527,7 @@ end record;",
         let type_decl = TypeDeclaration {
             ident: code.s1("foo").ident(),
             def: TypeDefinition::Record(vec![elem_decl]),
+            end_ident_pos: None,
         };
 
         assert_eq!(
@@ -541,6 +564,7 @@ end foo;",
         let type_decl = TypeDeclaration {
             ident: code.s1("foo").ident(),
             def: TypeDefinition::Record(vec![elem_decl0a, elem_decl0b, elem_decl1]),
+            end_ident_pos: Some(code.s("foo", 2).pos()),
         };
 
         assert_eq!(
@@ -559,7 +583,8 @@ end foo;",
                 ident: code.s1("vec_t").ident(),
                 def: TypeDefinition::Subtype(
                     code.s1("integer_vector(2-1 downto 0)").subtype_indication()
-                )
+                ),
+                end_ident_pos: None,
             }
         );
     }
@@ -574,7 +599,8 @@ end foo;",
                 ident: code.s1("ptr_t").ident(),
                 def: TypeDefinition::Access(
                     code.s1("integer_vector(2-1 downto 0)").subtype_indication()
-                )
+                ),
+                end_ident_pos: None,
             }
         );
     }
@@ -587,7 +613,8 @@ end foo;",
             code.with_stream_no_diagnostics(parse_type_declaration),
             TypeDeclaration {
                 ident: code.s1("incomplete").ident(),
-                def: TypeDefinition::Incomplete(Reference::default())
+                def: TypeDefinition::Incomplete(Reference::default()),
+                end_ident_pos: None,
             }
         );
     }
@@ -600,7 +627,8 @@ end foo;",
             code.with_stream_no_diagnostics(parse_type_declaration),
             TypeDeclaration {
                 ident: code.s1("foo").ident(),
-                def: TypeDefinition::File(code.s1("character").selected_name())
+                def: TypeDefinition::File(code.s1("character").selected_name()),
+                end_ident_pos: None,
             }
         );
     }
@@ -609,6 +637,7 @@ end foo;",
         TypeDeclaration {
             ident,
             def: TypeDefinition::Protected(ProtectedTypeDeclaration { items }),
+            end_ident_pos: None,
         }
     }
 
@@ -636,7 +665,10 @@ end protected foo;
         );
         assert_eq!(
             code.with_stream_no_diagnostics(parse_type_declaration),
-            protected_decl(code.s1("foo").ident(), vec![])
+            TypeDeclaration {
+                end_ident_pos: Some(code.s("foo", 2).pos()),
+                ..protected_decl(code.s1("foo").ident(), vec![])
+            }
         )
     }
 
@@ -693,10 +725,52 @@ end protected body;
                     type_reference: Reference::default(),
                     decl
                 }),
+                end_ident_pos: None,
+            }
+        )
+    }
+
+    #[test]
+    fn test_protected_type_body_simple_name_suffix() {
+        let code = Code::new(
+            "\
+type foo is protected body
+end protected body foo;
+",
+        );
+
+        assert_eq!(
+            code.with_stream_no_diagnostics(parse_type_declaration),
+            TypeDeclaration {
+                ident: code.s1("foo").ident(),
+                def: TypeDefinition::ProtectedBody(ProtectedTypeBody {
+                    type_reference: Reference::default(),
+                    decl: vec![],
+                }),
+                end_ident_pos: Some(code.s("foo", 2).pos()),
             }
         )
     }
 
+    #[test]
+    fn test_protected_type_body_end_identifier_mismatch() {
+        let code = Code::new(
+            "\
+type foo is protected body
+end protected body bar;
+",
+        );
+
+        let (_, diagnostics) = code.with_stream_diagnostics(parse_type_declaration);
+        assert_eq!(
+            diagnostics,
+            vec![Diagnostic::error(
+                code.s1("bar"),
+                "End identifier mismatch, expected foo"
+            )]
+        );
+    }
+
     #[test]
     fn test_physical_type_declaration() {
         let code = Code::new(
@@ -715,7 +789,8 @@ end units phys;
                     range: code.s1("0 to 15").range(),
                     primary_unit: code.s1("primary_unit").ident(),
                     secondary_units: vec![]
-                })
+                }),
+                end_ident_pos: Some(code.s("phys", 2).pos()),
             }
         )
     }
@@ -742,7 +817,8 @@ end units;
                         code.s1("secondary_unit").ident(),
                         Literal::Physical(AbstractLiteral::Integer(5), code.symbol("primary_unit"))
                     ),]
-                })
+                }),
+                end_ident_pos: None,
             }
         )
     }
@@ -769,7 +845,8 @@ end units;
                         code.s1("secondary_unit").ident(),
                         Literal::Physical(AbstractLiteral::Integer(1), code.symbol("primary_unit"))
                     ),]
-                })
+                }),
+                end_ident_pos: None,
             }
         )
     }
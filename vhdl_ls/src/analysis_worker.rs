@@ -0,0 +1,112 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2020, Olof Kraigher olof.kraigher@gmail.com
+
+//! Analysis of the project on a background thread such that requests can be answered
+//! from the last completed analysis while a new analysis is running.
+//!
+//! Analysis requests arriving in quick succession are coalesced into a single analysis.
+//! An ongoing analysis is cancelled when the project is about to be changed.
+
+use crossbeam_channel::{unbounded, Receiver, RecvTimeoutError, Sender};
+use parking_lot::{RwLock, RwLockWriteGuard};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use vhdl_lang::{Diagnostic, Project};
+
+/// The time without new analysis requests to wait for before starting an analysis
+const ANALYSIS_DELAY: Duration = Duration::from_millis(200);
+
+pub struct AnalysisResult {
+    /// The generation of the latest analysis request covered by the result
+    pub generation: u64,
+    pub diagnostics: Vec<Diagnostic>,
+    /// A copy of the analysed project to answer requests from until the next result
+    pub project: Arc<Project>,
+}
+
+pub struct AnalysisWorker {
+    cancelled: Arc<AtomicBool>,
+    requests: Sender<u64>,
+    results: Receiver<AnalysisResult>,
+}
+
+impl AnalysisWorker {
+    pub fn spawn(project: Arc<RwLock<Project>>) -> AnalysisWorker {
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let (requests, request_receiver) = unbounded();
+        let (result_sender, results) = unbounded();
+
+        {
+            let cancelled = cancelled.clone();
+            thread::spawn(move || run(project, cancelled, request_receiver, result_sender));
+        }
+
+        AnalysisWorker {
+            cancelled,
+            requests,
+            results,
+        }
+    }
+
+    /// Request an analysis of the current state of the project
+    pub fn request_analysis(&self, generation: u64) {
+        self.requests.send(generation).ok();
+    }
+
+    /// The results of completed analyses which were not cancelled
+    pub fn results(&self) -> Receiver<AnalysisResult> {
+        self.results.clone()
+    }
+
+    /// Lock the project analyzed by the worker for writing
+    ///
+    /// The ongoing analysis is cancelled for as long as the write lock is awaited,
+    /// this requires the project to be written by a single thread only.
+    pub fn write<'a>(&self, project: &'a RwLock<Project>) -> RwLockWriteGuard<'a, Project> {
+        self.cancelled.store(true, Ordering::Relaxed);
+        let project = project.write();
+        self.cancelled.store(false, Ordering::Relaxed);
+        project
+    }
+}
+
+fn run(
+    project: Arc<RwLock<Project>>,
+    cancelled: Arc<AtomicBool>,
+    requests: Receiver<u64>,
+    results: Sender<AnalysisResult>,
+) {
+    while let Ok(mut generation) = requests.recv() {
+        // Coalesce requests until there is a pause in the changes
+        loop {
+            match requests.recv_timeout(ANALYSIS_DELAY) {
+                Ok(next_generation) => generation = next_generation,
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        }
+
+        let mut project = project.write();
+        let mut diagnostics = project.prepare_analysis();
+
+        // Allow the sources of the project to be looked up while analyzing
+        let project = RwLockWriteGuard::downgrade(project);
+        if let Some(analysis_diagnostics) = project.analyse_prepared(&cancelled) {
+            diagnostics.extend(analysis_diagnostics);
+            let result = AnalysisResult {
+                generation,
+                diagnostics,
+                project: Arc::new(project.clone()),
+            };
+            if results.send(result).is_err() {
+                return;
+            }
+        }
+        // A cancelled analysis is completed by the analysis of the following request
+    }
+}
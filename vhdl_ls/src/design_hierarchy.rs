@@ -0,0 +1,58 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2020, Olof Kraigher olof.kraigher@gmail.com
+
+//! The `vhdl/designHierarchy` request returning the tree of instances below a top level entity
+
+use crate::vhdl_server::srcpos_to_location;
+use lsp_types::request::Request;
+use lsp_types::Location;
+use serde::{Deserialize, Serialize};
+use vhdl_lang::HierarchyNode;
+
+pub enum DesignHierarchyRequest {}
+
+impl Request for DesignHierarchyRequest {
+    type Params = DesignHierarchyParams;
+    type Result = Option<DesignHierarchyNode>;
+    const METHOD: &'static str = "vhdl/designHierarchy";
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DesignHierarchyParams {
+    /// All libraries are searched when the library is omitted
+    pub library: Option<String>,
+    /// The name of the top level entity or of a configuration of it
+    pub entity: String,
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DesignHierarchyNode {
+    /// The instance label which is omitted for the top level
+    pub label: Option<String>,
+    pub library: String,
+    /// The bound entity or the component of an unbound instance
+    pub entity: String,
+    pub architecture: Option<String>,
+    /// The location of the instance label or of the top level unit
+    pub location: Location,
+    /// The location of the bound architecture or entity
+    pub unit_location: Option<Location>,
+    pub children: Vec<DesignHierarchyNode>,
+}
+
+pub fn design_hierarchy_node(node: &HierarchyNode) -> DesignHierarchyNode {
+    DesignHierarchyNode {
+        label: node.label.as_ref().map(|label| label.to_string()),
+        library: node.library_name.to_string(),
+        entity: node.entity_name.to_string(),
+        architecture: node.architecture_name.as_ref().map(|name| name.to_string()),
+        location: srcpos_to_location(&node.pos),
+        unit_location: node.unit_pos.as_ref().map(srcpos_to_location),
+        children: node.children.iter().map(design_hierarchy_node).collect(),
+    }
+}
@@ -0,0 +1,420 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2020, Olof Kraigher olof.kraigher@gmail.com
+
+//! Hierarchical outline of the design units within a design file
+
+use crate::vhdl_server::to_lsp_range;
+use lsp_types::{DocumentSymbol, SymbolKind};
+use vhdl_lang::ast::*;
+use vhdl_lang::SrcPos;
+
+pub fn document_symbols(design_file: &DesignFile) -> Vec<DocumentSymbol> {
+    design_file
+        .design_units
+        .iter()
+        .map(|unit| match unit {
+            AnyDesignUnit::Primary(unit) => primary_unit_symbol(unit),
+            AnyDesignUnit::Secondary(unit) => secondary_unit_symbol(unit),
+        })
+        .collect()
+}
+
+fn primary_unit_symbol(unit: &AnyPrimaryUnit) -> DocumentSymbol {
+    match unit {
+        AnyPrimaryUnit::Entity(entity) => {
+            let mut children = Vec::new();
+            if let Some(ref generics) = entity.generic_clause {
+                children.extend(generics.iter().map(interface_symbol));
+            }
+            if let Some(ref ports) = entity.port_clause {
+                children.extend(ports.iter().map(interface_symbol));
+            }
+            children.extend(declaration_symbols(&entity.decl));
+            children.extend(statement_symbols(&entity.statements));
+
+            symbol(
+                entity.ident.to_string(),
+                Some("entity".to_owned()),
+                SymbolKind::INTERFACE,
+                &entity.source_range,
+                &entity.ident.pos,
+                children,
+            )
+        }
+        AnyPrimaryUnit::Configuration(config) => symbol(
+            config.ident.to_string(),
+            Some(format!("configuration of {}", config.entity_name)),
+            SymbolKind::CLASS,
+            &config.source_range,
+            &config.ident.pos,
+            Vec::new(),
+        ),
+        AnyPrimaryUnit::Package(package) => {
+            let mut children = Vec::new();
+            if let Some(ref generics) = package.generic_clause {
+                children.extend(generics.iter().map(interface_symbol));
+            }
+            children.extend(declaration_symbols(&package.decl));
+
+            symbol(
+                package.ident.to_string(),
+                Some("package".to_owned()),
+                SymbolKind::PACKAGE,
+                &package.source_range,
+                &package.ident.pos,
+                children,
+            )
+        }
+        AnyPrimaryUnit::PackageInstance(instance) => symbol(
+            instance.ident.to_string(),
+            Some(format!("package instance of {}", instance.package_name)),
+            SymbolKind::PACKAGE,
+            &instance.ident.pos,
+            &instance.ident.pos,
+            Vec::new(),
+        ),
+        AnyPrimaryUnit::Context(context) => symbol(
+            context.ident.to_string(),
+            Some("context".to_owned()),
+            SymbolKind::NAMESPACE,
+            &context.source_range,
+            &context.ident.pos,
+            Vec::new(),
+        ),
+    }
+}
+
+fn secondary_unit_symbol(unit: &AnySecondaryUnit) -> DocumentSymbol {
+    match unit {
+        AnySecondaryUnit::Architecture(arch) => {
+            let mut children = declaration_symbols(&arch.decl);
+            children.extend(statement_symbols(&arch.statements));
+
+            symbol(
+                arch.ident.to_string(),
+                Some(format!("architecture of {}", arch.entity_name)),
+                SymbolKind::CLASS,
+                &arch.source_range,
+                &arch.ident.pos,
+                children,
+            )
+        }
+        AnySecondaryUnit::PackageBody(body) => symbol(
+            body.ident.to_string(),
+            Some("package body".to_owned()),
+            SymbolKind::PACKAGE,
+            &body.source_range,
+            &body.ident.item.pos,
+            declaration_symbols(&body.decl),
+        ),
+    }
+}
+
+fn interface_symbol(decl: &InterfaceDeclaration) -> DocumentSymbol {
+    match decl {
+        InterfaceDeclaration::Object(object) => {
+            let (detail, kind) = match object.list_type {
+                InterfaceListType::Port => (
+                    format!("{} {}", object.mode, object.subtype_indication),
+                    SymbolKind::FIELD,
+                ),
+                InterfaceListType::Generic => {
+                    (object.subtype_indication.to_string(), SymbolKind::CONSTANT)
+                }
+                InterfaceListType::Parameter => (
+                    format!("{} {}", object.class, object.subtype_indication),
+                    SymbolKind::VARIABLE,
+                ),
+            };
+            leaf_symbol(&object.ident, Some(detail), kind)
+        }
+        InterfaceDeclaration::File(file) => leaf_symbol(
+            &file.ident,
+            Some(file.subtype_indication.to_string()),
+            SymbolKind::FILE,
+        ),
+        InterfaceDeclaration::Type(ident) => {
+            leaf_symbol(ident, Some("type".to_owned()), SymbolKind::TYPE_PARAMETER)
+        }
+        InterfaceDeclaration::Subprogram(decl, _) => subprogram_symbol(decl, Vec::new()),
+        InterfaceDeclaration::Package(package) => leaf_symbol(
+            &package.ident,
+            Some(format!("package instance of {}", package.package_name)),
+            SymbolKind::PACKAGE,
+        ),
+    }
+}
+
+fn declaration_symbols(decls: &[Declaration]) -> Vec<DocumentSymbol> {
+    decls.iter().filter_map(declaration_symbol).collect()
+}
+
+fn declaration_symbol(decl: &Declaration) -> Option<DocumentSymbol> {
+    let symbol = match decl {
+        Declaration::Object(object) => {
+            let kind = match object.class {
+                ObjectClass::Constant => SymbolKind::CONSTANT,
+                ObjectClass::Signal | ObjectClass::Variable | ObjectClass::SharedVariable => {
+                    SymbolKind::VARIABLE
+                }
+            };
+            leaf_symbol(
+                &object.ident,
+                Some(format!("{} {}", object.class, object.subtype_indication)),
+                kind,
+            )
+        }
+        Declaration::File(file) => leaf_symbol(
+            &file.ident,
+            Some(format!("file {}", file.subtype_indication)),
+            SymbolKind::FILE,
+        ),
+        Declaration::Type(typ) => type_symbol(typ),
+        Declaration::Component(component) => {
+            let mut children: Vec<_> = component
+                .generic_list
+                .iter()
+                .map(interface_symbol)
+                .collect();
+            children.extend(component.port_list.iter().map(interface_symbol));
+            symbol(
+                component.ident.to_string(),
+                Some("component".to_owned()),
+                SymbolKind::INTERFACE,
+                &component.ident.pos,
+                &component.ident.pos,
+                children,
+            )
+        }
+        Declaration::Alias(alias) => symbol(
+            alias.designator.to_string(),
+            Some(format!("alias of {}", alias.name)),
+            SymbolKind::VARIABLE,
+            &alias.designator.pos,
+            &alias.designator.pos,
+            Vec::new(),
+        ),
+        Declaration::SubprogramDeclaration(decl) => subprogram_symbol(decl, Vec::new()),
+        Declaration::SubprogramBody(body) => {
+            subprogram_symbol(&body.specification, declaration_symbols(&body.declarations))
+        }
+        Declaration::Package(instance) => leaf_symbol(
+            &instance.ident,
+            Some(format!("package instance of {}", instance.package_name)),
+            SymbolKind::PACKAGE,
+        ),
+        Declaration::Attribute(..) | Declaration::Use(..) | Declaration::Configuration(..) => {
+            return None
+        }
+    };
+    Some(symbol)
+}
+
+fn type_symbol(typ: &TypeDeclaration) -> DocumentSymbol {
+    let (detail, kind, children) = match typ.def {
+        TypeDefinition::Enumeration(..) => ("type", SymbolKind::ENUM, Vec::new()),
+        TypeDefinition::Record(ref elements) => (
+            "record",
+            SymbolKind::STRUCT,
+            elements
+                .iter()
+                .map(|elem| {
+                    leaf_symbol(
+                        &elem.ident,
+                        Some(elem.subtype.to_string()),
+                        SymbolKind::FIELD,
+                    )
+                })
+                .collect(),
+        ),
+        TypeDefinition::Protected(ref protected) => (
+            "protected",
+            SymbolKind::CLASS,
+            protected
+                .items
+                .iter()
+                .map(|item| match item {
+                    ProtectedTypeDeclarativeItem::Subprogram(decl) => {
+                        subprogram_symbol(decl, Vec::new())
+                    }
+                })
+                .collect(),
+        ),
+        TypeDefinition::ProtectedBody(ref body) => (
+            "protected body",
+            SymbolKind::CLASS,
+            declaration_symbols(&body.decl),
+        ),
+        TypeDefinition::Subtype(..) => ("subtype", SymbolKind::TYPE_PARAMETER, Vec::new()),
+        _ => ("type", SymbolKind::TYPE_PARAMETER, Vec::new()),
+    };
+
+    symbol(
+        typ.ident.to_string(),
+        Some(detail.to_owned()),
+        kind,
+        &typ.ident.pos,
+        &typ.ident.pos,
+        children,
+    )
+}
+
+fn subprogram_symbol(
+    decl: &SubprogramDeclaration,
+    children: Vec<DocumentSymbol>,
+) -> DocumentSymbol {
+    let designator = match decl {
+        SubprogramDeclaration::Function(function) => &function.designator,
+        SubprogramDeclaration::Procedure(procedure) => &procedure.designator,
+    };
+
+    symbol(
+        designator.to_string(),
+        Some(decl.to_string()),
+        SymbolKind::FUNCTION,
+        &designator.pos,
+        &designator.pos,
+        children,
+    )
+}
+
+fn statement_symbols(statements: &[LabeledConcurrentStatement]) -> Vec<DocumentSymbol> {
+    statements.iter().filter_map(statement_symbol).collect()
+}
+
+fn statement_symbol(statement: &LabeledConcurrentStatement) -> Option<DocumentSymbol> {
+    let (name, detail, kind, children) = match statement.statement.item {
+        ConcurrentStatement::Process(ref process) => (
+            "process",
+            None,
+            SymbolKind::EVENT,
+            declaration_symbols(&process.decl),
+        ),
+        ConcurrentStatement::Block(ref block) => {
+            let mut children = declaration_symbols(&block.decl);
+            children.extend(statement_symbols(&block.statements));
+            ("block", None, SymbolKind::NAMESPACE, children)
+        }
+        ConcurrentStatement::ForGenerate(ref gen) => (
+            "generate",
+            Some(gen.to_string()),
+            SymbolKind::NAMESPACE,
+            generate_body_symbols(&gen.body),
+        ),
+        ConcurrentStatement::IfGenerate(ref gen) => {
+            let mut children = Vec::new();
+            for conditional in gen.conds.conditionals.iter() {
+                children.extend(generate_body_symbols(&conditional.item));
+            }
+            if let Some(ref body) = gen.conds.else_item {
+                children.extend(generate_body_symbols(body));
+            }
+            ("generate", None, SymbolKind::NAMESPACE, children)
+        }
+        ConcurrentStatement::CaseGenerate(ref gen) => {
+            let mut children = Vec::new();
+            for alternative in gen.sels.alternatives.iter() {
+                children.extend(generate_body_symbols(&alternative.item));
+            }
+            ("generate", None, SymbolKind::NAMESPACE, children)
+        }
+        ConcurrentStatement::Instance(ref instance) => (
+            "instance",
+            Some(instantiated_unit_detail(&instance.unit)),
+            SymbolKind::MODULE,
+            Vec::new(),
+        ),
+        ConcurrentStatement::ProcedureCall(..)
+        | ConcurrentStatement::Assert(..)
+        | ConcurrentStatement::Assignment(..) => return None,
+    };
+
+    let (name, range, selection_range) = if let Some(ref label) = statement.label {
+        (
+            label.to_string(),
+            label.pos.combine(&statement.statement.pos),
+            label.pos.clone(),
+        )
+    } else {
+        // Unlabeled statements are named by their kind
+        (
+            name.to_owned(),
+            statement.statement.pos.clone(),
+            statement.statement.pos.clone(),
+        )
+    };
+
+    Some(symbol(
+        name,
+        detail,
+        kind,
+        &range,
+        &selection_range,
+        children,
+    ))
+}
+
+fn generate_body_symbols(body: &GenerateBody) -> Vec<DocumentSymbol> {
+    let mut children = body
+        .decl
+        .as_ref()
+        .map(|decl| declaration_symbols(decl))
+        .unwrap_or_default();
+    children.extend(statement_symbols(&body.statements));
+    children
+}
+
+fn instantiated_unit_detail(unit: &InstantiatedUnit) -> String {
+    match unit {
+        InstantiatedUnit::Component(name) => format!("component {}", name),
+        InstantiatedUnit::Entity(name, Some(arch)) => format!("entity {}({})", name, arch),
+        InstantiatedUnit::Entity(name, None) => format!("entity {}", name),
+        InstantiatedUnit::Configuration(name) => format!("configuration {}", name),
+    }
+}
+
+fn leaf_symbol(ident: &Ident, detail: Option<String>, kind: SymbolKind) -> DocumentSymbol {
+    symbol(
+        ident.to_string(),
+        detail,
+        kind,
+        &ident.pos,
+        &ident.pos,
+        Vec::new(),
+    )
+}
+
+#[allow(deprecated)]
+fn symbol(
+    name: String,
+    detail: Option<String>,
+    kind: SymbolKind,
+    range: &SrcPos,
+    selection_range: &SrcPos,
+    children: Vec<DocumentSymbol>,
+) -> DocumentSymbol {
+    // Declarations without a known extent are widened to enclose their children
+    let mut range = to_lsp_range(range.range());
+    for child in children.iter() {
+        range.start = std::cmp::min(range.start, child.range.start);
+        range.end = std::cmp::max(range.end, child.range.end);
+    }
+
+    DocumentSymbol {
+        name,
+        detail,
+        kind,
+        tags: None,
+        deprecated: None,
+        range,
+        selection_range: to_lsp_range(selection_range.range()),
+        children: if children.is_empty() {
+            None
+        } else {
+            Some(children)
+        },
+    }
+}
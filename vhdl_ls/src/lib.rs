@@ -7,7 +7,11 @@
 #[macro_use]
 extern crate log;
 
+mod analysis_worker;
+mod design_hierarchy;
+mod document_symbol;
 mod rpc_channel;
+mod semantic_tokens;
 mod stdio_server;
 mod vhdl_server;
 pub use crate::stdio_server::start;
@@ -0,0 +1,131 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this file,
+// You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) 2020, Olof Kraigher olof.kraigher@gmail.com
+
+//! Encoding of classified identifiers as LSP semantic tokens
+
+use lsp_types::{SemanticToken, SemanticTokenModifier, SemanticTokenType, SemanticTokensLegend};
+use vhdl_lang::ast::Mode;
+use vhdl_lang::{Range, SemanticTokenKind};
+
+// The index of a token type is its position within the legend
+const NAMESPACE: u32 = 0;
+const CLASS: u32 = 1;
+const TYPE: u32 = 2;
+const ENUM_MEMBER: u32 = 3;
+const FUNCTION: u32 = 4;
+const VARIABLE: u32 = 5;
+const PARAMETER: u32 = 6;
+
+// The bit of a token modifier is its position within the legend
+const DECLARATION: u32 = 1 << 0;
+const READONLY: u32 = 1 << 1;
+const SIGNAL: u32 = 1 << 2;
+const IN: u32 = 1 << 3;
+const OUT: u32 = 1 << 4;
+const INOUT: u32 = 1 << 5;
+const BUFFER: u32 = 1 << 6;
+const LINKAGE: u32 = 1 << 7;
+const SUBTYPE: u32 = 1 << 8;
+const PROCEDURE: u32 = 1 << 9;
+
+pub fn legend() -> SemanticTokensLegend {
+    SemanticTokensLegend {
+        token_types: vec![
+            SemanticTokenType::NAMESPACE,
+            SemanticTokenType::CLASS,
+            SemanticTokenType::TYPE,
+            SemanticTokenType::ENUM_MEMBER,
+            SemanticTokenType::FUNCTION,
+            SemanticTokenType::VARIABLE,
+            SemanticTokenType::PARAMETER,
+        ],
+        token_modifiers: vec![
+            SemanticTokenModifier::DECLARATION,
+            SemanticTokenModifier::READONLY,
+            SemanticTokenModifier::new("signal"),
+            SemanticTokenModifier::new("in"),
+            SemanticTokenModifier::new("out"),
+            SemanticTokenModifier::new("inout"),
+            SemanticTokenModifier::new("buffer"),
+            SemanticTokenModifier::new("linkage"),
+            SemanticTokenModifier::new("subtype"),
+            SemanticTokenModifier::new("procedure"),
+        ],
+    }
+}
+
+/// Encode the tokens relative to each other
+/// Only tokens overlapping the range are included when a range is given
+pub fn semantic_tokens(
+    tokens: &[vhdl_lang::SemanticToken],
+    range: Option<Range>,
+) -> Vec<SemanticToken> {
+    let mut result = Vec::with_capacity(tokens.len());
+    let mut prev_line = 0;
+    let mut prev_start = 0;
+
+    for token in tokens {
+        let token_range = token.pos.range();
+
+        if let Some(range) = range {
+            if token_range.end <= range.start || range.end <= token_range.start {
+                continue;
+            }
+        }
+
+        let line = token_range.start.line;
+        let start = token_range.start.character;
+        let (token_type, modifiers) = token_type_and_modifiers(token.kind);
+
+        result.push(SemanticToken {
+            delta_line: line - prev_line,
+            delta_start: if line == prev_line {
+                start - prev_start
+            } else {
+                start
+            },
+            length: token_range.end.character.saturating_sub(start),
+            token_type,
+            token_modifiers_bitset: if token.is_declaration {
+                modifiers | DECLARATION
+            } else {
+                modifiers
+            },
+        });
+
+        prev_line = line;
+        prev_start = start;
+    }
+
+    result
+}
+
+fn token_type_and_modifiers(kind: SemanticTokenKind) -> (u32, u32) {
+    match kind {
+        SemanticTokenKind::Signal => (VARIABLE, SIGNAL),
+        SemanticTokenKind::Variable => (VARIABLE, 0),
+        SemanticTokenKind::Constant => (VARIABLE, READONLY),
+        SemanticTokenKind::Port(mode) => (PARAMETER, SIGNAL | mode_modifier(mode)),
+        SemanticTokenKind::Generic => (PARAMETER, READONLY),
+        SemanticTokenKind::Type => (TYPE, 0),
+        SemanticTokenKind::Subtype => (TYPE, SUBTYPE),
+        SemanticTokenKind::EnumLiteral => (ENUM_MEMBER, 0),
+        SemanticTokenKind::Function => (FUNCTION, 0),
+        SemanticTokenKind::Procedure => (FUNCTION, PROCEDURE),
+        SemanticTokenKind::Library => (NAMESPACE, 0),
+        SemanticTokenKind::Unit => (CLASS, 0),
+    }
+}
+
+fn mode_modifier(mode: Mode) -> u32 {
+    match mode {
+        Mode::In => IN,
+        Mode::Out => OUT,
+        Mode::InOut => INOUT,
+        Mode::Buffer => BUFFER,
+        Mode::Linkage => LINKAGE,
+    }
+}
@@ -8,13 +8,15 @@
 //! It also contains the main event loop for handling incoming messages from the LSP client and
 //! dispatching them to the appropriate server methods.
 
-use lsp_server::{Connection, Request, RequestId};
+use crossbeam_channel::{never, select};
+use lsp_server::{Connection, ExtractError, Request, RequestId};
 use lsp_types::{
     notification::{self, Notification},
     request, InitializeParams,
 };
 use std::{cell::RefCell, rc::Rc};
 
+use crate::design_hierarchy::DesignHierarchyRequest;
 use crate::rpc_channel::RpcChannel;
 use crate::vhdl_server::VHDLServer;
 
@@ -35,7 +37,7 @@ pub fn start() {
 #[derive(Clone)]
 struct ConnectionRpcChannel {
     connection: Rc<Connection>,
-    next_outgoing_request_id: Rc<RefCell<u64>>,
+    next_outgoing_request_id: Rc<RefCell<i32>>,
 }
 
 impl RpcChannel for ConnectionRpcChannel {
@@ -91,25 +93,42 @@ impl ConnectionRpcChannel {
         server.initialized_notification();
     }
 
-    /// Main event loop handling incoming messages from the client.
+    /// Main event loop handling incoming messages from the client
+    /// and the results of the analyses running in the background.
     fn main_event_loop<T: RpcChannel + Clone>(&self, mut server: VHDLServer<T>) {
         info!("Language server initialized, waiting for messages ...");
-        while let Ok(message) = self.connection.receiver.recv() {
-            trace!("Received message: {:?}", message);
-            if let lsp_server::Message::Notification(notification) = &message {
-                if notification.method == notification::Exit::METHOD {
-                    return;
+        let mut analysis_results = server.analysis_results();
+        loop {
+            select! {
+                recv(self.connection.receiver) -> message => {
+                    let message = match message {
+                        Ok(message) => message,
+                        Err(_) => return,
+                    };
+                    trace!("Received message: {:?}", message);
+                    if let lsp_server::Message::Notification(notification) = &message {
+                        if notification.method == notification::Exit::METHOD {
+                            return;
+                        }
+                    }
+                    match message {
+                        lsp_server::Message::Request(request) => {
+                            self.handle_request(&mut server, request)
+                        }
+                        lsp_server::Message::Notification(notification) => {
+                            self.handle_notification(&mut server, notification);
+                        }
+                        lsp_server::Message::Response(response) => {
+                            self.handle_response(&mut server, response)
+                        }
+                    };
                 }
-            }
-            match message {
-                lsp_server::Message::Request(request) => self.handle_request(&mut server, request),
-                lsp_server::Message::Notification(notification) => {
-                    self.handle_notification(&mut server, notification);
-                }
-                lsp_server::Message::Response(response) => {
-                    self.handle_response(&mut server, response)
+                recv(analysis_results) -> result => match result {
+                    Ok(result) => server.analysis_finished(result),
+                    // The analysis thread is stopped when the server is shut down
+                    Err(_) => analysis_results = never(),
                 }
-            };
+            }
         }
     }
 
@@ -132,13 +151,19 @@ impl ConnectionRpcChannel {
             R: request::Request,
             R::Params: serde::de::DeserializeOwned,
         {
-            request.extract(R::METHOD)
+            request.extract(R::METHOD).map_err(|err| match err {
+                ExtractError::MethodMismatch(request) => request,
+                ExtractError::JsonError { method, error } => {
+                    panic!("Invalid parameters for {}: {}", method, error)
+                }
+            })
         }
 
         trace!("Handling request: {:?}", request);
         let request = match extract::<request::GotoDeclaration>(request) {
             Ok((id, params)) => {
-                let result = server.text_document_declaration(&params);
+                let result =
+                    server.text_document_declaration(&params.text_document_position_params);
                 self.send_response(lsp_server::Response::new_ok(id, result));
                 return;
             }
@@ -146,7 +171,25 @@ impl ConnectionRpcChannel {
         };
         let request = match extract::<request::GotoDefinition>(request) {
             Ok((id, params)) => {
-                let result = server.text_document_definition(&params);
+                let result = server.text_document_definition(&params.text_document_position_params);
+                self.send_response(lsp_server::Response::new_ok(id, result));
+                return;
+            }
+            Err(request) => request,
+        };
+        let request = match extract::<request::GotoImplementation>(request) {
+            Ok((id, params)) => {
+                let result =
+                    server.text_document_implementation(&params.text_document_position_params);
+                self.send_response(lsp_server::Response::new_ok(id, result));
+                return;
+            }
+            Err(request) => request,
+        };
+        let request = match extract::<request::GotoTypeDefinition>(request) {
+            Ok((id, params)) => {
+                let result =
+                    server.text_document_type_definition(&params.text_document_position_params);
                 self.send_response(lsp_server::Response::new_ok(id, result));
                 return;
             }
@@ -154,7 +197,7 @@ impl ConnectionRpcChannel {
         };
         let request = match extract::<request::HoverRequest>(request) {
             Ok((id, params)) => {
-                let result = server.text_document_hover(&params);
+                let result = server.text_document_hover(&params.text_document_position_params);
                 self.send_response(lsp_server::Response::new_ok(id, result));
                 return;
             }
@@ -168,6 +211,199 @@ impl ConnectionRpcChannel {
             }
             Err(request) => request,
         };
+        let request = match extract::<request::SelectionRangeRequest>(request) {
+            Ok((id, params)) => {
+                let result = server.text_document_selection_range(&params);
+                self.send_response(lsp_server::Response::new_ok(id, result));
+                return;
+            }
+            Err(request) => request,
+        };
+        let request = match extract::<request::LinkedEditingRange>(request) {
+            Ok((id, params)) => {
+                let result = server.text_document_linked_editing_range(&params);
+                self.send_response(lsp_server::Response::new_ok(id, result));
+                return;
+            }
+            Err(request) => request,
+        };
+        let request = match extract::<request::DocumentHighlightRequest>(request) {
+            Ok((id, params)) => {
+                let result =
+                    server.text_document_document_highlight(&params.text_document_position_params);
+                self.send_response(lsp_server::Response::new_ok(id, result));
+                return;
+            }
+            Err(request) => request,
+        };
+        let request = match extract::<request::Completion>(request) {
+            Ok((id, params)) => {
+                let result = server.text_document_completion(&params);
+                self.send_response(lsp_server::Response::new_ok(id, result));
+                return;
+            }
+            Err(request) => request,
+        };
+        let request = match extract::<request::CodeActionRequest>(request) {
+            Ok((id, params)) => {
+                let result = server.text_document_code_action(&params);
+                self.send_response(lsp_server::Response::new_ok(id, result));
+                return;
+            }
+            Err(request) => request,
+        };
+        let request = match extract::<request::ExecuteCommand>(request) {
+            Ok((id, params)) => {
+                let result = server.workspace_execute_command(&params);
+                self.send_response(lsp_server::Response::new_ok(id, result));
+                return;
+            }
+            Err(request) => request,
+        };
+        let request = match extract::<request::DocumentDiagnosticRequest>(request) {
+            Ok((id, params)) => {
+                let result = server.text_document_diagnostic(&params);
+                self.send_response(lsp_server::Response::new_ok(id, result));
+                return;
+            }
+            Err(request) => request,
+        };
+        let request = match extract::<request::WorkspaceDiagnosticRequest>(request) {
+            Ok((id, params)) => {
+                let result = server.workspace_diagnostic(&params);
+                self.send_response(lsp_server::Response::new_ok(id, result));
+                return;
+            }
+            Err(request) => request,
+        };
+        let request = match extract::<request::CodeLensRequest>(request) {
+            Ok((id, params)) => {
+                let result = server.text_document_code_lens(&params);
+                self.send_response(lsp_server::Response::new_ok(id, result));
+                return;
+            }
+            Err(request) => request,
+        };
+        let request = match extract::<request::SignatureHelpRequest>(request) {
+            Ok((id, params)) => {
+                let result =
+                    server.text_document_signature_help(&params.text_document_position_params);
+                self.send_response(lsp_server::Response::new_ok(id, result));
+                return;
+            }
+            Err(request) => request,
+        };
+        let request = match extract::<request::DocumentSymbolRequest>(request) {
+            Ok((id, params)) => {
+                let result = server.text_document_document_symbol(&params);
+                self.send_response(lsp_server::Response::new_ok(id, result));
+                return;
+            }
+            Err(request) => request,
+        };
+        let request = match extract::<request::FoldingRangeRequest>(request) {
+            Ok((id, params)) => {
+                let result = server.text_document_folding_range(&params);
+                self.send_response(lsp_server::Response::new_ok(id, result));
+                return;
+            }
+            Err(request) => request,
+        };
+        let request = match extract::<request::WorkspaceSymbolRequest>(request) {
+            Ok((id, params)) => {
+                let result = server.workspace_symbol(&params);
+                self.send_response(lsp_server::Response::new_ok(id, result));
+                return;
+            }
+            Err(request) => request,
+        };
+        let request = match extract::<request::SemanticTokensFullRequest>(request) {
+            Ok((id, params)) => {
+                let result = server.text_document_semantic_tokens_full(&params);
+                self.send_response(lsp_server::Response::new_ok(id, result));
+                return;
+            }
+            Err(request) => request,
+        };
+        let request = match extract::<request::CallHierarchyPrepare>(request) {
+            Ok((id, params)) => {
+                let result = server
+                    .text_document_prepare_call_hierarchy(&params.text_document_position_params);
+                self.send_response(lsp_server::Response::new_ok(id, result));
+                return;
+            }
+            Err(request) => request,
+        };
+        let request = match extract::<request::CallHierarchyIncomingCalls>(request) {
+            Ok((id, params)) => {
+                let result = server.call_hierarchy_incoming_calls(&params);
+                self.send_response(lsp_server::Response::new_ok(id, result));
+                return;
+            }
+            Err(request) => request,
+        };
+        let request = match extract::<request::CallHierarchyOutgoingCalls>(request) {
+            Ok((id, params)) => {
+                let result = server.call_hierarchy_outgoing_calls(&params);
+                self.send_response(lsp_server::Response::new_ok(id, result));
+                return;
+            }
+            Err(request) => request,
+        };
+        let request = match extract::<request::InlayHintRequest>(request) {
+            Ok((id, params)) => {
+                let result = server.text_document_inlay_hint(&params);
+                self.send_response(lsp_server::Response::new_ok(id, result));
+                return;
+            }
+            Err(request) => request,
+        };
+        let request = match extract::<DesignHierarchyRequest>(request) {
+            Ok((id, params)) => {
+                let result = server.design_hierarchy(&params);
+                self.send_response(lsp_server::Response::new_ok(id, result));
+                return;
+            }
+            Err(request) => request,
+        };
+        let request = match extract::<request::SemanticTokensRangeRequest>(request) {
+            Ok((id, params)) => {
+                let result = server.text_document_semantic_tokens_range(&params);
+                self.send_response(lsp_server::Response::new_ok(id, result));
+                return;
+            }
+            Err(request) => request,
+        };
+        let request = match extract::<request::PrepareRenameRequest>(request) {
+            Ok((id, params)) => {
+                let response = match server.text_document_prepare_rename(&params) {
+                    Ok(result) => lsp_server::Response::new_ok(id, result),
+                    Err(message) => lsp_server::Response::new_err(
+                        id,
+                        lsp_server::ErrorCode::InvalidRequest as i32,
+                        message,
+                    ),
+                };
+                self.send_response(response);
+                return;
+            }
+            Err(request) => request,
+        };
+        let request = match extract::<request::Rename>(request) {
+            Ok((id, params)) => {
+                let response = match server.text_document_rename(&params) {
+                    Ok(result) => lsp_server::Response::new_ok(id, result),
+                    Err(message) => lsp_server::Response::new_err(
+                        id,
+                        lsp_server::ErrorCode::InvalidRequest as i32,
+                        message,
+                    ),
+                };
+                self.send_response(response);
+                return;
+            }
+            Err(request) => request,
+        };
         let request = match extract::<request::Shutdown>(request) {
             Ok((id, _params)) => {
                 server.shutdown_server();
@@ -198,7 +434,12 @@ impl ConnectionRpcChannel {
             N: notification::Notification,
             N::Params: serde::de::DeserializeOwned,
         {
-            notification.extract(N::METHOD)
+            notification.extract(N::METHOD).map_err(|err| match err {
+                ExtractError::MethodMismatch(notification) => notification,
+                ExtractError::JsonError { method, error } => {
+                    panic!("Invalid parameters for {}: {}", method, error)
+                }
+            })
         }
 
         trace!("Handling notification: {:?}", notification);
@@ -212,6 +453,16 @@ impl ConnectionRpcChannel {
             Ok(params) => return server.text_document_did_open_notification(&params),
             Err(notification) => notification,
         };
+        // textDocument/didClose
+        let notification = match extract::<notification::DidCloseTextDocument>(notification) {
+            Ok(params) => return server.text_document_did_close_notification(&params),
+            Err(notification) => notification,
+        };
+        // textDocument/didSave
+        let notification = match extract::<notification::DidSaveTextDocument>(notification) {
+            Ok(params) => return server.text_document_did_save_notification(&params),
+            Err(notification) => notification,
+        };
         // workspace.didChangeWatchedFiles
         let notification = match extract::<notification::DidChangeWatchedFiles>(notification) {
             Ok(params) => return server.workspace_did_change_watched_files(&params),
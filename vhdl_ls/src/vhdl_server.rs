@@ -8,29 +8,48 @@ use lsp_types::*;
 
 use fnv::FnvHashMap;
 use std::collections::hash_map::Entry;
+use std::collections::HashMap;
 
+use crate::analysis_worker::{AnalysisResult, AnalysisWorker};
+use crate::design_hierarchy::{design_hierarchy_node, DesignHierarchyNode, DesignHierarchyParams};
+use crate::document_symbol::document_symbols;
 use crate::rpc_channel::{MessageChannel, RpcChannel};
+use crate::semantic_tokens::{legend, semantic_tokens};
+use crossbeam_channel::{never, Receiver};
+use parking_lot::{RwLock, RwLockWriteGuard};
 use std::io;
 use std::path::{Path, PathBuf};
-use vhdl_lang::{Config, Diagnostic, Message, Project, Severity, Source, SrcPos};
+use std::sync::Arc;
+use vhdl_lang::ast::{NamedEntity, NamedEntityKind, ObjectClass, SecondaryKind};
+use vhdl_lang::{
+    AssociationStyle, CallItem, CallItemKind, CallSignatures, Config, Diagnostic, Message,
+    OccurrenceKind, PortMapFixKind, Project, Severity, Source, SrcPos, WorkspaceSymbol,
+    WorkspaceSymbolKind,
+};
 
 pub struct VHDLServer<T: RpcChannel + Clone> {
     rpc_channel: T,
     // To have well defined unit tests that are not affected by environment
     use_external_config: bool,
+    // Unit tests analyse synchronously to get a well defined order of notifications
+    background_analysis: bool,
     server: Option<InitializedVHDLServer<T>>,
     config_file: Option<PathBuf>,
 }
 
 impl<T: RpcChannel + Clone> VHDLServer<T> {
     pub fn new(rpc_channel: T) -> VHDLServer<T> {
-        Self::new_external_config(rpc_channel, true)
+        VHDLServer {
+            background_analysis: true,
+            ..Self::new_external_config(rpc_channel, true)
+        }
     }
 
     fn new_external_config(rpc_channel: T, use_external_config: bool) -> VHDLServer<T> {
         VHDLServer {
             rpc_channel,
             use_external_config,
+            background_analysis: false,
             server: None,
             config_file: None,
         }
@@ -86,7 +105,12 @@ impl<T: RpcChannel + Clone> VHDLServer<T> {
     pub fn initialize_request(&mut self, params: InitializeParams) -> InitializeResult {
         self.config_file = self.root_uri_config_file(&params);
         let config = self.load_config();
-        let (server, result) = InitializedVHDLServer::new(self.rpc_channel.clone(), config, params);
+        let (server, result) = InitializedVHDLServer::new(
+            self.rpc_channel.clone(),
+            config,
+            params,
+            self.background_analysis,
+        );
         self.server = Some(server);
         result
     }
@@ -115,6 +139,21 @@ impl<T: RpcChannel + Clone> VHDLServer<T> {
         }
     }
 
+    /// The results of the analyses running on a background thread
+    pub fn analysis_results(&self) -> Receiver<AnalysisResult> {
+        self.server
+            .as_ref()
+            .and_then(|server| server.analysis_worker.as_ref())
+            .map(|worker| worker.results())
+            .unwrap_or_else(never)
+    }
+
+    pub fn analysis_finished(&mut self, result: AnalysisResult) {
+        if let Some(server) = self.server.as_mut() {
+            server.analysis_finished(result);
+        }
+    }
+
     pub fn shutdown_server(&mut self) {
         self.server = None;
     }
@@ -136,7 +175,7 @@ impl<T: RpcChannel + Clone> VHDLServer<T> {
         if self.mut_server().client_supports_did_change_watched_files() {
             let register_options = DidChangeWatchedFilesRegistrationOptions {
                 watchers: vec![FileSystemWatcher {
-                    glob_pattern: "**/vhdl_ls.toml".to_owned(),
+                    glob_pattern: GlobPattern::String("**/vhdl_ls.toml".to_owned()),
                     kind: None,
                 }],
             };
@@ -167,6 +206,16 @@ impl<T: RpcChannel + Clone> VHDLServer<T> {
             .text_document_did_open_notification(&params)
     }
 
+    pub fn text_document_did_close_notification(&mut self, params: &DidCloseTextDocumentParams) {
+        self.mut_server()
+            .text_document_did_close_notification(&params)
+    }
+
+    pub fn text_document_did_save_notification(&mut self, params: &DidSaveTextDocumentParams) {
+        self.mut_server()
+            .text_document_did_save_notification(&params)
+    }
+
     pub fn workspace_did_change_watched_files(&mut self, params: &DidChangeWatchedFilesParams) {
         if let Some(config_file) = &self.config_file {
             let config_file_has_changed = params
@@ -199,6 +248,22 @@ impl<T: RpcChannel + Clone> VHDLServer<T> {
         self.mut_server().text_document_definition(&params)
     }
 
+    // textDocument/implementation
+    pub fn text_document_implementation(
+        &mut self,
+        params: &TextDocumentPositionParams,
+    ) -> Vec<Location> {
+        self.mut_server().text_document_implementation(&params)
+    }
+
+    // textDocument/typeDefinition
+    pub fn text_document_type_definition(
+        &mut self,
+        params: &TextDocumentPositionParams,
+    ) -> Vec<Location> {
+        self.mut_server().text_document_type_definition(&params)
+    }
+
     // textDocument/hover
     pub fn text_document_hover(&mut self, params: &TextDocumentPositionParams) -> Option<Hover> {
         self.mut_server().text_document_hover(&params)
@@ -208,13 +273,198 @@ impl<T: RpcChannel + Clone> VHDLServer<T> {
     pub fn text_document_references(&mut self, params: &ReferenceParams) -> Vec<Location> {
         self.mut_server().text_document_references(&params)
     }
+
+    // textDocument/documentHighlight
+    pub fn text_document_document_highlight(
+        &mut self,
+        params: &TextDocumentPositionParams,
+    ) -> Option<Vec<DocumentHighlight>> {
+        self.mut_server().text_document_document_highlight(&params)
+    }
+
+    // textDocument/selectionRange
+    pub fn text_document_selection_range(
+        &mut self,
+        params: &SelectionRangeParams,
+    ) -> Option<Vec<SelectionRange>> {
+        self.mut_server().text_document_selection_range(&params)
+    }
+
+    // textDocument/linkedEditingRange
+    pub fn text_document_linked_editing_range(
+        &mut self,
+        params: &LinkedEditingRangeParams,
+    ) -> Option<LinkedEditingRanges> {
+        self.mut_server()
+            .text_document_linked_editing_range(&params)
+    }
+
+    // textDocument/completion
+    pub fn text_document_completion(&mut self, params: &CompletionParams) -> CompletionList {
+        self.mut_server().text_document_completion(&params)
+    }
+
+    // textDocument/signatureHelp
+    pub fn text_document_signature_help(
+        &mut self,
+        params: &TextDocumentPositionParams,
+    ) -> Option<SignatureHelp> {
+        self.mut_server().text_document_signature_help(&params)
+    }
+
+    // textDocument/foldingRange
+    pub fn text_document_folding_range(
+        &mut self,
+        params: &FoldingRangeParams,
+    ) -> Option<Vec<FoldingRange>> {
+        self.mut_server().text_document_folding_range(&params)
+    }
+
+    // textDocument/prepareCallHierarchy
+    pub fn text_document_prepare_call_hierarchy(
+        &mut self,
+        params: &TextDocumentPositionParams,
+    ) -> Option<Vec<CallHierarchyItem>> {
+        self.mut_server()
+            .text_document_prepare_call_hierarchy(&params)
+    }
+
+    // callHierarchy/incomingCalls
+    pub fn call_hierarchy_incoming_calls(
+        &mut self,
+        params: &CallHierarchyIncomingCallsParams,
+    ) -> Option<Vec<CallHierarchyIncomingCall>> {
+        self.mut_server().call_hierarchy_incoming_calls(&params)
+    }
+
+    // callHierarchy/outgoingCalls
+    pub fn call_hierarchy_outgoing_calls(
+        &mut self,
+        params: &CallHierarchyOutgoingCallsParams,
+    ) -> Option<Vec<CallHierarchyOutgoingCall>> {
+        self.mut_server().call_hierarchy_outgoing_calls(&params)
+    }
+
+    // textDocument/inlayHint
+    pub fn text_document_inlay_hint(&mut self, params: &InlayHintParams) -> Option<Vec<InlayHint>> {
+        self.mut_server().text_document_inlay_hint(&params)
+    }
+
+    // vhdl/designHierarchy
+    pub fn design_hierarchy(
+        &mut self,
+        params: &DesignHierarchyParams,
+    ) -> Option<DesignHierarchyNode> {
+        self.mut_server().design_hierarchy(&params)
+    }
+
+    // textDocument/codeAction
+    pub fn text_document_code_action(
+        &mut self,
+        params: &CodeActionParams,
+    ) -> Option<CodeActionResponse> {
+        self.mut_server().text_document_code_action(&params)
+    }
+
+    // workspace/executeCommand
+    pub fn workspace_execute_command(
+        &mut self,
+        params: &ExecuteCommandParams,
+    ) -> Option<serde_json::Value> {
+        self.mut_server().workspace_execute_command(&params)
+    }
+
+    // textDocument/diagnostic
+    pub fn text_document_diagnostic(
+        &mut self,
+        params: &DocumentDiagnosticParams,
+    ) -> DocumentDiagnosticReportResult {
+        self.mut_server().text_document_diagnostic(&params)
+    }
+
+    // workspace/diagnostic
+    pub fn workspace_diagnostic(
+        &mut self,
+        params: &WorkspaceDiagnosticParams,
+    ) -> WorkspaceDiagnosticReportResult {
+        self.mut_server().workspace_diagnostic(&params)
+    }
+
+    // textDocument/codeLens
+    pub fn text_document_code_lens(&mut self, params: &CodeLensParams) -> Option<Vec<CodeLens>> {
+        self.mut_server().text_document_code_lens(&params)
+    }
+
+    // textDocument/documentSymbol
+    pub fn text_document_document_symbol(
+        &mut self,
+        params: &DocumentSymbolParams,
+    ) -> Option<DocumentSymbolResponse> {
+        self.mut_server().text_document_document_symbol(&params)
+    }
+
+    // workspace/symbol
+    pub fn workspace_symbol(
+        &mut self,
+        params: &WorkspaceSymbolParams,
+    ) -> Option<Vec<SymbolInformation>> {
+        self.mut_server().workspace_symbol(&params)
+    }
+
+    // textDocument/prepareRename
+    pub fn text_document_prepare_rename(
+        &mut self,
+        params: &TextDocumentPositionParams,
+    ) -> Result<Option<PrepareRenameResponse>, String> {
+        self.mut_server().text_document_prepare_rename(&params)
+    }
+
+    // textDocument/rename
+    pub fn text_document_rename(
+        &mut self,
+        params: &RenameParams,
+    ) -> Result<Option<WorkspaceEdit>, String> {
+        self.mut_server().text_document_rename(&params)
+    }
+
+    // textDocument/semanticTokens/full
+    pub fn text_document_semantic_tokens_full(
+        &mut self,
+        params: &SemanticTokensParams,
+    ) -> Option<SemanticTokensResult> {
+        self.mut_server()
+            .text_document_semantic_tokens_full(&params)
+    }
+
+    // textDocument/semanticTokens/range
+    pub fn text_document_semantic_tokens_range(
+        &mut self,
+        params: &SemanticTokensRangeParams,
+    ) -> Option<SemanticTokensRangeResult> {
+        self.mut_server()
+            .text_document_semantic_tokens_range(&params)
+    }
 }
 
 struct InitializedVHDLServer<T: RpcChannel> {
     rpc_channel: T,
     init_params: InitializeParams,
-    project: Project,
-    files_with_notifications: FnvHashMap<Url, ()>,
+    project: Arc<RwLock<Project>>,
+    // The project as of the last completed analysis which requests are answered from
+    analysed_project: Arc<Project>,
+    analysis_worker: Option<AnalysisWorker>,
+    // The generation of the latest analysis request, results of earlier requests are outdated
+    analysis_generation: u64,
+    // The diagnostics last reported for each file, files are kept when their diagnostics are cleared
+    file_diagnostics: FnvHashMap<Url, FileDiagnostics>,
+    next_result_id: u64,
+}
+
+/// The diagnostics of a file as last published or returned by a diagnostic request
+struct FileDiagnostics {
+    /// Identifies the diagnostics such that a pulling client is only told they are unchanged
+    result_id: String,
+    diagnostics: Vec<lsp_types::Diagnostic>,
 }
 
 /// Allow VHDL Server to act as an RpcChannel
@@ -237,24 +487,98 @@ impl<T: RpcChannel + Clone> InitializedVHDLServer<T> {
         rpc_channel: T,
         config: Config,
         init_params: InitializeParams,
+        background_analysis: bool,
     ) -> (InitializedVHDLServer<T>, InitializeResult) {
         let project = Project::from_config(&config, &mut MessageChannel::new(&rpc_channel));
+        let analysed_project = Arc::new(project.clone());
+        let project = Arc::new(RwLock::new(project));
+        let analysis_worker = if background_analysis {
+            Some(AnalysisWorker::spawn(project.clone()))
+        } else {
+            None
+        };
 
         let server = InitializedVHDLServer {
             rpc_channel,
             init_params,
             project,
-            files_with_notifications: FnvHashMap::default(),
+            analysed_project,
+            analysis_worker,
+            analysis_generation: 0,
+            file_diagnostics: FnvHashMap::default(),
+            next_result_id: 0,
+        };
+
+        // Clients supporting pulled diagnostics must be told to pull again after each analysis
+        let diagnostic_provider = if server.client_supports_pull_diagnostics() {
+            Some(DiagnosticServerCapabilities::Options(DiagnosticOptions {
+                identifier: None,
+                inter_file_dependencies: true,
+                workspace_diagnostics: true,
+                work_done_progress_options: Default::default(),
+            }))
+        } else {
+            None
         };
 
         let capabilities = ServerCapabilities {
-            text_document_sync: Some(TextDocumentSyncCapability::Kind(
-                TextDocumentSyncKind::Incremental,
+            text_document_sync: Some(TextDocumentSyncCapability::Options(
+                TextDocumentSyncOptions {
+                    open_close: Some(true),
+                    change: Some(TextDocumentSyncKind::INCREMENTAL),
+                    will_save: None,
+                    will_save_wait_until: None,
+                    save: Some(TextDocumentSyncSaveOptions::Supported(true)),
+                },
             )),
-            declaration_provider: Some(true),
-            definition_provider: Some(true),
-            hover_provider: Some(true),
-            references_provider: Some(true),
+            declaration_provider: Some(DeclarationCapability::Simple(true)),
+            definition_provider: Some(OneOf::Left(true)),
+            implementation_provider: Some(ImplementationProviderCapability::Simple(true)),
+            type_definition_provider: Some(TypeDefinitionProviderCapability::Simple(true)),
+            hover_provider: Some(HoverProviderCapability::Simple(true)),
+            references_provider: Some(OneOf::Left(true)),
+            document_highlight_provider: Some(OneOf::Left(true)),
+            completion_provider: Some(CompletionOptions {
+                resolve_provider: Some(false),
+                trigger_characters: Some(vec![".".to_owned()]),
+                ..Default::default()
+            }),
+            signature_help_provider: Some(SignatureHelpOptions {
+                trigger_characters: Some(vec!["(".to_owned(), ",".to_owned()]),
+                retrigger_characters: None,
+                work_done_progress_options: Default::default(),
+            }),
+            document_symbol_provider: Some(OneOf::Left(true)),
+            folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
+            selection_range_provider: Some(SelectionRangeProviderCapability::Simple(true)),
+            linked_editing_range_provider: Some(LinkedEditingRangeServerCapabilities::Simple(true)),
+            inlay_hint_provider: Some(OneOf::Left(true)),
+            call_hierarchy_provider: Some(CallHierarchyServerCapability::Simple(true)),
+            code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+            code_lens_provider: Some(CodeLensOptions {
+                resolve_provider: Some(false),
+            }),
+            execute_command_provider: Some(ExecuteCommandOptions {
+                commands: vec![
+                    CREATE_ARCHITECTURE_COMMAND.to_owned(),
+                    CREATE_TESTBENCH_COMMAND.to_owned(),
+                ],
+                work_done_progress_options: Default::default(),
+            }),
+            diagnostic_provider,
+            workspace_symbol_provider: Some(OneOf::Left(true)),
+            rename_provider: Some(OneOf::Right(RenameOptions {
+                prepare_provider: Some(true),
+                work_done_progress_options: Default::default(),
+            })),
+            semantic_tokens_provider: Some(
+                SemanticTokensServerCapabilities::SemanticTokensOptions(SemanticTokensOptions {
+                    legend: legend(),
+                    range: Some(true),
+                    full: Some(SemanticTokensFullOptions::Bool(true)),
+                    work_done_progress_options: Default::default(),
+                }),
+            ),
             ..Default::default()
         };
 
@@ -266,14 +590,27 @@ impl<T: RpcChannel + Clone> InitializedVHDLServer<T> {
         (server, result)
     }
 
+    /// The project as of the last completed analysis
+    fn project(&self) -> &Project {
+        &self.analysed_project
+    }
+
+    /// Lock the project for changes cancelling any ongoing analysis
+    fn project_mut(&self) -> RwLockWriteGuard<'_, Project> {
+        match self.analysis_worker {
+            Some(ref worker) => worker.write(&self.project),
+            None => self.project.write(),
+        }
+    }
+
     pub fn change_configuration(&mut self, config: Config) {
-        self.project
+        self.project_mut()
             .update_config(&config, &mut MessageChannel::new(&self.rpc_channel));
-        self.publish_diagnostics();
+        self.analyse();
     }
 
     pub fn initialized_notification(&mut self) {
-        self.publish_diagnostics();
+        self.analyse();
     }
 
     fn client_supports_related_information(&self) -> bool {
@@ -289,6 +626,20 @@ impl<T: RpcChannel + Clone> InitializedVHDLServer<T> {
         try_fun().unwrap_or(false)
     }
 
+    fn client_supports_pull_diagnostics(&self) -> bool {
+        let try_fun = || {
+            let capabilities = &self.init_params.capabilities;
+            capabilities.text_document.as_ref()?.diagnostic.as_ref()?;
+            capabilities
+                .workspace
+                .as_ref()?
+                .diagnostic
+                .as_ref()?
+                .refresh_support
+        };
+        try_fun().unwrap_or(false)
+    }
+
     fn client_supports_did_change_watched_files(&self) -> bool {
         let try_fun = || {
             self.init_params
@@ -302,9 +653,32 @@ impl<T: RpcChannel + Clone> InitializedVHDLServer<T> {
         try_fun().unwrap_or(false)
     }
 
-    fn publish_diagnostics(&mut self) {
+    /// Analyse the project and publish the diagnostics
+    /// With background analysis the diagnostics are published when the analysis is finished
+    fn analyse(&mut self) {
+        self.analysis_generation += 1;
+        match self.analysis_worker {
+            Some(ref worker) => worker.request_analysis(self.analysis_generation),
+            None => {
+                let diagnostics = self.project.write().analyse();
+                self.analysed_project = Arc::new(self.project.read().clone());
+                self.publish_diagnostics(diagnostics);
+            }
+        }
+    }
+
+    pub fn analysis_finished(&mut self, result: AnalysisResult) {
+        self.analysed_project = result.project;
+        // The project was changed after the analysis was requested
+        if result.generation == self.analysis_generation {
+            self.publish_diagnostics(result.diagnostics);
+        }
+    }
+
+    /// Publish the diagnostics of the files whose diagnostics changed since the last analysis
+    /// A client pulling diagnostics is instead asked to pull them again
+    fn publish_diagnostics(&mut self, diagnostics: Vec<Diagnostic>) {
         let supports_related_information = self.client_supports_related_information();
-        let diagnostics = self.project.analyse();
         let diagnostics = {
             if supports_related_information {
                 diagnostics
@@ -313,64 +687,172 @@ impl<T: RpcChannel + Clone> InitializedVHDLServer<T> {
             }
         };
 
-        let mut files_with_notifications = std::mem::take(&mut self.files_with_notifications);
-        for (file_uri, diagnostics) in diagnostics_by_uri(diagnostics).into_iter() {
-            let mut lsp_diagnostics = Vec::new();
-            for diagnostic in diagnostics {
-                lsp_diagnostics.push(to_lsp_diagnostic(diagnostic));
+        let mut diagnostics_by_uri: FnvHashMap<Url, Vec<lsp_types::Diagnostic>> =
+            diagnostics_by_uri(diagnostics)
+                .into_iter()
+                .map(|(file_uri, diagnostics)| {
+                    (
+                        file_uri,
+                        diagnostics.into_iter().map(to_lsp_diagnostic).collect(),
+                    )
+                })
+                .collect();
+
+        // Files which no longer have any diagnostics are cleared
+        for file_uri in self.file_diagnostics.keys() {
+            diagnostics_by_uri.entry(file_uri.clone()).or_default();
+        }
+
+        let mut changed_files = Vec::new();
+        for (file_uri, diagnostics) in diagnostics_by_uri.into_iter() {
+            if let Some(previous) = self.file_diagnostics.get(&file_uri) {
+                if previous.diagnostics == diagnostics {
+                    continue;
+                }
             }
 
+            self.next_result_id += 1;
+            let file_diagnostics = FileDiagnostics {
+                result_id: self.next_result_id.to_string(),
+                diagnostics,
+            };
+            self.file_diagnostics
+                .insert(file_uri.clone(), file_diagnostics);
+            changed_files.push(file_uri);
+        }
+
+        if changed_files.is_empty() {
+            return;
+        }
+
+        if self.client_supports_pull_diagnostics() {
+            self.send_request("workspace/diagnostic/refresh", ());
+            return;
+        }
+
+        // The files with diagnostics are published before the cleared files
+        changed_files.sort_by_cached_key(|file_uri| {
+            (
+                self.file_diagnostics[file_uri].diagnostics.is_empty(),
+                file_uri.clone(),
+            )
+        });
+        for file_uri in changed_files {
             let publish_diagnostics = PublishDiagnosticsParams {
-                uri: file_uri.clone(),
-                diagnostics: lsp_diagnostics,
+                diagnostics: self.file_diagnostics[&file_uri].diagnostics.clone(),
+                uri: file_uri,
                 version: None,
             };
 
             self.send_notification("textDocument/publishDiagnostics", publish_diagnostics);
-
-            self.files_with_notifications.insert(file_uri.clone(), ());
         }
+    }
+
+    /// The diagnostics of a file as of the last completed analysis
+    pub fn text_document_diagnostic(
+        &mut self,
+        params: &DocumentDiagnosticParams,
+    ) -> DocumentDiagnosticReportResult {
+        let report = match self.file_diagnostics.get(&params.text_document.uri) {
+            Some(file_diagnostics)
+                if params.previous_result_id.as_ref() == Some(&file_diagnostics.result_id) =>
+            {
+                DocumentDiagnosticReport::Unchanged(RelatedUnchangedDocumentDiagnosticReport {
+                    related_documents: None,
+                    unchanged_document_diagnostic_report: UnchangedDocumentDiagnosticReport {
+                        result_id: file_diagnostics.result_id.clone(),
+                    },
+                })
+            }
+            file_diagnostics => {
+                DocumentDiagnosticReport::Full(RelatedFullDocumentDiagnosticReport {
+                    related_documents: None,
+                    full_document_diagnostic_report: FullDocumentDiagnosticReport {
+                        result_id: file_diagnostics
+                            .map(|diagnostics| diagnostics.result_id.clone()),
+                        items: file_diagnostics
+                            .map(|diagnostics| diagnostics.diagnostics.clone())
+                            .unwrap_or_default(),
+                    },
+                })
+            }
+        };
+        DocumentDiagnosticReportResult::Report(report)
+    }
 
-        for (file_uri, _) in files_with_notifications.drain() {
-            // File has no longer any diagnosics, publish empty notification to clear them
-            if !self.files_with_notifications.contains_key(&file_uri) {
-                let publish_diagnostics = PublishDiagnosticsParams {
+    /// The diagnostics of all files which changed since the client last pulled them
+    pub fn workspace_diagnostic(
+        &mut self,
+        params: &WorkspaceDiagnosticParams,
+    ) -> WorkspaceDiagnosticReportResult {
+        let previous_result_ids: FnvHashMap<&Url, &String> = params
+            .previous_result_ids
+            .iter()
+            .map(|previous| (&previous.uri, &previous.value))
+            .collect();
+
+        let mut file_uris: Vec<_> = self.file_diagnostics.keys().collect();
+        file_uris.sort();
+
+        let mut items = Vec::new();
+        for file_uri in file_uris {
+            let file_diagnostics = &self.file_diagnostics[file_uri];
+            let item = if previous_result_ids.get(file_uri) == Some(&&file_diagnostics.result_id) {
+                WorkspaceDocumentDiagnosticReport::Unchanged(
+                    WorkspaceUnchangedDocumentDiagnosticReport {
+                        uri: file_uri.clone(),
+                        version: None,
+                        unchanged_document_diagnostic_report: UnchangedDocumentDiagnosticReport {
+                            result_id: file_diagnostics.result_id.clone(),
+                        },
+                    },
+                )
+            } else {
+                WorkspaceDocumentDiagnosticReport::Full(WorkspaceFullDocumentDiagnosticReport {
                     uri: file_uri.clone(),
-                    diagnostics: vec![],
                     version: None,
-                };
-
-                self.send_notification("textDocument/publishDiagnostics", publish_diagnostics);
-            }
+                    full_document_diagnostic_report: FullDocumentDiagnosticReport {
+                        result_id: Some(file_diagnostics.result_id.clone()),
+                        items: file_diagnostics.diagnostics.clone(),
+                    },
+                })
+            };
+            items.push(item);
         }
+
+        WorkspaceDiagnosticReportResult::Report(WorkspaceDiagnosticReport { items })
     }
 
     fn open(&mut self, uri: &Url, code: &str) {
         let file_name = uri_to_file_name(uri);
-        if let Some(source) = self.project.get_source(&file_name) {
+        // The read lock must be released before the project is changed
+        let source = self.project.read().get_source(&file_name);
+        if let Some(source) = source {
             source.change(None, &code);
-            self.project.update_source(&source);
-            self.publish_diagnostics();
+            self.project_mut().update_source(&source);
+            self.analyse();
         } else {
             self.push_msg(Message::warning(format!(
                 "Opening file {} that is not part of the project",
                 file_name.to_string_lossy()
             )));
-            self.project
+            self.project_mut()
                 .update_source(&Source::inline(&file_name, code));
-            self.publish_diagnostics();
+            self.analyse();
         }
     }
 
     pub fn text_document_did_change_notification(&mut self, params: &DidChangeTextDocumentParams) {
         let file_name = uri_to_file_name(&params.text_document.uri);
-        if let Some(source) = self.project.get_source(&file_name) {
+        // The source may have been added to the project after the last completed analysis
+        let source = self.project.read().get_source(&file_name);
+        if let Some(source) = source {
             for content_change in params.content_changes.iter() {
                 let range = content_change.range.map(from_lsp_range);
                 source.change(range.as_ref(), &content_change.text);
             }
-            self.project.update_source(&source);
-            self.publish_diagnostics();
+            self.project_mut().update_source(&source);
+            self.analyse();
         } else {
             self.push_msg(Message::error(format!(
                 "Changing file {} that is not part of the project",
@@ -383,35 +865,106 @@ impl<T: RpcChannel + Clone> InitializedVHDLServer<T> {
         self.open(&params.text_document.uri, &params.text_document.text);
     }
 
+    /// Discard unsaved changes of a file of the project by reloading it from disk
+    /// Files outside of the project are only analysed while they are open
+    pub fn text_document_did_close_notification(&mut self, params: &DidCloseTextDocumentParams) {
+        let file_name = uri_to_file_name(&params.text_document.uri);
+        let result = {
+            let mut project = self.project_mut();
+            if project.remove_unmapped_source(&file_name) {
+                Ok(())
+            } else {
+                project.reload_source(&file_name)
+            }
+        };
+        self.reloaded(&file_name, result);
+    }
+
+    /// Synchronize the saved file with its contents on disk
+    pub fn text_document_did_save_notification(&mut self, params: &DidSaveTextDocumentParams) {
+        let file_name = uri_to_file_name(&params.text_document.uri);
+        let result = self.project_mut().reload_source(&file_name);
+        self.reloaded(&file_name, result);
+    }
+
+    fn reloaded(&mut self, file_name: &Path, result: io::Result<()>) {
+        if let Err(err) = result {
+            self.push_msg(Message::file_error(err.to_string(), file_name));
+        }
+        // Writing the project cancels any ongoing analysis which has to be requested again
+        self.analyse();
+    }
+
     pub fn text_document_declaration(
         &mut self,
         params: &TextDocumentPositionParams,
     ) -> Option<Location> {
-        self.project
+        self.project()
             .get_source(&uri_to_file_name(&params.text_document.uri))
             .and_then(|source| {
-                self.project
+                self.project()
                     .search_reference(&source, from_lsp_pos(params.position))
             })
             .map(|result| srcpos_to_location(&result))
     }
 
-    // Copy goto-declaration for now
     pub fn text_document_definition(
         &mut self,
         params: &TextDocumentPositionParams,
     ) -> Option<Location> {
-        self.text_document_declaration(params)
+        self.project()
+            .get_source(&uri_to_file_name(&params.text_document.uri))
+            .and_then(|source| {
+                self.project()
+                    .find_definition(&source, from_lsp_pos(params.position))
+            })
+            .map(|result| srcpos_to_location(&result))
+    }
+
+    pub fn text_document_implementation(
+        &mut self,
+        params: &TextDocumentPositionParams,
+    ) -> Vec<Location> {
+        match self
+            .project()
+            .get_source(&uri_to_file_name(&params.text_document.uri))
+        {
+            Some(source) => self
+                .project()
+                .find_implementation(&source, from_lsp_pos(params.position))
+                .iter()
+                .map(srcpos_to_location)
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn text_document_type_definition(
+        &mut self,
+        params: &TextDocumentPositionParams,
+    ) -> Vec<Location> {
+        match self
+            .project()
+            .get_source(&uri_to_file_name(&params.text_document.uri))
+        {
+            Some(source) => self
+                .project()
+                .find_type_definition(&source, from_lsp_pos(params.position))
+                .iter()
+                .map(srcpos_to_location)
+                .collect(),
+            None => Vec::new(),
+        }
     }
 
     pub fn text_document_hover(&mut self, params: &TextDocumentPositionParams) -> Option<Hover> {
-        self.project
+        self.project()
             .get_source(&uri_to_file_name(&params.text_document.uri))
             .and_then(|source| {
-                self.project
+                self.project()
                     .search_reference(&source, from_lsp_pos(params.position))
             })
-            .and_then(|decl_pos| self.project.format_declaration(&decl_pos))
+            .and_then(|decl_pos| self.project().format_declaration(&decl_pos))
             .map(|result| Hover {
                 contents: HoverContents::Markup(MarkupContent {
                     kind: MarkupKind::Markdown,
@@ -423,19 +976,19 @@ impl<T: RpcChannel + Clone> InitializedVHDLServer<T> {
 
     pub fn text_document_references(&mut self, params: &ReferenceParams) -> Vec<Location> {
         let decl_pos = self
-            .project
+            .project()
             .get_source(&uri_to_file_name(
                 &params.text_document_position.text_document.uri,
             ))
             .and_then(|source| {
-                self.project.search_reference(
+                self.project().search_reference(
                     &source,
                     from_lsp_pos(params.text_document_position.position),
                 )
             });
 
         if let Some(ref decl_pos) = decl_pos {
-            self.project
+            self.project()
                 .find_all_references(decl_pos)
                 .iter()
                 .map(|pos| srcpos_to_location(pos))
@@ -444,72 +997,994 @@ impl<T: RpcChannel + Clone> InitializedVHDLServer<T> {
             Vec::new()
         }
     }
-}
 
-fn srcpos_to_location(pos: &SrcPos) -> Location {
-    let uri = file_name_to_uri(pos.source.file_name());
-    Location {
-        uri,
-        range: to_lsp_range(pos.range()),
+    pub fn text_document_document_highlight(
+        &mut self,
+        params: &TextDocumentPositionParams,
+    ) -> Option<Vec<DocumentHighlight>> {
+        let source = self
+            .project()
+            .get_source(&uri_to_file_name(&params.text_document.uri))?;
+        Some(
+            self.project()
+                .find_occurrences(&source, from_lsp_pos(params.position))
+                .into_iter()
+                .map(|occurrence| DocumentHighlight {
+                    range: to_lsp_range(occurrence.pos.range()),
+                    kind: Some(match occurrence.kind {
+                        OccurrenceKind::Read => DocumentHighlightKind::READ,
+                        OccurrenceKind::Write => DocumentHighlightKind::WRITE,
+                    }),
+                })
+                .collect(),
+        )
     }
-}
 
-fn from_lsp_pos(position: lsp_types::Position) -> vhdl_lang::Position {
-    vhdl_lang::Position {
-        line: position.line as u32,
-        character: position.character as u32,
+    pub fn text_document_selection_range(
+        &mut self,
+        params: &SelectionRangeParams,
+    ) -> Option<Vec<SelectionRange>> {
+        let source = self
+            .project()
+            .get_source(&uri_to_file_name(&params.text_document.uri))?;
+        let project = self.project();
+
+        // A position which nothing encloses gets an empty range
+        let selection_ranges = params
+            .positions
+            .iter()
+            .map(|position| {
+                let ranges = project.find_selection_ranges(&source, from_lsp_pos(*position));
+                let mut selection_range = None;
+                for pos in ranges.into_iter().rev() {
+                    selection_range = Some(SelectionRange {
+                        range: to_lsp_range(pos.range()),
+                        parent: selection_range.map(Box::new),
+                    });
+                }
+                selection_range.unwrap_or(SelectionRange {
+                    range: Range::new(*position, *position),
+                    parent: None,
+                })
+            })
+            .collect();
+        Some(selection_ranges)
     }
-}
 
-fn to_lsp_pos(position: vhdl_lang::Position) -> lsp_types::Position {
-    lsp_types::Position {
-        line: position.line as u64,
-        character: position.character as u64,
+    pub fn text_document_linked_editing_range(
+        &mut self,
+        params: &LinkedEditingRangeParams,
+    ) -> Option<LinkedEditingRanges> {
+        let position_params = &params.text_document_position_params;
+        let source = self
+            .project()
+            .get_source(&uri_to_file_name(&position_params.text_document.uri))?;
+
+        let ranges = self
+            .project()
+            .find_linked_editing_ranges(&source, from_lsp_pos(position_params.position));
+        if ranges.is_empty() {
+            return None;
+        }
+
+        Some(LinkedEditingRanges {
+            ranges: ranges
+                .into_iter()
+                .map(|pos| to_lsp_range(pos.range()))
+                .collect(),
+            word_pattern: None,
+        })
     }
-}
 
-fn to_lsp_range(range: vhdl_lang::Range) -> lsp_types::Range {
-    lsp_types::Range {
-        start: to_lsp_pos(range.start),
-        end: to_lsp_pos(range.end),
+    pub fn text_document_prepare_call_hierarchy(
+        &mut self,
+        params: &TextDocumentPositionParams,
+    ) -> Option<Vec<CallHierarchyItem>> {
+        let source = self
+            .project()
+            .get_source(&uri_to_file_name(&params.text_document.uri))?;
+        let item = self
+            .project()
+            .prepare_call_hierarchy(&source, from_lsp_pos(params.position))?;
+        Some(vec![to_call_hierarchy_item(&item)])
     }
-}
 
-fn from_lsp_range(range: lsp_types::Range) -> vhdl_lang::Range {
-    vhdl_lang::Range {
-        start: from_lsp_pos(range.start),
-        end: from_lsp_pos(range.end),
+    pub fn call_hierarchy_incoming_calls(
+        &mut self,
+        params: &CallHierarchyIncomingCallsParams,
+    ) -> Option<Vec<CallHierarchyIncomingCall>> {
+        let source = self
+            .project()
+            .get_source(&uri_to_file_name(&params.item.uri))?;
+        Some(
+            self.project()
+                .find_incoming_calls(&source, from_lsp_pos(params.item.selection_range.start))
+                .into_iter()
+                .map(|calls| CallHierarchyIncomingCall {
+                    from: to_call_hierarchy_item(&calls.item),
+                    from_ranges: calls
+                        .positions
+                        .iter()
+                        .map(|pos| to_lsp_range(pos.range()))
+                        .collect(),
+                })
+                .collect(),
+        )
     }
-}
 
-fn diagnostics_by_uri(diagnostics: Vec<Diagnostic>) -> FnvHashMap<Url, Vec<Diagnostic>> {
-    let mut map: FnvHashMap<Url, Vec<Diagnostic>> = FnvHashMap::default();
+    pub fn call_hierarchy_outgoing_calls(
+        &mut self,
+        params: &CallHierarchyOutgoingCallsParams,
+    ) -> Option<Vec<CallHierarchyOutgoingCall>> {
+        let source = self
+            .project()
+            .get_source(&uri_to_file_name(&params.item.uri))?;
+        Some(
+            self.project()
+                .find_outgoing_calls(&source, from_lsp_pos(params.item.selection_range.start))
+                .into_iter()
+                .map(|calls| CallHierarchyOutgoingCall {
+                    to: to_call_hierarchy_item(&calls.item),
+                    from_ranges: calls
+                        .positions
+                        .iter()
+                        .map(|pos| to_lsp_range(pos.range()))
+                        .collect(),
+                })
+                .collect(),
+        )
+    }
 
-    for diagnostic in diagnostics {
-        let uri = file_name_to_uri(diagnostic.pos.source.file_name());
-        match map.entry(uri) {
-            Entry::Occupied(mut entry) => entry.get_mut().push(diagnostic),
-            Entry::Vacant(entry) => {
-                let vec = vec![diagnostic];
-                entry.insert(vec);
-            }
-        }
+    pub fn design_hierarchy(
+        &mut self,
+        params: &DesignHierarchyParams,
+    ) -> Option<DesignHierarchyNode> {
+        self.project()
+            .find_design_hierarchy(params.library.as_deref(), &params.entity)
+            .map(|node| design_hierarchy_node(&node))
     }
 
-    map
-}
+    pub fn text_document_inlay_hint(&mut self, params: &InlayHintParams) -> Option<Vec<InlayHint>> {
+        let source = self
+            .project()
+            .get_source(&uri_to_file_name(&params.text_document.uri))?;
+        let range = from_lsp_range(params.range);
+        Some(
+            self.project()
+                .find_inlay_hints(&source)
+                .into_iter()
+                .filter(|hint| range.start <= hint.position && hint.position <= range.end)
+                .map(|hint| {
+                    let is_parameter = hint.kind == vhdl_lang::InlayHintKind::Parameter;
+                    InlayHint {
+                        position: to_lsp_pos(hint.position),
+                        label: InlayHintLabel::String(hint.label),
+                        kind: Some(if is_parameter {
+                            InlayHintKind::PARAMETER
+                        } else {
+                            InlayHintKind::TYPE
+                        }),
+                        text_edits: None,
+                        tooltip: None,
+                        padding_left: None,
+                        padding_right: Some(is_parameter),
+                        data: None,
+                    }
+                })
+                .collect(),
+        )
+    }
 
-fn flatten_related(diagnostics: Vec<Diagnostic>) -> Vec<Diagnostic> {
-    let mut flat_diagnostics = Vec::new();
-    for mut diagnostic in diagnostics {
-        flat_diagnostics.extend(diagnostic.drain_related());
-        flat_diagnostics.push(diagnostic);
+    pub fn text_document_code_action(
+        &mut self,
+        params: &CodeActionParams,
+    ) -> Option<CodeActionResponse> {
+        let source = self
+            .project()
+            .get_source(&uri_to_file_name(&params.text_document.uri))?;
+        let cursor = from_lsp_pos(params.range.start);
+
+        let mut actions = Vec::new();
+        actions.extend(self.use_clause_code_actions(&params.text_document.uri, &source, cursor));
+        actions.extend(self.instantiation_code_actions(&params.text_document.uri, &source, cursor));
+        actions.extend(self.association_code_actions(&params.text_document.uri, &source, cursor));
+        actions.extend(self.port_map_code_actions(&params.text_document.uri, &source, cursor));
+        actions.extend(self.package_body_code_actions(&source, cursor));
+        actions.extend(self.entity_code_actions(&params.text_document.uri, &source, cursor));
+        Some(actions)
     }
-    flat_diagnostics
-}
 
-fn file_name_to_uri(file_name: &Path) -> Url {
-    // @TODO return error to client
+    /// Run the commands creating an architecture or a testbench of the entity at the cursor
+    fn entity_code_actions(
+        &self,
+        uri: &Url,
+        source: &Source,
+        cursor: vhdl_lang::Position,
+    ) -> Vec<CodeActionOrCommand> {
+        let skeletons = match self.project().find_entity_skeletons(source, cursor) {
+            Some(skeletons) => skeletons,
+            None => return Vec::new(),
+        };
+
+        let arguments = vec![
+            serde_json::to_value(uri).unwrap(),
+            serde_json::to_value(to_lsp_pos(cursor)).unwrap(),
+        ];
+        let action = |title: String, command: &str| {
+            CodeActionOrCommand::CodeAction(CodeAction {
+                title: title.clone(),
+                kind: Some(CodeActionKind::REFACTOR_REWRITE),
+                diagnostics: None,
+                edit: None,
+                command: Some(Command {
+                    title,
+                    command: command.to_owned(),
+                    arguments: Some(arguments.clone()),
+                }),
+                is_preferred: None,
+                disabled: None,
+                data: None,
+            })
+        };
+
+        vec![
+            action(
+                format!("Create architecture of {}", skeletons.entity_name),
+                CREATE_ARCHITECTURE_COMMAND,
+            ),
+            action(
+                format!("Create testbench of {}", skeletons.entity_name),
+                CREATE_TESTBENCH_COMMAND,
+            ),
+        ]
+    }
+
+    /// The commands have the arguments of the document uri and a position within an entity
+    /// and request the client to apply the edit creating an architecture after the entity
+    /// or a testbench in a new file next to the document
+    pub fn workspace_execute_command(
+        &mut self,
+        params: &ExecuteCommandParams,
+    ) -> Option<serde_json::Value> {
+        let (uri, position): (Url, lsp_types::Position) = match params.arguments.as_slice() {
+            [uri, position] => (
+                serde_json::from_value(uri.clone()).ok()?,
+                serde_json::from_value(position.clone()).ok()?,
+            ),
+            _ => return None,
+        };
+        let source = self.project().get_source(&uri_to_file_name(&uri))?;
+        let skeletons = self
+            .project()
+            .find_entity_skeletons(&source, from_lsp_pos(position))?;
+
+        let (label, edit) = match params.command.as_str() {
+            CREATE_ARCHITECTURE_COMMAND => {
+                let mut changes = HashMap::new();
+                changes.insert(
+                    uri,
+                    vec![TextEdit {
+                        range: to_lsp_range(skeletons.entity_end.range()),
+                        new_text: skeletons.architecture,
+                    }],
+                );
+                (
+                    format!("Create architecture of {}", skeletons.entity_name),
+                    WorkspaceEdit {
+                        changes: Some(changes),
+                        document_changes: None,
+                        change_annotations: None,
+                    },
+                )
+            }
+            CREATE_TESTBENCH_COMMAND => {
+                let testbench_uri = file_name_to_uri(
+                    &uri_to_file_name(&uri)
+                        .with_file_name(format!("{}_tb.vhd", skeletons.entity_name)),
+                );
+                let operations = vec![
+                    DocumentChangeOperation::Op(ResourceOp::Create(CreateFile {
+                        uri: testbench_uri.clone(),
+                        options: None,
+                        annotation_id: None,
+                    })),
+                    DocumentChangeOperation::Edit(TextDocumentEdit {
+                        text_document: OptionalVersionedTextDocumentIdentifier {
+                            uri: testbench_uri,
+                            version: None,
+                        },
+                        edits: vec![OneOf::Left(TextEdit {
+                            range: lsp_types::Range::default(),
+                            new_text: skeletons.testbench,
+                        })],
+                    }),
+                ];
+                (
+                    format!("Create testbench of {}", skeletons.entity_name),
+                    WorkspaceEdit {
+                        changes: None,
+                        document_changes: Some(DocumentChanges::Operations(operations)),
+                        change_annotations: None,
+                    },
+                )
+            }
+            _ => return None,
+        };
+
+        self.send_request(
+            "workspace/applyEdit",
+            ApplyWorkspaceEditParams {
+                label: Some(label),
+                edit,
+            },
+        );
+        None
+    }
+
+    /// Create the package body of the package declaration at the cursor or add the
+    /// missing declarations to an existing package body which may be in another file
+    fn package_body_code_actions(
+        &self,
+        source: &Source,
+        cursor: vhdl_lang::Position,
+    ) -> Vec<CodeActionOrCommand> {
+        let stub = match self.project().find_package_body_stub(source, cursor) {
+            Some(stub) => stub,
+            None => return Vec::new(),
+        };
+
+        let title = if stub.is_new {
+            format!(
+                "Create package body {}.{}",
+                stub.library_name, stub.package_name
+            )
+        } else {
+            format!(
+                "Add missing declarations to package body {}.{}",
+                stub.library_name, stub.package_name
+            )
+        };
+
+        // The edits are all in the file of the package body
+        let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+        for (pos, text) in stub.edits.into_iter() {
+            changes
+                .entry(file_name_to_uri(pos.source.file_name()))
+                .or_default()
+                .push(TextEdit {
+                    range: to_lsp_range(pos.range()),
+                    new_text: text,
+                });
+        }
+
+        vec![CodeActionOrCommand::CodeAction(CodeAction {
+            title,
+            kind: Some(CodeActionKind::REFACTOR_REWRITE),
+            diagnostics: None,
+            edit: Some(WorkspaceEdit {
+                changes: Some(changes),
+                document_changes: None,
+                change_annotations: None,
+            }),
+            command: None,
+            is_preferred: None,
+            disabled: None,
+            data: None,
+        })]
+    }
+
+    /// Quick fixes adding the missing ports to the port map of the instance at the cursor
+    /// or removing associations of formals which are not ports
+    fn port_map_code_actions(
+        &self,
+        uri: &Url,
+        source: &Source,
+        cursor: vhdl_lang::Position,
+    ) -> Vec<CodeActionOrCommand> {
+        self.project()
+            .find_port_map_fixes(source, cursor)
+            .into_iter()
+            .map(|fix| {
+                let title = match fix.kind {
+                    PortMapFixKind::AddMissing => "Add missing port associations",
+                    PortMapFixKind::RemoveUnknown => "Remove associations of unknown ports",
+                };
+                let edits = fix
+                    .edits
+                    .into_iter()
+                    .map(|(pos, new_text)| TextEdit {
+                        range: to_lsp_range(pos.range()),
+                        new_text,
+                    })
+                    .collect();
+                let mut changes = HashMap::new();
+                changes.insert(uri.clone(), edits);
+
+                CodeActionOrCommand::CodeAction(CodeAction {
+                    title: title.to_owned(),
+                    kind: Some(CodeActionKind::QUICKFIX),
+                    diagnostics: None,
+                    edit: Some(WorkspaceEdit {
+                        changes: Some(changes),
+                        document_changes: None,
+                        change_annotations: None,
+                    }),
+                    command: None,
+                    is_preferred: None,
+                    disabled: None,
+                    data: None,
+                })
+            })
+            .collect()
+    }
+
+    /// Convert the associations of the port map, generic map or subprogram call
+    /// at the cursor between positional and named associations
+    fn association_code_actions(
+        &self,
+        uri: &Url,
+        source: &Source,
+        cursor: vhdl_lang::Position,
+    ) -> Vec<CodeActionOrCommand> {
+        self.project()
+            .find_association_rewrites(source, cursor)
+            .into_iter()
+            .map(|rewrite| {
+                let title = match rewrite.style {
+                    AssociationStyle::Named => "Convert to named associations",
+                    AssociationStyle::Positional => "Convert to positional associations",
+                };
+                let edits = rewrite
+                    .edits
+                    .into_iter()
+                    .map(|(pos, new_text)| TextEdit {
+                        range: to_lsp_range(pos.range()),
+                        new_text,
+                    })
+                    .collect();
+                let mut changes = HashMap::new();
+                changes.insert(uri.clone(), edits);
+
+                CodeActionOrCommand::CodeAction(CodeAction {
+                    title: title.to_owned(),
+                    kind: Some(CodeActionKind::REFACTOR_REWRITE),
+                    diagnostics: None,
+                    edit: Some(WorkspaceEdit {
+                        changes: Some(changes),
+                        document_changes: None,
+                        change_annotations: None,
+                    }),
+                    command: None,
+                    is_preferred: None,
+                    disabled: None,
+                    data: None,
+                })
+            })
+            .collect()
+    }
+
+    /// Replace a statement naming an entity with an instantiation of the entity
+    /// and optionally also declare a matching component
+    fn instantiation_code_actions(
+        &self,
+        uri: &Url,
+        source: &Source,
+        cursor: vhdl_lang::Position,
+    ) -> Vec<CodeActionOrCommand> {
+        let template = match self.project().find_instantiation_template(source, cursor) {
+            Some(template) => template,
+            None => return Vec::new(),
+        };
+
+        let instantiation = TextEdit {
+            range: to_lsp_range(template.pos.range()),
+            new_text: template.instantiation,
+        };
+        let component_position = to_lsp_pos(template.component_position);
+        let component = TextEdit {
+            range: lsp_types::Range {
+                start: component_position,
+                end: component_position,
+            },
+            new_text: template.component,
+        };
+
+        let action = |title: String, edits: Vec<TextEdit>| {
+            let mut changes = HashMap::new();
+            changes.insert(uri.clone(), edits);
+            CodeActionOrCommand::CodeAction(CodeAction {
+                title,
+                kind: Some(CodeActionKind::REFACTOR_REWRITE),
+                diagnostics: None,
+                edit: Some(WorkspaceEdit {
+                    changes: Some(changes),
+                    document_changes: None,
+                    change_annotations: None,
+                }),
+                command: None,
+                is_preferred: None,
+                disabled: None,
+                data: None,
+            })
+        };
+
+        vec![
+            action(
+                format!(
+                    "Instantiate entity {}.{}",
+                    template.library_name, template.entity_name
+                ),
+                vec![instantiation.clone()],
+            ),
+            action(
+                format!(
+                    "Instantiate entity {}.{} and declare component {}",
+                    template.library_name, template.entity_name, template.entity_name
+                ),
+                vec![component, instantiation],
+            ),
+        ]
+    }
+
+    /// Quick fixes adding a library and use clause for the unresolved name at the cursor
+    fn use_clause_code_actions(
+        &self,
+        uri: &Url,
+        source: &Source,
+        cursor: vhdl_lang::Position,
+    ) -> Vec<CodeActionOrCommand> {
+        let fixes = self.project().find_use_clause_fixes(source, cursor);
+        let is_preferred = fixes.len() == 1;
+
+        fixes
+            .into_iter()
+            .map(|fix| {
+                let position = to_lsp_pos(fix.position);
+                let mut changes = HashMap::new();
+                changes.insert(
+                    uri.clone(),
+                    vec![TextEdit {
+                        range: lsp_types::Range {
+                            start: position,
+                            end: position,
+                        },
+                        new_text: fix.text,
+                    }],
+                );
+
+                CodeActionOrCommand::CodeAction(CodeAction {
+                    title: format!(
+                        "Add use clause for {}.{}",
+                        fix.library_name, fix.package_name
+                    ),
+                    kind: Some(CodeActionKind::QUICKFIX),
+                    diagnostics: None,
+                    edit: Some(WorkspaceEdit {
+                        changes: Some(changes),
+                        document_changes: None,
+                        change_annotations: None,
+                    }),
+                    command: None,
+                    is_preferred: Some(is_preferred),
+                    disabled: None,
+                    data: None,
+                })
+            })
+            .collect()
+    }
+
+    /// Each lens runs the client command `editor.action.showReferences` with the arguments
+    /// of the document uri, the position of the lens and the locations of the references
+    pub fn text_document_code_lens(&mut self, params: &CodeLensParams) -> Option<Vec<CodeLens>> {
+        let source = self
+            .project()
+            .get_source(&uri_to_file_name(&params.text_document.uri))?;
+
+        Some(
+            self.project()
+                .find_code_lenses(&source)
+                .into_iter()
+                .map(|lens| {
+                    let range = to_lsp_range(lens.pos.range());
+                    let locations: Vec<_> =
+                        lens.references.iter().map(srcpos_to_location).collect();
+                    CodeLens {
+                        range,
+                        command: Some(Command {
+                            title: lens.title,
+                            command: SHOW_REFERENCES_COMMAND.to_owned(),
+                            arguments: Some(vec![
+                                serde_json::to_value(&params.text_document.uri).unwrap(),
+                                serde_json::to_value(range.start).unwrap(),
+                                serde_json::to_value(locations).unwrap(),
+                            ]),
+                        }),
+                        data: None,
+                    }
+                })
+                .collect(),
+        )
+    }
+
+    pub fn text_document_completion(&mut self, params: &CompletionParams) -> CompletionList {
+        let items = self
+            .project()
+            .get_source(&uri_to_file_name(
+                &params.text_document_position.text_document.uri,
+            ))
+            .map(|source| {
+                self.project().list_completion_options(
+                    &source,
+                    from_lsp_pos(params.text_document_position.position),
+                )
+            })
+            .unwrap_or_default()
+            .iter()
+            .map(|ent| to_completion_item(ent))
+            .collect();
+
+        CompletionList {
+            is_incomplete: false,
+            items,
+        }
+    }
+
+    pub fn text_document_signature_help(
+        &mut self,
+        params: &TextDocumentPositionParams,
+    ) -> Option<SignatureHelp> {
+        let source = self
+            .project()
+            .get_source(&uri_to_file_name(&params.text_document.uri))?;
+        self.project()
+            .find_call_signatures(&source, from_lsp_pos(params.position))
+            .map(to_signature_help)
+    }
+
+    pub fn text_document_document_symbol(
+        &mut self,
+        params: &DocumentSymbolParams,
+    ) -> Option<DocumentSymbolResponse> {
+        let source = self
+            .project()
+            .get_source(&uri_to_file_name(&params.text_document.uri))?;
+        let design_file = self.project().parse_source(&source);
+        Some(DocumentSymbolResponse::Nested(document_symbols(
+            &design_file,
+        )))
+    }
+
+    pub fn text_document_folding_range(
+        &mut self,
+        params: &FoldingRangeParams,
+    ) -> Option<Vec<FoldingRange>> {
+        let source = self
+            .project()
+            .get_source(&uri_to_file_name(&params.text_document.uri))?;
+        Some(
+            self.project()
+                .folding_ranges(&source)
+                .into_iter()
+                .map(|range| FoldingRange {
+                    start_line: range.start_line,
+                    start_character: None,
+                    end_line: range.end_line,
+                    end_character: None,
+                    kind: Some(match range.kind {
+                        vhdl_lang::FoldingRangeKind::Comment => FoldingRangeKind::Comment,
+                        vhdl_lang::FoldingRangeKind::Region => FoldingRangeKind::Region,
+                    }),
+                    collapsed_text: None,
+                })
+                .collect(),
+        )
+    }
+
+    pub fn workspace_symbol(
+        &mut self,
+        params: &WorkspaceSymbolParams,
+    ) -> Option<Vec<SymbolInformation>> {
+        Some(
+            self.project()
+                .find_workspace_symbols(&params.query)
+                .iter()
+                .map(to_symbol_information)
+                .collect(),
+        )
+    }
+
+    pub fn text_document_prepare_rename(
+        &mut self,
+        params: &TextDocumentPositionParams,
+    ) -> Result<Option<PrepareRenameResponse>, String> {
+        let source = match self
+            .project()
+            .get_source(&uri_to_file_name(&params.text_document.uri))
+        {
+            Some(source) => source,
+            None => return Ok(None),
+        };
+
+        let pos = self
+            .project()
+            .prepare_rename(&source, from_lsp_pos(params.position))?;
+        Ok(Some(PrepareRenameResponse::Range(to_lsp_range(
+            pos.range(),
+        ))))
+    }
+
+    pub fn text_document_rename(
+        &mut self,
+        params: &RenameParams,
+    ) -> Result<Option<WorkspaceEdit>, String> {
+        let source = match self.project().get_source(&uri_to_file_name(
+            &params.text_document_position.text_document.uri,
+        )) {
+            Some(source) => source,
+            None => return Ok(None),
+        };
+
+        let positions = self.project().rename(
+            &source,
+            from_lsp_pos(params.text_document_position.position),
+            &params.new_name,
+        )?;
+
+        let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+        for pos in positions {
+            changes
+                .entry(file_name_to_uri(pos.file_name()))
+                .or_default()
+                .push(TextEdit {
+                    range: to_lsp_range(pos.range()),
+                    new_text: params.new_name.clone(),
+                });
+        }
+
+        Ok(Some(WorkspaceEdit {
+            changes: Some(changes),
+            document_changes: None,
+            change_annotations: None,
+        }))
+    }
+
+    pub fn text_document_semantic_tokens_full(
+        &mut self,
+        params: &SemanticTokensParams,
+    ) -> Option<SemanticTokensResult> {
+        let source = self
+            .project()
+            .get_source(&uri_to_file_name(&params.text_document.uri))?;
+        let tokens = self.project().find_semantic_tokens(&source);
+        Some(SemanticTokensResult::Tokens(SemanticTokens {
+            result_id: None,
+            data: semantic_tokens(&tokens, None),
+        }))
+    }
+
+    pub fn text_document_semantic_tokens_range(
+        &mut self,
+        params: &SemanticTokensRangeParams,
+    ) -> Option<SemanticTokensRangeResult> {
+        let source = self
+            .project()
+            .get_source(&uri_to_file_name(&params.text_document.uri))?;
+        let tokens = self.project().find_semantic_tokens(&source);
+        Some(SemanticTokensRangeResult::Tokens(SemanticTokens {
+            result_id: None,
+            data: semantic_tokens(&tokens, Some(from_lsp_range(params.range))),
+        }))
+    }
+}
+
+#[allow(deprecated)]
+fn to_symbol_information(symbol: &WorkspaceSymbol) -> SymbolInformation {
+    let kind = match symbol.kind {
+        WorkspaceSymbolKind::Named(ref ent) => to_symbol_kind(ent.kind()),
+        WorkspaceSymbolKind::Secondary(SecondaryKind::Architecture) => SymbolKind::CLASS,
+        WorkspaceSymbolKind::Secondary(SecondaryKind::PackageBody) => SymbolKind::PACKAGE,
+    };
+
+    SymbolInformation {
+        name: symbol.name.clone(),
+        kind,
+        tags: None,
+        deprecated: None,
+        location: srcpos_to_location(&symbol.decl_pos),
+        container_name: Some(symbol.container_name.clone()),
+    }
+}
+
+fn to_symbol_kind(kind: &NamedEntityKind) -> SymbolKind {
+    use NamedEntityKind::*;
+    match kind {
+        Object(ref object) => match object.class {
+            ObjectClass::Constant => SymbolKind::CONSTANT,
+            _ => SymbolKind::VARIABLE,
+        },
+        DeferredConstant => SymbolKind::CONSTANT,
+        LoopParameter => SymbolKind::VARIABLE,
+        File | InterfaceFile(..) => SymbolKind::FILE,
+        ElementDeclaration(..) => SymbolKind::FIELD,
+        SubprogramDecl(..) | Subprogram(..) => SymbolKind::FUNCTION,
+        EnumLiteral(..) => SymbolKind::ENUM_MEMBER,
+        PhysicalLiteral(..) => SymbolKind::CONSTANT,
+        RecordType(..) => SymbolKind::STRUCT,
+        ProtectedType(..) => SymbolKind::CLASS,
+        TypeDeclaration(..) | IntegerType(..) | AccessType(..) | Subtype(..)
+        | IncompleteType(..) | InterfaceType => SymbolKind::TYPE_PARAMETER,
+        Attribute => SymbolKind::PROPERTY,
+        Entity(..) | Component(..) => SymbolKind::INTERFACE,
+        Configuration(..) => SymbolKind::CLASS,
+        Library
+        | Package(..)
+        | UninstPackage(..)
+        | PackageInstance(..)
+        | LocalPackageInstance(..) => SymbolKind::PACKAGE,
+        Context(..) => SymbolKind::NAMESPACE,
+        AliasOf(..) | UnknownAlias | Label => SymbolKind::VARIABLE,
+    }
+}
+
+fn to_completion_item(ent: &NamedEntity) -> CompletionItem {
+    CompletionItem {
+        label: ent.designator().to_string(),
+        kind: Some(to_completion_item_kind(ent.actual_kind())),
+        detail: Some(ent.describe()),
+        ..Default::default()
+    }
+}
+
+fn to_completion_item_kind(kind: &NamedEntityKind) -> CompletionItemKind {
+    use NamedEntityKind::*;
+    match kind {
+        Object(ref object) => match object.class {
+            ObjectClass::Constant => CompletionItemKind::CONSTANT,
+            _ => CompletionItemKind::VARIABLE,
+        },
+        DeferredConstant => CompletionItemKind::CONSTANT,
+        LoopParameter => CompletionItemKind::VARIABLE,
+        File | InterfaceFile(..) => CompletionItemKind::FILE,
+        ElementDeclaration(..) => CompletionItemKind::FIELD,
+        SubprogramDecl(..) | Subprogram(..) => CompletionItemKind::FUNCTION,
+        EnumLiteral(..) => CompletionItemKind::ENUM_MEMBER,
+        PhysicalLiteral(..) => CompletionItemKind::UNIT,
+        RecordType(..) => CompletionItemKind::STRUCT,
+        TypeDeclaration(..) | IntegerType(..) | AccessType(..) | Subtype(..)
+        | IncompleteType(..) | InterfaceType | ProtectedType(..) => {
+            CompletionItemKind::TYPE_PARAMETER
+        }
+        Attribute => CompletionItemKind::PROPERTY,
+        Component(..) | Entity(..) | Configuration(..) => CompletionItemKind::CLASS,
+        Library
+        | Package(..)
+        | UninstPackage(..)
+        | PackageInstance(..)
+        | Context(..)
+        | LocalPackageInstance(..) => CompletionItemKind::MODULE,
+        AliasOf(..) | UnknownAlias | Label => CompletionItemKind::TEXT,
+    }
+}
+
+fn to_signature_help(call: CallSignatures) -> SignatureHelp {
+    SignatureHelp {
+        signatures: call
+            .signatures
+            .into_iter()
+            .map(|signature| SignatureInformation {
+                label: signature.label,
+                documentation: None,
+                active_parameter: None,
+                parameters: Some(
+                    signature
+                        .parameters
+                        .iter()
+                        .map(|&(start, end)| ParameterInformation {
+                            label: ParameterLabel::LabelOffsets([start as u32, end as u32]),
+                            documentation: None,
+                        })
+                        .collect(),
+                ),
+            })
+            .collect(),
+        active_signature: Some(call.active_signature as u32),
+        active_parameter: call.active_parameter.map(|idx| idx as u32),
+    }
+}
+
+fn to_call_hierarchy_item(item: &CallItem) -> CallHierarchyItem {
+    let kind = match item.kind {
+        CallItemKind::Function | CallItemKind::Procedure => SymbolKind::FUNCTION,
+        CallItemKind::Process => SymbolKind::EVENT,
+        CallItemKind::Unit => SymbolKind::MODULE,
+    };
+
+    let range = to_lsp_range(item.pos.range());
+    CallHierarchyItem {
+        name: item.name.clone(),
+        kind,
+        tags: None,
+        detail: item.detail.clone(),
+        uri: file_name_to_uri(item.pos.file_name()),
+        range,
+        selection_range: range,
+        data: None,
+    }
+}
+
+pub(crate) fn srcpos_to_location(pos: &SrcPos) -> Location {
+    let uri = file_name_to_uri(pos.source.file_name());
+    Location {
+        uri,
+        range: to_lsp_range(pos.range()),
+    }
+}
+
+fn from_lsp_pos(position: lsp_types::Position) -> vhdl_lang::Position {
+    vhdl_lang::Position {
+        line: position.line,
+        character: position.character,
+    }
+}
+
+fn to_lsp_pos(position: vhdl_lang::Position) -> lsp_types::Position {
+    lsp_types::Position {
+        line: position.line,
+        character: position.character,
+    }
+}
+
+pub(crate) fn to_lsp_range(range: vhdl_lang::Range) -> lsp_types::Range {
+    lsp_types::Range {
+        start: to_lsp_pos(range.start),
+        end: to_lsp_pos(range.end),
+    }
+}
+
+fn from_lsp_range(range: lsp_types::Range) -> vhdl_lang::Range {
+    vhdl_lang::Range {
+        start: from_lsp_pos(range.start),
+        end: from_lsp_pos(range.end),
+    }
+}
+
+fn diagnostics_by_uri(diagnostics: Vec<Diagnostic>) -> FnvHashMap<Url, Vec<Diagnostic>> {
+    let mut map: FnvHashMap<Url, Vec<Diagnostic>> = FnvHashMap::default();
+
+    for diagnostic in diagnostics {
+        let uri = file_name_to_uri(diagnostic.pos.source.file_name());
+        match map.entry(uri) {
+            Entry::Occupied(mut entry) => entry.get_mut().push(diagnostic),
+            Entry::Vacant(entry) => {
+                let vec = vec![diagnostic];
+                entry.insert(vec);
+            }
+        }
+    }
+
+    map
+}
+
+fn flatten_related(diagnostics: Vec<Diagnostic>) -> Vec<Diagnostic> {
+    let mut flat_diagnostics = Vec::new();
+    for mut diagnostic in diagnostics {
+        flat_diagnostics.extend(diagnostic.drain_related());
+        flat_diagnostics.push(diagnostic);
+    }
+    flat_diagnostics
+}
+
+const CREATE_ARCHITECTURE_COMMAND: &str = "vhdl_ls.createArchitecture";
+const CREATE_TESTBENCH_COMMAND: &str = "vhdl_ls.createTestbench";
+
+/// The client command of VS Code showing references which code lenses run
+///
+/// The arguments are the uri of the document, the position and the locations of the
+/// references. Other clients have to implement the command themselves.
+const SHOW_REFERENCES_COMMAND: &str = "editor.action.showReferences";
+
+fn file_name_to_uri(file_name: &Path) -> Url {
+    // @TODO return error to client
     Url::from_file_path(file_name).unwrap()
 }
 
@@ -520,10 +1995,10 @@ fn uri_to_file_name(uri: &Url) -> PathBuf {
 
 fn to_lsp_diagnostic(diagnostic: Diagnostic) -> lsp_types::Diagnostic {
     let severity = match diagnostic.severity {
-        Severity::Error => DiagnosticSeverity::Error,
-        Severity::Warning => DiagnosticSeverity::Warning,
-        Severity::Info => DiagnosticSeverity::Information,
-        Severity::Hint => DiagnosticSeverity::Hint,
+        Severity::Error => DiagnosticSeverity::ERROR,
+        Severity::Warning => DiagnosticSeverity::WARNING,
+        Severity::Info => DiagnosticSeverity::INFORMATION,
+        Severity::Hint => DiagnosticSeverity::HINT,
     };
 
     let related_information = if !diagnostic.related.is_empty() {
@@ -547,10 +2022,12 @@ fn to_lsp_diagnostic(diagnostic: Diagnostic) -> lsp_types::Diagnostic {
         range: to_lsp_range(diagnostic.pos.range()),
         severity: Some(severity),
         code: None,
+        code_description: None,
         source: Some("vhdl ls".to_owned()),
         message: diagnostic.message,
         related_information,
         tags: None,
+        data: None,
     }
 }
 
@@ -572,6 +2049,7 @@ mod tests {
             trace: None,
             workspace_folders: None,
             client_info: None,
+            locale: None,
         };
 
         server.initialize_request(initialize_params);
@@ -677,19 +2155,21 @@ end entity ent2;
                 range: Range {
                     start: lsp_types::Position {
                         line: 2,
-                        character: "end entity ".len() as u64,
+                        character: "end entity ".len() as u32,
                     },
                     end: lsp_types::Position {
                         line: 2,
-                        character: "end entity ent2".len() as u64,
+                        character: "end entity ent2".len() as u32,
                     },
                 },
                 code: None,
-                severity: Some(DiagnosticSeverity::Error),
+                code_description: None,
+                severity: Some(DiagnosticSeverity::ERROR),
                 source: Some("vhdl ls".to_owned()),
                 message: "End identifier mismatch, expected ent".to_owned(),
                 related_information: None,
                 tags: None,
+                data: None,
             }],
             version: None,
         };
@@ -708,7 +2188,7 @@ end entity ent;
         let did_change = DidChangeTextDocumentParams {
             text_document: VersionedTextDocumentIdentifier {
                 uri: file_url.clone(),
-                version: Some(1),
+                version: 1,
             },
             content_changes: vec![TextDocumentContentChangeEvent {
                 range: None,
@@ -727,30 +2207,35 @@ end entity ent;
         server.text_document_did_change_notification(&did_change);
     }
 
-    fn write_file(root_uri: &Url, file_name: impl AsRef<str>, contents: impl AsRef<str>) -> Url {
-        let path = root_uri.to_file_path().unwrap().join(file_name.as_ref());
-        std::fs::write(&path, contents.as_ref()).unwrap();
-        Url::from_file_path(path).unwrap()
-    }
-
-    fn write_config(root_uri: &Url, contents: impl AsRef<str>) -> Url {
-        write_file(root_uri, "vhdl_ls.toml", contents)
+    fn end_identifier_mismatch(file_url: &Url) -> PublishDiagnosticsParams {
+        PublishDiagnosticsParams {
+            uri: file_url.clone(),
+            diagnostics: vec![lsp_types::Diagnostic {
+                range: lsp_range(1, 11, 15),
+                code: None,
+                code_description: None,
+                severity: Some(DiagnosticSeverity::ERROR),
+                source: Some("vhdl ls".to_owned()),
+                message: "End identifier mismatch, expected ent".to_owned(),
+                related_information: None,
+                tags: None,
+                data: None,
+            }],
+            version: None,
+        }
     }
 
     #[test]
-    fn initialize_with_config() {
+    fn did_close_discards_unsaved_changes() {
         let (mock, mut server) = setup_server();
         let (_tempdir, root_uri) = temp_root_uri();
-        let file_uri = write_file(
+
+        let file_url = write_file(
             &root_uri,
-            "file.vhd",
+            "ent.vhd",
             "\
 entity ent is
-end entity;
-
-architecture rtl of ent2 is
-begin
-end;
+end entity ent;
 ",
         );
 
@@ -758,37 +2243,370 @@ end;
             &root_uri,
             "
 [libraries]
-lib.files = [
-  'file.vhd'
-]
+lib.files = ['ent.vhd']
 ",
         );
 
-        let publish_diagnostics = PublishDiagnosticsParams {
-            uri: file_uri,
-            diagnostics: vec![lsp_types::Diagnostic {
-                range: Range {
-                    start: lsp_types::Position {
-                        line: 3,
-                        character: "architecture rtl of ".len() as u64,
-                    },
-                    end: lsp_types::Position {
-                        line: 3,
-                        character: "architecture rtl of ent2".len() as u64,
-                    },
-                },
-                code: None,
-                severity: Some(DiagnosticSeverity::Error),
-                source: Some("vhdl ls".to_owned()),
-                message: "No entity \'ent2\' within library \'lib\'".to_owned(),
-                related_information: None,
-                tags: None,
-            }],
-            version: None,
-        };
-
         expect_loaded_config_messages(&mock, &config_uri);
-        mock.expect_notification("textDocument/publishDiagnostics", publish_diagnostics);
+        initialize_server(&mut server, root_uri);
+
+        let did_change = DidChangeTextDocumentParams {
+            text_document: VersionedTextDocumentIdentifier {
+                uri: file_url.clone(),
+                version: 1,
+            },
+            content_changes: vec![TextDocumentContentChangeEvent {
+                range: None,
+                range_length: None,
+                text: "\
+entity ent is
+end entity ent2;
+"
+                .to_owned(),
+            }],
+        };
+
+        mock.expect_notification(
+            "textDocument/publishDiagnostics",
+            end_identifier_mismatch(&file_url),
+        );
+        server.text_document_did_change_notification(&did_change);
+
+        let publish_diagnostics = PublishDiagnosticsParams {
+            uri: file_url.clone(),
+            diagnostics: vec![],
+            version: None,
+        };
+
+        mock.expect_notification("textDocument/publishDiagnostics", publish_diagnostics);
+        server.text_document_did_close_notification(&DidCloseTextDocumentParams {
+            text_document: TextDocumentIdentifier { uri: file_url },
+        });
+    }
+
+    #[test]
+    fn did_close_removes_file_outside_of_project() {
+        let (mock, mut server) = setup_server();
+        let (_tempdir, root_uri) = temp_root_uri();
+        expect_missing_config_messages(&mock);
+        initialize_server(&mut server, root_uri.clone());
+
+        let file_url = root_uri.join("ent.vhd").unwrap();
+        let did_open = DidOpenTextDocumentParams {
+            text_document: TextDocumentItem {
+                uri: file_url.clone(),
+                language_id: "vhdl".to_owned(),
+                version: 0,
+                text: "\
+entity ent is
+end entity ent2;
+"
+                .to_owned(),
+            },
+        };
+
+        mock.expect_warning_contains("is not part of the project");
+        mock.expect_notification(
+            "textDocument/publishDiagnostics",
+            end_identifier_mismatch(&file_url),
+        );
+        server.text_document_did_open_notification(&did_open);
+
+        let publish_diagnostics = PublishDiagnosticsParams {
+            uri: file_url.clone(),
+            diagnostics: vec![],
+            version: None,
+        };
+
+        mock.expect_notification("textDocument/publishDiagnostics", publish_diagnostics);
+        server.text_document_did_close_notification(&DidCloseTextDocumentParams {
+            text_document: TextDocumentIdentifier {
+                uri: file_url.clone(),
+            },
+        });
+
+        assert!(server
+            .mut_server()
+            .project()
+            .get_source(&uri_to_file_name(&file_url))
+            .is_none());
+    }
+
+    #[test]
+    fn publish_diagnostics_of_changed_files_only() {
+        let (mock, mut server) = setup_server();
+        let (_tempdir, root_uri) = temp_root_uri();
+
+        let code = "\
+entity ent is
+end entity ent2;
+";
+        let file1_url = write_file(&root_uri, "file1.vhd", code);
+        let file2_url = write_file(&root_uri, "file2.vhd", code);
+
+        let config_uri = write_config(
+            &root_uri,
+            "
+[libraries]
+lib1.files = ['file1.vhd']
+lib2.files = ['file2.vhd']
+",
+        );
+
+        expect_loaded_config_messages(&mock, &config_uri);
+        mock.expect_notification(
+            "textDocument/publishDiagnostics",
+            end_identifier_mismatch(&file1_url),
+        );
+        mock.expect_notification(
+            "textDocument/publishDiagnostics",
+            end_identifier_mismatch(&file2_url),
+        );
+        initialize_server(&mut server, root_uri);
+
+        let change = |text: &str| DidChangeTextDocumentParams {
+            text_document: VersionedTextDocumentIdentifier {
+                uri: file1_url.clone(),
+                version: 1,
+            },
+            content_changes: vec![TextDocumentContentChangeEvent {
+                range: None,
+                range_length: None,
+                text: text.to_owned(),
+            }],
+        };
+
+        // The diagnostics are unchanged
+        server.text_document_did_change_notification(&change(
+            "\
+entity ent is
+end entity ent2; -- comment
+",
+        ));
+
+        let publish_diagnostics = PublishDiagnosticsParams {
+            uri: file1_url.clone(),
+            diagnostics: vec![],
+            version: None,
+        };
+        mock.expect_notification("textDocument/publishDiagnostics", publish_diagnostics);
+        server.text_document_did_change_notification(&change(
+            "\
+entity ent is
+end entity ent;
+",
+        ));
+    }
+
+    #[test]
+    fn pull_diagnostics_with_result_ids() {
+        let (mock, mut server) = setup_server();
+        let (_tempdir, root_uri) = temp_root_uri();
+
+        let file_url = write_file(
+            &root_uri,
+            "ent.vhd",
+            "\
+entity ent is
+end entity ent2;
+",
+        );
+
+        let config_uri = write_config(
+            &root_uri,
+            "
+[libraries]
+lib.files = ['ent.vhd']
+",
+        );
+
+        expect_loaded_config_messages(&mock, &config_uri);
+        mock.expect_request("workspace/diagnostic/refresh", ());
+
+        let capabilities = ClientCapabilities {
+            text_document: Some(TextDocumentClientCapabilities {
+                diagnostic: Some(DiagnosticClientCapabilities::default()),
+                ..TextDocumentClientCapabilities::default()
+            }),
+            workspace: Some(WorkspaceClientCapabilities {
+                diagnostic: Some(DiagnosticWorkspaceClientCapabilities {
+                    refresh_support: Some(true),
+                }),
+                ..WorkspaceClientCapabilities::default()
+            }),
+            ..ClientCapabilities::default()
+        };
+        #[allow(deprecated)]
+        let initialize_params = InitializeParams {
+            process_id: None,
+            root_path: None,
+            root_uri: Some(root_uri),
+            initialization_options: None,
+            capabilities,
+            trace: None,
+            workspace_folders: None,
+            client_info: None,
+            locale: None,
+        };
+
+        let result = server.initialize_request(initialize_params);
+        assert!(result.capabilities.diagnostic_provider.is_some());
+        server.initialized_notification();
+
+        let document_params = |previous_result_id: Option<String>| DocumentDiagnosticParams {
+            text_document: TextDocumentIdentifier {
+                uri: file_url.clone(),
+            },
+            identifier: None,
+            previous_result_id,
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        };
+
+        let result_id = match server.text_document_diagnostic(&document_params(None)) {
+            DocumentDiagnosticReportResult::Report(DocumentDiagnosticReport::Full(report)) => {
+                let report = report.full_document_diagnostic_report;
+                assert_eq!(report.items, end_identifier_mismatch(&file_url).diagnostics);
+                report.result_id.unwrap()
+            }
+            report => panic!("Expected full report, got {:?}", report),
+        };
+
+        assert_eq!(
+            server.text_document_diagnostic(&document_params(Some(result_id.clone()))),
+            DocumentDiagnosticReportResult::Report(DocumentDiagnosticReport::Unchanged(
+                RelatedUnchangedDocumentDiagnosticReport {
+                    related_documents: None,
+                    unchanged_document_diagnostic_report: UnchangedDocumentDiagnosticReport {
+                        result_id: result_id.clone(),
+                    },
+                }
+            ))
+        );
+
+        let workspace_params = WorkspaceDiagnosticParams {
+            identifier: None,
+            previous_result_ids: vec![PreviousResultId {
+                uri: file_url.clone(),
+                value: result_id.clone(),
+            }],
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        };
+
+        assert_eq!(
+            server.workspace_diagnostic(&workspace_params),
+            WorkspaceDiagnosticReportResult::Report(WorkspaceDiagnosticReport {
+                items: vec![WorkspaceDocumentDiagnosticReport::Unchanged(
+                    WorkspaceUnchangedDocumentDiagnosticReport {
+                        uri: file_url.clone(),
+                        version: None,
+                        unchanged_document_diagnostic_report: UnchangedDocumentDiagnosticReport {
+                            result_id: result_id.clone(),
+                        },
+                    }
+                )]
+            })
+        );
+
+        // Diagnostics are not pushed to a client pulling them
+        mock.expect_request("workspace/diagnostic/refresh", ());
+        server.text_document_did_change_notification(&DidChangeTextDocumentParams {
+            text_document: VersionedTextDocumentIdentifier {
+                uri: file_url.clone(),
+                version: 1,
+            },
+            content_changes: vec![TextDocumentContentChangeEvent {
+                range: None,
+                range_length: None,
+                text: "\
+entity ent is
+end entity ent;
+"
+                .to_owned(),
+            }],
+        });
+
+        match server.workspace_diagnostic(&workspace_params) {
+            WorkspaceDiagnosticReportResult::Report(WorkspaceDiagnosticReport { items }) => {
+                match items.as_slice() {
+                    [WorkspaceDocumentDiagnosticReport::Full(report)] => {
+                        assert_eq!(report.uri, file_url);
+                        let report = &report.full_document_diagnostic_report;
+                        assert_ne!(report.result_id, Some(result_id));
+                        assert!(report.items.is_empty());
+                    }
+                    items => panic!("Expected a single full report, got {:?}", items),
+                }
+            }
+            report => panic!("Expected report, got {:?}", report),
+        }
+    }
+
+    fn write_file(root_uri: &Url, file_name: impl AsRef<str>, contents: impl AsRef<str>) -> Url {
+        let path = root_uri.to_file_path().unwrap().join(file_name.as_ref());
+        std::fs::write(&path, contents.as_ref()).unwrap();
+        Url::from_file_path(path).unwrap()
+    }
+
+    fn write_config(root_uri: &Url, contents: impl AsRef<str>) -> Url {
+        write_file(root_uri, "vhdl_ls.toml", contents)
+    }
+
+    #[test]
+    fn initialize_with_config() {
+        let (mock, mut server) = setup_server();
+        let (_tempdir, root_uri) = temp_root_uri();
+        let file_uri = write_file(
+            &root_uri,
+            "file.vhd",
+            "\
+entity ent is
+end entity;
+
+architecture rtl of ent2 is
+begin
+end;
+",
+        );
+
+        let config_uri = write_config(
+            &root_uri,
+            "
+[libraries]
+lib.files = [
+  'file.vhd'
+]
+",
+        );
+
+        let publish_diagnostics = PublishDiagnosticsParams {
+            uri: file_uri,
+            diagnostics: vec![lsp_types::Diagnostic {
+                range: Range {
+                    start: lsp_types::Position {
+                        line: 3,
+                        character: "architecture rtl of ".len() as u32,
+                    },
+                    end: lsp_types::Position {
+                        line: 3,
+                        character: "architecture rtl of ent2".len() as u32,
+                    },
+                },
+                code: None,
+                code_description: None,
+                severity: Some(DiagnosticSeverity::ERROR),
+                source: Some("vhdl ls".to_owned()),
+                message: "No entity \'ent2\' within library \'lib\'".to_owned(),
+                related_information: None,
+                tags: None,
+                data: None,
+            }],
+            version: None,
+        };
+
+        expect_loaded_config_messages(&mock, &config_uri);
+        mock.expect_notification("textDocument/publishDiagnostics", publish_diagnostics);
 
         initialize_server(&mut server, root_uri);
     }
@@ -881,7 +2699,7 @@ lib.files = [
             text_document: TextDocumentIdentifier { uri: file_url2 },
             position: lsp_types::Position {
                 line: 2,
-                character: "  constant c : t".len() as u64,
+                character: "  constant c : t".len() as u32,
             },
         });
 
@@ -890,11 +2708,11 @@ lib.files = [
             range: Range {
                 start: lsp_types::Position {
                     line: 1,
-                    character: "  type ".len() as u64,
+                    character: "  type ".len() as u32,
                 },
                 end: lsp_types::Position {
                     line: 1,
-                    character: "  type tpe_t".len() as u64,
+                    character: "  type tpe_t".len() as u32,
                 },
             },
         };
@@ -902,6 +2720,1985 @@ lib.files = [
         assert_eq!(response, Some(expected));
     }
 
+    #[test]
+    fn text_document_definition_and_implementation() {
+        let (mock, mut server) = setup_server();
+        let (_tempdir, root_uri) = temp_root_uri();
+
+        let file_url = write_file(
+            &root_uri,
+            "pkg.vhd",
+            "\
+package pkg is
+  procedure proc;
+end package;
+
+package body pkg is
+  procedure proc is
+  begin
+  end procedure;
+end package body;
+",
+        );
+
+        let config_uri = write_config(
+            &root_uri,
+            "
+[libraries]
+lib.files = [
+  '*.vhd'
+]
+",
+        );
+
+        expect_loaded_config_messages(&mock, &config_uri);
+        initialize_server(&mut server, root_uri);
+
+        let response = server.text_document_definition(&TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier {
+                uri: file_url.clone(),
+            },
+            position: lsp_types::Position {
+                line: 1,
+                character: "  procedure p".len() as u32,
+            },
+        });
+
+        assert_eq!(
+            response,
+            Some(Location {
+                uri: file_url.clone(),
+                range: lsp_range(5, 12, 16),
+            })
+        );
+
+        let response = server.text_document_implementation(&TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier {
+                uri: file_url.clone(),
+            },
+            position: lsp_types::Position {
+                line: 0,
+                character: "package p".len() as u32,
+            },
+        });
+
+        assert_eq!(
+            response,
+            vec![Location {
+                uri: file_url,
+                range: lsp_range(4, 13, 16),
+            }]
+        );
+    }
+
+    #[test]
+    fn text_document_type_definition() {
+        let (mock, mut server) = setup_server();
+        let (_tempdir, root_uri) = temp_root_uri();
+
+        let file_url = write_file(
+            &root_uri,
+            "ent.vhd",
+            "\
+entity ent is
+end entity;
+
+architecture a of ent is
+  type state_t is (idle, busy);
+  subtype my_state_t is state_t;
+  signal x : my_state_t;
+begin
+  x <= idle;
+end architecture;
+",
+        );
+
+        let config_uri = write_config(
+            &root_uri,
+            "
+[libraries]
+lib.files = [
+  '*.vhd'
+]
+",
+        );
+
+        expect_loaded_config_messages(&mock, &config_uri);
+        initialize_server(&mut server, root_uri);
+
+        let response = server.text_document_type_definition(&TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier {
+                uri: file_url.clone(),
+            },
+            position: Position::new(8, 2),
+        });
+
+        assert_eq!(
+            response,
+            vec![
+                Location {
+                    uri: file_url.clone(),
+                    range: lsp_range(5, 10, 20),
+                },
+                Location {
+                    uri: file_url,
+                    range: lsp_range(4, 7, 14),
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn text_document_document_highlight() {
+        let (mock, mut server) = setup_server();
+        let (_tempdir, root_uri) = temp_root_uri();
+
+        let file_url = write_file(
+            &root_uri,
+            "ent.vhd",
+            "\
+entity ent is
+end entity;
+
+architecture a of ent is
+  type state_t is (idle, busy);
+  signal state : state_t;
+begin
+  state <= state;
+end architecture;
+",
+        );
+
+        let config_uri = write_config(
+            &root_uri,
+            "
+[libraries]
+lib.files = [
+  '*.vhd'
+]
+",
+        );
+
+        expect_loaded_config_messages(&mock, &config_uri);
+        initialize_server(&mut server, root_uri);
+
+        let response = server.text_document_document_highlight(&TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier { uri: file_url },
+            position: lsp_types::Position {
+                line: 7,
+                character: "  state <= s".len() as u32,
+            },
+        });
+
+        assert_eq!(
+            response,
+            Some(vec![
+                DocumentHighlight {
+                    range: lsp_range(5, 9, 14),
+                    kind: Some(DocumentHighlightKind::READ),
+                },
+                DocumentHighlight {
+                    range: lsp_range(7, 2, 7),
+                    kind: Some(DocumentHighlightKind::WRITE),
+                },
+                DocumentHighlight {
+                    range: lsp_range(7, 11, 16),
+                    kind: Some(DocumentHighlightKind::READ),
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn text_document_completion() {
+        let (mock, mut server) = setup_server();
+        let (_tempdir, root_uri) = temp_root_uri();
+
+        write_file(
+            &root_uri,
+            "pkg1.vhd",
+            "\
+package pkg1 is
+  type typ_t is (foo, bar);
+  constant const_c : typ_t := foo;
+end package;
+",
+        );
+
+        let code2 = "\
+package pkg2 is
+  constant c : work.pkg1.typ_t := work.pkg1.foo;
+end package;
+        "
+        .to_owned();
+        let file_url2 = write_file(&root_uri, "pkg2.vhd", &code2);
+
+        let config_uri = write_config(
+            &root_uri,
+            "
+[libraries]
+lib.files = [
+  '*.vhd'
+]
+",
+        );
+
+        expect_loaded_config_messages(&mock, &config_uri);
+        initialize_server(&mut server, root_uri);
+
+        let response = server.text_document_completion(&CompletionParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: file_url2 },
+                position: lsp_types::Position {
+                    line: 1,
+                    character: "  constant c : work.pkg1.typ_t := work.pkg1.".len() as u32,
+                },
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+            context: None,
+        });
+
+        let mut labels: Vec<_> = response
+            .items
+            .iter()
+            .map(|item| (item.label.as_str(), item.kind))
+            .collect();
+        labels.sort_by_key(|(label, _)| label.to_owned());
+
+        assert_eq!(
+            labels,
+            vec![
+                ("bar", Some(CompletionItemKind::ENUM_MEMBER)),
+                ("const_c", Some(CompletionItemKind::CONSTANT)),
+                ("foo", Some(CompletionItemKind::ENUM_MEMBER)),
+                ("typ_t", Some(CompletionItemKind::TYPE_PARAMETER)),
+            ]
+        );
+    }
+
+    #[test]
+    fn text_document_signature_help() {
+        let (mock, mut server) = setup_server();
+        let (_tempdir, root_uri) = temp_root_uri();
+
+        let code = "\
+package pkg is
+  type typ_t is (foo, bar);
+  function fun(arg : typ_t) return typ_t;
+  function fun(arg : typ_t; other : typ_t) return typ_t;
+  constant c : typ_t := fun(foo, bar);
+end package;
+"
+        .to_owned();
+        let file_url = write_file(&root_uri, "pkg.vhd", &code);
+
+        let config_uri = write_config(
+            &root_uri,
+            "
+[libraries]
+lib.files = [
+  '*.vhd'
+]
+",
+        );
+
+        expect_loaded_config_messages(&mock, &config_uri);
+        initialize_server(&mut server, root_uri);
+
+        let response = server.text_document_signature_help(&TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier { uri: file_url },
+            position: lsp_types::Position {
+                line: 4,
+                character: "  constant c : typ_t := fun(foo, ".len() as u32,
+            },
+        });
+
+        let parameter = |start: u32, end: u32| ParameterInformation {
+            label: ParameterLabel::LabelOffsets([start, end]),
+            documentation: None,
+        };
+
+        assert_eq!(
+            response,
+            Some(SignatureHelp {
+                signatures: vec![
+                    SignatureInformation {
+                        label: "fun[typ_t return typ_t]".to_owned(),
+                        documentation: None,
+                        active_parameter: None,
+                        parameters: Some(vec![parameter(4, 9)]),
+                    },
+                    SignatureInformation {
+                        label: "fun[typ_t, typ_t return typ_t]".to_owned(),
+                        documentation: None,
+                        active_parameter: None,
+                        parameters: Some(vec![parameter(4, 9), parameter(11, 16)]),
+                    },
+                ],
+                active_signature: Some(1),
+                active_parameter: Some(1),
+            })
+        );
+    }
+
+    fn flatten_symbols<'a>(
+        symbols: &'a [DocumentSymbol],
+        depth: usize,
+        result: &mut Vec<(usize, &'a str, SymbolKind)>,
+    ) {
+        for symbol in symbols {
+            result.push((depth, symbol.name.as_str(), symbol.kind));
+            if let Some(ref children) = symbol.children {
+                flatten_symbols(children, depth + 1, result);
+            }
+        }
+    }
+
+    #[test]
+    fn text_document_document_symbol() {
+        let (mock, mut server) = setup_server();
+        let (_tempdir, root_uri) = temp_root_uri();
+
+        let code = "\
+package types is
+  type bit_t is ('0', '1');
+end package;
+
+use work.types.all;
+
+entity ent is
+  generic (width : bit_t);
+  port (clk : in bit_t);
+end entity;
+
+architecture rtl of ent is
+  signal sig : bit_t;
+begin
+  p_main : process
+    variable var : bit_t;
+  begin
+  end process;
+
+  gen : for i in 0 to 1 generate
+    inst : entity work.ent
+      generic map (width => '1')
+      port map (clk => clk);
+  end generate;
+end architecture;
+";
+        let file_url = write_file(&root_uri, "ent.vhd", code);
+
+        let config_uri = write_config(
+            &root_uri,
+            "
+[libraries]
+lib.files = [
+  '*.vhd'
+]
+",
+        );
+
+        expect_loaded_config_messages(&mock, &config_uri);
+        initialize_server(&mut server, root_uri);
+
+        let response = server.text_document_document_symbol(&DocumentSymbolParams {
+            text_document: TextDocumentIdentifier { uri: file_url },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        });
+
+        let symbols = match response {
+            Some(DocumentSymbolResponse::Nested(symbols)) => symbols,
+            _ => panic!("Expected nested document symbols"),
+        };
+
+        let mut flat = Vec::new();
+        flatten_symbols(&symbols, 0, &mut flat);
+        assert_eq!(
+            flat,
+            vec![
+                (0, "types", SymbolKind::PACKAGE),
+                (1, "bit_t", SymbolKind::ENUM),
+                (0, "ent", SymbolKind::INTERFACE),
+                (1, "width", SymbolKind::CONSTANT),
+                (1, "clk", SymbolKind::FIELD),
+                (0, "rtl", SymbolKind::CLASS),
+                (1, "sig", SymbolKind::VARIABLE),
+                (1, "p_main", SymbolKind::EVENT),
+                (2, "var", SymbolKind::VARIABLE),
+                (1, "gen", SymbolKind::NAMESPACE),
+                (2, "inst", SymbolKind::MODULE),
+            ]
+        );
+
+        // The range of the architecture spans from the keyword to the end semicolon
+        assert_eq!(
+            symbols[2].range,
+            lsp_types::Range {
+                start: lsp_types::Position {
+                    line: 11,
+                    character: 0
+                },
+                end: lsp_types::Position {
+                    line: 24,
+                    character: "end architecture;".len() as u32
+                },
+            }
+        );
+
+        let gen = &symbols[2].children.as_ref().unwrap()[2];
+        assert_eq!(
+            gen.children.as_ref().unwrap()[0].detail,
+            Some("entity work.ent".to_owned())
+        );
+    }
+
+    #[test]
+    fn text_document_folding_range() {
+        let (mock, mut server) = setup_server();
+        let (_tempdir, root_uri) = temp_root_uri();
+
+        let file_url = write_file(
+            &root_uri,
+            "ent.vhd",
+            "\
+-- Header
+-- comment
+entity ent is
+end entity;
+",
+        );
+
+        let config_uri = write_config(
+            &root_uri,
+            "
+[libraries]
+lib.files = [
+  '*.vhd'
+]
+",
+        );
+
+        expect_loaded_config_messages(&mock, &config_uri);
+        initialize_server(&mut server, root_uri);
+
+        let response = server.text_document_folding_range(&FoldingRangeParams {
+            text_document: TextDocumentIdentifier { uri: file_url },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        });
+
+        let fold = |start_line, end_line, kind| FoldingRange {
+            start_line,
+            start_character: None,
+            end_line,
+            end_character: None,
+            kind: Some(kind),
+            collapsed_text: None,
+        };
+
+        assert_eq!(
+            response,
+            Some(vec![
+                fold(0, 1, FoldingRangeKind::Comment),
+                fold(2, 3, FoldingRangeKind::Region),
+            ])
+        );
+    }
+
+    #[test]
+    fn text_document_selection_range() {
+        let (mock, mut server) = setup_server();
+        let (_tempdir, root_uri) = temp_root_uri();
+
+        let file_url = write_file(
+            &root_uri,
+            "ent.vhd",
+            "\
+entity ent is
+end entity;
+
+architecture a of ent is
+  type state_t is (idle, busy);
+  signal x, y : state_t;
+begin
+  x <= y;
+end architecture;
+",
+        );
+
+        let config_uri = write_config(
+            &root_uri,
+            "
+[libraries]
+lib.files = [
+  '*.vhd'
+]
+",
+        );
+
+        expect_loaded_config_messages(&mock, &config_uri);
+        initialize_server(&mut server, root_uri);
+
+        let response = server.text_document_selection_range(&SelectionRangeParams {
+            text_document: TextDocumentIdentifier { uri: file_url },
+            positions: vec![Position::new(7, 7), Position::new(2, 0)],
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        });
+
+        let architecture = SelectionRange {
+            range: Range::new(Position::new(3, 0), Position::new(8, 17)),
+            parent: None,
+        };
+        let statement = SelectionRange {
+            range: lsp_range(7, 2, 9),
+            parent: Some(Box::new(architecture)),
+        };
+        let name = SelectionRange {
+            range: lsp_range(7, 7, 8),
+            parent: Some(Box::new(statement)),
+        };
+
+        assert_eq!(
+            response,
+            Some(vec![
+                name,
+                SelectionRange {
+                    range: lsp_range(2, 0, 0),
+                    parent: None,
+                }
+            ])
+        );
+    }
+
+    #[test]
+    fn text_document_linked_editing_range() {
+        let (mock, mut server) = setup_server();
+        let (_tempdir, root_uri) = temp_root_uri();
+
+        let file_url = write_file(
+            &root_uri,
+            "ent.vhd",
+            "\
+entity ent is
+end entity;
+
+architecture rtl of ent is
+begin
+  main : process
+  begin
+  end process main;
+end architecture rtl;
+",
+        );
+
+        let config_uri = write_config(
+            &root_uri,
+            "
+[libraries]
+lib.files = [
+  '*.vhd'
+]
+",
+        );
+
+        expect_loaded_config_messages(&mock, &config_uri);
+        initialize_server(&mut server, root_uri);
+
+        let mut linked_editing_range = |line, character| {
+            server.text_document_linked_editing_range(&LinkedEditingRangeParams {
+                text_document_position_params: TextDocumentPositionParams {
+                    text_document: TextDocumentIdentifier {
+                        uri: file_url.clone(),
+                    },
+                    position: Position::new(line, character),
+                },
+                work_done_progress_params: Default::default(),
+            })
+        };
+
+        assert_eq!(
+            linked_editing_range(8, 20),
+            Some(LinkedEditingRanges {
+                ranges: vec![lsp_range(3, 13, 16), lsp_range(8, 17, 20)],
+                word_pattern: None,
+            })
+        );
+        assert_eq!(
+            linked_editing_range(5, 3),
+            Some(LinkedEditingRanges {
+                ranges: vec![lsp_range(5, 2, 6), lsp_range(7, 14, 18)],
+                word_pattern: None,
+            })
+        );
+        assert_eq!(linked_editing_range(4, 2), None);
+    }
+
+    #[test]
+    fn call_hierarchy_incoming_calls() {
+        let (mock, mut server) = setup_server();
+        let (_tempdir, root_uri) = temp_root_uri();
+
+        let file_url = write_file(
+            &root_uri,
+            "tb.vhd",
+            "\
+package pkg is
+  procedure proc;
+end package;
+
+package body pkg is
+  procedure proc is
+  begin
+  end procedure;
+end package body;
+
+use work.pkg.all;
+entity tb is
+end entity;
+
+architecture sim of tb is
+begin
+  main : process
+  begin
+    proc;
+    wait;
+  end process;
+end architecture;
+",
+        );
+
+        let config_uri = write_config(
+            &root_uri,
+            "
+[libraries]
+lib.files = [
+  '*.vhd'
+]
+",
+        );
+
+        expect_loaded_config_messages(&mock, &config_uri);
+        initialize_server(&mut server, root_uri);
+
+        let items = server
+            .text_document_prepare_call_hierarchy(&TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier {
+                    uri: file_url.clone(),
+                },
+                position: lsp_types::Position {
+                    line: 1,
+                    character: "  procedure p".len() as u32,
+                },
+            })
+            .unwrap();
+
+        assert_eq!(items.len(), 1);
+        let item = items[0].clone();
+        assert_eq!(item.name, "proc");
+        assert_eq!(item.kind, SymbolKind::FUNCTION);
+        assert_eq!(item.uri, file_url);
+        assert_eq!(item.selection_range, lsp_range(1, 12, 16));
+
+        let calls = server
+            .call_hierarchy_incoming_calls(&CallHierarchyIncomingCallsParams {
+                item,
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            })
+            .unwrap();
+
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].from.name, "main");
+        assert_eq!(calls[0].from.kind, SymbolKind::EVENT);
+        assert_eq!(calls[0].from.selection_range, lsp_range(16, 2, 6));
+        assert_eq!(calls[0].from_ranges, vec![lsp_range(18, 4, 8)]);
+    }
+
+    #[test]
+    fn text_document_code_action_adds_use_clause() {
+        let (mock, mut server) = setup_server();
+        let (_tempdir, root_uri) = temp_root_uri();
+
+        write_file(
+            &root_uri,
+            "pkg.vhd",
+            "\
+package pkg is
+  type level_t is (lo, hi);
+end package;
+",
+        );
+
+        let file_url = write_file(
+            &root_uri,
+            "ent.vhd",
+            "\
+entity ent is
+end entity;
+
+architecture a of ent is
+  signal level : level_t;
+begin
+end architecture;
+",
+        );
+
+        let config_uri = write_config(
+            &root_uri,
+            "
+[libraries]
+pkglib.files = ['pkg.vhd']
+lib.files = ['ent.vhd']
+",
+        );
+
+        let publish_diagnostics = PublishDiagnosticsParams {
+            uri: file_url.clone(),
+            diagnostics: vec![lsp_types::Diagnostic {
+                range: lsp_range(4, 17, 24),
+                code: None,
+                code_description: None,
+                severity: Some(DiagnosticSeverity::ERROR),
+                source: Some("vhdl ls".to_owned()),
+                message: "No declaration of 'level_t'".to_owned(),
+                related_information: None,
+                tags: None,
+                data: None,
+            }],
+            version: None,
+        };
+
+        expect_loaded_config_messages(&mock, &config_uri);
+        mock.expect_notification("textDocument/publishDiagnostics", publish_diagnostics);
+        initialize_server(&mut server, root_uri);
+
+        let actions = server
+            .text_document_code_action(&CodeActionParams {
+                text_document: TextDocumentIdentifier {
+                    uri: file_url.clone(),
+                },
+                range: lsp_range(4, 18, 18),
+                context: CodeActionContext::default(),
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            })
+            .unwrap();
+
+        let mut changes = HashMap::new();
+        changes.insert(
+            file_url,
+            vec![TextEdit {
+                range: lsp_range(3, 0, 0),
+                new_text: "library pkglib;\nuse pkglib.pkg.all;\n".to_owned(),
+            }],
+        );
+
+        assert_eq!(
+            actions,
+            vec![CodeActionOrCommand::CodeAction(CodeAction {
+                title: "Add use clause for pkglib.pkg".to_owned(),
+                kind: Some(CodeActionKind::QUICKFIX),
+                diagnostics: None,
+                edit: Some(WorkspaceEdit {
+                    changes: Some(changes),
+                    document_changes: None,
+                    change_annotations: None,
+                }),
+                command: None,
+                is_preferred: Some(true),
+                disabled: None,
+                data: None,
+            })]
+        );
+    }
+
+    #[test]
+    fn text_document_code_action_instantiates_entity() {
+        let (mock, mut server) = setup_server();
+        let (_tempdir, root_uri) = temp_root_uri();
+
+        write_file(
+            &root_uri,
+            "dma.vhd",
+            "\
+package pkg is
+  type level_t is (lo, hi);
+end package;
+
+use work.pkg.all;
+
+entity dma is
+  port (
+    clk : in level_t;
+    irq_out : out level_t
+  );
+end entity;
+",
+        );
+
+        let file_url = write_file(
+            &root_uri,
+            "top.vhd",
+            "\
+entity top is
+end entity;
+
+architecture a of top is
+begin
+  dma;
+end architecture;
+",
+        );
+
+        let config_uri = write_config(
+            &root_uri,
+            "
+[libraries]
+lib.files = ['*.vhd']
+",
+        );
+
+        let publish_diagnostics = PublishDiagnosticsParams {
+            uri: file_url.clone(),
+            diagnostics: vec![lsp_types::Diagnostic {
+                range: lsp_range(5, 2, 5),
+                code: None,
+                code_description: None,
+                severity: Some(DiagnosticSeverity::ERROR),
+                source: Some("vhdl ls".to_owned()),
+                message: "No declaration of 'dma'".to_owned(),
+                related_information: None,
+                tags: None,
+                data: None,
+            }],
+            version: None,
+        };
+
+        expect_loaded_config_messages(&mock, &config_uri);
+        mock.expect_notification("textDocument/publishDiagnostics", publish_diagnostics);
+        initialize_server(&mut server, root_uri);
+
+        let actions = server
+            .text_document_code_action(&CodeActionParams {
+                text_document: TextDocumentIdentifier {
+                    uri: file_url.clone(),
+                },
+                range: lsp_range(5, 3, 3),
+                context: CodeActionContext::default(),
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            })
+            .unwrap();
+
+        let instantiation = TextEdit {
+            range: lsp_range(5, 2, 6),
+            new_text: "\
+dma_inst : entity lib.dma
+    port map (
+      clk     => clk,
+      irq_out => irq_out
+    );"
+            .to_owned(),
+        };
+        let component = TextEdit {
+            range: lsp_range(4, 0, 0),
+            new_text: "  component dma
+    port (
+      clk : in level_t;
+      irq_out : out level_t
+    );
+  end component;
+"
+            .to_owned(),
+        };
+
+        let action = |title: &str, edits: Vec<TextEdit>| {
+            let mut changes = HashMap::new();
+            changes.insert(file_url.clone(), edits);
+            CodeActionOrCommand::CodeAction(CodeAction {
+                title: title.to_owned(),
+                kind: Some(CodeActionKind::REFACTOR_REWRITE),
+                diagnostics: None,
+                edit: Some(WorkspaceEdit {
+                    changes: Some(changes),
+                    document_changes: None,
+                    change_annotations: None,
+                }),
+                command: None,
+                is_preferred: None,
+                disabled: None,
+                data: None,
+            })
+        };
+
+        assert_eq!(
+            actions,
+            vec![
+                action("Instantiate entity lib.dma", vec![instantiation.clone()]),
+                action(
+                    "Instantiate entity lib.dma and declare component dma",
+                    vec![component, instantiation]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn text_document_code_action_converts_associations() {
+        let (mock, mut server) = setup_server();
+        let (_tempdir, root_uri) = temp_root_uri();
+
+        let file_url = write_file(
+            &root_uri,
+            "ent.vhd",
+            "\
+entity ent is
+end entity;
+
+architecture a of ent is
+  type state_t is (idle, busy);
+  procedure set(first, second : state_t) is
+  begin
+  end procedure;
+begin
+  set(idle, second => busy);
+end architecture;
+",
+        );
+
+        let config_uri = write_config(
+            &root_uri,
+            "
+[libraries]
+lib.files = ['*.vhd']
+",
+        );
+
+        expect_loaded_config_messages(&mock, &config_uri);
+        initialize_server(&mut server, root_uri);
+
+        let actions = server
+            .text_document_code_action(&CodeActionParams {
+                text_document: TextDocumentIdentifier {
+                    uri: file_url.clone(),
+                },
+                range: lsp_range(9, 7, 7),
+                context: CodeActionContext::default(),
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            })
+            .unwrap();
+
+        let action = |title: &str, edit: TextEdit| {
+            let mut changes = HashMap::new();
+            changes.insert(file_url.clone(), vec![edit]);
+            CodeActionOrCommand::CodeAction(CodeAction {
+                title: title.to_owned(),
+                kind: Some(CodeActionKind::REFACTOR_REWRITE),
+                diagnostics: None,
+                edit: Some(WorkspaceEdit {
+                    changes: Some(changes),
+                    document_changes: None,
+                    change_annotations: None,
+                }),
+                command: None,
+                is_preferred: None,
+                disabled: None,
+                data: None,
+            })
+        };
+
+        assert_eq!(
+            actions,
+            vec![
+                action(
+                    "Convert to named associations",
+                    TextEdit {
+                        range: lsp_range(9, 6, 6),
+                        new_text: "first => ".to_owned(),
+                    }
+                ),
+                action(
+                    "Convert to positional associations",
+                    TextEdit {
+                        range: lsp_range(9, 12, 22),
+                        new_text: "".to_owned(),
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn text_document_code_action_fixes_port_map() {
+        let (mock, mut server) = setup_server();
+        let (_tempdir, root_uri) = temp_root_uri();
+
+        let file_url = write_file(
+            &root_uri,
+            "top.vhd",
+            "\
+package pkg is
+  type state_t is (idle, busy);
+end package;
+
+use work.pkg.all;
+
+entity dma is
+  port (
+    req : in state_t := idle;
+    ack : out state_t
+  );
+end entity;
+
+use work.pkg.all;
+
+entity top is
+end entity;
+
+architecture a of top is
+  signal state : state_t;
+begin
+  inst : entity work.dma port map (req => state, valid => state);
+end architecture;
+",
+        );
+
+        let config_uri = write_config(
+            &root_uri,
+            "
+[libraries]
+lib.files = ['*.vhd']
+",
+        );
+
+        let publish_diagnostics = PublishDiagnosticsParams {
+            uri: file_url.clone(),
+            diagnostics: vec![lsp_types::Diagnostic {
+                range: lsp_range(21, 49, 54),
+                code: None,
+                code_description: None,
+                severity: Some(DiagnosticSeverity::ERROR),
+                source: Some("vhdl ls".to_owned()),
+                message: "No declaration of 'valid' within entity 'dma'".to_owned(),
+                related_information: None,
+                tags: None,
+                data: None,
+            }],
+            version: None,
+        };
+
+        expect_loaded_config_messages(&mock, &config_uri);
+        mock.expect_notification("textDocument/publishDiagnostics", publish_diagnostics);
+        initialize_server(&mut server, root_uri);
+
+        let actions = server
+            .text_document_code_action(&CodeActionParams {
+                text_document: TextDocumentIdentifier {
+                    uri: file_url.clone(),
+                },
+                range: lsp_range(21, 2, 2),
+                context: CodeActionContext::default(),
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            })
+            .unwrap();
+
+        let action = |title: &str, edit: TextEdit| {
+            let mut changes = HashMap::new();
+            changes.insert(file_url.clone(), vec![edit]);
+            CodeActionOrCommand::CodeAction(CodeAction {
+                title: title.to_owned(),
+                kind: Some(CodeActionKind::QUICKFIX),
+                diagnostics: None,
+                edit: Some(WorkspaceEdit {
+                    changes: Some(changes),
+                    document_changes: None,
+                    change_annotations: None,
+                }),
+                command: None,
+                is_preferred: None,
+                disabled: None,
+                data: None,
+            })
+        };
+
+        assert_eq!(
+            actions,
+            vec![
+                action(
+                    "Remove associations of unknown ports",
+                    TextEdit {
+                        range: lsp_range(21, 47, 63),
+                        new_text: "".to_owned(),
+                    }
+                ),
+                action(
+                    "Add missing port associations",
+                    TextEdit {
+                        range: lsp_range(21, 63, 63),
+                        new_text: ", ack => open".to_owned(),
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn text_document_code_action_adds_package_body_declarations() {
+        let (mock, mut server) = setup_server();
+        let (_tempdir, root_uri) = temp_root_uri();
+
+        let pkg_url = write_file(
+            &root_uri,
+            "pkg.vhd",
+            "\
+package pkg is
+  type state_t is (idle, busy);
+  procedure reset;
+  impure function next_state return state_t;
+end package;
+",
+        );
+
+        let body_url = write_file(
+            &root_uri,
+            "pkg_body.vhd",
+            "\
+package body pkg is
+  procedure reset is
+  begin
+  end procedure;
+end package body;
+",
+        );
+
+        let config_uri = write_config(
+            &root_uri,
+            "
+[libraries]
+lib.files = ['*.vhd']
+",
+        );
+
+        expect_loaded_config_messages(&mock, &config_uri);
+        initialize_server(&mut server, root_uri);
+
+        let actions = server
+            .text_document_code_action(&CodeActionParams {
+                text_document: TextDocumentIdentifier {
+                    uri: pkg_url.clone(),
+                },
+                range: lsp_range(3, 20, 20),
+                context: CodeActionContext::default(),
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            })
+            .unwrap();
+
+        let mut changes = HashMap::new();
+        changes.insert(
+            body_url,
+            vec![TextEdit {
+                range: lsp_range(4, 0, 0),
+                new_text: "
+  impure function next_state return state_t is
+  begin
+  end function;
+"
+                .to_owned(),
+            }],
+        );
+
+        assert_eq!(
+            actions,
+            vec![CodeActionOrCommand::CodeAction(CodeAction {
+                title: "Add missing declarations to package body lib.pkg".to_owned(),
+                kind: Some(CodeActionKind::REFACTOR_REWRITE),
+                diagnostics: None,
+                edit: Some(WorkspaceEdit {
+                    changes: Some(changes),
+                    document_changes: None,
+                    change_annotations: None,
+                }),
+                command: None,
+                is_preferred: None,
+                disabled: None,
+                data: None,
+            })]
+        );
+    }
+
+    #[test]
+    fn workspace_execute_command_creates_architecture_and_testbench() {
+        let (mock, mut server) = setup_server();
+        let (_tempdir, root_uri) = temp_root_uri();
+
+        let file_url = write_file(
+            &root_uri,
+            "dma.vhd",
+            "\
+package types is
+  type logic_t is ('0', '1');
+end package;
+
+use work.types.all;
+
+entity dma is
+  port (
+    clk : in logic_t
+  );
+end entity;
+",
+        );
+
+        let config_uri = write_config(
+            &root_uri,
+            "
+[libraries]
+lib.files = ['*.vhd']
+",
+        );
+
+        expect_loaded_config_messages(&mock, &config_uri);
+        initialize_server(&mut server, root_uri);
+
+        let actions = server
+            .text_document_code_action(&CodeActionParams {
+                text_document: TextDocumentIdentifier {
+                    uri: file_url.clone(),
+                },
+                range: lsp_range(8, 4, 4),
+                context: CodeActionContext::default(),
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            })
+            .unwrap();
+
+        let commands: Vec<_> = actions
+            .into_iter()
+            .filter_map(|action| match action {
+                CodeActionOrCommand::CodeAction(action) => action.command,
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            commands
+                .iter()
+                .map(|command| command.command.as_str())
+                .collect::<Vec<_>>(),
+            vec!["vhdl_ls.createArchitecture", "vhdl_ls.createTestbench"]
+        );
+
+        let mut changes = HashMap::new();
+        changes.insert(
+            file_url.clone(),
+            vec![TextEdit {
+                range: lsp_range(10, 11, 11),
+                new_text: "
+
+architecture rtl of dma is
+begin
+end architecture;"
+                    .to_owned(),
+            }],
+        );
+        mock.expect_request(
+            "workspace/applyEdit",
+            ApplyWorkspaceEditParams {
+                label: Some("Create architecture of dma".to_owned()),
+                edit: WorkspaceEdit {
+                    changes: Some(changes),
+                    document_changes: None,
+                    change_annotations: None,
+                },
+            },
+        );
+        server.workspace_execute_command(&ExecuteCommandParams {
+            command: commands[0].command.clone(),
+            arguments: commands[0].arguments.clone().unwrap(),
+            work_done_progress_params: Default::default(),
+        });
+
+        let testbench_url = file_url.join("dma_tb.vhd").unwrap();
+        mock.expect_request(
+            "workspace/applyEdit",
+            ApplyWorkspaceEditParams {
+                label: Some("Create testbench of dma".to_owned()),
+                edit: WorkspaceEdit {
+                    changes: None,
+                    document_changes: Some(DocumentChanges::Operations(vec![
+                        DocumentChangeOperation::Op(ResourceOp::Create(CreateFile {
+                            uri: testbench_url.clone(),
+                            options: None,
+                            annotation_id: None,
+                        })),
+                        DocumentChangeOperation::Edit(TextDocumentEdit {
+                            text_document: OptionalVersionedTextDocumentIdentifier {
+                                uri: testbench_url,
+                                version: None,
+                            },
+                            edits: vec![OneOf::Left(TextEdit {
+                                range: lsp_range(0, 0, 0),
+                                new_text: "\
+library lib;
+use lib.types.all;
+
+entity dma_tb is
+end entity;
+
+architecture tb of dma_tb is
+  signal clk : logic_t;
+
+  constant clk_period : time := 10 ns;
+begin
+  dut : entity lib.dma
+    port map (
+      clk => clk
+    );
+
+  clock : process
+  begin
+    -- TODO: values of the clock
+    clk <= logic_t'low;
+    wait for clk_period / 2;
+    clk <= logic_t'high;
+    wait for clk_period / 2;
+  end process;
+
+  stimulus : process
+  begin
+    wait for 10 * clk_period;
+    wait;
+  end process;
+end architecture;
+"
+                                .to_owned(),
+                            })],
+                        }),
+                    ])),
+                    change_annotations: None,
+                },
+            },
+        );
+        server.workspace_execute_command(&ExecuteCommandParams {
+            command: commands[1].command.clone(),
+            arguments: commands[1].arguments.clone().unwrap(),
+            work_done_progress_params: Default::default(),
+        });
+    }
+
+    #[test]
+    fn text_document_code_lens() {
+        let (mock, mut server) = setup_server();
+        let (_tempdir, root_uri) = temp_root_uri();
+
+        let file_url = write_file(
+            &root_uri,
+            "ent.vhd",
+            "\
+entity ent is
+end entity;
+
+architecture a of ent is
+begin
+end architecture;
+
+entity top is
+end entity;
+
+architecture a of top is
+begin
+  inst : entity work.ent;
+end architecture;
+",
+        );
+
+        let config_uri = write_config(
+            &root_uri,
+            "
+[libraries]
+lib.files = ['ent.vhd']
+",
+        );
+
+        expect_loaded_config_messages(&mock, &config_uri);
+        initialize_server(&mut server, root_uri);
+
+        let lenses = server
+            .text_document_code_lens(&CodeLensParams {
+                text_document: TextDocumentIdentifier {
+                    uri: file_url.clone(),
+                },
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            })
+            .unwrap();
+
+        let references = vec![
+            Location {
+                uri: file_url.clone(),
+                range: lsp_range(3, 18, 21),
+            },
+            Location {
+                uri: file_url.clone(),
+                range: lsp_range(12, 21, 24),
+            },
+        ];
+
+        assert_eq!(lenses.len(), 2);
+        assert_eq!(
+            lenses[0],
+            CodeLens {
+                range: lsp_range(0, 7, 10),
+                command: Some(Command {
+                    title: "1 architecture \u{b7} 1 instantiation".to_owned(),
+                    command: "editor.action.showReferences".to_owned(),
+                    arguments: Some(vec![
+                        serde_json::to_value(file_url).unwrap(),
+                        serde_json::to_value(lsp_range(0, 7, 10).start).unwrap(),
+                        serde_json::to_value(references).unwrap(),
+                    ]),
+                }),
+                data: None,
+            }
+        );
+        assert_eq!(
+            lenses[1].command.as_ref().unwrap().title,
+            "1 architecture \u{b7} 0 instantiations"
+        );
+    }
+
+    #[test]
+    fn background_analysis_publishes_diagnostics_of_latest_change() {
+        let mock = RpcMock::new();
+        let mut server = VHDLServer {
+            background_analysis: true,
+            ..VHDLServer::new_external_config(mock.clone(), false)
+        };
+        let (_tempdir, root_uri) = temp_root_uri();
+
+        let file_url = write_file(
+            &root_uri,
+            "ent.vhd",
+            "\
+entity ent is
+end entity;
+",
+        );
+
+        let config_uri = write_config(
+            &root_uri,
+            "
+[libraries]
+lib.files = ['ent.vhd']
+",
+        );
+
+        expect_loaded_config_messages(&mock, &config_uri);
+        initialize_server(&mut server, root_uri);
+
+        let results = server.analysis_results();
+        let timeout = std::time::Duration::from_secs(10);
+        server.analysis_finished(results.recv_timeout(timeout).unwrap());
+
+        let change = |version: i32, type_name: &str| DidChangeTextDocumentParams {
+            text_document: VersionedTextDocumentIdentifier {
+                uri: file_url.clone(),
+                version,
+            },
+            content_changes: vec![TextDocumentContentChangeEvent {
+                range: None,
+                range_length: None,
+                text: format!(
+                    "\
+entity ent is
+end entity;
+
+architecture a of ent is
+  signal level : {};
+begin
+end architecture;
+",
+                    type_name
+                ),
+            }],
+        };
+
+        let publish_diagnostics = PublishDiagnosticsParams {
+            uri: file_url.clone(),
+            diagnostics: vec![lsp_types::Diagnostic {
+                range: lsp_range(4, 17, 24),
+                code: None,
+                code_description: None,
+                severity: Some(DiagnosticSeverity::ERROR),
+                source: Some("vhdl ls".to_owned()),
+                message: "No declaration of 'level_t'".to_owned(),
+                related_information: None,
+                tags: None,
+                data: None,
+            }],
+            version: None,
+        };
+
+        // Only the diagnostics of the latest change are published
+        server.text_document_did_change_notification(&change(1, "state_t"));
+        server.text_document_did_change_notification(&change(2, "level_t"));
+        mock.expect_notification("textDocument/publishDiagnostics", publish_diagnostics);
+
+        let result = results.recv_timeout(timeout).unwrap();
+        server.analysis_finished(result);
+        while let Ok(result) = results.recv_timeout(std::time::Duration::from_millis(500)) {
+            server.analysis_finished(result);
+        }
+    }
+
+    #[test]
+    fn design_hierarchy() {
+        let (mock, mut server) = setup_server();
+        let (_tempdir, root_uri) = temp_root_uri();
+
+        let file_url = write_file(
+            &root_uri,
+            "top.vhd",
+            "\
+entity sub is
+end entity;
+
+architecture rtl of sub is
+begin
+end architecture;
+
+entity top is
+end entity;
+
+architecture rtl of top is
+begin
+  inst : entity work.sub;
+end architecture;
+",
+        );
+
+        let config_uri = write_config(
+            &root_uri,
+            "
+[libraries]
+lib.files = [
+  '*.vhd'
+]
+",
+        );
+
+        expect_loaded_config_messages(&mock, &config_uri);
+        initialize_server(&mut server, root_uri);
+
+        let location = |line, start, end| Location {
+            uri: file_url.clone(),
+            range: lsp_range(line, start, end),
+        };
+
+        let hierarchy = server.design_hierarchy(&DesignHierarchyParams {
+            library: None,
+            entity: "TOP".to_owned(),
+        });
+
+        assert_eq!(
+            hierarchy,
+            Some(DesignHierarchyNode {
+                label: None,
+                library: "lib".to_owned(),
+                entity: "top".to_owned(),
+                architecture: Some("rtl".to_owned()),
+                location: location(7, 7, 10),
+                unit_location: Some(location(10, 13, 16)),
+                children: vec![DesignHierarchyNode {
+                    label: Some("inst".to_owned()),
+                    library: "lib".to_owned(),
+                    entity: "sub".to_owned(),
+                    architecture: Some("rtl".to_owned()),
+                    location: location(12, 2, 6),
+                    unit_location: Some(location(3, 13, 16)),
+                    children: vec![],
+                }],
+            })
+        );
+    }
+
+    #[test]
+    fn text_document_inlay_hint() {
+        let (mock, mut server) = setup_server();
+        let (_tempdir, root_uri) = temp_root_uri();
+
+        let file_url = write_file(
+            &root_uri,
+            "top.vhd",
+            "\
+package pkg is
+  type level_t is (lo, hi);
+end package;
+
+use work.pkg.all;
+entity sub is
+  port (a : level_t; b : level_t);
+end entity;
+
+use work.pkg.all;
+entity top is
+end entity;
+
+architecture rtl of top is
+  signal x, y : level_t;
+begin
+  inst : entity work.sub port map (x, y);
+end architecture;
+",
+        );
+
+        let config_uri = write_config(
+            &root_uri,
+            "
+[libraries]
+lib.files = [
+  '*.vhd'
+]
+",
+        );
+
+        expect_loaded_config_messages(&mock, &config_uri);
+        initialize_server(&mut server, root_uri);
+
+        let response = server.text_document_inlay_hint(&InlayHintParams {
+            work_done_progress_params: Default::default(),
+            text_document: TextDocumentIdentifier { uri: file_url },
+            range: lsp_range(16, 0, 40),
+        });
+
+        let hints: Vec<_> = response
+            .unwrap()
+            .into_iter()
+            .map(|hint| {
+                assert_eq!(hint.kind, Some(InlayHintKind::PARAMETER));
+                assert_eq!(hint.padding_right, Some(true));
+                match hint.label {
+                    InlayHintLabel::String(label) => (hint.position, label),
+                    InlayHintLabel::LabelParts(..) => panic!("Expected string label"),
+                }
+            })
+            .collect();
+
+        assert_eq!(
+            hints,
+            vec![
+                (lsp_types::Position::new(16, 35), "a =>".to_owned()),
+                (lsp_types::Position::new(16, 38), "b =>".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn workspace_symbol() {
+        let (mock, mut server) = setup_server();
+        let (_tempdir, root_uri) = temp_root_uri();
+
+        let file_url = write_file(
+            &root_uri,
+            "pkg.vhd",
+            "\
+package counter_pkg is
+  type counter_t is (idle, counting);
+end package;
+
+package body counter_pkg is
+end package body;
+",
+        );
+
+        let config_uri = write_config(
+            &root_uri,
+            "
+[libraries]
+lib.files = [
+  '*.vhd'
+]
+",
+        );
+
+        expect_loaded_config_messages(&mock, &config_uri);
+        initialize_server(&mut server, root_uri);
+
+        let response = server
+            .workspace_symbol(&WorkspaceSymbolParams {
+                partial_result_params: Default::default(),
+                work_done_progress_params: Default::default(),
+                query: "COUNTER".to_owned(),
+            })
+            .unwrap();
+
+        let symbols: Vec<_> = response
+            .iter()
+            .map(|symbol| {
+                (
+                    symbol.name.as_str(),
+                    symbol.kind,
+                    symbol.container_name.as_deref(),
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            symbols,
+            vec![
+                ("counter_pkg", SymbolKind::PACKAGE, Some("lib")),
+                ("counter_pkg", SymbolKind::PACKAGE, Some("lib")),
+                (
+                    "counter_t",
+                    SymbolKind::TYPE_PARAMETER,
+                    Some("lib.counter_pkg")
+                ),
+            ]
+        );
+
+        assert_eq!(
+            response[2].location,
+            Location {
+                uri: file_url,
+                range: lsp_types::Range {
+                    start: lsp_types::Position {
+                        line: 1,
+                        character: "  type ".len() as u32,
+                    },
+                    end: lsp_types::Position {
+                        line: 1,
+                        character: "  type counter_t".len() as u32,
+                    },
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn text_document_semantic_tokens() {
+        let (mock, mut server) = setup_server();
+        let (_tempdir, root_uri) = temp_root_uri();
+
+        let file_url = write_file(
+            &root_uri,
+            "pkg.vhd",
+            "\
+package pkg is
+  type state_t is (idle, busy);
+  constant init : state_t := idle;
+end package pkg;
+",
+        );
+
+        let config_uri = write_config(
+            &root_uri,
+            "
+[libraries]
+lib.files = [
+  '*.vhd'
+]
+",
+        );
+
+        expect_loaded_config_messages(&mock, &config_uri);
+        initialize_server(&mut server, root_uri);
+
+        let token =
+            |delta_line, delta_start, length, token_type, token_modifiers_bitset| SemanticToken {
+                delta_line,
+                delta_start,
+                length,
+                token_type,
+                token_modifiers_bitset,
+            };
+
+        let full = server.text_document_semantic_tokens_full(&SemanticTokensParams {
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+            text_document: TextDocumentIdentifier {
+                uri: file_url.clone(),
+            },
+        });
+
+        // Token types: 1 = class, 2 = type, 3 = enumMember, 5 = variable
+        // Modifiers: 1 = declaration, 2 = readonly
+        assert_eq!(
+            full,
+            Some(SemanticTokensResult::Tokens(SemanticTokens {
+                result_id: None,
+                data: vec![
+                    token(0, 8, 3, 1, 1),
+                    token(1, 7, 7, 2, 1),
+                    token(1, 11, 4, 5, 3),
+                    token(0, 7, 7, 2, 0),
+                    token(0, 11, 4, 3, 0),
+                    token(1, 12, 3, 1, 1),
+                ],
+            }))
+        );
+
+        let range = server.text_document_semantic_tokens_range(&SemanticTokensRangeParams {
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+            text_document: TextDocumentIdentifier { uri: file_url },
+            range: lsp_range(2, 0, 20),
+        });
+
+        assert_eq!(
+            range,
+            Some(SemanticTokensRangeResult::Tokens(SemanticTokens {
+                result_id: None,
+                data: vec![token(2, 11, 4, 5, 3), token(0, 7, 7, 2, 0)],
+            }))
+        );
+    }
+
+    fn lsp_range(line: u32, start: usize, end: usize) -> lsp_types::Range {
+        lsp_types::Range {
+            start: lsp_types::Position {
+                line,
+                character: start as u32,
+            },
+            end: lsp_types::Position {
+                line,
+                character: end as u32,
+            },
+        }
+    }
+
+    #[test]
+    fn text_document_rename() {
+        let (mock, mut server) = setup_server();
+        let (_tempdir, root_uri) = temp_root_uri();
+
+        let ent_url = write_file(
+            &root_uri,
+            "ent.vhd",
+            "\
+entity ent is
+end entity ent;
+",
+        );
+
+        let arch_url = write_file(
+            &root_uri,
+            "arch.vhd",
+            "\
+library ieee;
+use ieee.pkg.all;
+
+architecture rtl of ENT is
+  constant c : bit_t := zero;
+begin
+end architecture;
+",
+        );
+
+        write_file(
+            &root_uri,
+            "pkg.vhd",
+            "\
+package pkg is
+  type bit_t is ('0', '1');
+  constant zero : bit_t := '0';
+end package;
+",
+        );
+
+        let config_uri = write_config(
+            &root_uri,
+            "
+[libraries]
+ieee.files = ['pkg.vhd']
+ieee.read_only = true
+lib.files = ['ent.vhd', 'arch.vhd']
+",
+        );
+
+        expect_loaded_config_messages(&mock, &config_uri);
+        initialize_server(&mut server, root_uri);
+
+        let end_label = TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier {
+                uri: ent_url.clone(),
+            },
+            position: lsp_types::Position {
+                line: 1,
+                character: "end entity e".len() as u32,
+            },
+        };
+
+        assert_eq!(
+            server.text_document_prepare_rename(&end_label),
+            Ok(Some(PrepareRenameResponse::Range(lsp_range(
+                1,
+                "end entity ".len(),
+                "end entity ent".len()
+            ))))
+        );
+
+        let edit = server
+            .text_document_rename(&RenameParams {
+                text_document_position: end_label,
+                new_name: "new_ent".to_owned(),
+                work_done_progress_params: Default::default(),
+            })
+            .unwrap()
+            .unwrap();
+
+        let mut expected = HashMap::new();
+        expected.insert(
+            ent_url,
+            vec![
+                TextEdit {
+                    range: lsp_range(0, "entity ".len(), "entity ent".len()),
+                    new_text: "new_ent".to_owned(),
+                },
+                TextEdit {
+                    range: lsp_range(1, "end entity ".len(), "end entity ent".len()),
+                    new_text: "new_ent".to_owned(),
+                },
+            ],
+        );
+        expected.insert(
+            arch_url.clone(),
+            vec![TextEdit {
+                range: lsp_range(
+                    3,
+                    "architecture rtl of ".len(),
+                    "architecture rtl of ENT".len(),
+                ),
+                new_text: "new_ent".to_owned(),
+            }],
+        );
+        assert_eq!(edit.changes, Some(expected));
+
+        // Declarations within the ieee library are read-only
+        let ieee_reference = TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier { uri: arch_url },
+            position: lsp_types::Position {
+                line: 4,
+                character: "  constant c : bit_t := z".len() as u32,
+            },
+        };
+        assert!(server
+            .text_document_prepare_rename(&ieee_reference)
+            .unwrap_err()
+            .contains("read-only library 'ieee'"));
+        assert!(server
+            .text_document_rename(&RenameParams {
+                text_document_position: ieee_reference,
+                new_name: "one".to_owned(),
+                work_done_progress_params: Default::default(),
+            })
+            .is_err());
+    }
+
     #[test]
     fn client_register_capability() {
         let (mock, mut server) = setup_server();
@@ -916,7 +4713,7 @@ lib.files = [
 
         let register_options = DidChangeWatchedFilesRegistrationOptions {
             watchers: vec![FileSystemWatcher {
-                glob_pattern: "**/vhdl_ls.toml".to_owned(),
+                glob_pattern: GlobPattern::String("**/vhdl_ls.toml".to_owned()),
                 kind: None,
             }],
         };
@@ -933,8 +4730,9 @@ lib.files = [
 
         let capabilities = ClientCapabilities {
             workspace: Some(WorkspaceClientCapabilities {
-                did_change_watched_files: Some(GenericCapability {
+                did_change_watched_files: Some(DidChangeWatchedFilesClientCapabilities {
                     dynamic_registration: Some(true),
+                    relative_pattern_support: None,
                 }),
                 ..WorkspaceClientCapabilities::default()
             }),
@@ -950,6 +4748,7 @@ lib.files = [
             trace: None,
             workspace_folders: None,
             client_info: None,
+            locale: None,
         };
 
         server.initialize_request(initialize_params);
@@ -994,19 +4793,21 @@ lib.files = [
                 range: Range {
                     start: lsp_types::Position {
                         line: 0,
-                        character: "architecture rtl of ".len() as u64,
+                        character: "architecture rtl of ".len() as u32,
                     },
                     end: lsp_types::Position {
                         line: 0,
-                        character: "architecture rtl of ent".len() as u64,
+                        character: "architecture rtl of ent".len() as u32,
                     },
                 },
                 code: None,
-                severity: Some(DiagnosticSeverity::Error),
+                code_description: None,
+                severity: Some(DiagnosticSeverity::ERROR),
                 source: Some("vhdl ls".to_owned()),
                 message: "No entity \'ent\' within library \'lib\'".to_owned(),
                 related_information: None,
                 tags: None,
+                data: None,
             }],
             version: None,
         };
@@ -1042,7 +4843,7 @@ lib.files = [
         );
         server.workspace_did_change_watched_files(&DidChangeWatchedFilesParams {
             changes: vec![FileEvent {
-                typ: FileChangeType::Changed,
+                typ: FileChangeType::CHANGED,
                 uri: config_uri,
             }],
         });